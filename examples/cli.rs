@@ -0,0 +1,29 @@
+// Runs the command-line runner on a small calculator grammar, e.g.
+//
+//     echo "1 + 2 * (3 - 4)" | cargo run --example cli --features cli -- parse expression -
+
+#[macro_use]
+extern crate pest;
+
+use pest::prelude::*;
+
+impl_rdp! {
+    grammar! {
+        expression = _{
+            { ["("] ~ expression ~ [")"] | number }
+            addition       = { plus  | minus }
+            multiplication = { times | slash }
+        }
+        number = @{ ["-"]? ~ (["0"] | ['1'..'9'] ~ ['0'..'9']*) }
+        plus   =  { ["+"] }
+        minus  =  { ["-"] }
+        times  =  { ["*"] }
+        slash  =  { ["/"] }
+
+        whitespace = _{ [" "] | ["\n"] }
+    }
+}
+
+fn main() {
+    pest::cli::main::<Rdp<StringInput>>();
+}
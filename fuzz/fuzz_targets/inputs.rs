@@ -0,0 +1,83 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Drives every `Input` with a random text and a random sequence of operations and checks that
+//! the position always stays on a `char` boundary, so that `slice` and `set_pos` never panic.
+//!
+//! Run with `cargo fuzz run inputs` from the repository root.
+
+#![no_main]
+
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate pest;
+
+use pest::{CrlfInput, DecodedInput, Input, LossyInput, StringInput};
+
+fn exercise<I: Input>(input: &mut I, ops: &[u8]) {
+    let start = input.start();
+    let mut marks = vec![input.pos()];
+
+    for chunk in ops.chunks(2) {
+        let (op, arg) = (chunk[0], *chunk.get(1).unwrap_or(&0));
+        let pos = input.pos();
+
+        match op % 6 {
+            0 => {
+                input.advance_by_chars(arg as usize % 8);
+            },
+            1 => {
+                let text: String = input.slice(pos, input.len()).chars()
+                                                                 .take(arg as usize % 4)
+                                                                 .collect();
+
+                input.match_string(&text);
+            },
+            2 => {
+                input.match_range('\u{0}', char::from(arg & 0x7f));
+            },
+            3 => {
+                input.match_range('\u{80}', '\u{7ff}');
+            },
+            4 => input.set_pos(marks[arg as usize % marks.len()]),
+            _ => {
+                marks.push(pos);
+            }
+        }
+
+        let pos = input.pos();
+
+        assert!(start <= pos && pos <= input.len());
+
+        input.slice(start, pos);
+        input.slice(pos, input.len());
+        input.line_col(pos);
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let (len, data) = match data.split_first() {
+        Some((&len, data)) => (len as usize, data),
+        None               => return
+    };
+    let (text, ops) = data.split_at(len.min(data.len()));
+    let string = String::from_utf8_lossy(text);
+
+    exercise(&mut StringInput::new(&string), ops);
+    exercise(&mut CrlfInput::new(&string), ops);
+    exercise(&mut LossyInput::new(text), ops);
+
+    if let Ok(mut input) = DecodedInput::new(text) {
+        exercise(&mut input, ops);
+    }
+
+    // skips the first and last char
+    let start = string.chars().next().map_or(0, char::len_utf8);
+    let end = string.char_indices().last().map_or(0, |(i, _)| i).max(start);
+
+    exercise(&mut StringInput::with_range(&string, start, end), ops);
+});
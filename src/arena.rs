@@ -0,0 +1,156 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::cell::RefCell;
+use std::cmp;
+
+/// A `struct` that allocates values of type `T` which all live as long as the `Arena` itself.
+/// Allocating only needs `&self`, so an `Arena` can be threaded through `process!` matchers in
+/// order to build ASTs out of references instead of `Box`ing every node.
+///
+/// Values are stored in chunks that never reallocate; once a chunk is full, a new one twice its
+/// size is created.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::Arena;
+/// # fn main() {
+/// #[derive(Debug, PartialEq)]
+/// pub enum Expression<'a> {
+///     Paren(&'a Expression<'a>),
+///     Letter(char)
+/// }
+///
+/// impl_rdp! {
+///     grammar! {
+///         expression = _{ paren | letter }
+///         paren      =  { ["("] ~ expression ~ [")"] }
+///         letter     =  { ['a'..'z'] }
+///     }
+///
+///     process! {
+///         main<'a>(&self, arena: &'a Arena<Expression<'a>>) -> &'a Expression<'a> {
+///             (&letter: letter) => {
+///                 arena.alloc(Expression::Letter(letter.chars().next().unwrap()))
+///             },
+///             (_: paren, expression: main(arena)) => {
+///                 arena.alloc(Expression::Paren(expression))
+///             }
+///         }
+///     }
+/// }
+///
+/// let arena = Arena::new();
+/// let mut parser = Rdp::new(StringInput::new("((z))"));
+///
+/// assert!(parser.expression());
+/// assert_eq!(parser.process(&arena),
+///            &Expression::Paren(&Expression::Paren(&Expression::Letter('z'))));
+/// assert_eq!(arena.len(), 3);
+/// # }
+/// ```
+pub struct Arena<T> {
+    chunks: RefCell<Vec<Vec<T>>>
+}
+
+impl<T> Arena<T> {
+    /// Creates an empty `Arena`.
+    pub fn new() -> Arena<T> {
+        Arena::with_capacity(16)
+    }
+
+    /// Creates an empty `Arena` whose first chunk can hold `capacity` values.
+    pub fn with_capacity(capacity: usize) -> Arena<T> {
+        Arena {
+            chunks: RefCell::new(vec![Vec::with_capacity(cmp::max(capacity, 1))])
+        }
+    }
+
+    /// Moves `value` into an `Arena` and returns a reference to it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::Arena;
+    /// let arena = Arena::new();
+    ///
+    /// let a = arena.alloc(1);
+    /// let b = arena.alloc(2);
+    ///
+    /// assert_eq!(*a + *b, 3);
+    /// ```
+    pub fn alloc(&self, value: T) -> &T {
+        let mut chunks = self.chunks.borrow_mut();
+
+        let capacity = {
+            let last = chunks.last().unwrap();
+
+            if last.len() == last.capacity() {
+                Some(last.capacity() * 2)
+            } else {
+                None
+            }
+        };
+
+        if let Some(capacity) = capacity {
+            chunks.push(Vec::with_capacity(capacity));
+        }
+
+        let chunk = chunks.last_mut().unwrap();
+        chunk.push(value);
+
+        let value: *const T = chunk.last().unwrap();
+
+        // chunks never grow past their capacity, so their values never move; they are only
+        // dropped together with the Arena
+        unsafe { &*value }
+    }
+
+    /// Returns the number of values allocated in an `Arena`.
+    pub fn len(&self) -> usize {
+        self.chunks.borrow().iter().map(|chunk| chunk.len()).sum()
+    }
+
+    /// Returns whether an `Arena` is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Arena<T> {
+        Arena::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Arena;
+
+    #[test]
+    fn alloc() {
+        let arena = Arena::with_capacity(1);
+
+        let values: Vec<&usize> = (0..100).map(|i| arena.alloc(i)).collect();
+
+        for (i, value) in values.iter().enumerate() {
+            assert_eq!(**value, i);
+        }
+
+        assert_eq!(arena.len(), 100);
+    }
+
+    #[test]
+    fn empty() {
+        let arena: Arena<()> = Arena::new();
+
+        assert!(arena.is_empty());
+    }
+}
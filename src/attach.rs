@@ -0,0 +1,233 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::ops::Range;
+
+use super::{Token, Trivia};
+
+/// A `struct` associating a `comment` with the `Token` at index `token` in a
+/// [queue](trait.Parser#tymethod.queue).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Attachment {
+    /// span of the `comment`
+    pub comment: Range<usize>,
+    /// index of the `Token` in the queue
+    pub token:   usize,
+    /// `Trivia::Leading` if the `comment` precedes the `Token`, `Trivia::Trailing` if it follows
+    /// it on the same line
+    pub trivia:  Trivia
+}
+
+/// A `struct` that associates [`comments`](trait.Parser#tymethod.comments) with the `Token`s
+/// they document.
+///
+/// A `comment` that follows a `Token` on the same line, with nothing else after it on that line,
+/// trails the `Token` with the latest end. Otherwise, it leads the next `Token`, as long as only
+/// `whitespace` and other leading `comment`s come in between, without too many blank lines.
+/// Among `Token`s with the same span, the outermost one wins. `Token` spans should not include
+/// [`Trivia`](enum.Trivia).
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::{Attacher, Attachment, Trivia};
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         items = { item* ~ eoi }
+///         item  = { ident ~ [";"] }
+///         ident = @{ ['a'..'z']+ }
+///
+///         whitespace = _{ [" "] | ["\n"] }
+///         comment    = _{ ["/*"] ~ (!["*/"] ~ any)* ~ ["*/"] }
+///     }
+/// }
+///
+/// let source = "/* a's doc */\na;\nb; /* b's note */\n\n/* detached */\n\nc;\n";
+/// let mut parser = Rdp::new(StringInput::new(source));
+///
+/// assert!(parser.items());
+///
+/// let attachments = Attacher::new().rule(Rule::item)
+///                                  .attach(source, parser.queue(), parser.comments());
+///
+/// assert_eq!(attachments, vec![
+///     Attachment { comment: 0..13, token: 1, trivia: Trivia::Leading },
+///     Attachment { comment: 20..34, token: 3, trivia: Trivia::Trailing }
+/// ]);
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Attacher<R> {
+    rules:       Vec<R>,
+    blank_lines: usize,
+    trailing:    bool
+}
+
+impl<R: PartialEq> Attacher<R> {
+    /// Creates an `Attacher` that considers all `Token`s, allows no blank lines between a
+    /// leading `comment` and its `Token`, and attaches trailing `comment`s.
+    pub fn new() -> Attacher<R> {
+        Attacher {
+            rules:       vec![],
+            blank_lines: 0,
+            trailing:    true
+        }
+    }
+
+    /// Only attaches `comment`s to `Token`s of `rule`. Can be called multiple times.
+    pub fn rule(mut self, rule: R) -> Attacher<R> {
+        self.rules.push(rule);
+
+        self
+    }
+
+    /// Sets the maximum number of blank lines between a leading `comment` and its `Token`.
+    pub fn blank_lines(mut self, blank_lines: usize) -> Attacher<R> {
+        self.blank_lines = blank_lines;
+
+        self
+    }
+
+    /// Sets whether `comment`s trailing a `Token` on the same line are attached to it.
+    pub fn trailing(mut self, value: bool) -> Attacher<R> {
+        self.trailing = value;
+
+        self
+    }
+
+    /// Attaches the sorted `comments` of `source` to the `Token`s of a pre-order `queue`.
+    /// `comment`s that don't belong to any `Token` are left out.
+    pub fn attach(&self, source: &str, queue: &[Token<R>], comments: &[Range<usize>])
+                  -> Vec<Attachment> {
+        let candidates: Vec<usize> = (0..queue.len()).filter(|&i| {
+            self.rules.is_empty() || self.rules.contains(&queue[i].rule)
+        }).collect();
+
+        let mut by_end = candidates.clone();
+        by_end.sort_by_key(|&i| (queue[i].end, i));
+
+        let breaks = |start: usize, end: usize| source[start..end].matches('\n').count();
+
+        // whether a leading comment is close enough to end
+        let leads = |comment: &Range<usize>, end: usize| {
+            let gap = &source[comment.end..end];
+            let ends_line = source[comment.clone()].ends_with('\n') as usize;
+
+            gap.trim().is_empty() && gap.matches('\n').count() + ends_line <= self.blank_lines + 1
+        };
+
+        let mut attached: Vec<Option<(usize, Trivia)>> = vec![None; comments.len()];
+
+        for (c, comment) in comments.iter().enumerate().rev() {
+            let next = candidates.get(candidates.partition_point(|&i| {
+                queue[i].start < comment.end
+            })).cloned();
+
+            if self.trailing {
+                let before = by_end.partition_point(|&i| queue[i].end <= comment.start);
+
+                if before > 0 {
+                    let end = queue[by_end[before - 1]].end;
+                    let prev = by_end[by_end.partition_point(|&i| queue[i].end < end)];
+
+                    let next_on_line = next.is_some_and(|next| {
+                        breaks(comment.start, queue[next].start) == 0
+                    });
+
+                    if breaks(end, comment.start) == 0 && !next_on_line {
+                        attached[c] = Some((prev, Trivia::Trailing));
+
+                        continue;
+                    }
+                }
+            }
+
+            let following = comments.get(c + 1).filter(|following| {
+                next.is_none_or(|next| following.start < queue[next].start)
+            });
+
+            attached[c] = match following {
+                Some(following) => {
+                    match attached[c + 1] {
+                        Some((token, Trivia::Leading)) if leads(comment, following.start) => {
+                            Some((token, Trivia::Leading))
+                        },
+                        _ => None
+                    }
+                },
+                None => {
+                    next.filter(|&next| leads(comment, queue[next].start))
+                        .map(|next| (next, Trivia::Leading))
+                }
+            };
+        }
+
+        comments.iter().zip(attached).filter_map(|(comment, attached)| {
+            attached.map(|(token, trivia)| Attachment { comment: comment.clone(), token, trivia })
+        }).collect()
+    }
+}
+
+impl<R: PartialEq> Default for Attacher<R> {
+    fn default() -> Attacher<R> {
+        Attacher::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(start: usize, end: usize) -> Token<()> {
+        Token { rule: (), start, end }
+    }
+
+    #[test]
+    fn leading_block() {
+        let source = "/* a */\n/* b */\nx";
+        let queue = vec![token(16, 17)];
+
+        assert_eq!(Attacher::new().attach(source, &queue, &[0..7, 8..15]), vec![
+            Attachment { comment: 0..7, token: 0, trivia: Trivia::Leading },
+            Attachment { comment: 8..15, token: 0, trivia: Trivia::Leading }
+        ]);
+    }
+
+    #[test]
+    fn blank_lines() {
+        let source = "/* a */\n\nx";
+        let queue = vec![token(9, 10)];
+
+        let comments = [Range { start: 0, end: 7 }];
+
+        assert!(Attacher::new().attach(source, &queue, &comments).is_empty());
+        assert_eq!(Attacher::new().blank_lines(1).attach(source, &queue, &comments).len(), 1);
+    }
+
+    #[test]
+    fn inline() {
+        let source = "x /* a */ y";
+        let queue = vec![token(0, 1), token(10, 11)];
+
+        assert_eq!(Attacher::new().attach(source, &queue, &[Range { start: 2, end: 9 }]), vec![
+            Attachment { comment: 2..9, token: 1, trivia: Trivia::Leading }
+        ]);
+    }
+
+    #[test]
+    fn outermost() {
+        let source = "xy // a\n";
+        let queue = vec![token(0, 2), token(1, 2)];
+
+        assert_eq!(Attacher::new().attach(source, &queue, &[Range { start: 3, end: 8 }]), vec![
+            Attachment { comment: 3..8, token: 0, trivia: Trivia::Trailing }
+        ]);
+    }
+}
@@ -0,0 +1,758 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::cmp;
+use std::collections::HashMap;
+use std::fmt;
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+use super::Alternative;
+
+/// A `struct` describing the farthest backtrack of a `Parser`, returned by
+/// [`Parser::max_backtrack`](trait.Parser#tymethod.max_backtrack).
+///
+/// A backtrack, or the end of a lookahead, returns to a position `to` after the `Parser` had
+/// already read up to `from`, the farthest position reached so far. Input between `to` and `from`
+/// is thus read again, so `distance` is how much input has to be kept when parsing a stream.
+/// A grammar whose `distance` stays small for any input is effectively LL(k).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Backtrack<R> {
+    /// number of bytes between `to` and `from`
+    pub distance: usize,
+    /// farthest position reached before backtracking
+    pub from:     usize,
+    /// position backtracked to
+    pub to:       usize,
+    /// innermost non-silent rule being matched when backtracking, if any
+    pub rule:     Option<R>
+}
+
+/// An `enum` representing a static upper bound on a number of bytes.
+///
+/// `Bytes` bounds are ordered by their number, and all of them are smaller than `Unbounded`.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Bound {
+    /// at most this many bytes
+    Bytes(usize),
+    /// no upper bound could be found
+    Unbounded
+}
+
+impl Bound {
+    fn plus(self, other: Bound) -> Bound {
+        match (self, other) {
+            (Bound::Bytes(a), Bound::Bytes(b)) => Bound::Bytes(a.saturating_add(b)),
+            _                                  => Bound::Unbounded
+        }
+    }
+}
+
+impl fmt::Display for Bound {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Bound::Bytes(bytes) => write!(f, "{} bytes", bytes),
+            Bound::Unbounded    => write!(f, "unbounded")
+        }
+    }
+}
+
+/// A `struct` with a conservative static bound, for every rule defined in `grammar!`, on how far
+/// matching it can [backtrack](struct.Backtrack), returned by
+/// [`Parser::backtrack_bounds`](trait.Parser#tymethod.backtrack_bounds). A rule whose `Bound`
+/// fits into the retention window of a streaming input never needs input that was already
+/// discarded.
+///
+/// The bounds are computed from the source of the rules' `Alternative`s and are never smaller
+/// than what [`Parser::max_backtrack`](trait.Parser#tymethod.max_backtrack) measures when
+/// starting from the rule. They are conservative in the following ways:
+///
+/// * trivia, i.e. `whitespace`, `newline`, and `comment`, is unbounded, so any rule skipping it
+///   between sub-rules that can still fail is unbounded
+/// * sub-rules are always analyzed as defined, not as atomic when called from an atomic rule
+/// * [precedence climbing](macro.grammar!#precedence-climbing) rules, keywords rules, aliases,
+///   and strings given as anything other than a literal are unbounded
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::Bound;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         value  = @{ number | range }
+///         number = @{ ["-"]? ~ ['0'..'9']+ }
+///         range  = @{ ['0'..'9'] ~ [".."] ~ ['0'..'9'] }
+///         list   = @{ number ~ ([","] ~ number)* ~ [";"] }
+///     }
+/// }
+///
+/// let parser = Rdp::new(StringInput::new(""));
+/// let bounds = parser.backtrack_bounds();
+///
+/// assert_eq!(bounds.bound("number"), Some(Bound::Bytes(1)));
+/// assert_eq!(bounds.bound("range"), Some(Bound::Bytes(3)));
+/// assert_eq!(bounds.bound("value"), Some(Bound::Bytes(3)));
+/// assert_eq!(bounds.bound("list"), Some(Bound::Unbounded));
+///
+/// assert!(bounds.fits("value", 16));
+/// assert_eq!(bounds.unbounded(), vec!["list"]);
+/// # }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BacktrackBounds {
+    rules: Vec<(&'static str, Bound)>
+}
+
+impl BacktrackBounds {
+    /// Analyzes the rules defined by `alternatives`, where the rules named in `atomic` are
+    /// [atomic](macro.grammar!#atomic-rules-).
+    pub fn new(alternatives: &[Alternative], atomic: &[&str]) -> BacktrackBounds {
+        let mut analysis = Analysis::new(alternatives, atomic);
+
+        analysis.run();
+
+        BacktrackBounds {
+            rules: analysis.names.iter().map(|&name| {
+                (name, analysis.summaries[name].bound)
+            }).collect()
+        }
+    }
+
+    /// Returns the `Bound` of `rule`, or `None` if `rule` has no `Alternative`s.
+    pub fn bound(&self, rule: &str) -> Option<Bound> {
+        self.rules.iter().find(|&&(name, _)| name == rule).map(|&(_, bound)| bound)
+    }
+
+    /// Returns whether matching `rule` never backtracks more than `window` bytes. Unknown rules
+    /// never fit.
+    pub fn fits(&self, rule: &str, window: usize) -> bool {
+        self.bound(rule).is_some_and(|bound| bound <= Bound::Bytes(window))
+    }
+
+    /// Returns every rule with its `Bound`, in grammar order.
+    pub fn rules(&self) -> &[(&'static str, Bound)] {
+        &self.rules
+    }
+
+    /// Returns the rules that can backtrack arbitrarily far, in grammar order.
+    pub fn unbounded(&self) -> Vec<&'static str> {
+        self.rules.iter()
+                  .filter(|&&(_, bound)| bound == Bound::Unbounded)
+                  .map(|&(name, _)| name)
+                  .collect()
+    }
+}
+
+impl fmt::Display for BacktrackBounds {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for &(rule, bound) in &self.rules {
+            writeln!(f, "{}: {}", rule, bound)?;
+        }
+
+        Ok(())
+    }
+}
+
+// rule expressions recovered from the source of their alternatives, also used by lint
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Expr {
+    Str(String),
+    Range(usize),
+    Rule(&'static str),
+    Seq(Vec<Expr>),
+    Choice(Vec<Expr>),
+    Opt(Box<Expr>),
+    Rep(Box<Expr>, bool),
+    Look(Box<Expr>),
+    Builtin(&'static str),
+    Unknown
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Tok {
+    Open(char),
+    Close(char),
+    Op(char),
+    Ident(&'static str),
+    Str(String),
+    Char(char),
+    DotDot,
+    Other
+}
+
+fn unescape(chars: &mut Peekable<CharIndices>, quote: char) -> String {
+    let mut string = String::new();
+
+    while let Some((_, c)) = chars.next() {
+        if c == quote {
+            break;
+        }
+
+        if c != '\\' {
+            string.push(c);
+
+            continue;
+        }
+
+        match chars.next().map(|(_, c)| c) {
+            Some('n') => string.push('\n'),
+            Some('r') => string.push('\r'),
+            Some('t') => string.push('\t'),
+            Some('0') => string.push('\0'),
+            Some('x') => {
+                let hex: String = chars.by_ref().take(2).map(|(_, c)| c).collect();
+
+                string.push(u8::from_str_radix(&hex, 16).map_or('\u{fffd}', char::from));
+            },
+            Some('u') => {
+                let hex: String = chars.by_ref()
+                                       .map(|(_, c)| c)
+                                       .take_while(|&c| c != '}')
+                                       .filter(|&c| c != '{')
+                                       .collect();
+                let c = u32::from_str_radix(&hex, 16).ok().and_then(::std::char::from_u32);
+
+                string.push(c.unwrap_or('\u{fffd}'));
+            },
+            Some(c)   => string.push(c),
+            None      => ()
+        }
+    }
+
+    string
+}
+
+fn lex(text: &'static str) -> Vec<Tok> {
+    let mut tokens = vec![];
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        let token = match c {
+            '"'                                   => Tok::Str(unescape(&mut chars, '"')),
+            '\''                                  => {
+                unescape(&mut chars, '\'').chars().next().map_or(Tok::Other, Tok::Char)
+            },
+            '(' | '[' | '{'                       => Tok::Open(c),
+            ')' | ']' | '}'                       => Tok::Close(c),
+            '~' | '|' | '?' | '*' | '+' | '&' | '!' | ',' | '-' => Tok::Op(c),
+            '.' if chars.peek().map(|&(_, c)| c) == Some('.') => {
+                chars.next();
+
+                Tok::DotDot
+            },
+            _ if c.is_alphanumeric() || c == '_'  => {
+                let mut end = i + c.len_utf8();
+
+                while let Some(&(j, c)) = chars.peek() {
+                    if !c.is_alphanumeric() && c != '_' {
+                        break;
+                    }
+
+                    end = j + c.len_utf8();
+                    chars.next();
+                }
+
+                Tok::Ident(&text[i..end])
+            },
+            _ if c.is_whitespace()                => continue,
+            _                                     => Tok::Other
+        };
+
+        tokens.push(token);
+    }
+
+    tokens
+}
+
+pub struct ExprParser {
+    tokens: Vec<Tok>,
+    pos:    usize
+}
+
+impl ExprParser {
+    pub fn parse(text: &'static str) -> Expr {
+        let mut parser = ExprParser {
+            tokens: lex(text),
+            pos:    0
+        };
+
+        let expr = parser.choice();
+
+        if parser.pos == parser.tokens.len() {
+            expr
+        } else {
+            Expr::Unknown
+        }
+    }
+
+    fn eat(&mut self, token: &Tok) -> bool {
+        if self.tokens.get(self.pos) == Some(token) {
+            self.pos += 1;
+
+            true
+        } else {
+            false
+        }
+    }
+
+    fn list(&mut self, separator: char, item: fn(&mut ExprParser) -> Expr) -> Vec<Expr> {
+        let mut items = vec![item(self)];
+
+        while self.eat(&Tok::Op(separator)) {
+            items.push(item(self));
+        }
+
+        items
+    }
+
+    fn choice(&mut self) -> Expr {
+        let mut alternatives = self.list('|', ExprParser::seq);
+
+        if alternatives.len() == 1 {
+            alternatives.pop().unwrap()
+        } else {
+            Expr::Choice(alternatives)
+        }
+    }
+
+    fn seq(&mut self) -> Expr {
+        let mut terms = self.list('~', ExprParser::prefix);
+
+        if terms.len() == 1 {
+            terms.pop().unwrap()
+        } else {
+            Expr::Seq(terms)
+        }
+    }
+
+    fn prefix(&mut self) -> Expr {
+        if self.eat(&Tok::Op('&')) || self.eat(&Tok::Op('!')) {
+            Expr::Look(Box::new(self.prefix()))
+        } else {
+            self.postfix()
+        }
+    }
+
+    fn postfix(&mut self) -> Expr {
+        let mut expr = self.class();
+
+        loop {
+            expr = if self.eat(&Tok::Op('?')) {
+                Expr::Opt(Box::new(expr))
+            } else if self.eat(&Tok::Op('*')) {
+                Expr::Rep(Box::new(expr), false)
+            } else if self.eat(&Tok::Op('+')) {
+                Expr::Rep(Box::new(expr), true)
+            } else {
+                return expr;
+            };
+        }
+    }
+
+    // `a - b` and `a & b` read like `&b ~ a`: `b` is looked at, and `a` matched
+    fn class(&mut self) -> Expr {
+        let mut expr = self.primary();
+
+        while self.eat(&Tok::Op('-')) || self.eat(&Tok::Op('&')) {
+            let other = self.primary();
+
+            expr = Expr::Seq(vec![Expr::Look(Box::new(other)), expr]);
+        }
+
+        expr
+    }
+
+    fn primary(&mut self) -> Expr {
+        let token = self.tokens.get(self.pos).cloned();
+
+        self.pos += 1;
+
+        match token {
+            Some(Tok::Ident(name @ "nested_comment")) |
+            Some(Tok::Ident(name @ "heredoc"))        |
+            Some(Tok::Ident(name @ "with_prefix")) if self.eat(&Tok::Open('(')) => {
+                while self.pos < self.tokens.len() && self.tokens[self.pos] != Tok::Close(')') {
+                    self.pos += 1;
+                }
+
+                self.pos += 1;
+
+                Expr::Builtin(name)
+            },
+            Some(Tok::Ident(name)) => Expr::Rule(name),
+            Some(Tok::Open('('))   => {
+                let expr = self.choice();
+
+                if self.eat(&Tok::Close(')')) {
+                    expr
+                } else {
+                    Expr::Unknown
+                }
+            },
+            Some(Tok::Open('['))   => {
+                let start = self.pos;
+
+                while self.pos < self.tokens.len() && self.tokens[self.pos] != Tok::Close(']') {
+                    self.pos += 1;
+                }
+
+                self.pos += 1;
+
+                match self.tokens.get(start..self.pos - 1) {
+                    Some([Tok::Str(string)])                       => Expr::Str(string.clone()),
+                    Some([Tok::Char(_), Tok::DotDot, Tok::Char(c)]) |
+                    Some([Tok::Char(_), Tok::Op(','), Tok::Char(c)]) => Expr::Range(c.len_utf8()),
+                    _                                              => Expr::Unknown
+                }
+            },
+            _                      => Expr::Unknown
+        }
+    }
+}
+
+// what matching an expression can do: `reach` is how far it can read when matching, `miss` how
+// far when failing, and `bound` how far it can backtrack
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Summary {
+    reach: Bound,
+    miss:  Bound,
+    bound: Bound,
+    fails: bool
+}
+
+impl Summary {
+    fn new(reach: usize, fails: bool) -> Summary {
+        Summary {
+            reach: Bound::Bytes(reach),
+            miss:  Bound::Bytes(0),
+            bound: Bound::Bytes(0),
+            fails
+        }
+    }
+
+    fn unknown() -> Summary {
+        Summary {
+            reach: Bound::Unbounded,
+            miss:  Bound::Unbounded,
+            bound: Bound::Unbounded,
+            fails: true
+        }
+    }
+
+    fn max(self, other: Summary) -> Summary {
+        Summary {
+            reach: cmp::max(self.reach, other.reach),
+            miss:  cmp::max(self.miss, other.miss),
+            bound: cmp::max(self.bound, other.bound),
+            fails: self.fails
+        }
+    }
+}
+
+const TRIVIA: [&str; 3] = ["whitespace", "newline", "comment"];
+
+struct Analysis {
+    names:     Vec<&'static str>,
+    rules:     HashMap<&'static str, (Expr, bool)>,
+    summaries: HashMap<&'static str, Summary>,
+    trivia:    bool
+}
+
+impl Analysis {
+    fn new(alternatives: &[Alternative], atomic: &[&str]) -> Analysis {
+        let mut names = vec![];
+        let mut bodies: HashMap<&'static str, Vec<Expr>> = HashMap::new();
+
+        for alternative in alternatives {
+            if !bodies.contains_key(alternative.rule) {
+                names.push(alternative.rule);
+            }
+
+            bodies.entry(alternative.rule).or_default().push(ExprParser::parse(alternative.text));
+        }
+
+        let rules = bodies.into_iter().map(|(name, mut alternatives)| {
+            let expr = if alternatives.len() == 1 {
+                alternatives.pop().unwrap()
+            } else {
+                Expr::Choice(alternatives)
+            };
+
+            (name, (expr, TRIVIA[..2].contains(&name) || atomic.contains(&name)))
+        }).collect();
+
+        let summaries = names.iter().map(|&name| (name, Summary::new(0, true))).collect();
+        let trivia = names.iter().any(|name| TRIVIA.contains(name));
+
+        Analysis {
+            names,
+            rules,
+            summaries,
+            trivia
+        }
+    }
+
+    // the bounds only grow, so they are iterated to a fixed point, like in Bellman-Ford; bounds
+    // that still grow after every rule had the chance to contribute once are on a growing cycle
+    fn run(&mut self) {
+        loop {
+            let fails: Vec<_> = self.names.iter().map(|&name| {
+                let (ref expr, atomic) = self.rules[name];
+
+                (name, self.summarize(expr, atomic).fails)
+            }).collect();
+
+            let mut changed = false;
+
+            for (name, fails) in fails {
+                let summary = self.summaries.get_mut(name).unwrap();
+
+                changed |= summary.fails != fails;
+                summary.fails = fails;
+            }
+
+            if !changed {
+                break;
+            }
+        }
+
+        loop {
+            let mut growing = vec![];
+
+            for _ in 0..self.names.len() + 1 {
+                growing = self.round();
+
+                if growing.is_empty() {
+                    return;
+                }
+            }
+
+            for name in growing {
+                let summary = self.summaries.get_mut(name).unwrap();
+                let fails = summary.fails;
+
+                *summary = Summary::unknown();
+                summary.fails = fails;
+            }
+        }
+    }
+
+    fn round(&mut self) -> Vec<&'static str> {
+        let summaries: Vec<_> = self.names.iter().map(|&name| {
+            let (ref expr, atomic) = self.rules[name];
+
+            (name, self.summarize(expr, atomic))
+        }).collect();
+
+        let mut growing = vec![];
+
+        for (name, summary) in summaries {
+            let old = self.summaries[name];
+            let new = old.max(summary);
+
+            if new != old {
+                growing.push(name);
+                self.summaries.insert(name, new);
+            }
+        }
+
+        growing
+    }
+
+    fn summarize(&self, expr: &Expr, atomic: bool) -> Summary {
+        let trivia = if self.trivia && !atomic {
+            Bound::Unbounded
+        } else {
+            Bound::Bytes(0)
+        };
+
+        match *expr {
+            Expr::Str(ref string)         => Summary::new(string.len(), !string.is_empty()),
+            Expr::Range(len)              => Summary::new(len, true),
+            // rules of the grammar take precedence over predefined ones of the same name
+            Expr::Rule(name) if self.summaries.contains_key(name) => {
+                let summary = self.summaries[name];
+
+                if TRIVIA.contains(&name) {
+                    summary
+                } else {
+                    Summary {
+                        reach: trivia.plus(summary.reach),
+                        miss:  trivia.plus(summary.miss),
+                        bound: summary.bound,
+                        fails: summary.fails
+                    }
+                }
+            },
+            Expr::Rule("any")             => Summary::new(4, true),
+            Expr::Rule("any_byte")        |
+            Expr::Rule("any_ascii")       => Summary::new(1, true),
+            // line endings also skip line prefixes, which are only known at runtime
+            Expr::Rule("line_ending")     => {
+                Summary {
+                    reach: Bound::Unbounded,
+                    ..Summary::new(2, true)
+                }
+            },
+            Expr::Rule("eoi")             |
+            Expr::Rule("terminator")      |
+            Expr::Rule("prefixed")        => Summary::new(0, true),
+            Expr::Rule("prolog")          => {
+                Summary {
+                    reach: Bound::Unbounded,
+                    ..Summary::new(0, false)
+                }
+            },
+            Expr::Rule(name) if TRIVIA.contains(&name) => Summary::new(0, true),
+            Expr::Rule(_)                 => Summary::unknown(),
+            Expr::Seq(ref terms)          => {
+                let terms: Vec<_> = terms.iter().map(|term| self.summarize(term, atomic)).collect();
+
+                let mut reach = Bound::Bytes(0);
+                let mut miss = Bound::Bytes(0);
+                let mut bound = trivia;
+
+                for (i, term) in terms.iter().enumerate() {
+                    if i > 0 {
+                        reach = reach.plus(trivia);
+
+                        if term.fails {
+                            bound = cmp::max(bound, reach.plus(term.miss));
+                        }
+                    }
+
+                    if term.fails {
+                        miss = cmp::max(miss, reach.plus(term.miss));
+                    }
+
+                    reach = reach.plus(term.reach);
+                    bound = cmp::max(bound, term.bound);
+                }
+
+                Summary {
+                    reach,
+                    miss,
+                    bound,
+                    fails: terms.iter().any(|term| term.fails)
+                }
+            },
+            Expr::Choice(ref alternatives) => {
+                alternatives.iter().map(|alternative| self.summarize(alternative, atomic)).fold(
+                    Summary { fails: true, ..Summary::new(0, true) },
+                    |summary, alternative| {
+                        Summary {
+                            fails: summary.fails && alternative.fails,
+                            ..summary.max(alternative)
+                        }
+                    }
+                )
+            },
+            Expr::Opt(ref expr)           => {
+                Summary {
+                    miss:  Bound::Bytes(0),
+                    fails: false,
+                    ..self.summarize(expr, atomic)
+                }
+            },
+            Expr::Rep(ref expr, once)     => {
+                let summary = self.summarize(expr, atomic);
+                let bound = if atomic {
+                    summary.bound
+                } else {
+                    cmp::max(summary.bound, trivia.plus(summary.miss))
+                };
+
+                Summary {
+                    reach: if summary.reach == Bound::Bytes(0) {
+                        summary.reach
+                    } else {
+                        Bound::Unbounded
+                    },
+                    miss:  if once { summary.miss } else { Bound::Bytes(0) },
+                    bound,
+                    fails: once && summary.fails
+                }
+            },
+            Expr::Look(ref expr)          => {
+                let summary = self.summarize(expr, atomic);
+
+                Summary {
+                    reach: summary.reach,
+                    miss:  summary.reach,
+                    bound: cmp::max(summary.bound, summary.reach),
+                    fails: true
+                }
+            },
+            // built-ins with arguments may scan to the end of the input and rewind
+            Expr::Builtin(_)              |
+            Expr::Unknown                 => Summary::unknown()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bounds(rules: &[(&'static str, &'static str)], atomic: &[&str]) -> Vec<Bound> {
+        let alternatives: Vec<_> = rules.iter().map(|&(rule, text)| {
+            Alternative { rule, index: 0, text }
+        }).collect();
+        let bounds = BacktrackBounds::new(&alternatives, atomic);
+
+        bounds.rules().iter().map(|&(_, bound)| bound).collect()
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(ExprParser::parse("[\"ab\"] ~ (a | ['a'..'\\u{e9}'])* ~ !b?"), Expr::Seq(vec![
+            Expr::Str("ab".to_owned()),
+            Expr::Rep(Box::new(Expr::Choice(vec![Expr::Rule("a"), Expr::Range(2)])), false),
+            Expr::Look(Box::new(Expr::Opt(Box::new(Expr::Rule("b")))))
+        ]));
+        assert_eq!(ExprParser::parse("[\"\\\"\\x41\"]"), Expr::Str("\"A".to_owned()));
+        assert_eq!(ExprParser::parse("nested_comment(\"/*\", \"*/\") | [\"#\"]"),
+                   Expr::Choice(vec![Expr::Builtin("nested_comment"),
+                                     Expr::Str("#".to_owned())]));
+        assert_eq!(ExprParser::parse("(['a'..'z'] - [\"q\"])+"), Expr::Rep(Box::new(Expr::Seq(vec![
+            Expr::Look(Box::new(Expr::Str("q".to_owned()))),
+            Expr::Range(1)
+        ])), true));
+        assert_eq!(ExprParser::parse("[KEYWORD]"), Expr::Unknown);
+        assert_eq!(ExprParser::parse("{ a } b = { c }"), Expr::Unknown);
+    }
+
+    #[test]
+    fn recursion() {
+        let rules = [
+            ("nested", "[\"(\"] ~ nested? ~ [\")\"]"),
+            ("cycle", "[\"a\"] | cycle ~ [\"b\"]"),
+            ("user", "[\"x\"]? ~ [\"y\"]")
+        ];
+
+        assert_eq!(bounds(&rules, &["nested", "cycle", "user"]), vec![
+            Bound::Unbounded,
+            Bound::Unbounded,
+            Bound::Bytes(1)
+        ]);
+    }
+
+    #[test]
+    fn trivia() {
+        let rules = [
+            ("pair", "[\"a\"] ~ [\"b\"]"),
+            ("single", "[\"a\"]"),
+            ("whitespace", "[\" \"]")
+        ];
+
+        assert_eq!(bounds(&rules, &[]), vec![Bound::Unbounded, Bound::Bytes(0), Bound::Bytes(0)]);
+        assert_eq!(bounds(&rules, &["pair"])[0], Bound::Bytes(1));
+    }
+}
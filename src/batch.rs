@@ -0,0 +1,241 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::cmp;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// An `enum` describing why an input of a `BatchParser` failed.
+#[derive(Debug)]
+pub enum BatchError<E> {
+    /// the input file could not be read
+    Io(io::Error),
+    /// the input did not parse
+    Parse(E)
+}
+
+/// A `struct` holding the outcome of parsing one input of a `BatchParser`.
+#[derive(Debug)]
+pub struct BatchEntry<E> {
+    /// path of the file or index of the `&str`
+    pub name:     String,
+    /// number of `Token`s or error
+    pub result:   Result<usize, BatchError<E>>,
+    /// time spent reading and parsing
+    pub duration: Duration
+}
+
+/// A `struct` aggregating the `BatchEntry`s of a `BatchParser` run, in input order.
+#[derive(Debug)]
+pub struct BatchReport<E> {
+    /// outcome of every input
+    pub entries:  Vec<BatchEntry<E>>,
+    /// wall-clock time of the whole run
+    pub duration: Duration
+}
+
+impl<E> BatchReport<E> {
+    /// Returns the `BatchEntry`s that parsed.
+    pub fn succeeded(&self) -> Vec<&BatchEntry<E>> {
+        self.entries.iter().filter(|entry| entry.result.is_ok()).collect()
+    }
+
+    /// Returns the `BatchEntry`s that failed.
+    pub fn failed(&self) -> Vec<&BatchEntry<E>> {
+        self.entries.iter().filter(|entry| entry.result.is_err()).collect()
+    }
+
+    /// Returns the total number of `Token`s of all inputs that parsed.
+    pub fn tokens(&self) -> usize {
+        self.entries.iter().filter_map(|entry| entry.result.as_ref().ok()).sum()
+    }
+
+    /// Returns whether all inputs parsed.
+    pub fn is_success(&self) -> bool {
+        self.entries.iter().all(|entry| entry.result.is_ok())
+    }
+}
+
+/// A `struct` that parses many inputs with the same `parse` closure, optionally on several
+/// threads, and aggregates the results in a `BatchReport`. Useful for validating a grammar
+/// against a corpus.
+///
+/// `parse` creates a `Parser` for every input, matches the start rule, and returns either the
+/// number of `Token`s or an error.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::BatchParser;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         number = @{ ['0'..'9']+ ~ eoi }
+///     }
+/// }
+///
+/// let batch = BatchParser::new(|input: &str| {
+///     let mut parser = Rdp::new(StringInput::new(input));
+///
+///     if parser.number() {
+///         Ok(parser.queue().len())
+///     } else {
+///         Err(parser.expected())
+///     }
+/// }).threads(2);
+///
+/// let report = batch.parse_strs(&["12", "3", "a"]);
+///
+/// assert_eq!(report.succeeded().len(), 2);
+/// assert_eq!(report.failed()[0].name, "2");
+/// assert_eq!(report.tokens(), 2);
+/// # }
+/// ```
+pub struct BatchParser<F> {
+    parse:   F,
+    threads: usize
+}
+
+impl<F, E> BatchParser<F> where F: Fn(&str) -> Result<usize, E> + Sync, E: Send {
+    /// Creates a single-threaded `BatchParser`.
+    pub fn new(parse: F) -> BatchParser<F> {
+        BatchParser {
+            parse,
+            threads: 1
+        }
+    }
+
+    /// Sets the number of threads used for parsing.
+    pub fn threads(mut self, threads: usize) -> BatchParser<F> {
+        self.threads = cmp::max(threads, 1);
+
+        self
+    }
+
+    /// Parses every `&str` in `inputs`. Entries are named after their index.
+    pub fn parse_strs(&self, inputs: &[&str]) -> BatchReport<E> {
+        self.run(inputs.len(), |i| {
+            (i.to_string(), (self.parse)(inputs[i]).map_err(BatchError::Parse))
+        })
+    }
+
+    /// Reads and parses every file in `paths`. Entries are named after their path.
+    pub fn parse_files<P>(&self, paths: &[P]) -> BatchReport<E> where P: AsRef<Path> + Sync {
+        self.run(paths.len(), |i| {
+            let path = paths[i].as_ref();
+            let result = match fs::read_to_string(path) {
+                Ok(string) => (self.parse)(&string).map_err(BatchError::Parse),
+                Err(error) => Err(BatchError::Io(error))
+            };
+
+            (path.display().to_string(), result)
+        })
+    }
+
+    fn run<G>(&self, len: usize, parse: G) -> BatchReport<E>
+        where G: Fn(usize) -> (String, Result<usize, BatchError<E>>) + Sync {
+
+        let start = Instant::now();
+        let next = AtomicUsize::new(0);
+        let entries = Mutex::new((0..len).map(|_| None).collect::<Vec<_>>());
+
+        let work = || {
+            loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+
+                if i >= len {
+                    break
+                }
+
+                let entry_start = Instant::now();
+                let (name, result) = parse(i);
+
+                entries.lock().unwrap()[i] = Some(BatchEntry {
+                    name,
+                    result,
+                    duration: entry_start.elapsed()
+                });
+            }
+        };
+
+        if self.threads == 1 {
+            work();
+        } else {
+            thread::scope(|scope| {
+                for _ in 0..cmp::min(self.threads, len) {
+                    scope.spawn(work);
+                }
+            });
+        }
+
+        BatchReport {
+            entries:  entries.into_inner().unwrap().into_iter().map(Option::unwrap).collect(),
+            duration: start.elapsed()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digits(input: &str) -> Result<usize, usize> {
+        match input.find(|c: char| !c.is_ascii_digit()) {
+            Some(pos) => Err(pos),
+            None      => Ok(input.len())
+        }
+    }
+
+    #[test]
+    fn strs() {
+        let inputs: Vec<String> = (0..100).map(|i| {
+            if i % 10 == 0 { format!("{}a", i) } else { i.to_string() }
+        }).collect();
+        let inputs: Vec<&str> = inputs.iter().map(|s| s.as_str()).collect();
+
+        for &threads in &[1, 4] {
+            let report = BatchParser::new(digits).threads(threads).parse_strs(&inputs);
+
+            assert_eq!(report.entries.len(), 100);
+            assert_eq!(report.failed().len(), 10);
+            assert_eq!(report.entries[20].name, "20");
+            assert!(!report.is_success());
+
+            match report.entries[20].result {
+                Err(BatchError::Parse(2)) => (),
+                _                         => panic!("expected parse error")
+            }
+        }
+    }
+
+    #[test]
+    fn files() {
+        let dir = ::std::env::temp_dir();
+        let path = dir.join("pest_batch_files_test");
+
+        fs::write(&path, "123").unwrap();
+
+        let report = BatchParser::new(digits).parse_files(&[path.clone(),
+                                                             dir.join("pest_batch_missing")]);
+
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(report.tokens(), 3);
+
+        match report.entries[1].result {
+            Err(BatchError::Io(_)) => (),
+            _                      => panic!("expected io error")
+        }
+    }
+}
@@ -0,0 +1,407 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A `mod` implementing a command-line runner for any
+//! [`GeneratedParser`](../trait.GeneratedParser), enabled with the `cli` feature. A grammar crate
+//! only needs a two-line binary to get it:
+//!
+//! ```ignore
+//! fn main() {
+//!     pest::cli::main::<Rdp<StringInput>>();
+//! }
+//! ```
+//!
+//! The runner understands the following subcommands, where `-` reads the input from stdin:
+//!
+//! * `rules` - lists all rules; operators of precedence climbing rules are marked as such
+//! * `parse <rule> <file>` - dumps the tree of `Token`s with their text
+//! * `errors <rule> <file>` - prints `<file>:<line>:<col>` of the error, followed by a note
+//!   pointing at an unclosed delimiter if there is one, or `ok`
+//! * `errors --json <rule> <file>` - prints the errors as JSON, see
+//!   [`Messages::to_json`](../struct.Messages#method.to_json)
+//! * `bench <rule> <file> [iterations]` - times parsing, `100` iterations by default
+//! * `trace <rule> <file>` - prints where every `Token` starts and ends, in order
+//! * `expand <file>` - writes an outline of the generated parser to `<file>`, or prints it for
+//!   `-`, see [`GeneratedParser::expansion`](../trait.GeneratedParser#method.expansion)
+//!
+//! Every subcommand that parses exits with `1` if the input does not completely match.
+
+use std::env;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process;
+use std::time::Instant;
+
+use super::{Event, Events, GeneratedParser, Messages, ParseResult};
+
+const USAGE: &str = "usage: <rules | parse | errors | trace> [rule file] | errors --json rule \
+                     file | bench rule file [iterations] | expand file";
+
+/// Runs the command-line runner with the process' arguments and exits with its exit code.
+pub fn main<P: GeneratedParser>() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+    let stdout = io::stdout();
+
+    let code = match run::<P, _>(&args, &mut stdout.lock()) {
+        Ok(code)   => code,
+        Err(error) => {
+            eprintln!("{}", error);
+
+            2
+        }
+    };
+
+    process::exit(code);
+}
+
+/// Runs the subcommand in `args`, without the program's name, writes its output to `out`, and
+/// returns the exit code. Errors are returned for invalid arguments and failed I/O.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         word = @{ ['a'..'z']+ }
+///     }
+/// }
+///
+/// let mut out = vec![];
+///
+/// assert_eq!(pest::cli::run::<Rdp<StringInput>, _>(&["rules"], &mut out).unwrap(), 0);
+/// assert!(String::from_utf8(out).unwrap().contains("word\n"));
+/// # }
+/// ```
+pub fn run<P, W>(args: &[&str], out: &mut W) -> Result<i32, String>
+    where P: GeneratedParser, W: Write {
+
+    match args {
+        ["rules"]                           => rules::<P, W>(out).map(|_| 0),
+        ["parse", rule, file]               => parse::<P, W>(rule, file, out),
+        ["errors", rule, file]              => errors::<P, W>(rule, file, false, out),
+        ["errors", "--json", rule, file]    => errors::<P, W>(rule, file, true, out),
+        ["trace", rule, file]               => trace::<P, W>(rule, file, out),
+        ["expand", file]                    => expand::<P, W>(file, out).map(|_| 0),
+        ["bench", rule, file]               => bench::<P, W>(rule, file, 100, out),
+        ["bench", rule, file, iterations]   => {
+            let iterations = iterations.parse().map_err(|_| {
+                format!("invalid number of iterations: {}", iterations)
+            })?;
+
+            bench::<P, W>(rule, file, iterations, out)
+        },
+        _                                   => Err(USAGE.to_owned())
+    }
+}
+
+fn rule<P: GeneratedParser>(name: &str) -> Result<P::Rule, String> {
+    let rule = P::rules().iter().cloned().find(|&rule| P::rule_name(rule) == name);
+
+    match rule {
+        Some(rule) if P::is_callable(rule) => Ok(rule),
+        Some(_)                            => Err(format!("rule {} is an operator", name)),
+        None                               => Err(format!("unknown rule {}", name))
+    }
+}
+
+// a &str, and thus every position in it, must fit into isize, which on 32-bit targets is
+// smaller than many files
+fn fits(file: &str, len: u64) -> Result<(), String> {
+    if len > isize::MAX as u64 {
+        Err(format!("{}: input of {} bytes is too large for this target", file, len))
+    } else {
+        Ok(())
+    }
+}
+
+// reads at most limit bytes, since unlike the length of a file, the length of stdin is only known
+// after reading all of it
+fn read_to_limit<R: Read>(file: &str, reader: R, limit: u64) -> Result<String, String> {
+    let error = |error: io::Error| format!("{}: {}", file, error);
+    let mut input = String::new();
+    let mut reader = reader.take(limit);
+
+    reader.read_to_string(&mut input).map_err(error)?;
+
+    if reader.into_inner().read(&mut [0]).map_err(error)? > 0 {
+        return Err(format!("{}: input of more than {} bytes is too large for this target", file,
+                           limit));
+    }
+
+    Ok(input)
+}
+
+fn read(file: &str) -> Result<String, String> {
+    let limit = isize::MAX as u64;
+
+    if file == "-" {
+        read_to_limit(file, io::stdin(), limit)
+    } else {
+        let handle = fs::File::open(file).map_err(|error| format!("{}: {}", file, error))?;
+        let len = handle.metadata().map_err(|error| format!("{}: {}", file, error))?.len();
+
+        fits(file, len)?;
+
+        read_to_limit(file, handle, limit)
+    }
+}
+
+fn write_err(error: io::Error) -> String {
+    error.to_string()
+}
+
+fn code<R>(result: &ParseResult<R>) -> i32 {
+    if result.is_complete() {
+        0
+    } else {
+        1
+    }
+}
+
+fn rules<P: GeneratedParser, W: Write>(out: &mut W) -> Result<(), String> {
+    for &rule in P::rules() {
+        if P::is_callable(rule) {
+            writeln!(out, "{}", P::rule_name(rule)).map_err(write_err)?;
+        } else {
+            writeln!(out, "{} (operator)", P::rule_name(rule)).map_err(write_err)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn parse<P: GeneratedParser, W: Write>(rule: &str, file: &str, out: &mut W)
+                                       -> Result<i32, String> {
+    let rule = self::rule::<P>(rule)?;
+    let input = read(file)?;
+    let result = P::parse(rule, &input);
+    let mut depth = 0;
+
+    for event in Events::new(&result.tokens) {
+        match event {
+            Event::Start(token) => {
+                writeln!(out, "{:indent$}{} {}..{} {:?}", "", P::rule_name(token.rule),
+                         token.start, token.end, result.slice(&token), indent = depth * 2)
+                    .map_err(write_err)?;
+
+                depth += 1;
+            },
+            Event::End(_)       => depth -= 1
+        }
+    }
+
+    if !result.is_complete() {
+        error::<P, W>(file, &result, out)?;
+    }
+
+    Ok(code(&result))
+}
+
+fn error<P: GeneratedParser, W: Write>(file: &str, result: &ParseResult<P::Rule>, out: &mut W)
+                                       -> Result<(), String> {
+    for (i, (pos, message)) in Messages::new().render::<P>(result).into_iter().enumerate() {
+        let (line, col) = result.line_col(pos);
+        let note = if i > 0 { "note: " } else { "" };
+
+        writeln!(out, "{}:{}:{}: {}{}", file, line, col, note, message).map_err(write_err)?;
+    }
+
+    Ok(())
+}
+
+fn errors<P: GeneratedParser, W: Write>(rule: &str, file: &str, json: bool, out: &mut W)
+                                        -> Result<i32, String> {
+    let rule = self::rule::<P>(rule)?;
+    let input = read(file)?;
+    let result = P::parse(rule, &input);
+
+    if json {
+        writeln!(out, "{}", Messages::new().to_json::<P>(file, &result)).map_err(write_err)?;
+    } else if result.is_complete() {
+        writeln!(out, "ok").map_err(write_err)?;
+    } else {
+        error::<P, W>(file, &result, out)?;
+    }
+
+    Ok(code(&result))
+}
+
+fn expand<P: GeneratedParser, W: Write>(file: &str, out: &mut W) -> Result<(), String> {
+    let expansion = P::expansion();
+
+    if file == "-" {
+        out.write_all(expansion.as_bytes()).map_err(write_err)
+    } else {
+        fs::write(file, expansion).map_err(|error| format!("{}: {}", file, error))
+    }
+}
+
+fn trace<P: GeneratedParser, W: Write>(rule: &str, file: &str, out: &mut W)
+                                       -> Result<i32, String> {
+    let rule = self::rule::<P>(rule)?;
+    let input = read(file)?;
+    let result = P::parse(rule, &input);
+
+    for event in Events::new(&result.tokens) {
+        let (mark, token, pos) = match event {
+            Event::Start(token) => (">", token, token.start),
+            Event::End(token)   => ("<", token, token.end)
+        };
+        let (line, col) = result.line_col(pos);
+
+        writeln!(out, "{} {} {}:{}", mark, P::rule_name(token.rule), line, col)
+            .map_err(write_err)?;
+    }
+
+    if !result.is_complete() {
+        error::<P, W>(file, &result, out)?;
+    }
+
+    Ok(code(&result))
+}
+
+fn bench<P: GeneratedParser, W: Write>(rule: &str, file: &str, iterations: u32, out: &mut W)
+                                       -> Result<i32, String> {
+    let rule = self::rule::<P>(rule)?;
+    let input = read(file)?;
+    let start = Instant::now();
+    let mut result = None;
+
+    for _ in 0..iterations {
+        result = Some(P::parse(rule, &input));
+    }
+
+    let elapsed = start.elapsed();
+    let per_iteration = elapsed / iterations.max(1);
+    let throughput = input.len() as f64 / per_iteration.as_secs_f64().max(1e-9) / 1e6;
+
+    writeln!(out, "{} iterations in {:?}, {:?} per iteration, {:.2} MB/s", iterations, elapsed,
+             per_iteration, throughput).map_err(write_err)?;
+
+    Ok(result.as_ref().map_or(0, code))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::StringInput;
+    use super::{fits, read_to_limit, run};
+
+    impl_rdp! {
+        grammar! {
+            list   = { ["("] ~ (number ~ ([","] ~ number)*)? ~ [")"] }
+            number = @{ ['0'..'9']+ }
+        }
+    }
+
+    fn output(args: &[&str]) -> (Result<i32, String>, String) {
+        let mut out = vec![];
+        let result = run::<Rdp<StringInput>, _>(args, &mut out);
+
+        (result, String::from_utf8(out).unwrap())
+    }
+
+    fn file(name: &str, content: &str) -> String {
+        let path = ::std::env::temp_dir().join(format!("pest-cli-{}", name));
+
+        ::std::fs::write(&path, content).unwrap();
+
+        path.to_str().unwrap().to_owned()
+    }
+
+    #[test]
+    fn parse() {
+        let path = file("parse", "(1,23)");
+
+        assert_eq!(output(&["parse", "list", &path]),
+                   (Ok(0), "list 0..6 \"(1,23)\"\n  number 1..2 \"1\"\n  number 3..5 \"23\"\n"
+                       .to_owned()));
+    }
+
+    #[test]
+    fn errors() {
+        let path = file("errors", "(1,\n)");
+
+        assert_eq!(output(&["errors", "list", &path]),
+                   (Ok(1), format!("{}:1:4: expected number\n", path)));
+    }
+
+    #[test]
+    fn unclosed() {
+        let path = file("unclosed", "(1,2");
+
+        assert_eq!(output(&["errors", "list", &path]),
+                   (Ok(1), format!("{0}:1:1: expected list\n\
+                                    {0}:1:1: note: unclosed \"(\" opened here\n", path)));
+    }
+
+    #[test]
+    fn json() {
+        let path = file("json", "(1,2");
+        let escaped = path.replace('\\', "\\\\");
+
+        assert_eq!(output(&["errors", "--json", "list", &path]),
+                   (Ok(1), format!("{{\"file\":\"{}\",\"errors\":[{{\"severity\":\"error\",\
+                                    \"message\":\"expected list\",\"start\":0,\"end\":0,\
+                                    \"line\":1,\"column\":1,\"excerpt\":\"(1,2\",\
+                                    \"expected\":[{{\"rule\":\"list\",\"label\":\"list\"}}]}},\
+                                    {{\"severity\":\"note\",\"message\":\
+                                    \"unclosed \\\"(\\\" opened here\",\"start\":0,\"end\":1,\
+                                    \"line\":1,\"column\":1,\"excerpt\":\"(1,2\"}}]}}\n",
+                                   escaped)));
+
+        let path = file("json-ok", "()");
+
+        assert_eq!(output(&["errors", "--json", "list", &path]),
+                   (Ok(0), format!("{{\"file\":\"{}\",\"errors\":[]}}\n",
+                                   path.replace('\\', "\\\\"))));
+    }
+
+    #[test]
+    fn trace() {
+        let path = file("trace", "(1)");
+
+        assert_eq!(output(&["trace", "list", &path]),
+                   (Ok(0), "> list 1:1\n> number 1:2\n< number 1:3\n< list 1:4\n".to_owned()));
+    }
+
+    #[test]
+    fn expand() {
+        let path = file("expand", "");
+
+        assert_eq!(output(&["expand", &path]), (Ok(0), String::new()));
+        assert_eq!(::std::fs::read_to_string(&path).unwrap(), output(&["expand", "-"]).1);
+        assert!(output(&["expand", "-"]).1.contains("\n    // atomic\n    \
+                                                     // number = ['0'..'9']+\n    \
+                                                     pub fn number(&mut self) -> bool;\n"));
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(output(&["parse", "item", "-"]).0, Err("unknown rule item".to_owned()));
+        assert_eq!(output(&["bench", "list", "-", "x"]).0,
+                   Err("invalid number of iterations: x".to_owned()));
+        assert!(output(&[]).0.is_err());
+    }
+
+    #[test]
+    fn too_large() {
+        assert_eq!(fits("f", 1 << 20), Ok(()));
+        assert_eq!(fits("f", u64::MAX),
+                   Err(format!("f: input of {} bytes is too large for this target", u64::MAX)));
+    }
+
+    #[test]
+    fn too_large_stream() {
+        assert_eq!(read_to_limit("-", &b"abc"[..], 3), Ok("abc".to_owned()));
+        assert_eq!(read_to_limit("-", &b"abc"[..], 2),
+                   Err("-: input of more than 2 bytes is too large for this target".to_owned()));
+    }
+}
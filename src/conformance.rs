@@ -0,0 +1,261 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Checks behind [`input_conformance_tests!`](../macro.input_conformance_tests!). Every check runs
+//! against all `SAMPLES`, at every `char` boundary.
+
+use super::Input;
+
+/// `&str`s every check runs against, covering empty input, multi-byte `char`s, and all line
+/// endings.
+pub const SAMPLES: &[&str] = &[
+    "",
+    "a",
+    "abc def",
+    "aß€𝄞b",
+    "a\nb\r\nc\rd\n\r\n",
+    "\r\n\r\n",
+    "€\n𝄞\r\nß"
+];
+
+fn boundaries(string: &str) -> Vec<usize> {
+    string.char_indices().map(|(i, _)| i).chain(Some(string.len())).collect()
+}
+
+// a char with the same UTF-8 length as c, but different from it
+fn other(c: char) -> char {
+    let candidates = match c.len_utf8() {
+        1 => ['a', 'b'],
+        2 => ['ß', 'é'],
+        3 => ['€', '₤'],
+        _ => ['𝄞', '😀']
+    };
+
+    if c == candidates[0] { candidates[1] } else { candidates[0] }
+}
+
+// line and column of pos, where "\r\n" and a lone '\r' are both a single line ending
+fn line_col(string: &str, pos: usize) -> (usize, usize) {
+    let mut line_col = (1, 1);
+    let mut chars = string[..pos].chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '\r' && chars.peek() == Some(&'\n') {
+            chars.next();
+        }
+
+        line_col = if c == '\r' || c == '\n' {
+            (line_col.0 + 1, 1)
+        } else {
+            (line_col.0, line_col.1 + 1)
+        };
+    }
+
+    line_col
+}
+
+/// Checks `len`, `is_empty`, `start`, and the initial `pos`.
+pub fn check_len<I: Input>(new: &dyn Fn(&'static str) -> I) {
+    for &string in SAMPLES {
+        let input = new(string);
+
+        assert_eq!(input.len(), string.len(), "len of {:?}", string);
+        assert_eq!(input.is_empty(), string.is_empty(), "is_empty of {:?}", string);
+        assert_eq!(input.start(), 0, "start of {:?}", string);
+        assert_eq!(input.pos(), 0, "initial pos of {:?}", string);
+    }
+}
+
+/// Checks that `set_pos` moves to every `char` boundary and `advance_by_chars` moves over every
+/// `char`.
+pub fn check_pos<I: Input>(new: &dyn Fn(&'static str) -> I) {
+    for &string in SAMPLES {
+        let mut input = new(string);
+        let boundaries = boundaries(string);
+
+        for &pos in boundaries.iter().rev() {
+            input.set_pos(pos);
+
+            assert_eq!(input.pos(), pos, "set_pos({}) in {:?}", pos, string);
+        }
+
+        for (n, &pos) in boundaries.iter().enumerate().skip(1) {
+            input.set_pos(0);
+
+            assert_eq!(input.advance_by_chars(n), n, "advance_by_chars({}) in {:?}", n, string);
+            assert_eq!(input.pos(), pos, "advance_by_chars({}) in {:?}", n, string);
+        }
+
+        assert_eq!(input.advance_by_chars(1), 0, "advance_by_chars(1) at the end of {:?}", string);
+    }
+}
+
+/// Checks `slice` between every pair of `char` boundaries.
+pub fn check_slice<I: Input>(new: &dyn Fn(&'static str) -> I) {
+    for &string in SAMPLES {
+        let input = new(string);
+        let boundaries = boundaries(string);
+
+        for &start in &boundaries {
+            for &end in boundaries.iter().filter(|&&end| end >= start) {
+                assert_eq!(input.slice(start, end), &string[start..end],
+                           "slice({}, {}) of {:?}", start, end, string);
+            }
+        }
+    }
+}
+
+/// Checks that `match_string` matches every substring at its start and advances to its end, and
+/// that a failed match does not move.
+pub fn check_match_string<I: Input>(new: &dyn Fn(&'static str) -> I) {
+    for &string in SAMPLES {
+        let mut input = new(string);
+        let boundaries = boundaries(string);
+
+        for &start in &boundaries {
+            input.set_pos(start);
+
+            assert!(input.match_string(""), "empty match at {} in {:?}", start, string);
+            assert_eq!(input.pos(), start, "empty match at {} in {:?}", start, string);
+
+            for &end in boundaries.iter().filter(|&&end| end >= start) {
+                let substring = &string[start..end];
+
+                input.set_pos(start);
+
+                assert!(input.match_string(substring), "match of {:?} at {} in {:?}", substring,
+                        start, string);
+                assert_eq!(input.pos(), end, "match of {:?} at {} in {:?}", substring, start,
+                           string);
+
+                let failing = format!("{}\u{0}", substring);
+
+                input.set_pos(start);
+
+                assert!(!input.match_string(&failing), "match of {:?} at {} in {:?}", failing,
+                        start, string);
+                assert_eq!(input.pos(), start, "failed match of {:?} at {} in {:?}", failing,
+                           start, string);
+            }
+        }
+    }
+}
+
+/// Checks that `match_range` matches every `char` and advances over it, and that it neither
+/// matches past the end nor moves when it fails.
+pub fn check_match_range<I: Input>(new: &dyn Fn(&'static str) -> I) {
+    for &string in SAMPLES {
+        let mut input = new(string);
+
+        for (pos, c) in string.char_indices() {
+            let other = other(c);
+
+            input.set_pos(pos);
+
+            assert!(!input.match_range(other, other), "match of {:?} at {} in {:?}", other, pos,
+                    string);
+            assert_eq!(input.pos(), pos, "failed match of {:?} at {} in {:?}", other, pos,
+                       string);
+            assert!(input.match_range(c, c), "match of {:?} at {} in {:?}", c, pos, string);
+            assert!(input.pos() > pos, "match of {:?} at {} in {:?}", c, pos, string);
+        }
+
+        input.set_pos(string.len());
+
+        assert!(!input.match_range('\u{0}', '\u{7f}'), "match at the end of {:?}", string);
+        assert!(!input.match_string("a"), "match at the end of {:?}", string);
+        assert_eq!(input.pos(), string.len(), "failed match at the end of {:?}", string);
+    }
+}
+
+/// Checks `line_col` at every `char` boundary.
+pub fn check_line_col<I: Input>(new: &dyn Fn(&'static str) -> I) {
+    for &string in SAMPLES {
+        let input = new(string);
+
+        for pos in boundaries(string) {
+            assert_eq!(input.line_col(pos), line_col(string, pos), "line_col({}) in {:?}", pos,
+                       string);
+        }
+    }
+}
+
+/// A `macro` that generates a `#[test]` for every part of the contract of
+/// [`Input`](trait.Input), so that custom `Input`s, e.g. backed by ropes or memory-mapped files,
+/// can check that they behave like the ones pest provides.
+///
+/// It takes either a type with a `new(&str)` constructor, or a closure creating the `Input` from
+/// a `&'static str`. The `Input` needs to cover the whole `&str`, starting at position `0`. Since
+/// the tests are generated in place, the `macro` is best called in a module of its own.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// mod string_input {
+///     # use pest::StringInput;
+///     input_conformance_tests!(StringInput);
+/// }
+///
+/// mod lossy_input {
+///     # use pest::LossyInput;
+///     input_conformance_tests!(|string| LossyInput::new(string.as_bytes()));
+/// }
+/// # fn main() {}
+/// ```
+#[macro_export]
+macro_rules! input_conformance_tests {
+    ( | $string:ident | $new:expr ) => {
+        $crate::input_conformance_tests!(@tests (|$string: &'static str| $new)
+                                         input_len          check_len
+                                         input_pos          check_pos
+                                         input_slice        check_slice
+                                         input_match_string check_match_string
+                                         input_match_range  check_match_range
+                                         input_line_col     check_line_col);
+    };
+    ( $input:ty ) => {
+        $crate::input_conformance_tests!(|string| <$input>::new(string));
+    };
+
+    ( @tests $new:tt $( $name:ident $check:ident )* ) => {
+        $(
+            #[test]
+            fn $name() {
+                $crate::conformance::$check(&$new);
+            }
+        )*
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    mod string_input {
+        use super::super::super::StringInput;
+
+        input_conformance_tests!(StringInput);
+    }
+
+    mod crlf_input {
+        use super::super::super::CrlfInput;
+
+        input_conformance_tests!(CrlfInput);
+    }
+
+    mod lossy_input {
+        use super::super::super::LossyInput;
+
+        input_conformance_tests!(|string| LossyInput::new(string.as_bytes()));
+    }
+
+    #[cfg(feature = "encoding")]
+    mod decoded_input {
+        use super::super::super::DecodedInput;
+
+        input_conformance_tests!(|string| DecodedInput::new(string.as_bytes()).unwrap());
+    }
+}
@@ -0,0 +1,185 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::HashMap;
+use std::fmt;
+
+/// A `struct` describing a top-level alternative of a rule defined in `grammar!`. Rules without
+/// `|` at the top level have a single alternative.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Alternative {
+    /// name of the rule
+    pub rule:  &'static str,
+    /// index of the alternative within the rule
+    pub index: usize,
+    /// source of the alternative
+    pub text:  &'static str
+}
+
+/// A `struct` that counts how many times every `Alternative` of a grammar matched. Used to find
+/// parts of a grammar that a test corpus never exercises.
+///
+/// Only top-level alternatives are recorded; alternatives nested in parentheses or in
+/// [precedence climbing](macro.grammar!#precedence-climbing) rules count as part of their
+/// enclosing `Alternative`.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         value  = { number | string | ["null"] }
+///         number = @{ ['0'..'9']+ }
+///         string = @{ ["\""] ~ (!["\""] ~ any)* ~ ["\""] }
+///     }
+/// }
+///
+/// let mut parser = Rdp::new(StringInput::new("1"));
+///
+/// parser.enable_coverage();
+///
+/// assert!(parser.value());
+///
+/// let coverage = parser.coverage().unwrap();
+///
+/// assert_eq!(coverage.hits("value", 0), 1);
+/// assert_eq!(coverage.uncovered_rules(), vec!["string"]);
+/// assert_eq!(coverage.untaken().iter().map(|a| (a.rule, a.index)).collect::<Vec<_>>(),
+///            vec![("value", 1), ("value", 2), ("string", 0)]);
+/// assert_eq!(coverage.untaken()[0].text, "string");
+/// # }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Coverage {
+    alternatives: Vec<Alternative>,
+    hits:         HashMap<(&'static str, usize), usize>
+}
+
+impl Coverage {
+    /// Creates an empty `Coverage` of `alternatives`.
+    pub fn new(alternatives: Vec<Alternative>) -> Coverage {
+        Coverage {
+            alternatives,
+            hits: HashMap::new()
+        }
+    }
+
+    /// Records that the `Alternative` `index` of `rule` matched.
+    #[inline]
+    pub fn hit(&mut self, rule: &'static str, index: usize) {
+        *self.hits.entry((rule, index)).or_insert(0) += 1;
+    }
+
+    /// Returns how many times the `Alternative` `index` of `rule` matched.
+    pub fn hits(&self, rule: &str, index: usize) -> usize {
+        self.hits.iter()
+                 .filter(|&(&(r, i), _)| r == rule && i == index)
+                 .map(|(_, &hits)| hits)
+                 .sum()
+    }
+
+    /// Adds the hits of `other` to a `Coverage`, e.g. to combine the `Coverage`s of several
+    /// `Parser`s.
+    pub fn merge(&mut self, other: &Coverage) {
+        for &alternative in &other.alternatives {
+            if !self.alternatives.contains(&alternative) {
+                self.alternatives.push(alternative);
+            }
+        }
+
+        for (&key, &hits) in &other.hits {
+            *self.hits.entry(key).or_insert(0) += hits;
+        }
+    }
+
+    /// Returns all `Alternative`s in grammar order.
+    pub fn alternatives(&self) -> &[Alternative] {
+        &self.alternatives
+    }
+
+    /// Returns the `Alternative`s that never matched.
+    pub fn untaken(&self) -> Vec<&Alternative> {
+        self.alternatives.iter().filter(|alternative| {
+            !self.hits.contains_key(&(alternative.rule, alternative.index))
+        }).collect()
+    }
+
+    /// Returns the rules that never matched.
+    pub fn uncovered_rules(&self) -> Vec<&'static str> {
+        let mut rules: Vec<&'static str> = vec![];
+
+        for alternative in &self.alternatives {
+            if !rules.contains(&alternative.rule) && !self.hits.keys().any(|&(rule, _)| {
+                rule == alternative.rule
+            }) {
+                rules.push(alternative.rule);
+            }
+        }
+
+        rules
+    }
+}
+
+impl fmt::Display for Coverage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let untaken = self.untaken();
+
+        writeln!(f, "{} of {} alternatives taken", self.alternatives.len() - untaken.len(),
+                 self.alternatives.len())?;
+
+        for alternative in untaken {
+            writeln!(f, "never taken: {} #{}: {}", alternative.rule, alternative.index,
+                     alternative.text)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alternatives() -> Vec<Alternative> {
+        vec![
+            Alternative { rule: "a", index: 0, text: "[\"a\"]" },
+            Alternative { rule: "a", index: 1, text: "b" },
+            Alternative { rule: "b", index: 0, text: "[\"b\"]" }
+        ]
+    }
+
+    #[test]
+    fn merge() {
+        let mut first = Coverage::new(alternatives());
+        let mut second = Coverage::new(alternatives());
+
+        first.hit("a", 0);
+        second.hit("a", 0);
+        second.hit("b", 0);
+
+        first.merge(&second);
+
+        assert_eq!(first.hits("a", 0), 2);
+        assert_eq!(first.hits("b", 0), 1);
+        assert_eq!(first.untaken(), vec![&alternatives()[1]]);
+        assert!(first.uncovered_rules().is_empty());
+    }
+
+    #[test]
+    fn display() {
+        let mut coverage = Coverage::new(alternatives());
+
+        coverage.hit("a", 1);
+
+        assert_eq!(coverage.to_string(), "1 of 3 alternatives taken\n\
+                                          never taken: a #0: [\"a\"]\n\
+                                          never taken: b #0: [\"b\"]\n");
+    }
+}
@@ -0,0 +1,142 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+/// A `struct` pointing at a `Token` in the queue, i.e. how far [`process!`](macro.process!) has
+/// gotten. `process` starts with a `Cursor` at the first `Token` and passes it through every
+/// matcher, which advances it past the `Token`s it matches. A matcher that names its `Cursor`
+/// after `&self`, e.g. `_items(&self, &mut cursor)`, can use it in its bodies, e.g. to call other
+/// matchers or to [peek](trait.Parser#tymethod.peek_token) at the next `Token`.
+///
+/// Since a `Cursor` is a plain value, matchers can try an interpretation of the queue
+/// speculatively with a [fork](#method.fork) of their `Cursor`, which leaves it where it was, and
+/// only commit it with [`Cursor::join`](#method.join).
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         list   = { item* }
+///         item   = _{ number | word }
+///         number = @{ ['0'..'9']+ }
+///         word   = @{ ['a'..'z']+ }
+///
+///         whitespace = _{ [" "] }
+///     }
+///
+///     process! {
+///         main(&self) -> Vec<String> {
+///             (_: list, items: _items()) => items
+///         }
+///
+///         _items(&self, &mut cursor) -> Vec<String> {
+///             () => {
+///                 // a number followed by a word is read as a pair, or else as a number
+///                 let mut fork = cursor.fork();
+///
+///                 let item = match self._pair(&mut fork) {
+///                     Some(pair) => {
+///                         cursor.join(fork);
+///
+///                         pair
+///                     },
+///                     None => match self._single(cursor) {
+///                         Some(single) => single,
+///                         None         => return vec![]
+///                     }
+///                 };
+///
+///                 let mut items = self._items(cursor);
+///
+///                 items.insert(0, item);
+///                 items
+///             }
+///         }
+///
+///         _pair(&self) -> Option<String> {
+///             (&number: number, &word: word) => Some(format!("{} {}", number, word)),
+///             () => None
+///         }
+///
+///         _single(&self) -> Option<String> {
+///             (&number: number) => Some(number.to_owned()),
+///             (&word: word) => Some(word.to_owned()),
+///             () => None
+///         }
+///     }
+/// }
+///
+/// let mut parser = Rdp::new(StringInput::new("1 a 2 3 b"));
+///
+/// assert!(parser.list());
+/// assert_eq!(parser.process(), vec!["1 a", "2", "3 b"]);
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Cursor {
+    index: usize
+}
+
+impl Cursor {
+    /// Creates a `Cursor` pointing at the `Token` at `index`.
+    pub fn new(index: usize) -> Cursor {
+        Cursor { index }
+    }
+
+    /// Returns the index of the `Token` a `Cursor` points at.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns a `Cursor` pointing `n` `Token`s further.
+    pub fn advance(&self, n: usize) -> Cursor {
+        Cursor { index: self.index + n }
+    }
+
+    /// Returns a copy of a `Cursor` to match with speculatively. The `Token`s the copy is advanced
+    /// past are only consumed once it is passed to [`Cursor::join`](#method.join).
+    pub fn fork(&self) -> Cursor {
+        *self
+    }
+
+    /// Commits a `Cursor` returned by [`Cursor::fork`](#method.fork), moving a `Cursor` to it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fork` is behind a `Cursor`, i.e. if it was not forked from it.
+    pub fn join(&mut self, fork: Cursor) {
+        assert!(fork >= *self, "joined a cursor that was not forked");
+
+        *self = fork;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cursor;
+
+    #[test]
+    fn fork() {
+        let mut cursor = Cursor::default();
+        let fork = cursor.fork().advance(2);
+
+        assert_eq!(cursor, Cursor::new(0));
+
+        cursor.join(fork);
+
+        assert_eq!(cursor.index(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "joined a cursor that was not forked")]
+    fn join_behind() {
+        Cursor::new(3).join(Cursor::new(1));
+    }
+}
@@ -0,0 +1,424 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::error::Error;
+use std::fmt;
+
+use serde::de::{self, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor};
+use serde::de::value::{BorrowedStrDeserializer, StringDeserializer};
+
+use super::Token;
+use super::stdlib::escape::Unescaper;
+
+/// An `enum` mapping a `Rule` to a concept of the serde data model.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Model {
+    /// children alternate between keys and values
+    Map,
+    /// children are elements
+    Seq,
+    /// the matched `&str`, borrowed from the input
+    Str,
+    /// the matched `&str` without its quotes and with its escapes decoded by
+    /// `Unescaper::default`
+    Quoted,
+    /// the matched `&str` parsed as an integer or a float
+    Number,
+    /// `true` if the matched `&str` is `"true"`, `false` otherwise
+    Bool,
+    /// unit, or `None` for `Option`s
+    Null,
+    /// the `Token` is replaced by its children; a lone `Transparent` `Token` deserializes as its
+    /// first child
+    Transparent,
+    /// the `Token` and its children are ignored
+    Skip
+}
+
+/// A `struct` describing an error that occurred while deserializing the `Token` starting at
+/// `pos`, if known.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DeserializeError {
+    /// description of the error
+    pub message: String,
+    /// position of the `Token`
+    pub pos:     Option<usize>
+}
+
+impl DeserializeError {
+    fn at(mut self, pos: usize) -> DeserializeError {
+        self.pos = self.pos.or(Some(pos));
+
+        self
+    }
+}
+
+impl fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.pos {
+            Some(pos) => write!(f, "{} at {}", self.message, pos),
+            None      => write!(f, "{}", self.message)
+        }
+    }
+}
+
+impl Error for DeserializeError {}
+
+impl de::Error for DeserializeError {
+    fn custom<T: fmt::Display>(message: T) -> DeserializeError {
+        DeserializeError {
+            message: message.to_string(),
+            pos:     None
+        }
+    }
+}
+
+/// A serde `Deserializer` that reads a value out of a [queue](trait.Parser#tymethod.queue) of
+/// `Token`s, using a function that maps every `Rule` to a `Model`.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::{from_tokens, Model};
+/// # use std::collections::BTreeMap;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         config  = { section* ~ eoi }
+///         section = { ["["] ~ name ~ ["]"] ~ pairs }
+///         pairs   = { pair* }
+///         pair    = { name ~ ["="] ~ number }
+///         name    = @{ ['a'..'z']+ }
+///         number  = @{ ['0'..'9']+ }
+///
+///         whitespace = _{ [" "] | ["\n"] }
+///     }
+/// }
+///
+/// let source = "[a]\nx = 1\ny = 2\n[b]\nz = 3\n";
+/// let mut parser = Rdp::new(StringInput::new(source));
+///
+/// assert!(parser.config());
+///
+/// let model = |rule| {
+///     match rule {
+///         Rule::config | Rule::pairs => Model::Map,
+///         Rule::name                 => Model::Str,
+///         Rule::number               => Model::Number,
+///         _                          => Model::Transparent
+///     }
+/// };
+///
+/// let config: BTreeMap<String, BTreeMap<String, u32>> =
+///     from_tokens(source, parser.queue(), &model).unwrap();
+///
+/// assert_eq!(config["a"]["y"], 2);
+/// assert_eq!(config["b"]["z"], 3);
+/// # }
+/// ```
+pub struct Deserializer<'a, 'de, R: 'a> {
+    source: &'de str,
+    queue:  &'a [Token<R>],
+    model:  &'a dyn Fn(R) -> Model,
+    index:  usize
+}
+
+impl<'a, 'de, R: 'a> Clone for Deserializer<'a, 'de, R> {
+    fn clone(&self) -> Deserializer<'a, 'de, R> {
+        *self
+    }
+}
+
+impl<'a, 'de, R: 'a> Copy for Deserializer<'a, 'de, R> {}
+
+/// Deserializes a `T` from the first `Token` of `queue`, and its descendants, matched in
+/// `source`. See [`Deserializer`](struct.Deserializer).
+pub fn from_tokens<'a, 'de, R, T>(source: &'de str, queue: &'a [Token<R>],
+                                  model: &'a dyn Fn(R) -> Model) -> Result<T, DeserializeError>
+    where R: Copy + PartialEq, T: de::Deserialize<'de> {
+
+    T::deserialize(Deserializer::new(source, queue, model))
+}
+
+impl<'a, 'de, R: Copy + PartialEq> Deserializer<'a, 'de, R> {
+    /// Creates a `Deserializer` of the first `Token` of `queue` matched in `source`.
+    pub fn new(source: &'de str, queue: &'a [Token<R>], model: &'a dyn Fn(R) -> Model)
+               -> Deserializer<'a, 'de, R> {
+        Deserializer {
+            source,
+            queue,
+            model,
+            index: 0
+        }
+    }
+
+    fn at(&self, index: usize) -> Deserializer<'a, 'de, R> {
+        Deserializer {
+            index,
+            ..*self
+        }
+    }
+
+    fn end_of(&self, index: usize) -> usize {
+        let token = &self.queue[index];
+
+        index + 1 + self.queue[index + 1..].iter().take_while(|other| {
+            token.is_ancestor_of(other)
+        }).count()
+    }
+
+    // children with Transparent ones replaced by their own children
+    fn children(&self, index: usize, children: &mut Vec<usize>) {
+        let end = self.end_of(index);
+        let mut i = index + 1;
+
+        while i < end {
+            match (self.model)(self.queue[i].rule) {
+                Model::Transparent => self.children(i, children),
+                Model::Skip        => (),
+                _                  => children.push(i)
+            }
+
+            i = self.end_of(i);
+        }
+    }
+
+    fn token(&self) -> Result<&'a Token<R>, DeserializeError> {
+        self.queue.get(self.index).ok_or_else(|| de::Error::custom("no Token to deserialize"))
+    }
+
+    fn text(&self) -> Result<&'de str, DeserializeError> {
+        let token = self.token()?;
+
+        Ok(&self.source[token.start..token.end])
+    }
+
+    fn unquote(&self) -> Result<String, DeserializeError> {
+        Unescaper::default().unquote(self.text()?).map_err(de::Error::custom)
+    }
+
+    fn visit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeserializeError> {
+        let token = self.token()?;
+        let text = self.text()?;
+
+        match (self.model)(token.rule) {
+            Model::Map         => {
+                let mut children = vec![];
+                self.children(self.index, &mut children);
+
+                visitor.visit_map(Children { deserializer: self, children, index: 0 })
+            },
+            Model::Seq         => {
+                let mut children = vec![];
+                self.children(self.index, &mut children);
+
+                visitor.visit_seq(Children { deserializer: self, children, index: 0 })
+            },
+            Model::Str         => visitor.visit_borrowed_str(text),
+            Model::Quoted      => visitor.visit_string(self.unquote()?),
+            Model::Number      => {
+                if let Ok(value) = text.parse::<u64>() {
+                    visitor.visit_u64(value)
+                } else if let Ok(value) = text.parse::<i64>() {
+                    visitor.visit_i64(value)
+                } else if let Ok(value) = text.parse::<f64>() {
+                    visitor.visit_f64(value)
+                } else {
+                    Err(de::Error::custom(format!("invalid number {:?}", text)))
+                }
+            },
+            Model::Bool        => visitor.visit_bool(text == "true"),
+            Model::Null        => visitor.visit_unit(),
+            Model::Transparent => {
+                let mut children = vec![];
+                self.children(self.index, &mut children);
+
+                match children.first() {
+                    Some(&child) => self.at(child).visit(visitor),
+                    None         => visitor.visit_unit()
+                }
+            },
+            Model::Skip        => Err(de::Error::custom("cannot deserialize a skipped Token"))
+        }
+    }
+}
+
+impl<'a, 'de, R: Copy + PartialEq> de::Deserializer<'de> for Deserializer<'a, 'de, R> {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeserializeError> {
+        let pos = self.token()?.start;
+
+        self.visit(visitor).map_err(|error| error.at(pos))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V)
+                                           -> Result<V::Value, DeserializeError> {
+        let token = self.token()?;
+
+        match (self.model)(token.rule) {
+            Model::Null => visitor.visit_none(),
+            _           => visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(self, _name: &'static str,
+                                         _variants: &'static [&'static str], visitor: V)
+                                         -> Result<V::Value, DeserializeError> {
+        let token = self.token()?;
+
+        match (self.model)(token.rule) {
+            Model::Str    => {
+                visitor.visit_enum(BorrowedStrDeserializer::new(self.text()?))
+            },
+            Model::Quoted => {
+                let variant: StringDeserializer<DeserializeError> =
+                    self.unquote()?.into_deserializer();
+
+                visitor.visit_enum(variant)
+            },
+            _             => self.deserialize_any(visitor)
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        unit unit_struct newtype_struct seq tuple tuple_struct map struct identifier
+        ignored_any
+    }
+}
+
+struct Children<'a, 'de, R: 'a> {
+    deserializer: Deserializer<'a, 'de, R>,
+    children:     Vec<usize>,
+    index:        usize
+}
+
+impl<'a, 'de, R: Copy + PartialEq> Children<'a, 'de, R> {
+    fn next(&mut self) -> Option<Deserializer<'a, 'de, R>> {
+        let child = self.children.get(self.index).cloned();
+        self.index += 1;
+
+        child.map(|child| self.deserializer.at(child))
+    }
+}
+
+impl<'a, 'de, R: Copy + PartialEq> SeqAccess<'de> for Children<'a, 'de, R> {
+    type Error = DeserializeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T)
+                                                  -> Result<Option<T::Value>, DeserializeError> {
+        match self.next() {
+            Some(child) => seed.deserialize(child).map(Some),
+            None        => Ok(None)
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.children.len() - self.index)
+    }
+}
+
+impl<'a, 'de, R: Copy + PartialEq> MapAccess<'de> for Children<'a, 'de, R> {
+    type Error = DeserializeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K)
+                                              -> Result<Option<K::Value>, DeserializeError> {
+        match self.next() {
+            Some(child) => seed.deserialize(child).map(Some),
+            None        => Ok(None)
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V)
+                                                -> Result<V::Value, DeserializeError> {
+        let pos = self.deserializer.token()?.start;
+
+        match self.next() {
+            Some(child) => seed.deserialize(child),
+            None        => Err(DeserializeError {
+                message: "map key without a value".to_owned(),
+                pos:     Some(pos)
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use super::super::prelude::*;
+
+    impl_rdp! {
+        grammar! {
+            value  = _{ object | array | string | number | boolean | null }
+            object =  { ["{"] ~ (pair ~ ([","] ~ pair)*)? ~ ["}"] }
+            pair   =  { string ~ [":"] ~ value }
+            array  =  { ["["] ~ (value ~ ([","] ~ value)*)? ~ ["]"] }
+            string = @{ ["\""] ~ (!["\""] ~ any)* ~ ["\""] }
+            number = @{ ["-"]? ~ ['0'..'9']+ ~ (["."] ~ ['0'..'9']+)? }
+            boolean = { ["true"] | ["false"] }
+            null   =  { ["null"] }
+        }
+    }
+
+    fn model(rule: Rule) -> Model {
+        match rule {
+            Rule::object  => Model::Map,
+            Rule::array   => Model::Seq,
+            Rule::string  => Model::Quoted,
+            Rule::number  => Model::Number,
+            Rule::boolean => Model::Bool,
+            Rule::null    => Model::Null,
+            _             => Model::Transparent
+        }
+    }
+
+    fn parse(source: &str) -> Vec<Token<Rule>> {
+        let mut parser = Rdp::new(StringInput::new(source));
+
+        assert!(parser.value());
+        assert!(parser.end());
+
+        parser.queue().clone()
+    }
+
+    #[test]
+    fn nested() {
+        let source = r#"{"a":[1,-2],"b\n":[]}"#;
+        let queue = parse(source);
+
+        let value: BTreeMap<String, Vec<i32>> = from_tokens(source, &queue, &model).unwrap();
+
+        assert_eq!(value["a"], vec![1, -2]);
+        assert!(value["b\n"].is_empty());
+    }
+
+    #[test]
+    fn scalars() {
+        let source = r#"[true,null,2.5,"x"]"#;
+        let queue = parse(source);
+
+        let value: (bool, Option<u8>, f64, String) = from_tokens(source, &queue, &model).unwrap();
+
+        assert_eq!(value, (true, None, 2.5, "x".to_owned()));
+    }
+
+    #[test]
+    fn error() {
+        let source = r#"[1,"a"]"#;
+        let queue = parse(source);
+
+        let error = from_tokens::<_, Vec<u8>>(source, &queue, &model).unwrap_err();
+
+        assert_eq!(error.pos, Some(3));
+    }
+}
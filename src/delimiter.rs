@@ -0,0 +1,93 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fmt;
+
+// pairs of delimiters tracked by parsers
+const PAIRS: [(&str, &str); 3] = [("(", ")"), ("[", "]"), ("{", "}")];
+
+/// A `struct` pointing at an opening delimiter that was never closed, returned by
+/// [`Parser::unclosed`](trait.Parser#tymethod.unclosed) when the closing delimiter is what
+/// failed at the farthest position. It is a secondary span of the error, usually more helpful
+/// than the position of the error itself.
+///
+/// `"("`, `"["`, and `"{"` strings matched outside of atomic rules and trivia are tracked, and
+/// every `")"`, `"]"`, or `"}"` string closes the innermost delimiter of its kind.
+///
+/// Its `Display` prints e.g. `expected ")" at 9 to close "(" at 2`.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::Unclosed;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         expression = { term ~ (["+"] ~ term)* }
+///         term       = { ["("] ~ expression ~ [")"] | number }
+///         number     = @{ ['0'..'9']+ }
+///
+///         whitespace = _{ [" "] }
+///     }
+/// }
+///
+/// let mut parser = Rdp::new(StringInput::new("1 + (2 + 3 4"));
+///
+/// assert!(parser.expression());
+/// assert!(!parser.end());
+/// assert_eq!(parser.unclosed(), Some(Unclosed { open: "(", close: ")", start: 4, pos: 11 }));
+/// assert_eq!(parser.unclosed().unwrap().to_string(), "expected \")\" at 11 to close \"(\" at 4");
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Unclosed {
+    /// opening delimiter
+    pub open:  &'static str,
+    /// closing delimiter
+    pub close: &'static str,
+    /// position of the opening delimiter in `Input`
+    pub start: usize,
+    /// position where the closing delimiter was expected
+    pub pos:   usize
+}
+
+impl Unclosed {
+    /// Returns the index of the pair of delimiters `string` belongs to and whether it opens it.
+    #[doc(hidden)]
+    #[inline]
+    pub fn delimiter(string: &str) -> Option<(usize, bool)> {
+        PAIRS.iter().enumerate().filter_map(|(i, &(open, close))| {
+            if string == open {
+                Some((i, true))
+            } else if string == close {
+                Some((i, false))
+            } else {
+                None
+            }
+        }).next()
+    }
+
+    /// Creates an `Unclosed` for the pair of delimiters at index `pair`.
+    #[doc(hidden)]
+    pub fn new(pair: usize, start: usize, pos: usize) -> Unclosed {
+        Unclosed {
+            open:  PAIRS[pair].0,
+            close: PAIRS[pair].1,
+            start,
+            pos
+        }
+    }
+}
+
+impl fmt::Display for Unclosed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "expected {:?} at {} to close {:?} at {}", self.close, self.pos, self.open,
+               self.start)
+    }
+}
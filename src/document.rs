@@ -0,0 +1,281 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fmt;
+
+use super::Token;
+use super::rewrite::parents;
+
+/// A `struct` that keeps a parsed document around as a tree of nodes whose text can be replaced,
+/// e.g. to bump a version in a configuration file. Every node corresponds to the `Token` with the
+/// same index in the pre-order [queue](trait.Parser#tymethod.queue) it was built from.
+///
+/// Re-serializing a `Document` with `to_string` only rewrites replaced nodes; everything else,
+/// including trivia between `Token`s and text no `Token` covers, is copied from the source byte
+/// for byte.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::Document;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         deps    = { dep* }
+///         dep     = { name ~ ["="] ~ version ~ [";"] }
+///         name    = @{ ['a'..'z']+ }
+///         version = @{ (['0'..'9'] | ["."])+ }
+///
+///         whitespace = _{ [" "] | ["\n"] }
+///     }
+/// }
+///
+/// let source = "serde = 1.0;\nlog  =  0.3;\n";
+/// let mut parser = Rdp::new(StringInput::new(source));
+///
+/// assert!(parser.deps());
+///
+/// let mut document = Document::new(source, parser.queue());
+///
+/// let log = document.find(Rule::name).into_iter().find(|&node| document.text(node) == "log");
+/// let dep = document.parent(log.unwrap()).unwrap();
+/// let version = document.children(dep)[1];
+///
+/// document.replace(version, "0.4");
+///
+/// assert_eq!(document.to_string(), "serde = 1.0;\nlog  =  0.4;\n");
+/// # }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Document<R> {
+    source:       String,
+    tokens:       Vec<Token<R>>,
+    parents:      Vec<Option<usize>>,
+    children:     Vec<Vec<usize>>,
+    roots:        Vec<usize>,
+    replacements: Vec<Option<String>>
+}
+
+impl<R: Copy + PartialEq> Document<R> {
+    /// Creates a `Document` from the `source` a pre-order `queue` was parsed from.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a `Token` ends after the end of `source`.
+    pub fn new(source: &str, queue: &[Token<R>]) -> Document<R> {
+        if let Some(token) = queue.iter().find(|token| token.end > source.len()) {
+            panic!("token ends at {}, after the end of the source at {}", token.end,
+                   source.len());
+        }
+
+        let parents = parents(queue);
+        let mut children = vec![vec![]; queue.len()];
+        let mut roots = vec![];
+
+        for (i, parent) in parents.iter().enumerate() {
+            match *parent {
+                Some(parent) => children[parent].push(i),
+                None         => roots.push(i)
+            }
+        }
+
+        Document {
+            source:       source.to_owned(),
+            tokens:       queue.to_vec(),
+            parents,
+            children,
+            roots,
+            replacements: vec![None; queue.len()]
+        }
+    }
+
+    /// Returns the unmodified source the `Document` was created from.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns the number of nodes.
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    /// Returns whether the `Document` has no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// Returns the `Rule` of `node`.
+    pub fn rule(&self, node: usize) -> R {
+        self.tokens[node].rule
+    }
+
+    /// Returns the `Token` of `node`, spanning the source it was parsed from.
+    pub fn token(&self, node: usize) -> &Token<R> {
+        &self.tokens[node]
+    }
+
+    /// Returns the parent of `node`, or `None` for top-level nodes.
+    pub fn parent(&self, node: usize) -> Option<usize> {
+        self.parents[node]
+    }
+
+    /// Returns the direct children of `node`, in source order.
+    pub fn children(&self, node: usize) -> &[usize] {
+        &self.children[node]
+    }
+
+    /// Returns the top-level nodes, in source order.
+    pub fn roots(&self) -> &[usize] {
+        &self.roots
+    }
+
+    /// Returns all nodes of rule `rule`, in source order.
+    pub fn find(&self, rule: R) -> Vec<usize> {
+        (0..self.tokens.len()).filter(|&node| self.tokens[node].rule == rule).collect()
+    }
+
+    /// Returns the current text of `node`, including the replacements of its descendants.
+    pub fn text(&self, node: usize) -> String {
+        let mut text = String::new();
+
+        self.write(node, &mut text);
+
+        text
+    }
+
+    /// Replaces the text of `node` with `text`. The replacements of its descendants are kept, but
+    /// have no effect for as long as the node stays replaced.
+    pub fn replace(&mut self, node: usize, text: &str) {
+        self.replacements[node] = Some(text.to_owned());
+    }
+
+    /// Undoes the replacement of `node`, if any.
+    pub fn restore(&mut self, node: usize) {
+        self.replacements[node] = None;
+    }
+
+    /// Returns whether `node` is replaced.
+    pub fn is_replaced(&self, node: usize) -> bool {
+        self.replacements[node].is_some()
+    }
+
+    /// Returns whether any node is replaced.
+    pub fn is_modified(&self) -> bool {
+        self.replacements.iter().any(|replacement| replacement.is_some())
+    }
+
+    fn write(&self, node: usize, out: &mut String) {
+        if let Some(ref text) = self.replacements[node] {
+            out.push_str(text);
+
+            return;
+        }
+
+        let token = &self.tokens[node];
+
+        self.write_between(token.start, token.end, &self.children[node], out);
+    }
+
+    // copies the source from start to end, except for the nodes in between
+    fn write_between(&self, start: usize, end: usize, nodes: &[usize], out: &mut String) {
+        let mut pos = start;
+
+        for &node in nodes {
+            let token = &self.tokens[node];
+
+            out.push_str(&self.source[pos..token.start]);
+            self.write(node, out);
+
+            pos = token.end;
+        }
+
+        out.push_str(&self.source[pos..end]);
+    }
+}
+
+impl<R: Copy + PartialEq> fmt::Display for Document<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut text = String::with_capacity(self.source.len());
+
+        self.write_between(0, self.source.len(), &self.roots, &mut text);
+
+        f.write_str(&text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(rule: u8, start: usize, end: usize) -> Token<u8> {
+        Token { rule, start, end }
+    }
+
+    fn document() -> Document<u8> {
+        // "[a, bc] # x" with a list, its items, and a comment
+        Document::new("[a, bc] # x", &[
+            token(0, 0, 7),
+            token(1, 1, 2),
+            token(1, 4, 6),
+            token(2, 8, 11)
+        ])
+    }
+
+    #[test]
+    fn untouched() {
+        let document = document();
+
+        assert!(!document.is_modified());
+        assert_eq!(document.to_string(), "[a, bc] # x");
+        assert_eq!(document.roots(), &[0, 3]);
+        assert_eq!(document.children(0), &[1, 2]);
+        assert_eq!(document.parent(2), Some(0));
+        assert_eq!(document.find(1), vec![1, 2]);
+    }
+
+    #[test]
+    fn replace() {
+        let mut document = document();
+
+        document.replace(2, "defg");
+
+        assert!(document.is_replaced(2));
+        assert_eq!(document.text(0), "[a, defg]");
+        assert_eq!(document.to_string(), "[a, defg] # x");
+        assert_eq!(document.source(), "[a, bc] # x");
+    }
+
+    #[test]
+    fn replace_ancestor() {
+        let mut document = document();
+
+        document.replace(1, "z");
+        document.replace(0, "[]");
+
+        assert_eq!(document.to_string(), "[] # x");
+
+        document.restore(0);
+
+        assert_eq!(document.to_string(), "[z, bc] # x");
+    }
+
+    #[test]
+    fn empty() {
+        let document: Document<u8> = Document::new(" \n", &[]);
+
+        assert!(document.is_empty());
+        assert_eq!(document.to_string(), " \n");
+    }
+
+    #[test]
+    #[should_panic(expected = "token ends at 3, after the end of the source at 2")]
+    fn out_of_bounds() {
+        Document::new("ab", &[token(0, 0, 3)]);
+    }
+}
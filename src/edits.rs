@@ -0,0 +1,221 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::error::Error;
+use std::fmt;
+
+use super::Token;
+
+/// A `struct` that collects replacements of spans of an input, e.g. from the passes of a formatter
+/// or a fixer, and applies them all at once. All spans refer to the original input, so passes do
+/// not need to account for each other's edits.
+///
+/// Spans may touch, but not overlap; an insertion, i.e. an empty span, conflicts with a
+/// replacement that strictly contains its position. Several insertions at the same position are
+/// applied in the order they were recorded, before a replacement starting at that position.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::{EditConflict, Edits};
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         list  = { ident ~ ([","] ~ ident)* }
+///         ident = @{ ['a'..'z']+ }
+///
+///         whitespace = _{ [" "] }
+///     }
+/// }
+///
+/// let input = "a,b , c";
+/// let mut parser = Rdp::new(StringInput::new(input));
+///
+/// assert!(parser.list());
+///
+/// let mut edits = Edits::new();
+///
+/// for token in parser.queue().iter().filter(|token| token.rule == Rule::ident) {
+///     edits.replace_token(token, &input[token.start..token.end].to_uppercase()).unwrap();
+/// }
+///
+/// edits.insert(0, "[").unwrap();
+/// edits.insert(7, "]").unwrap();
+///
+/// assert_eq!(edits.replace(2, 4, "x"), Err(EditConflict { existing: (2, 3), new: (2, 4) }));
+/// assert_eq!(edits.apply(input), "[A,B , C]");
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Edits {
+    edits: Vec<(usize, usize, String)>
+}
+
+impl Edits {
+    /// Creates an empty `Edits`.
+    pub fn new() -> Edits {
+        Edits {
+            edits: vec![]
+        }
+    }
+
+    /// Records that the input from `start` to `end` is to be replaced with `text`. Returns an
+    /// `EditConflict` with the first recorded edit it overlaps, in which case nothing is recorded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start` is greater than `end`.
+    pub fn replace(&mut self, start: usize, end: usize, text: &str) -> Result<(), EditConflict> {
+        assert!(start <= end, "edit starts at {} after its end at {}", start, end);
+
+        let conflict = self.edits.iter().find(|&&(other_start, other_end, _)| {
+            if start == end {
+                other_start < start && start < other_end
+            } else if other_start == other_end {
+                start < other_start && other_start < end
+            } else {
+                start < other_end && other_start < end
+            }
+        });
+
+        if let Some(&(other_start, other_end, _)) = conflict {
+            return Err(EditConflict {
+                existing: (other_start, other_end),
+                new:      (start, end)
+            });
+        }
+
+        self.edits.push((start, end, text.to_owned()));
+
+        Ok(())
+    }
+
+    /// Records that the span of `token` is to be replaced with `text`.
+    pub fn replace_token<R>(&mut self, token: &Token<R>, text: &str) -> Result<(), EditConflict> {
+        self.replace(token.start, token.end, text)
+    }
+
+    /// Records that `text` is to be inserted at `pos`.
+    pub fn insert(&mut self, pos: usize, text: &str) -> Result<(), EditConflict> {
+        self.replace(pos, pos, text)
+    }
+
+    /// Records that the input from `start` to `end` is to be deleted.
+    pub fn delete(&mut self, start: usize, end: usize) -> Result<(), EditConflict> {
+        self.replace(start, end, "")
+    }
+
+    /// Returns the number of recorded edits.
+    pub fn len(&self) -> usize {
+        self.edits.len()
+    }
+
+    /// Returns whether no edits were recorded.
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+
+    /// Returns a copy of `input` with all recorded edits applied.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an edit ends after the end of `input` or if one of its positions is not on a
+    /// `char` boundary.
+    pub fn apply(&self, input: &str) -> String {
+        let mut edits: Vec<_> = self.edits.iter().collect();
+
+        // stable, so that insertions at the same position keep their order
+        edits.sort_by_key(|&&(start, end, _)| (start, end));
+
+        let mut result = String::with_capacity(input.len());
+        let mut pos = 0;
+
+        for &&(start, end, ref text) in &edits {
+            result.push_str(&input[pos..start]);
+            result.push_str(text);
+
+            pos = end;
+        }
+
+        result.push_str(&input[pos..]);
+
+        result
+    }
+}
+
+/// A `struct` describing an edit recorded in `Edits` that overlaps an earlier one.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct EditConflict {
+    /// span of the edit that was already recorded
+    pub existing: (usize, usize),
+    /// span of the rejected edit
+    pub new:      (usize, usize)
+}
+
+impl fmt::Display for EditConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "edit of {}..{} overlaps earlier edit of {}..{}", self.new.0, self.new.1,
+               self.existing.0, self.existing.1)
+    }
+}
+
+impl Error for EditConflict {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply() {
+        let mut edits = Edits::new();
+
+        edits.replace(4, 7, "X").unwrap();
+        edits.delete(0, 1).unwrap();
+        edits.insert(4, "<").unwrap();
+        edits.insert(4, "!").unwrap();
+        edits.insert(9, ">").unwrap();
+
+        assert_eq!(edits.len(), 5);
+        assert_eq!(edits.apply("abc defgh i"), "bc <!Xgh> i");
+    }
+
+    #[test]
+    fn touching() {
+        let mut edits = Edits::new();
+
+        edits.replace(0, 2, "x").unwrap();
+        edits.replace(2, 4, "y").unwrap();
+        edits.insert(2, "-").unwrap();
+
+        assert_eq!(edits.apply("abcd"), "x-y");
+    }
+
+    #[test]
+    fn conflicts() {
+        let mut edits = Edits::new();
+
+        edits.replace(2, 5, "x").unwrap();
+
+        assert_eq!(edits.replace(4, 6, "y"), Err(EditConflict { existing: (2, 5), new: (4, 6) }));
+        assert_eq!(edits.replace(0, 9, "y"), Err(EditConflict { existing: (2, 5), new: (0, 9) }));
+        assert_eq!(edits.insert(3, "y"), Err(EditConflict { existing: (2, 5), new: (3, 3) }));
+
+        edits.insert(7, "z").unwrap();
+
+        assert_eq!(edits.replace(6, 8, "y").unwrap_err().to_string(),
+                   "edit of 6..8 overlaps earlier edit of 7..7");
+        assert_eq!(edits.len(), 2);
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(Edits::new().apply("abc"), "abc");
+        assert!(Edits::new().is_empty());
+    }
+}
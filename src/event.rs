@@ -0,0 +1,100 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::Token;
+
+/// An `enum` marking where a `Token` starts or ends while walking a tree of `Token`s, e.g. in a
+/// [stream](trait.Parser#tymethod.set_stream).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Event<R> {
+    /// the `Token` starts; its descendants follow
+    Start(Token<R>),
+    /// the `Token` ends after all its descendants
+    End(Token<R>)
+}
+
+/// An `Iterator` over the `Event`s of a pre-order queue.
+///
+/// # Examples
+///
+/// ```
+/// # use pest::{Event, Events, Token};
+/// let parent = Token { rule: 0, start: 0, end: 2 };
+/// let child  = Token { rule: 1, start: 0, end: 1 };
+///
+/// assert_eq!(Events::new(&[parent, child]).collect::<Vec<_>>(), vec![
+///     Event::Start(parent),
+///     Event::Start(child),
+///     Event::End(child),
+///     Event::End(parent)
+/// ]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Events<'a, R: 'a> {
+    queue: &'a [Token<R>],
+    index: usize,
+    stack: Vec<Token<R>>
+}
+
+impl<'a, R: Copy + PartialEq> Events<'a, R> {
+    /// Creates an `Iterator` over the `Event`s of a pre-order `queue`.
+    pub fn new(queue: &'a [Token<R>]) -> Events<'a, R> {
+        Events {
+            queue,
+            index: 0,
+            stack: vec![]
+        }
+    }
+}
+
+impl<'a, R: Copy + PartialEq> Iterator for Events<'a, R> {
+    type Item = Event<R>;
+
+    fn next(&mut self) -> Option<Event<R>> {
+        match (self.stack.last(), self.queue.get(self.index)) {
+            (Some(&top), Some(next)) if !top.is_ancestor_of(next) => {
+                self.stack.pop();
+
+                Some(Event::End(top))
+            },
+            (_, Some(&next)) => {
+                self.index += 1;
+                self.stack.push(next);
+
+                Some(Event::Start(next))
+            },
+            (_, None) => self.stack.pop().map(Event::End)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn siblings() {
+        let queue = [
+            Token { rule: 0, start: 0, end: 4 },
+            Token { rule: 1, start: 0, end: 2 },
+            Token { rule: 2, start: 2, end: 4 },
+            Token { rule: 3, start: 4, end: 5 }
+        ];
+
+        let events: Vec<_> = Events::new(&queue).map(|event| {
+            match event {
+                Event::Start(token) => (true, token.rule),
+                Event::End(token)   => (false, token.rule)
+            }
+        }).collect();
+
+        assert_eq!(events, vec![
+            (true, 0), (true, 1), (false, 1), (true, 2), (false, 2), (false, 0), (true, 3),
+            (false, 3)
+        ]);
+    }
+}
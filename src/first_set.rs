@@ -0,0 +1,345 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::Alternative;
+use super::profile::first_term;
+
+/// A `struct` holding the `char`s a rule can start with, derived from the source of its
+/// [`Alternative`](struct.Alternative)s. Used by the `skip_to(rule)` built-in of
+/// [`grammar!`](macro.grammar!#islands) to only try a rule where it could match.
+///
+/// Only the first term of every alternative is looked at. Strings, ranges, parentheses, and
+/// other rules are understood; anything else, like built-in rules, constants, or alternatives
+/// that might not consume input, makes the `FirstSet` of a rule unknown.
+///
+/// # Examples
+///
+/// ```
+/// # use pest::{Alternative, FirstSet};
+/// let alternatives = [
+///     Alternative { rule: "number", index: 0, text: "[\"-\"] ~ digits" },
+///     Alternative { rule: "number", index: 1, text: "digits" },
+///     Alternative { rule: "digits", index: 0, text: "['0'..'9']+" }
+/// ];
+///
+/// let first = FirstSet::of(&alternatives, "number").unwrap();
+///
+/// assert!(first.contains('-'));
+/// assert!(first.contains('7'));
+/// assert!(!first.contains('a'));
+/// assert_eq!(first.find("ab 12"), Some(3));
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FirstSet {
+    ranges: Vec<(char, char)>
+}
+
+impl FirstSet {
+    /// Returns the `FirstSet` of `rule` in `alternatives`, or `None` if it is unknown.
+    pub fn of(alternatives: &[Alternative], rule: &str) -> Option<FirstSet> {
+        let mut ranges = vec![];
+
+        let known = rule_terms(alternatives, rule, &mut vec![], &mut |contents| {
+            bracket_range(contents).map(|range| ranges.push(range)).is_some()
+        });
+
+        if known {
+            ranges.sort();
+            ranges.dedup();
+
+            Some(FirstSet { ranges })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the inclusive ranges of `char`s in a `FirstSet`, sorted.
+    pub fn ranges(&self) -> &[(char, char)] {
+        &self.ranges
+    }
+
+    /// Returns whether a rule can start with `c`.
+    pub fn contains(&self, c: char) -> bool {
+        self.ranges.iter().any(|&(left, right)| left <= c && c <= right)
+    }
+
+    /// Returns the position of the first `char` in `string` that a rule can start with.
+    pub fn find(&self, string: &str) -> Option<usize> {
+        string.char_indices().find(|&(_, c)| self.contains(c)).map(|(i, _)| i)
+    }
+}
+
+/// Returns the strings that every match of `rule` in `alternatives` starts with, or `None` if an
+/// alternative does not start with a string. Only strings, parentheses, and other rules are
+/// understood, like in [`FirstSet::of`](struct.FirstSet#method.of). Used by
+/// [`Scanner`](struct.Scanner).
+pub fn literal_prefixes(alternatives: &[Alternative], rule: &str) -> Option<Vec<String>> {
+    let mut prefixes = vec![];
+
+    let known = rule_terms(alternatives, rule, &mut vec![], &mut |contents| {
+        string_literal(contents).map(|string| prefixes.push(string)).is_some()
+    });
+
+    if known {
+        prefixes.sort();
+        prefixes.dedup();
+
+        Some(prefixes)
+    } else {
+        None
+    }
+}
+
+// passes the contents of the brackets of every term a match of rule can start with to leaf, and
+// returns whether all of them are known to leaf; visiting tracks the rules being expanded, so
+// that left recursion makes the terms unknown instead of looping
+fn rule_terms(alternatives: &[Alternative], rule: &str, visiting: &mut Vec<String>,
+              leaf: &mut dyn FnMut(&str) -> bool) -> bool {
+    if visiting.iter().any(|visited| visited == rule) {
+        return false;
+    }
+
+    let texts: Vec<_> = alternatives.iter().filter(|alternative| alternative.rule == rule)
+                                    .map(|alternative| alternative.text).collect();
+
+    if texts.is_empty() {
+        return false;
+    }
+
+    visiting.push(rule.to_owned());
+
+    let known = texts.iter().all(|text| alt_terms(alternatives, text, visiting, leaf));
+
+    visiting.pop();
+
+    known
+}
+
+fn alt_terms(alternatives: &[Alternative], text: &str, visiting: &mut Vec<String>,
+             leaf: &mut dyn FnMut(&str) -> bool) -> bool {
+    let term = match first_term(text) {
+        Some(term) => class_operand(term.trim_end_matches('+')).trim(),
+        None       => return false
+    };
+
+    if term.starts_with('(') && term.ends_with(')') {
+        split_alts(&term[1..term.len() - 1]).iter().all(|alt| {
+            alt_terms(alternatives, alt, visiting, leaf)
+        })
+    } else if term.starts_with('[') && term.ends_with(']') {
+        leaf(&term[1..term.len() - 1])
+    } else if !term.is_empty() && term.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        rule_terms(alternatives, term, visiting, leaf)
+    } else {
+        false
+    }
+}
+
+// the left operand of a term combined with character-class operators, whose matches all match
+// the left operand as well
+fn class_operand(term: &str) -> &str {
+    let mut depth = 0;
+    let mut quoted = false;
+    let mut escaped = false;
+
+    for (i, c) in term.char_indices() {
+        if quoted {
+            match c {
+                _ if escaped => escaped = false,
+                '\\'         => escaped = true,
+                '"'          => quoted = false,
+                _            => ()
+            }
+
+            continue;
+        }
+
+        match c {
+            '"'                     => quoted = true,
+            '(' | '['               => depth += 1,
+            ')' | ']'               => depth -= 1,
+            '-' | '&' if depth == 0 => return &term[..i],
+            _                       => ()
+        }
+    }
+
+    term
+}
+
+// splits the source of a parenthesized expression at its top-level `|`s
+fn split_alts(text: &str) -> Vec<&str> {
+    let mut alts = vec![];
+    let mut depth = 0;
+    let mut quoted = false;
+    let mut escaped = false;
+    let mut start = 0;
+
+    for (i, c) in text.char_indices() {
+        if quoted {
+            match c {
+                _ if escaped => escaped = false,
+                '\\'         => escaped = true,
+                '"'          => quoted = false,
+                _            => ()
+            }
+
+            continue;
+        }
+
+        match c {
+            '"'               => quoted = true,
+            '(' | '['         => depth += 1,
+            ')' | ']'         => depth -= 1,
+            '|' if depth == 0 => {
+                alts.push(&text[start..i]);
+                start = i + 1;
+            },
+            _                 => ()
+        }
+    }
+
+    alts.push(&text[start..]);
+
+    alts
+}
+
+// the range of chars a `["string"]`, `['a'..'z']`, or `['a', 'z']` can start with
+fn bracket_range(text: &str) -> Option<(char, char)> {
+    let text = text.trim();
+
+    if let Some(string) = text.strip_prefix('"') {
+        let c = first_char(string.strip_suffix('"')?)?;
+
+        Some((c, c))
+    } else {
+        let (left, right) = match text.find("..") {
+            Some(i) => (&text[..i], &text[i + 2..]),
+            None    => text.split_at(text.find(',')?)
+        };
+        let right = right.trim_start_matches(',');
+
+        Some((char_literal(left)?, char_literal(right)?))
+    }
+}
+
+// the contents of a non-empty `"string"`, with its escapes replaced
+fn string_literal(text: &str) -> Option<String> {
+    let mut rest = text.trim().strip_prefix('"')?.strip_suffix('"')?;
+    let mut string = String::with_capacity(rest.len());
+
+    while let Some(i) = rest.find('\\') {
+        string.push_str(&rest[..i]);
+        string.push(first_char(&rest[i..])?);
+
+        rest = &rest[i + 2..];
+    }
+
+    string.push_str(rest);
+
+    Some(string).filter(|string| !string.is_empty())
+}
+
+fn char_literal(text: &str) -> Option<char> {
+    let text = text.trim().strip_prefix('\'')?.strip_suffix('\'')?;
+
+    first_char(text).filter(|c| c.len_utf8() == text.len() || text.starts_with('\\'))
+}
+
+// the first char of the contents of a string or char literal, unescaping simple escapes
+fn first_char(text: &str) -> Option<char> {
+    let mut chars = text.chars();
+
+    match chars.next()? {
+        '\\' => match chars.next()? {
+            'n'  => Some('\n'),
+            'r'  => Some('\r'),
+            't'  => Some('\t'),
+            '0'  => Some('\0'),
+            '\\' => Some('\\'),
+            '\'' => Some('\''),
+            '"'  => Some('"'),
+            _    => None
+        },
+        c => Some(c)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alternative(rule: &'static str, index: usize, text: &'static str) -> Alternative {
+        Alternative { rule, index, text }
+    }
+
+    #[test]
+    fn terms() {
+        let alternatives = [
+            alternative("a", 0, "[\"\\n\"] ~ b"),
+            alternative("a", 1, "(b | ['x', 'z']) ~ c"),
+            alternative("b", 0, "['0'..'9']+"),
+            alternative("c", 0, "[\"\"]")
+        ];
+
+        assert_eq!(FirstSet::of(&alternatives, "a").unwrap().ranges(),
+                   &[('\n', '\n'), ('0', '9'), ('x', 'z')]);
+        assert_eq!(FirstSet::of(&alternatives, "c"), None);
+        assert_eq!(FirstSet::of(&alternatives, "d"), None);
+    }
+
+    #[test]
+    fn unknown() {
+        let alternatives = [
+            alternative("a", 0, "b? ~ c"),
+            alternative("b", 0, "any"),
+            alternative("c", 0, "c ~ [\"x\"]"),
+            alternative("d", 0, "[CONSTANT]")
+        ];
+
+        assert_eq!(FirstSet::of(&alternatives, "a"), None);
+        assert_eq!(FirstSet::of(&alternatives, "b"), None);
+        assert_eq!(FirstSet::of(&alternatives, "c"), None);
+        assert_eq!(FirstSet::of(&alternatives, "d"), None);
+    }
+
+    #[test]
+    fn prefixes() {
+        let alternatives = [
+            alternative("a", 0, "([\"ERROR\"] | [\"WARN\\t\"]) ~ b"),
+            alternative("a", 1, "c ~ b"),
+            alternative("b", 0, "['0'..'9']+"),
+            alternative("c", 0, "[\"INFO\"]"),
+            alternative("d", 0, "[\"\\u{41}\"]")
+        ];
+
+        assert_eq!(literal_prefixes(&alternatives, "a"),
+                   Some(vec!["ERROR".to_owned(), "INFO".to_owned(), "WARN\t".to_owned()]));
+        assert_eq!(literal_prefixes(&alternatives, "b"), None);
+        assert_eq!(literal_prefixes(&alternatives, "d"), None);
+    }
+
+    #[test]
+    fn classes() {
+        let alternatives = [
+            alternative("a", 0, "['a'..'z'] - [\"q\"] ~ b"),
+            alternative("b", 0, "([\"ab\"] | [\"-\"]) & c"),
+            alternative("c", 0, "[\"a\"] | [\"-\"]")
+        ];
+
+        assert_eq!(FirstSet::of(&alternatives, "a").unwrap().ranges(), &[('a', 'z')]);
+        assert_eq!(literal_prefixes(&alternatives, "b"),
+                   Some(vec!["-".to_owned(), "ab".to_owned()]));
+    }
+
+    #[test]
+    fn find() {
+        let first = FirstSet { ranges: vec![('€', '€')] };
+
+        assert_eq!(first.find("ab€"), Some(2));
+        assert_eq!(first.find("ab"), None);
+    }
+}
@@ -0,0 +1,65 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! An [RFC 4180](https://tools.ietf.org/html/rfc4180) CSV parser built on
+//! [`Delimited`](../../stdlib/delimited/trait.Delimited.html).
+//!
+//! # Examples
+//!
+//! ```
+//! use pest::formats::csv;
+//!
+//! let records = csv::parse("a,\"b,\"\"c\"\"\"\r\n,d\n").unwrap();
+//!
+//! assert_eq!(records, vec![vec!["a", "b,\"c\""], vec!["", "d"]]);
+//! ```
+
+use std::borrow::Cow;
+
+use super::super::prelude::*;
+use super::super::stdlib::delimited::{unquote_field, Delimited};
+
+impl_rdp! {
+    grammar! {
+        csv    =  { record ~ (eol ~ !eoi ~ record)* ~ eol? ~ eoi }
+        record =  { fields }
+        field  =  token
+        eol    = _{ ["\r\n"] | ["\n"] }
+    }
+}
+
+impl<T: Input> Rdp<T> {
+    fn fields(&mut self) -> bool {
+        self.delimited_fields(',', '"', Rule::field)
+    }
+}
+
+/// Parses `source` into records of unquoted fields, returning `None` if it is not valid CSV. A
+/// final line break does not start a new record.
+pub fn parse(source: &str) -> Option<Vec<Vec<Cow<'_, str>>>> {
+    let mut parser = Rdp::new(StringInput::new(source));
+
+    if !parser.csv() {
+        return None;
+    }
+
+    let mut records = vec![];
+
+    for token in parser.queue() {
+        match token.rule {
+            Rule::record => records.push(vec![]),
+            Rule::field  => {
+                let field = unquote_field(&source[token.start..token.end], '"');
+
+                records.last_mut().unwrap().push(field);
+            },
+            _            => ()
+        }
+    }
+
+    Some(records)
+}
@@ -0,0 +1,92 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! An INI parser with `[section]` headers, `key = value` pairs, and comments starting with `;`
+//! or `#`.
+//!
+//! # Examples
+//!
+//! ```
+//! use pest::formats::ini;
+//!
+//! let sections = ini::parse("name = pest\n\n[owner]\nuser = dragostis ; comment\n").unwrap();
+//!
+//! assert_eq!(sections[0].name, "");
+//! assert_eq!(sections[0].pairs, vec![("name", "pest")]);
+//! assert_eq!(sections[1].name, "owner");
+//! assert_eq!(sections[1].pairs, vec![("user", "dragostis")]);
+//! ```
+
+use super::super::prelude::*;
+
+impl_rdp! {
+    grammar! {
+        ini    =  { entry? ~ (eol ~ entry?)* ~ eoi }
+        entry  = _{ header | pair }
+        header =  { ["["] ~ name ~ ["]"] }
+        name   = @{ (!(["]"] | eol) ~ any)+ }
+        pair   =  { key ~ ["="] ~ value }
+        key    = @{ (!(["="] | ["["] | [";"] | ["#"] | [" "] | ["\t"] | eol) ~ any)+ }
+        value  = @{ (!([";"] | ["#"] | eol) ~ any)* }
+        eol    = _{ ["\r\n"] | ["\n"] }
+
+        whitespace = _{ [" "] | ["\t"] }
+        comment    = _{ ([";"] | ["#"]) ~ (!eol ~ any)* }
+    }
+}
+
+/// A `struct` representing an INI section. Pairs preceding the first header belong to a section
+/// with an empty `name`, which is always the first one.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Section<'a> {
+    /// name between the brackets, without surrounding whitespace
+    pub name:  &'a str,
+    /// keys and values in order, with values trimmed
+    pub pairs: Vec<(&'a str, &'a str)>
+}
+
+/// Parses `source` into its sections, returning `None` if it is not valid INI.
+pub fn parse(source: &str) -> Option<Vec<Section<'_>>> {
+    let mut parser = Rdp::new(StringInput::new(source));
+
+    if !parser.ini() {
+        return None;
+    }
+
+    let mut sections = vec![Section { name: "", pairs: vec![] }];
+    let queue = parser.queue();
+    let mut i = 0;
+
+    while i < queue.len() {
+        let token = &queue[i];
+
+        match token.rule {
+            Rule::name => {
+                sections.push(Section {
+                    name:  source[token.start..token.end].trim(),
+                    pairs: vec![]
+                });
+            },
+            Rule::pair => {
+                let key = &queue[i + 1];
+                let value = &queue[i + 2];
+
+                sections.last_mut().unwrap().pairs.push((
+                    &source[key.start..key.end],
+                    source[value.start..value.end].trim()
+                ));
+
+                i += 2;
+            },
+            _ => ()
+        }
+
+        i += 1;
+    }
+
+    Some(sections)
+}
@@ -0,0 +1,104 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A JSON parser. Strings are decoded with
+//! [`Unescaper::default`](../../stdlib/escape/struct.Unescaper.html), which does not combine
+//! UTF-16 surrogate pairs, and numbers are read as `f64`s.
+//!
+//! # Examples
+//!
+//! ```
+//! use pest::formats::json::{self, Json};
+//!
+//! let value = json::parse(r#"{"a": [1, true, null], "b": "\n"}"#).unwrap();
+//!
+//! assert_eq!(value, Json::Object(vec![
+//!     ("a".to_owned(), Json::Array(vec![Json::Number(1.0), Json::Bool(true), Json::Null])),
+//!     ("b".to_owned(), Json::String("\n".to_owned()))
+//! ]));
+//! ```
+
+use super::super::prelude::*;
+use super::super::stdlib::escape::Unescaper;
+
+impl_rdp! {
+    grammar! {
+        json = { value ~ eoi }
+
+        value  = _{ object | array | string | number | bool | null }
+        object =  { ["{"] ~ (pair ~ ([","] ~ pair)*)? ~ ["}"] }
+        pair   =  { string ~ [":"] ~ value }
+        array  =  { ["["] ~ (value ~ ([","] ~ value)*)? ~ ["]"] }
+
+        string = @{ ["\""] ~ (["\\"] ~ any | !(["\""] | ["\\"]) ~ any)* ~ ["\""] }
+        number = @{ ["-"]? ~ int ~ (["."] ~ ['0'..'9']+)? ~ exp? }
+        int    =  { ["0"] | ['1'..'9'] ~ ['0'..'9']* }
+        exp    =  { (["E"] | ["e"]) ~ (["+"] | ["-"])? ~ ['0'..'9']+ }
+        bool   =  { ["true"] | ["false"] }
+        null   =  { ["null"] }
+
+        whitespace = _{ [" "] | ["\t"] | ["\r"] | ["\n"] }
+    }
+}
+
+/// An `enum` representing a JSON value. Objects keep their pairs in order, including duplicate
+/// keys.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    /// `null`
+    Null,
+    /// `true` or `false`
+    Bool(bool),
+    /// number
+    Number(f64),
+    /// decoded string
+    String(String),
+    /// array
+    Array(Vec<Json>),
+    /// object
+    Object(Vec<(String, Json)>)
+}
+
+/// Parses `source` into a `Json` value, returning `None` if it is not valid JSON or if one of
+/// its strings contains an invalid escape.
+pub fn parse(source: &str) -> Option<Json> {
+    let mut parser = Rdp::new(StringInput::new(source));
+
+    if !parser.json() {
+        return None;
+    }
+
+    let json = parser.queue().first()?;
+
+    parser.children_of(json).first().and_then(|value| convert(&parser, source, value))
+}
+
+fn convert<T: Input>(parser: &Rdp<T>, source: &str, token: &Token<Rule>) -> Option<Json> {
+    let text = &source[token.start..token.end];
+
+    match token.rule {
+        Rule::object => {
+            parser.children_of(token).into_iter().map(|pair| {
+                let children = parser.children_of(pair);
+                let key = &source[children[0].start..children[0].end];
+
+                Some((Unescaper::default().unquote(key).ok()?,
+                      convert(parser, source, children[1])?))
+            }).collect::<Option<_>>().map(Json::Object)
+        },
+        Rule::array  => {
+            parser.children_of(token).into_iter().map(|value| {
+                convert(parser, source, value)
+            }).collect::<Option<_>>().map(Json::Array)
+        },
+        Rule::string => Unescaper::default().unquote(text).ok().map(Json::String),
+        Rule::number => text.parse().ok().map(Json::Number),
+        Rule::bool   => Some(Json::Bool(text == "true")),
+        Rule::null   => Some(Json::Null),
+        _            => None
+    }
+}
@@ -0,0 +1,17 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A `mod` of complete parsers for common data formats, enabled with the `formats` feature.
+//!
+//! Every format is a `mod` with its own `Rdp` and `Rule` generated by `impl_rdp!`, along with a
+//! `parse` function that turns a `&str` into plain Rust values. Their `grammar!`s only use
+//! ordinary rules and the [`stdlib`](../stdlib/index.html), so they make good starting points
+//! for grammars of your own: copy one into your `impl_rdp!` and add or replace rules as needed.
+
+pub mod csv;
+pub mod ini;
+pub mod json;
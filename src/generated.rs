@@ -0,0 +1,252 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fmt::{Debug, Write};
+use std::hash::Hash;
+
+use super::{Alternative, ParseResult};
+
+/// An `enum` representing how a `Rule` is defined in the grammar, returned by the generated
+/// `Rule::category`. Silent rules have no `Rule`, so they have no category either.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::RuleKind;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         expression = _{
+///             { number }
+///             sum = { plus }
+///         }
+///         number = @{ ['0'..'9']+ }
+///         plus   =  { ["+"] }
+///         id     =  alias number
+///         label  =  token
+///     }
+/// }
+///
+/// assert_eq!(Rule::eoi.category(), RuleKind::Builtin);
+/// assert_eq!(Rule::number.category(), RuleKind::Atomic);
+/// assert_eq!(Rule::plus.category(), RuleKind::Normal);
+/// assert_eq!(Rule::sum.category(), RuleKind::Operator);
+/// assert_eq!(Rule::id.category(), RuleKind::Atomic);
+/// assert_eq!(Rule::label.category(), RuleKind::Token);
+///
+/// assert!(Rule::number.is_terminal());
+/// assert!(!Rule::sum.is_terminal());
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum RuleKind {
+    /// one of the predefined rules, e.g. `any` or `eoi`
+    Builtin,
+    /// an [atomic](macro.grammar!#atomic-rules-) or a [keywords](macro.grammar!#keywords) rule
+    Atomic,
+    /// a [normal](macro.grammar!#normal-rules), a
+    /// [non-atomic](macro.grammar!#non-atomic-rules-), or a [flat](macro.grammar!#flat-rules)
+    /// rule, including non-silent precedence climbing rules
+    Normal,
+    /// an operator of a [precedence climbing](macro.grammar!#precedence-climbing) rule
+    Operator,
+    /// a [token](macro.grammar!#token-rules) rule, which only names `Token`s pushed by hand
+    Token
+}
+
+impl RuleKind {
+    /// Returns whether a `RuleKind` is lexical, i.e. `Builtin`, `Atomic`, or `Token`, rather
+    /// than structural.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::RuleKind;
+    /// assert!(RuleKind::Atomic.is_terminal());
+    /// assert!(!RuleKind::Operator.is_terminal());
+    /// ```
+    pub fn is_terminal(&self) -> bool {
+        match *self {
+            RuleKind::Builtin | RuleKind::Atomic   | RuleKind::Token => true,
+            RuleKind::Normal  | RuleKind::Operator                   => false
+        }
+    }
+}
+
+/// A `trait` implemented by every parser generated with [`impl_rdp!`](macro.impl_rdp!), as
+/// `Rdp<StringInput>`, so that code driving parsers, like command-line runners, test harnesses,
+/// or language servers, can be written once for any grammar.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::GeneratedParser;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         list   = { ["["] ~ (number ~ ([","] ~ number)*)? ~ ["]"] }
+///         number = @{ ['0'..'9']+ }
+///     }
+/// }
+///
+/// fn check<P: GeneratedParser>(rule: &str, input: &str) -> Result<usize, String> {
+///     let rule = P::rules().iter().cloned().find(|&r| P::rule_name(r) == rule)
+///                          .ok_or_else(|| format!("unknown rule {}", rule))?;
+///     let result = P::parse(rule, input);
+///
+///     if result.is_complete() {
+///         Ok(result.tokens.len())
+///     } else {
+///         let names: Vec<_> = result.expected.0.iter().map(|&r| P::rule_name(r)).collect();
+///
+///         Err(format!("expected {} at {}", names.join(", "), result.expected.1))
+///     }
+/// }
+///
+/// assert_eq!(check::<Rdp<StringInput>>("list", "[1,2]"), Ok(3));
+/// assert_eq!(check::<Rdp<StringInput>>("list", "[1,]"), Err("expected number at 3".to_owned()));
+/// assert_eq!(check::<Rdp<StringInput>>("item", "1"), Err("unknown rule item".to_owned()));
+/// # }
+/// ```
+pub trait GeneratedParser {
+    type Rule: Copy + Debug + Eq + Hash + Ord + 'static;
+
+    /// Returns every `Rule`, ordered by discriminant.
+    fn rules() -> &'static [Self::Rule];
+
+    /// Returns the name of `rule` as written in the grammar.
+    fn rule_name(rule: Self::Rule) -> &'static str;
+
+    /// Returns whether `rule` can be passed to [`parse`](#tymethod.parse), i.e. whether it is
+    /// not an operator of a [precedence climbing](macro.grammar!#precedence-climbing) rule.
+    fn is_callable(rule: Self::Rule) -> bool;
+
+    /// Parses the whole `input` with `rule`, like
+    /// [`Parser::parse_complete`](trait.Parser#tymethod.parse_complete), and returns everything
+    /// produced. Rules that are not [callable](#tymethod.is_callable) never match.
+    fn parse(rule: Self::Rule, input: &str) -> ParseResult<Self::Rule>;
+
+    /// Returns how `rule` is defined in the grammar.
+    fn rule_kind(rule: Self::Rule) -> RuleKind;
+
+    /// Returns every top-level `Alternative` of the rules defined in `grammar!`, like
+    /// [`Parser::alternatives`](trait.Parser#tymethod.alternatives).
+    fn alternatives() -> Vec<Alternative>;
+
+    /// Returns an outline of the items that [`impl_rdp!`](macro.impl_rdp!) expands to: the
+    /// `Rule` `enum` and every rule method with its kind and source, in grammar order. The outline
+    /// only depends on the grammar, so writing it to a file, e.g. with the `expand` subcommand of
+    /// the [`cli`](cli/index), lets grammar authors review and diff the generated parser across
+    /// changes, or attach it to a report of a macro bug.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate pest;
+    /// # use pest::prelude::*;
+    /// # use pest::GeneratedParser;
+    /// # fn main() {
+    /// impl_rdp! {
+    ///     grammar! {
+    ///         list   = { number ~ ([","] ~ number)* | ["-"] }
+    ///         number = @{ ['0'..'9']+ }
+    ///         blank  = _{ [" "] }
+    ///     }
+    /// }
+    ///
+    /// let expansion = <Rdp<StringInput>>::expansion();
+    ///
+    /// assert!(expansion.contains("pub enum Rule {\n    any,\n    eoi,\n    list,\n"));
+    /// assert!(expansion.contains("\n    // silent\n    // blank = [\" \"]\n    \
+    ///                             pub fn blank(&mut self) -> bool;\n"));
+    /// # }
+    /// ```
+    fn expansion() -> String {
+        let mut out = String::new();
+        let name = |rule: &Self::Rule| Self::rule_name(*rule);
+
+        out.push_str("#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]\n");
+        out.push_str("pub enum Rule {\n");
+
+        for rule in Self::rules() {
+            match Self::rule_kind(*rule) {
+                RuleKind::Operator => {
+                    let _ = writeln!(out, "    {}, // operator", name(rule));
+                },
+                RuleKind::Token    => {
+                    let _ = writeln!(out, "    {}, // token", name(rule));
+                },
+                _                  => {
+                    let _ = writeln!(out, "    {},", name(rule));
+                }
+            }
+        }
+
+        out.push_str("}\n\nimpl<T: Input> Rdp<T> {\n");
+
+        let alternatives = Self::alternatives();
+        let mut methods: Vec<(&str, Option<RuleKind>, Vec<&str>)> = vec![];
+
+        for alternative in &alternatives {
+            match methods.last_mut() {
+                Some(&mut (rule, _, ref mut texts)) if rule == alternative.rule => {
+                    texts.push(alternative.text);
+                },
+                _ => {
+                    let kind = Self::rules().iter().find(|rule| name(rule) == alternative.rule)
+                                                    .map(|&rule| Self::rule_kind(rule));
+
+                    methods.push((alternative.rule, kind, vec![alternative.text]));
+                }
+            }
+        }
+
+        // predefined, keywords, and alias rules have no alternatives
+        for rule in Self::rules() {
+            let defined = methods.iter().any(|&(other, _, _)| other == name(rule));
+
+            if Self::is_callable(*rule) && !defined {
+                methods.push((name(rule), Some(Self::rule_kind(*rule)), vec![]));
+            }
+        }
+
+        for (i, &(rule, kind, ref texts)) in methods.iter().enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+
+            let kind = match kind {
+                Some(RuleKind::Builtin)  => "builtin",
+                Some(RuleKind::Atomic)   => "atomic",
+                Some(RuleKind::Normal)   => "normal",
+                Some(RuleKind::Operator) => "operator",
+                Some(RuleKind::Token)    => "token",
+                None                     => "silent"
+            };
+
+            let _ = writeln!(out, "    // {}", kind);
+
+            for (j, text) in texts.iter().enumerate() {
+                if j == 0 {
+                    let _ = writeln!(out, "    // {} = {}", rule, text);
+                } else {
+                    let _ = writeln!(out, "    // {:width$} | {}", "", text, width = rule.len());
+                }
+            }
+
+            let _ = writeln!(out, "    pub fn {}(&mut self) -> bool;", rule);
+        }
+
+        out.push_str("}\n");
+
+        out
+    }
+}
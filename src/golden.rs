@@ -0,0 +1,350 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+use super::{Parser, Token};
+
+/// A `struct` representing a `Token` independently of its `Rule` type, so that `Token`s of
+/// different grammar versions can be compared.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct SnapshotToken {
+    /// `Debug` name of the `Rule`
+    pub rule:  String,
+    /// starting position in `Input`
+    pub start: usize,
+    /// ending position in `Input`
+    pub end:   usize
+}
+
+/// An `enum` representing the outcome of parsing one input.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Snapshot {
+    /// the input matched and produced the queue of `Token`s
+    Tokens(Vec<SnapshotToken>),
+    /// the input failed to match at the given position
+    Failed(usize)
+}
+
+impl Snapshot {
+    /// Creates a `Snapshot` of `parser` after its start rule returned `matched`. Failures are
+    /// recorded at the position of [`Parser::expected`](trait.Parser#tymethod.expected).
+    pub fn of<P, R>(parser: &mut P, matched: bool) -> Snapshot
+        where P: Parser<Rule = R, Token = Token<R>>, R: fmt::Debug {
+
+        if matched {
+            Snapshot::Tokens(parser.queue().iter().map(|token| {
+                SnapshotToken {
+                    rule:  format!("{:?}", token.rule),
+                    start: token.start,
+                    end:   token.end
+                }
+            }).collect())
+        } else {
+            Snapshot::Failed(parser.expected().1)
+        }
+    }
+}
+
+/// An `enum` describing how the `Snapshot` of an input changed between two `Golden`s.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum GoldenChange {
+    /// the input only exists in the new `Golden`
+    Added,
+    /// the input only exists in the old `Golden`
+    Removed,
+    /// the input failed before and matches now
+    Fixed,
+    /// the input matched before and fails now at the given position
+    Broken(usize),
+    /// both `Snapshot`s differ starting at the `Token` with the given index
+    Changed(usize)
+}
+
+/// A `struct` mapping the names of a corpus' inputs to their `Snapshot`s. A `Golden` recorded
+/// with a trusted grammar can be stored with `to_string` and later diffed against a refactored
+/// grammar's `Golden` to find accidental changes.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::{Golden, GoldenChange, Snapshot};
+/// # fn main() {
+/// mod old {
+///     use pest::prelude::*;
+///
+///     impl_rdp! {
+///         grammar! {
+///             sum    = { number ~ (["+"] ~ number)* ~ eoi }
+///             number = @{ ['0'..'9']+ }
+///         }
+///     }
+/// }
+///
+/// mod new {
+///     use pest::prelude::*;
+///
+///     impl_rdp! {
+///         grammar! {
+///             sum    = { number ~ (["+"] ~ number)* ~ eoi }
+///             number = @{ ['0'..'9'] }
+///         }
+///     }
+/// }
+///
+/// let corpus = [("one", "1"), ("two", "12+3")];
+///
+/// let baseline = Golden::record(&corpus, |input| {
+///     let mut parser = old::Rdp::new(StringInput::new(input));
+///     let matched = parser.sum();
+///
+///     Snapshot::of(&mut parser, matched)
+/// });
+/// let stored = baseline.to_string();
+///
+/// let current = Golden::record(&corpus, |input| {
+///     let mut parser = new::Rdp::new(StringInput::new(input));
+///     let matched = parser.sum();
+///
+///     Snapshot::of(&mut parser, matched)
+/// });
+///
+/// let baseline: Golden = stored.parse().unwrap();
+///
+/// assert_eq!(baseline.diff(&current), vec![("two".to_owned(), GoldenChange::Broken(1))]);
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Golden {
+    snapshots: BTreeMap<String, Snapshot>
+}
+
+impl Golden {
+    /// Creates an empty `Golden`.
+    pub fn new() -> Golden {
+        Golden::default()
+    }
+
+    /// Creates a `Golden` by running `parse` on every named input of `corpus`.
+    pub fn record<F>(corpus: &[(&str, &str)], parse: F) -> Golden where F: Fn(&str) -> Snapshot {
+        let mut golden = Golden::new();
+
+        for &(name, input) in corpus {
+            golden.insert(name, parse(input));
+        }
+
+        golden
+    }
+
+    /// Adds the `Snapshot` of the input `name`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` contains a line break.
+    pub fn insert(&mut self, name: &str, snapshot: Snapshot) {
+        if name.contains('\n') || name.contains('\r') {
+            panic!("names cannot contain line breaks");
+        }
+
+        self.snapshots.insert(name.to_owned(), snapshot);
+    }
+
+    /// Returns the `Snapshot` of the input `name`.
+    pub fn get(&self, name: &str) -> Option<&Snapshot> {
+        self.snapshots.get(name)
+    }
+
+    /// Returns the names of the inputs whose `Snapshot`s differ between a `Golden` and `new`,
+    /// sorted by name, along with how they changed.
+    pub fn diff(&self, new: &Golden) -> Vec<(String, GoldenChange)> {
+        let mut changes = vec![];
+
+        for (name, old) in &self.snapshots {
+            let change = match (old, new.snapshots.get(name)) {
+                (_, None) => Some(GoldenChange::Removed),
+                (Snapshot::Failed(_), Some(Snapshot::Tokens(_))) => Some(GoldenChange::Fixed),
+                (Snapshot::Tokens(_), Some(Snapshot::Failed(pos))) => {
+                    Some(GoldenChange::Broken(*pos))
+                },
+                (Snapshot::Failed(old), Some(Snapshot::Failed(new))) => {
+                    if old != new {
+                        Some(GoldenChange::Broken(*new))
+                    } else {
+                        None
+                    }
+                },
+                (Snapshot::Tokens(old), Some(Snapshot::Tokens(new))) => {
+                    old.iter().zip(new.iter())
+                              .position(|(old, new)| old != new)
+                              .or_else(|| {
+                                  if old.len() != new.len() {
+                                      Some(old.len().min(new.len()))
+                                  } else {
+                                      None
+                                  }
+                              })
+                              .map(GoldenChange::Changed)
+                }
+            };
+
+            if let Some(change) = change {
+                changes.push((name.clone(), change));
+            }
+        }
+
+        for name in new.snapshots.keys() {
+            if !self.snapshots.contains_key(name) {
+                changes.push((name.clone(), GoldenChange::Added));
+            }
+        }
+
+        changes.sort_by(|a, b| a.0.cmp(&b.0));
+
+        changes
+    }
+}
+
+impl fmt::Display for Golden {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, snapshot) in &self.snapshots {
+            writeln!(f, "== {}", name)?;
+
+            match *snapshot {
+                Snapshot::Tokens(ref tokens) => {
+                    for token in tokens {
+                        writeln!(f, "{} {} {}", token.rule, token.start, token.end)?;
+                    }
+                },
+                Snapshot::Failed(pos) => writeln!(f, "! {}", pos)?
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A `struct` describing a malformed line of a stored `Golden`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct GoldenParseError {
+    /// line number, starting at `1`
+    pub line: usize
+}
+
+impl fmt::Display for GoldenParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "malformed golden snapshot at line {}", self.line)
+    }
+}
+
+impl Error for GoldenParseError {}
+
+impl ::std::str::FromStr for Golden {
+    type Err = GoldenParseError;
+
+    fn from_str(string: &str) -> Result<Golden, GoldenParseError> {
+        let mut golden = Golden::new();
+        let mut current: Option<(String, Snapshot)> = None;
+
+        for (i, line) in string.lines().enumerate() {
+            let error = GoldenParseError { line: i + 1 };
+
+            if let Some(name) = line.strip_prefix("== ") {
+                if let Some((name, snapshot)) = current.take() {
+                    golden.snapshots.insert(name, snapshot);
+                }
+
+                current = Some((name.to_owned(), Snapshot::Tokens(vec![])));
+
+                continue;
+            }
+
+            let snapshot = match current {
+                Some((_, ref mut snapshot)) => snapshot,
+                None                        => return Err(error)
+            };
+            let parts: Vec<&str> = line.split(' ').collect();
+
+            match (parts.as_slice(), snapshot) {
+                (&["!", pos], &mut Snapshot::Tokens(ref tokens)) if tokens.is_empty() => {
+                    let pos = pos.parse().map_err(|_| error)?;
+
+                    current.as_mut().unwrap().1 = Snapshot::Failed(pos);
+                },
+                (&[rule, start, end], &mut Snapshot::Tokens(ref mut tokens)) => {
+                    tokens.push(SnapshotToken {
+                        rule:  rule.to_owned(),
+                        start: start.parse().map_err(|_| error)?,
+                        end:   end.parse().map_err(|_| error)?
+                    });
+                },
+                _ => return Err(error)
+            }
+        }
+
+        if let Some((name, snapshot)) = current {
+            golden.snapshots.insert(name, snapshot);
+        }
+
+        Ok(golden)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(rule: &str, start: usize, end: usize) -> SnapshotToken {
+        SnapshotToken { rule: rule.to_owned(), start, end }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut golden = Golden::new();
+
+        golden.insert("a", Snapshot::Tokens(vec![token("x", 0, 2), token("y", 1, 2)]));
+        golden.insert("b", Snapshot::Failed(3));
+        golden.insert("c", Snapshot::Tokens(vec![]));
+
+        assert_eq!(golden.to_string().parse(), Ok(golden));
+    }
+
+    #[test]
+    fn malformed() {
+        assert_eq!("x 0 1".parse::<Golden>(), Err(GoldenParseError { line: 1 }));
+        assert_eq!("== a\nx 0".parse::<Golden>(), Err(GoldenParseError { line: 2 }));
+        assert_eq!("== a\nx 0 1\n! 2".parse::<Golden>(), Err(GoldenParseError { line: 3 }));
+    }
+
+    #[test]
+    fn diff() {
+        let mut old = Golden::new();
+        let mut new = Golden::new();
+
+        old.insert("same", Snapshot::Tokens(vec![token("x", 0, 1)]));
+        new.insert("same", Snapshot::Tokens(vec![token("x", 0, 1)]));
+        old.insert("changed", Snapshot::Tokens(vec![token("x", 0, 2), token("y", 0, 1)]));
+        new.insert("changed", Snapshot::Tokens(vec![token("x", 0, 2), token("z", 0, 1)]));
+        old.insert("longer", Snapshot::Tokens(vec![token("x", 0, 1)]));
+        new.insert("longer", Snapshot::Tokens(vec![token("x", 0, 1), token("y", 0, 1)]));
+        old.insert("fixed", Snapshot::Failed(0));
+        new.insert("fixed", Snapshot::Tokens(vec![]));
+        old.insert("removed", Snapshot::Failed(0));
+        new.insert("added", Snapshot::Failed(0));
+
+        assert_eq!(old.diff(&new), vec![
+            ("added".to_owned(), GoldenChange::Added),
+            ("changed".to_owned(), GoldenChange::Changed(1)),
+            ("fixed".to_owned(), GoldenChange::Fixed),
+            ("longer".to_owned(), GoldenChange::Changed(1)),
+            ("removed".to_owned(), GoldenChange::Removed)
+        ]);
+    }
+}
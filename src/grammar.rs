@@ -10,18 +10,53 @@
 /// rule defined.
 ///
 /// *Note:* `grammar!` may require you to increase the recursion limit of your create with
-/// `#![recursion_limit = "*"]` where * is the new limit.
+/// `#![recursion_limit = "*"]` where * is the new limit. Every rule is emitted into its own `impl`
+/// block, so the limit needed grows by roughly one per rule and with the length of the longest
+/// `~` sequence in a rule, while alternatives are cheap. The default limit of 128 fits around a
+/// hundred short rules or sequences of about 40 terms.
 ///
-/// pest has four special rules:
+/// pest has eleven special rules:
 ///
 /// * `whitespace` - gets run between rules and sub-rules
+/// * `newline` - gets run between rules and sub-rules like `whitespace`, but can insert
+///   terminators (see [`Parser::set_newline_hook`](trait.Parser#tymethod.set_newline_hook))
 /// * `comment` - gets run only between rules
 /// * `any` - matches exactly one `char`
+/// * `any_byte` - matches exactly one byte; only useful for inputs that are never sliced in the
+///   middle of a `char`, e.g. ASCII or binary-like formats
+/// * `any_ascii` - matches exactly one ASCII `char`
+/// * `line_ending` - matches `"\r\n"`, `"\n"`, or `"\r"` as a single line ending; it is not
+///   called `newline`, since `newline` is overridden like `whitespace`; it also skips
+///   [line prefixes](#line-prefixes)
 /// * `eoi` - (end-of-input) matches only when a `Parser` has reached its
 ///   [end](trait.Parser#tymethod.end)
+/// * `terminator` - matches once, without making progress, after a `newline` for which a
+///   terminator `Token` was inserted
+/// * `prolog` - always matches; at the start of the input, it skips a byte order mark and a
+///   `#!` shebang line, which is recorded in [`Parser::comments`](trait.Parser#tymethod.comments)
+/// * `prefixed` - matches, without making progress, on lines that start with all
+///   [line prefixes](#line-prefixes)
 ///
-/// `whitespace` and `comment` should be overridden and are void otherwise, while `any` and `eoi`
-/// are predefined rules.
+/// `whitespace`, `newline`, and `comment` should be overridden and are void otherwise, while `any`,
+/// `any_byte`, `any_ascii`, `line_ending`, `eoi`, `terminator`, `prolog`, and `prefixed` are
+/// predefined rules. `any_byte`, `any_ascii`, `line_ending`, `prolog`, and `prefixed` can still be
+/// overridden like `whitespace`, in which case the grammar's rule replaces the predefined one.
+///
+/// Scripting languages can thus start with `prolog` instead of spelling out the same rules:
+///
+/// ```ignore
+/// script = { prolog ~ statement* ~ eoi }
+/// ```
+///
+/// `whitespace`, `newline`, and `comment` are always [atomic](#atomic-rules-), so no trivia is
+/// skipped inside of them or of the rules they call. A `comment` can thus call itself for nested
+/// block comments, and `whitespace` can call a comment rule, without skipping trivia recursively:
+///
+/// ```ignore
+/// comment = _{ ["/*"] ~ (comment | !["*/"] ~ any)* ~ ["*/"] }
+/// ```
+///
+/// The [`nested_comment`](#nested-comments) built-in matches such comments faster.
 ///
 /// # Normal rules
 ///
@@ -54,6 +89,26 @@
 /// being matched in an atomic context. In other words, `a` and `b` will also be atomic when being
 /// matched inside of `ab`.
 ///
+/// Words like identifiers or numbers should be atomic: `number = { ['0'..'9']+ }` also matches
+/// `"1 2"` as a single `number`. The `split_word` [lint](#lints) warns about normal rules that only
+/// repeat characters like this one.
+///
+/// # Non-atomic rules `!`
+///
+/// Non-atomic rules work as normal rules, but they also stop the cascade of an atomic rule that
+/// calls them: `whitespace` and `comment` are skipped between their sub-rules again, which are
+/// tracked in [`Parser::expected`](trait.Parser#tymethod.expected) as well. This lets an atomic
+/// rule re-enter the rest of the grammar, e.g. for string interpolation.
+///
+/// ```ignore
+/// string = @{ ["\""] ~ (["${"] ~ interp ~ ["}"] | !["\""] ~ any)* ~ ["\""] }
+/// interp = !{ expr }
+/// ```
+///
+/// Trivia around `expr` is skipped, even though `string` is atomic. The atomic state is restored
+/// once `interp` returns. When it fails, it leaves the position where it was, so that an atomic
+/// caller never skips trivia.
+///
 /// # Silent rules `_`
 ///
 /// Silent rules work like normal rules without appearing in
@@ -81,6 +136,172 @@
 /// | `a?`         | optionally matches `a`                               |
 /// | `&a`         | matches `a` without making progress                  |
 /// | `!a`         | matches if `a` doesn't match without making progress |
+/// | `a - b`      | matches `a` unless `b` matches the same text         |
+/// | `a & b`      | matches `a` only if `b` matches the same text        |
+///
+/// ## Character-class operators
+///
+/// `a - b` (difference) and `a & b` (intersection) combine character classes, so that precise
+/// definitions do not need to enumerate ranges by hand. They bind tighter than any other operator
+/// and associate to the left, so `!a - b` is `!(a - b)` and `a - b*` is `(a - b)*`. Their operands
+/// are checked against the same text, without skipping trivia; for single characters, `a - b`
+/// matches a character in `a` that is not in `b`.
+///
+/// ```ignore
+/// ident_start = { ['a'..'z'] - ["q"] | ['A'..'Z'] & (['A'..'F'] | ['X'..'Z']) }
+/// digit       = { ['0'..'9'] - ["0"] }
+/// ```
+///
+/// ## Nested comments
+///
+/// `nested_comment(open, close)` matches a comment from `open` to its matching `close`, counting
+/// nested comments in between with a depth counter rather than recursion. Unterminated comments
+/// do not match, and `close` is then [expected](trait.Parser#tymethod.expectations) at the end of
+/// the input.
+///
+/// ```ignore
+/// comment = _{ nested_comment("/*", "*/") | ["//"] ~ (!["\n"] ~ any)* }
+/// ```
+///
+/// ## Here-documents
+///
+/// `heredoc(open)` matches a [`Heredoc`](struct.Heredoc) like `<<EOF` or `<<~EOF`, with the body
+/// on the following lines and the closing tag on a line of its own, which may be indented after
+/// `<<~`. It stops right after the closing tag. [`Heredoc::parse`](struct.Heredoc#method.parse)
+/// recovers the tag and the body, with its indentation stripped, from the matched text.
+///
+/// ```ignore
+/// string = @{ heredoc("<<") | ["\""] ~ (!["\""] ~ any)* ~ ["\""] }
+/// ```
+///
+/// ## Line prefixes
+///
+/// `with_prefix(prefix, rule)` matches `rule` while `prefix` is pushed onto a stack of line
+/// prefixes, as with [`Parser::with_prefix`](trait.Parser#tymethod.with_prefix). Line endings
+/// matched by `line_ending` or skipped by `newline` inside of it are followed by the registered
+/// prefixes, which are consumed transparently, so that container blocks like block quotes or list
+/// items can be parsed with the same rules as the top level. Prefixes are consumed up to the first
+/// missing one and lazy continuation lines thus still match, while `prefixed` tells them apart:
+///
+/// ```ignore
+/// quote = { ["> "] ~ with_prefix("> ", block) }
+/// block = { quote | lines }
+/// lines = @{ line ~ (line_ending ~ prefixed ~ line)* }
+/// line  = @{ (!line_ending ~ any)+ }
+/// ```
+///
+/// ## Islands
+///
+/// `skip_to(rule)` skips ahead to the next position where `rule` matches, without matching it,
+/// and fails if there is none. This way, island grammars can pick out the parts of an input they
+/// understand, like function signatures in arbitrary code, and ignore the water in between. As
+/// with [`Parser::skip_to`](trait.Parser#tymethod.skip_to), `rule` is only tried in front of the
+/// `char`s it can start with whenever its first term makes them known.
+///
+/// ```ignore
+/// signatures = { (skip_to(signature) ~ signature)* ~ any* ~ eoi }
+/// signature  = { ["fn"] ~ ident ~ ["("] ~ (ident ~ ([","] ~ ident)*)? ~ [")"] }
+/// ```
+///
+/// ## Flat rules
+///
+/// A flat rule works like a normal rule, but when it matches directly inside of itself, i.e. with
+/// only silent rules in between, it doesn't create a `Token` of its own. Its sub-rules' `Token`s
+/// are then attached to the outermost `Token`, so recursive lists produce a single `Token` with
+/// one child per element instead of one nested `Token` per element.
+///
+/// ```ignore
+/// args = flat { arg ~ ([","] ~ args)? }
+/// ```
+///
+/// Above, `a, b, c` produces one `args` `Token` containing three `arg` `Token`s.
+///
+/// ## Keywords
+///
+/// A keywords rule reserves a list of words and excludes them from an identifier rule, which then
+/// fails whenever its whole match is one of the words. The keywords rule itself matches the
+/// longest of its words, but only where the identifier rule doesn't match, so that `iffy` is an
+/// identifier and not `if` followed by `fy`.
+///
+/// ```ignore
+/// keyword = keywords(ident) { "if", "else", "while" }
+/// ident   = @{ ['a'..'z']+ }
+/// ```
+///
+/// ## Constants
+///
+/// Brackets accept any Rust expression, so strings and ranges can name `const` items in scope of
+/// the grammar, including ones brought in with `use` or named by path. The words of a keywords
+/// rule can also be `const` slices or arrays of `&str` (see [`Keywords`](trait.Keywords)), which
+/// lets highlighting tables and completion lists share the same list as the grammar.
+///
+/// ```ignore
+/// const CONTROL: &[&str] = &["if", "else", "while"];
+///
+/// keyword    = keywords(ident) { CONTROL, "let" }
+/// assignment = { ident ~ [syntax::ASSIGN] ~ expression }
+/// ident      = @{ [LOW..HIGH]+ }
+/// ```
+///
+/// ## Aliases
+///
+/// An alias matches exactly like another normal or atomic rule, but its `Token` has the alias'
+/// own `Rule`. Silent rules cannot be aliased. This is useful for telling apart rules that parse
+/// identically, but mean different things.
+///
+/// ```ignore
+/// ident     = @{ ['a'..'z']+ }
+/// type_name = alias ident
+/// ```
+///
+/// Since the alias reuses the rule's method, failures are reported as failures of the aliased
+/// rule in [`Parser::expected`](trait.Parser#tymethod.expected).
+///
+/// ## External rules
+///
+/// An external rule is matched by a rule of another parser generated by
+/// [`impl_rdp!`](macro.impl_rdp!), e.g. one named with `names!`, which runs on the rest of the
+/// input. This allows documents that mix several languages, like SQL in strings or CSS in HTML,
+/// to be parsed with one grammar per language.
+///
+/// ```ignore
+/// query        = { ["query"] ~ ["{"] ~ embedded_sql ~ ["}"] }
+/// embedded_sql = external SqlRdp::statement
+/// ```
+///
+/// The queue gets a single `Token` of the external rule spanning everything the other parser
+/// matched. The other parser's `Token`s are kept next to the queue and returned by
+/// [`Parser::embedded`](trait.Parser#tymethod.embedded), with their rules prefixed with the
+/// external rule's name, e.g. `embedded_sql::select`. The other parser starts out with default
+/// settings, so it does not share limits, trivia, or tracking with the one calling it.
+///
+/// ## Token rules
+///
+/// A token rule is only a `Rule`, without a method or a way of matching anything. It names
+/// `Token`s that are not produced by the grammar itself, like ones pushed by
+/// [`inject`](trait.Parser#tymethod.inject), relabeled by a rewrite, or used by declared
+/// operators.
+///
+/// ```ignore
+/// call  = { ident ~ ["("] ~ [")"] }
+/// field = token
+/// ```
+///
+/// Their [`RuleKind`](enum.RuleKind) is `Token`. Since they cannot be called, they are left out
+/// of [`GeneratedParser::is_callable`](trait.GeneratedParser#tymethod.is_callable).
+///
+/// ## Attributes
+///
+/// Every rule method is `#[inline]` by default. Attributes written in front of a rule replace
+/// that default and are forwarded to its method, e.g. to force tiny lexical rules inline or to
+/// keep rules only used on error paths out of the way.
+///
+/// ```ignore
+/// #[inline(always)]
+/// digit   = { ['0'..'9'] }
+/// #[cold]
+/// garbage = { (!["\n"] ~ any)* }
+/// ```
 ///
 /// ## Precedence climbing
 ///
@@ -99,6 +320,63 @@
 /// braces. It's followed by any number of rules, each rule having a precedence higher than the
 /// previous one. The `<` denote right-associativity, the default being left-associativity.
 ///
+/// Operators can also be added while parsing with
+/// [`Parser::add_operator`](trait.Parser#tymethod.add_operator), e.g. from a fixity
+/// declaration. The rules above have precedences `0`, `1`, and `2` in that case.
+///
+/// ## Errors
+///
+/// Every rule is checked before it is expanded, so that mistakes are reported with the name of
+/// the rule and the offending token instead of a failure deep inside of the macro.
+///
+/// ```compile_fail
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         // rule `list`: expected `~`, `|`, `-`, `&`, `*`, `+`, or `?`, found `[","]`
+///         list = { ['a'..'z'] [","] }
+///     }
+/// }
+/// # }
+/// ```
+///
+/// ## Lints
+///
+/// Mistakes that still compile are found by [`Parser::lints`](trait.Parser#tymethod.lints),
+/// which reports every [`LintKind`](enum.LintKind) as a warning by default. Attributes at the
+/// start of the grammar set their [`LintLevel`](enum.LintLevel)s, with later ones taking
+/// precedence:
+///
+/// ```ignore
+/// #![deny(nullable_loop, shadowed_alternative)]
+/// #![allow(unused_rule)]
+/// ```
+///
+/// Unknown lints and levels are compile errors:
+///
+/// ```compile_fail
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         #![deny(unused_rules)] // unknown lint `unused_rules`
+///
+///         list = { ['a'..'z'] ~ ([","] ~ ['a'..'z'])* }
+///     }
+/// }
+/// # }
+/// ```
+///
+/// The rules themselves can only be analyzed at run time, e.g. in a test that fails on denied
+/// lints:
+///
+/// ```ignore
+/// assert!(parser.lints().iter().all(|lint| lint.level != LintLevel::Deny));
+/// ```
+///
 /// # Examples
 ///
 /// ```
@@ -126,222 +404,339 @@ macro_rules! grammar {
 
     // handle right associativity rule
     ( @conv $atomic:tt $slf:ident [ < $( $ts:tt )* ] [] [] ) => {
-        grammar!(@conv $atomic $slf [ $( $ts )* ] [] [])
+        $crate::grammar!(@conv $atomic $slf [ $( $ts )* ] [] [])
     };
 
     // handle precedence climbing
-    ( @conv_prec $pos:ident ($_prec:expr) $_atomic:tt $slf:ident [] [] [] ) => {
+    ( @conv_prec $pos:ident $start:ident ($_prec:expr) $_atomic:tt $slf:ident [] [] [] ) => {
         {
-            $slf.set_pos($pos);
+            $crate::runtime::Parser::set_pos($slf, $start);
+
+            let operator = $crate::runtime::Parser::match_operator($slf);
 
-            None
+            if operator.is_none() {
+                $crate::runtime::Parser::set_pos($slf, $pos);
+            }
+
+            operator
         }
     };
     // normal
-    ( @conv_prec $pos:ident ($prec:expr) $atomic:tt $slf:ident
+    ( @conv_prec $pos:ident $start:ident ($prec:expr) $atomic:tt $slf:ident
       [ $name:ident = { $( $head:tt )* } $( $tail:tt )* ] [] [] ) => {
         {
-            if grammar!(@conv $atomic $slf [ $( $head )* ] [] []) {
-                return Some((Some(Rule::$name), $prec, grammar!(@assoc $( $head )*)))
+            if $crate::grammar!(@conv $atomic $slf [ $( $head )* ] [] []) {
+                return Some((Some(Rule::$name), $prec, $crate::grammar!(@assoc $( $head )*),
+                             $start..$crate::runtime::Parser::pos($slf)))
             } else {
-                grammar!(@conv_prec $pos ($prec + 1) $atomic $slf [ $( $tail )* ] [] [])
+                $crate::grammar!(@conv_prec $pos $start ($prec + 1) $atomic $slf [ $( $tail )* ]
+                                 [] [])
             }
         }
     };
     // atomic
-    ( @conv_prec $pos:ident ($prec:expr) $atomic:tt $slf:ident
+    ( @conv_prec $pos:ident $start:ident ($prec:expr) $atomic:tt $slf:ident
       [ $name:ident = @{ $( $head:tt )* } $( $tail:tt )* ] [] [] ) => {
         {
-            if grammar!(@conv true $slf [ $( $head )* ] [] []) {
-                return Some((Some(Rule::$name), $prec, grammar!(@assoc $( $head )*)))
+            if $crate::grammar!(@conv true $slf [ $( $head )* ] [] []) {
+                return Some((Some(Rule::$name), $prec, $crate::grammar!(@assoc $( $head )*),
+                             $start..$crate::runtime::Parser::pos($slf)))
             } else {
-                grammar!(@conv_prec $pos ($prec + 1) $atomic $slf [ $( $tail )* ] [] [])
+                $crate::grammar!(@conv_prec $pos $start ($prec + 1) $atomic $slf [ $( $tail )* ]
+                                 [] [])
             }
         }
     };
     // silent
-    ( @conv_prec $pos:ident ($prec:expr) $atomic:tt $slf:ident
+    ( @conv_prec $pos:ident $start:ident ($prec:expr) $atomic:tt $slf:ident
       [ $name:ident = _{ $( $head:tt )* } $( $tail:tt )* ] [] [] ) => {
         {
-            if grammar!(@conv $atomic $slf [ $( $head )* ] [] []) {
-                return Some((None, $prec, grammar!(@assoc $( $head )*)))
+            if $crate::grammar!(@conv $atomic $slf [ $( $head )* ] [] []) {
+                return Some((None, $prec, $crate::grammar!(@assoc $( $head )*),
+                             $start..$crate::runtime::Parser::pos($slf)))
             } else {
-                grammar!(@conv_prec $pos ($prec + 1) $atomic $slf [ $( $tail )* ] [] [])
+                $crate::grammar!(@conv_prec $pos $start ($prec + 1) $atomic $slf [ $( $tail )* ]
+                                 [] [])
             }
         }
     };
+    ( @conv_prec $_pos:ident $_start:ident ($_prec:expr) $_atomic:tt $_slf:ident $_rules:tt [] []
+    ) => {
+        compile_error!("invalid rule expression")
+    };
     ( @conv $atomic:tt $slf:ident [ { $( $primary:tt )* } $( $ts:tt )* ] [] [] ) => {
 
           {
               let mut primary = |slf: &mut Self| {
-                  let pos = slf.pos();
+                  let pos = $crate::runtime::Parser::pos(slf);
 
-                  grammar!(@skip $atomic slf);
+                  $crate::grammar!(@skip $atomic slf);
 
-                  let result = grammar!(@conv $atomic slf [ $( $primary )* ] [] []);
+                  let result = $crate::grammar!(@conv $atomic slf [ $( $primary )* ] [] []);
 
                   if !result {
-                      slf.set_pos(pos);
+                      $crate::runtime::Parser::set_pos(slf, pos);
                   }
 
                   result
               };
               let mut climb = |slf: &mut Self| {
-                  let pos = slf.pos();
+                  let pos = $crate::runtime::Parser::pos(slf);
 
-                  grammar!(@skip $atomic slf);
+                  $crate::grammar!(@skip $atomic slf);
 
-                  grammar!(@conv_prec pos (0u8) $atomic slf [ $( $ts )* ] [] [])
+                  let start = $crate::runtime::Parser::pos(slf);
+
+                  $crate::grammar!(@conv_prec pos start (0u8) $atomic slf [ $( $ts )* ] [] [])
               };
 
-              let mut pos = $slf.pos();
-              let queue_pos = $slf.queue().len();
+              let mut pos = $crate::runtime::Parser::pos($slf);
+              let queue_pos = $crate::runtime::Parser::queue($slf).len();
 
               let result = primary($slf);
 
-              if let Some(token) = $slf.queue().get(queue_pos) {
-                  pos = token.start;
+              if let Some(token) = $crate::runtime::Parser::queue($slf).get(queue_pos) {
+                  pos = token.start();
               }
 
               if result {
-                  $slf.prec_climb(queue_pos, pos, 0, None, &mut primary, &mut climb);
+                  $crate::runtime::Parser::prec_climb($slf, queue_pos, pos, 0, None, &mut primary,
+                                                      &mut climb);
               }
 
               result
           }
     };
 
+    // turn built-ins with arguments into single operands
+    ( @conv $atomic:tt $slf:ident [ $builtin:ident ( $( $args:tt )* ) $( $tail:tt )* ] $ops:tt
+      $output:tt ) => {
+        $crate::grammar!(@conv $atomic $slf [ [ @call $builtin $( $args )* ] $( $tail )* ] $ops
+                         $output)
+    };
+    ( @conv $atomic:tt $slf:ident [ & $builtin:ident ( $( $args:tt )* ) $( $tail:tt )* ]
+      $ops:tt $output:tt ) => {
+        $crate::grammar!(@conv $atomic $slf [ & [ @call $builtin $( $args )* ] $( $tail )* ] $ops
+                         $output)
+    };
+    ( @conv $atomic:tt $slf:ident [ ! $builtin:ident ( $( $args:tt )* ) $( $tail:tt )* ]
+      $ops:tt $output:tt ) => {
+        $crate::grammar!(@conv $atomic $slf [ ! [ @call $builtin $( $args )* ] $( $tail )* ] $ops
+                         $output)
+    };
+
+    // character-class operators bind tighter than any other operator, so `a - b` and `a & b`
+    // become single operands, also after a prefix operator and around parentheses
+    ( @conv $atomic:tt $slf:ident [ ( $( $a:tt )* ) - $b:tt $( $tail:tt )* ] $ops:tt $output:tt )
+    => {
+        $crate::grammar!(@conv $atomic $slf [ [ @class - ( $( $a )* ) $b ] $( $tail )* ] $ops
+                         $output)
+    };
+    ( @conv $atomic:tt $slf:ident [ ( $( $a:tt )* ) & $b:tt $( $tail:tt )* ] $ops:tt $output:tt )
+    => {
+        $crate::grammar!(@conv $atomic $slf [ [ @class & ( $( $a )* ) $b ] $( $tail )* ] $ops
+                         $output)
+    };
+    ( @conv $atomic:tt $slf:ident [ & $a:tt - $b:tt $( $tail:tt )* ] $ops:tt $output:tt ) => {
+        $crate::grammar!(@conv $atomic $slf [ & [ @class - $a $b ] $( $tail )* ] $ops $output)
+    };
+    ( @conv $atomic:tt $slf:ident [ & $a:tt & $b:tt $( $tail:tt )* ] $ops:tt $output:tt ) => {
+        $crate::grammar!(@conv $atomic $slf [ & [ @class & $a $b ] $( $tail )* ] $ops $output)
+    };
+    ( @conv $atomic:tt $slf:ident [ ! $a:tt - $b:tt $( $tail:tt )* ] $ops:tt $output:tt ) => {
+        $crate::grammar!(@conv $atomic $slf [ ! [ @class - $a $b ] $( $tail )* ] $ops $output)
+    };
+    ( @conv $atomic:tt $slf:ident [ ! $a:tt & $b:tt $( $tail:tt )* ] $ops:tt $output:tt ) => {
+        $crate::grammar!(@conv $atomic $slf [ ! [ @class & $a $b ] $( $tail )* ] $ops $output)
+    };
+
     // handle parens
     ( @conv $atomic:tt $slf:ident [ ( $( $head:tt )* ) $( $tail:tt )* ] [ $( $optail:tt )* ]
       [ $( $output:tt )* ] ) => {
-        grammar!(@conv $atomic $slf [ $( $head )* _rp $( $tail )* ] [ _lp $( $optail )* ]
-                 [ $( $output )* ])
+        $crate::grammar!(@conv $atomic $slf [ $( $head )* _rp $( $tail )* ] [ _lp $( $optail )* ]
+                         [ $( $output )* ])
     };
     ( @conv $atomic:tt $slf:ident [ _rp $( $tail:tt )* ] [ _lp $( $optail:tt )* ]
       [ $( $output:tt )* ] ) => {
-        grammar!(@conv $atomic $slf [ $( $tail )* ] [ $( $optail )* ] [ $( $output )* ])
+        $crate::grammar!(@conv $atomic $slf [ $( $tail )* ] [ $( $optail )* ] [ $( $output )* ])
     };
     ( @conv $atomic:tt $slf:ident [ _rp $( $tail:tt )* ] [ $op:tt $( $optail:tt )* ]
       [ $( $output:tt )* ] ) => {
-        grammar!(@conv $atomic $slf [ _rp $( $tail )* ] [ $( $optail )* ] [ $( $output )* $op ])
+        $crate::grammar!(@conv $atomic $slf [ _rp $( $tail )* ] [ $( $optail )* ]
+                         [ $( $output )* $op ])
     };
 
     // handle prefix operands
     ( @conv $atomic:tt $slf:ident [ & $head:tt $( $tail:tt )* ] [ $( $optail:tt )* ]
       $output:tt ) => {
-        grammar!(@conv $atomic $slf [ $head _pres $( $tail )* ] [ $( $optail )* ] $output)
+        $crate::grammar!(@conv $atomic $slf [ $head _pres $( $tail )* ] [ $( $optail )* ] $output)
     };
     ( @conv $atomic:tt $slf:ident [ ! $head:tt $( $tail:tt )* ] [ $( $optail:tt )* ]
       $output:tt ) => {
-        grammar!(@conv $atomic $slf [ $head _abs $( $tail )* ] [ $( $optail )* ] $output)
+        $crate::grammar!(@conv $atomic $slf [ $head _abs $( $tail )* ] [ $( $optail )* ] $output)
     };
 
     // handle infix operands
     ( @conv $atomic:tt $slf:ident [ ~ $( $tail:tt )* ] [ ~ $( $optail:tt )* ]
       [ $( $output:tt )* ] ) => {
-        grammar!(@conv $atomic $slf [ ~ $( $tail )* ] [ $( $optail )* ] [ $( $output )* ~ ])
+        $crate::grammar!(@conv $atomic $slf [ ~ $( $tail )* ] [ $( $optail )* ] [ $( $output )* ~ ])
     };
     ( @conv $atomic:tt $slf:ident [ ~ $( $tail:tt )* ] [ $( $optail:tt )* ] $output:tt) => {
-        grammar!(@conv $atomic $slf [ $( $tail )* ] [ ~ $( $optail )* ] $output)
+        $crate::grammar!(@conv $atomic $slf [ $( $tail )* ] [ ~ $( $optail )* ] $output)
     };
     ( @conv $atomic:tt $slf:ident [ | $( $tail:tt )* ] [ ~ $( $optail:tt )* ]
       [ $( $output:tt )* ] ) => {
-        grammar!(@conv $atomic $slf [ | $( $tail )* ] [ $( $optail )* ] [ $( $output )* ~ ])
+        $crate::grammar!(@conv $atomic $slf [ | $( $tail )* ] [ $( $optail )* ] [ $( $output )* ~ ])
     };
     ( @conv $atomic:tt $slf:ident [ | $( $tail:tt )* ] [ | $( $optail:tt )* ]
       [ $( $output:tt )* ] ) => {
-        grammar!(@conv $atomic $slf [ | $( $tail )* ] [ $( $optail )* ] [ $( $output )* | ])
+        $crate::grammar!(@conv $atomic $slf [ | $( $tail )* ] [ $( $optail )* ] [ $( $output )* | ])
     };
     ( @conv $atomic:tt $slf:ident [ | $( $tail:tt )* ] [ $( $optail:tt )* ] $output:tt) => {
-        grammar!(@conv $atomic $slf [ $( $tail )* ] [ | $( $optail )* ] $output)
+        $crate::grammar!(@conv $atomic $slf [ $( $tail )* ] [ | $( $optail )* ] $output)
     };
 
-    // handle everything else
+    // operands of character-class operators; operators have been handled above at this point
+    ( @conv $atomic:tt $slf:ident [ $a:tt - $b:tt $( $tail:tt )* ] $ops:tt $output:tt ) => {
+        $crate::grammar!(@conv $atomic $slf [ [ @class - $a $b ] $( $tail )* ] $ops $output)
+    };
+    ( @conv $atomic:tt $slf:ident [ $a:tt & $b:tt $( $tail:tt )* ] $ops:tt $output:tt ) => {
+        $crate::grammar!(@conv $atomic $slf [ [ @class & $a $b ] $( $tail )* ] $ops $output)
+    };
+
+    // handle everything else, together with a following `~` in one step
+    ( @conv $atomic:tt $slf:ident [ $head:tt ~ $( $tail:tt )* ] [ ~ $( $optail:tt )* ]
+      [ $( $output:tt )* ] ) => {
+        $crate::grammar!(@conv $atomic $slf [ $( $tail )* ] [ ~ $( $optail )* ]
+                         [ $( $output )* $head ~ ])
+    };
+    ( @conv $atomic:tt $slf:ident [ $head:tt ~ $( $tail:tt )* ] [ $( $optail:tt )* ]
+      [ $( $output:tt )* ] ) => {
+        $crate::grammar!(@conv $atomic $slf [ $( $tail )* ] [ ~ $( $optail )* ]
+                         [ $( $output )* $head ])
+    };
     ( @conv $atomic:tt $slf:ident [ $head:tt $( $tail:tt )* ] $ops:tt [ $( $output:tt )* ] ) => {
-        grammar!(@conv $atomic $slf [ $( $tail )* ] $ops [ $( $output )* $head ])
+        $crate::grammar!(@conv $atomic $slf [ $( $tail )* ] $ops [ $( $output )* $head ])
     };
 
     // output remaining operators
     ( @conv $atomic:tt $slf:ident [] [] [ $( $output:tt )* ] ) => {
-        grammar!(@process $atomic $slf [] [ $( $output )* ])
+        $crate::grammar!(@process $atomic $slf [] [ $( $output )* ])
     };
     ( @conv $atomic:tt $slf:ident [] [ $op:tt $( $optail:tt )* ] [ $( $output:tt )* ] ) => {
-        grammar!(@conv $atomic $slf [] [ $( $optail )* ] [ $( $output )* $op ])
+        $crate::grammar!(@conv $atomic $slf [] [ $( $optail )* ] [ $( $output )* $op ])
     };
 
     // match
     ( @mtc $slf:ident (( $exp:expr )) ) => (($exp));
-    ( @mtc $slf:ident [ $left:tt .. $right:tt ]) => (grammar!(@mtc $slf [$left, $right]));
-    ( @mtc $slf:ident [ $left:expr, $right:expr ]) => ($slf.match_range($left, $right));
-    ( @mtc $slf:ident [ $str:expr ]) => ($slf.match_string($str));
+    ( @mtc $slf:ident [ @call nested_comment $open:expr, $close:expr ] ) => {
+        $crate::runtime::Parser::match_nested($slf, $open, $close)
+    };
+    ( @mtc $slf:ident [ @call heredoc $open:expr ] ) => {
+        $crate::runtime::Parser::match_heredoc($slf, $open)
+    };
+    ( @mtc $slf:ident [ @call with_prefix $prefix:expr, $rule:ident ] ) => {
+        $crate::runtime::Parser::with_prefix($slf, $prefix, |slf| slf.$rule())
+    };
+    ( @mtc $slf:ident [ @call skip_to $rule:ident ] ) => {
+        $crate::runtime::Parser::skip_to($slf, stringify!($rule), |slf| slf.$rule())
+    };
+    ( @mtc $slf:ident [ @class - $a:tt $b:tt ] ) => {
+        $crate::runtime::Parser::match_difference($slf, |slf| $crate::grammar!(@operand slf $a),
+                              |slf| $crate::grammar!(@operand slf $b))
+    };
+    ( @mtc $slf:ident [ @class & $a:tt $b:tt ] ) => {
+        $crate::runtime::Parser::match_intersection($slf, |slf| $crate::grammar!(@operand slf $a),
+                                |slf| $crate::grammar!(@operand slf $b))
+    };
+    ( @mtc $slf:ident [ $left:tt .. $right:tt ]) => ($crate::grammar!(@mtc $slf [$left, $right]));
+    ( @mtc $slf:ident [ $left:expr, $right:expr ]) => {
+        $crate::runtime::Parser::match_range($slf, $left, $right)
+    };
+    ( @mtc $slf:ident [ $str:expr ]) => ($crate::runtime::Parser::match_string($slf, $str));
     ( @mtc $slf:ident $rule:ident) => ($slf.$rule());
+    ( @mtc $slf:ident $_other:tt) => (compile_error!("invalid rule expression"));
+
+    // operands of character-class operators never skip trivia, like atomic rules
+    ( @operand $slf:ident ( $( $ts:tt )* ) ) => {
+        $crate::grammar!(@conv true $slf [ $( $ts )* ] [] [])
+    };
+    ( @operand $slf:ident $single:tt ) => ($crate::grammar!(@mtc $slf $single));
 
     // process postfix
     ( @process $_atomic:tt $_slf:ident [( $result:expr )] [] ) => ($result);
-    ( @process false $slf:ident [ $b:tt $a:tt $( $tail:tt )* ] [ ~ $( $optail:tt )* ] ) => {
+    ( @process false $slf:ident [ (( $b:expr )) (( $a:expr )) $( $tail:tt )* ]
+      [ ~ $( $optail:tt )* ] ) => {
         {
-            grammar!(@process false $slf [(( $slf.try(false, |$slf| {
-                if grammar!(@mtc $slf $a) {
-                    let original = $slf.pos();
+            $crate::grammar!(@process false $slf [((
+                $crate::runtime::Parser::try($slf, false, |$slf| {
+                    if ($a) {
+                        let original = $crate::runtime::Parser::pos($slf);
 
-                    $slf.skip_ws();
+                        $crate::runtime::Parser::skip_ws($slf);
 
-                    let pos = $slf.pos();
-                    let len = $slf.queue().len();
+                        let pos = $crate::runtime::Parser::pos($slf);
+                        let len = $crate::runtime::Parser::queue($slf).len();
 
-                    let result = grammar!(@mtc $slf $b);
+                        let result = ($b);
 
-                    if $slf.pos() == pos && !$slf.eoi_matched() {
-                        $slf.set_pos(original);
-                    }
+                        if $crate::runtime::Parser::pos($slf) == pos &&
+                           !$crate::runtime::Parser::eoi_matched($slf) {
+                            $crate::runtime::Parser::set_pos($slf, original);
+                        }
 
-                    result
-                } else {
-                    false
-                }
-            }) )) $( $tail )* ] [ $( $optail )* ])
+                        result
+                    } else {
+                        false
+                    }
+                })
+            )) $( $tail )* ] [ $( $optail )* ])
         }
     };
-    ( @process true $slf:ident [ $b:tt $a:tt $( $tail:tt )* ] [ ~ $( $optail:tt )* ] ) => {
+    ( @process true $slf:ident [ (( $b:expr )) (( $a:expr )) $( $tail:tt )* ]
+      [ ~ $( $optail:tt )* ] ) => {
         {
-            grammar!(@process true $slf [(( $slf.try(false, |$slf| {
-                grammar!(@mtc $slf $a) && grammar!(@mtc $slf $b)
-            }) )) $( $tail )* ] [ $( $optail )* ])
+            $crate::grammar!(@process true $slf [((
+                $crate::runtime::Parser::try($slf, false, |$slf| {
+                    ($a) && ($b)
+                })
+            )) $( $tail )* ] [ $( $optail )* ])
         }
     };
-    ( @process $atomic:tt $slf:ident [ $b:tt $a:tt $( $tail:tt )* ] [ | $( $optail:tt )* ] ) => {
+    ( @process $atomic:tt $slf:ident [ (( $b:expr )) (( $a:expr )) $( $tail:tt )* ]
+      [ | $( $optail:tt )* ] ) => {
         {
-            grammar!(@process $atomic $slf [((
-                grammar!(@mtc $slf $a) || grammar!(@mtc $slf $b)
+            $crate::grammar!(@process $atomic $slf [((
+                ($a) || ($b)
             )) $( $tail )* ] [ $( $optail )* ])
         }
     };
-    ( @process false $slf:ident [ $a:tt $( $tail:tt )* ] [ * $( $optail:tt )* ] ) => {
+    ( @process false $slf:ident [ (( $a:expr )) $( $tail:tt )* ] [ * $( $optail:tt )* ] ) => {
         {
-            grammar!(@process false $slf [(( {
-                let mut pos = $slf.pos();
+            $crate::grammar!(@process false $slf [(( {
+                let mut pos = $crate::runtime::Parser::pos($slf);
 
                 loop {
-                    if !grammar!(@mtc $slf $a) {
-                        $slf.set_pos(pos);
+                    if !($a) {
+                        $crate::runtime::Parser::set_pos($slf, pos);
 
                         break
                     }
 
-                    pos = $slf.pos();
+                    pos = $crate::runtime::Parser::pos($slf);
 
-                    $slf.skip_ws();
+                    $crate::runtime::Parser::skip_ws($slf);
                 }
 
                 true
             } )) $( $tail )* ] [ $( $optail )* ])
         }
     };
-    ( @process true $slf:ident [ $a:tt $( $tail:tt )* ] [ * $( $optail:tt )* ] ) => {
+    ( @process true $slf:ident [ (( $a:expr )) $( $tail:tt )* ] [ * $( $optail:tt )* ] ) => {
         {
-            grammar!(@process true $slf [(( {
+            $crate::grammar!(@process true $slf [(( {
                 loop {
-                    if !grammar!(@mtc $slf $a) {
+                    if !($a) {
                         break
                     }
                 }
@@ -350,16 +745,16 @@ macro_rules! grammar {
             } )) $( $tail )* ] [ $( $optail )* ])
         }
     };
-    ( @process false $slf:ident [ $a:tt $( $tail:tt )* ] [ + $( $optail:tt )* ] ) => {
+    ( @process false $slf:ident [ (( $a:expr )) $( $tail:tt )* ] [ + $( $optail:tt )* ] ) => {
         {
-            grammar!(@process false $slf [(( if grammar!(@mtc $slf $a) {
+            $crate::grammar!(@process false $slf [(( if ($a) {
                 loop {
-                    let pos = $slf.pos();
+                    let pos = $crate::runtime::Parser::pos($slf);
 
-                    $slf.skip_ws();
+                    $crate::runtime::Parser::skip_ws($slf);
 
-                    if !grammar!(@mtc $slf $a) {
-                        $slf.set_pos(pos);
+                    if !($a) {
+                        $crate::runtime::Parser::set_pos($slf, pos);
 
                         break
                     }
@@ -371,11 +766,11 @@ macro_rules! grammar {
             } )) $( $tail )* ] [ $( $optail )* ])
         }
     };
-    ( @process true $slf:ident [ $a:tt $( $tail:tt )* ] [ + $( $optail:tt )* ] ) => {
+    ( @process true $slf:ident [ (( $a:expr )) $( $tail:tt )* ] [ + $( $optail:tt )* ] ) => {
         {
-            grammar!(@process true $slf [(( if grammar!(@mtc $slf $a) {
+            $crate::grammar!(@process true $slf [(( if ($a) {
                 loop {
-                    if !grammar!(@mtc $slf $a) {
+                    if !($a) {
                         break
                     }
                 }
@@ -386,162 +781,825 @@ macro_rules! grammar {
             } )) $( $tail )* ] [ $( $optail )* ])
         }
     };
-    ( @process $atomic:tt $slf:ident [ $a:tt $( $tail:tt )* ] [ ? $( $optail:tt )* ] ) => {
+    ( @process $atomic:tt $slf:ident [ (( $a:expr )) $( $tail:tt )* ] [ ? $( $optail:tt )* ] ) => {
         {
-            grammar!(@process $atomic $slf [(( {
-                grammar!(@mtc $slf $a);
+            $crate::grammar!(@process $atomic $slf [(( {
+                let _ = ($a);
 
                 true
             } )) $( $tail )* ] [ $( $optail )* ])
         }
     };
-    ( @process $atomic:tt $slf:ident [ $a:tt $( $tail:tt )* ] [ _pres $( $optail:tt )* ] ) => {
+    ( @process $atomic:tt $slf:ident [ (( $a:expr )) $( $tail:tt )* ]
+      [ _pres $( $optail:tt )* ] ) => {
         {
-            grammar!(@process $atomic $slf [(( $slf.try(true, |$slf| {
-                grammar!(@mtc $slf $a)
-            }) )) $( $tail )* ] [ $( $optail )* ])
+            $crate::grammar!(@process $atomic $slf [((
+                $crate::runtime::Parser::try($slf, true, |$slf| {
+                    ($a)
+                })
+            )) $( $tail )* ] [ $( $optail )* ])
         }
     };
-    ( @process $atomic:tt $slf:ident [ $a:tt $( $tail:tt )* ] [ _abs $( $optail:tt )* ] ) => {
+    ( @process $atomic:tt $slf:ident [ (( $a:expr )) $( $tail:tt )* ]
+      [ _abs $( $optail:tt )* ] ) => {
         {
-            grammar!(@process $atomic $slf [(( $slf.try(true, |$slf| {
-                !grammar!(@mtc $slf $a)
-            }) )) $( $tail )* ] [ $( $optail )* ])
+            $crate::grammar!(@process $atomic $slf [((
+                $crate::runtime::Parser::try($slf, true, |$slf| {
+                    !($a)
+                })
+            )) $( $tail )* ] [ $( $optail )* ])
         }
     };
     ( @process $_atomic:tt $slf:ident [] [ $single:tt ] ) => {
-        grammar!(@mtc $slf $single)
+        $crate::grammar!(@mtc $slf $single)
+    };
+    // operands are matched once, when shifted, so that results do not nest further macro calls
+    // an operand followed by `~` is matched and sequenced in one step; every operator has been
+    // handled above at this point
+    ( @process false $slf:ident [ (( $a:expr )) $( $tail:tt )* ]
+      [ $b:tt ~ $( $optail:tt )* ] ) => {
+        {
+            $crate::grammar!(@process false $slf [((
+                $crate::runtime::Parser::try($slf, false, |$slf| {
+                    if ($a) {
+                        let original = $crate::runtime::Parser::pos($slf);
+
+                        $crate::runtime::Parser::skip_ws($slf);
+
+                        let pos = $crate::runtime::Parser::pos($slf);
+                        let len = $crate::runtime::Parser::queue($slf).len();
+
+                        let result = ($crate::grammar!(@mtc $slf $b));
+
+                        if $crate::runtime::Parser::pos($slf) == pos &&
+                           !$crate::runtime::Parser::eoi_matched($slf) {
+                            $crate::runtime::Parser::set_pos($slf, original);
+                        }
+
+                        result
+                    } else {
+                        false
+                    }
+                })
+            )) $( $tail )* ] [ $( $optail )* ])
+        }
+    };
+    ( @process true $slf:ident [ (( $a:expr )) $( $tail:tt )* ]
+      [ $b:tt ~ $( $optail:tt )* ] ) => {
+        {
+            $crate::grammar!(@process true $slf [((
+                $crate::runtime::Parser::try($slf, false, |$slf| {
+                    ($a) && ($crate::grammar!(@mtc $slf $b))
+                })
+            )) $( $tail )* ] [ $( $optail )* ])
+        }
     };
     ( @process $atomic:tt $slf:ident [ $( $optail:tt )* ] [ $head:tt $( $tail:tt )* ] ) => {
-        grammar!(@process $atomic $slf [ $head $( $optail )* ] [ $( $tail )* ])
+        $crate::grammar!(@process $atomic $slf
+                         [ (( ($crate::grammar!(@mtc $slf $head)) )) $( $optail )* ]
+                         [ $( $tail )* ])
+    };
+    ( @process $_atomic:tt $_slf:ident $_stack:tt [] ) => {
+        compile_error!("invalid rule expression")
     };
 
     // skip only if not whitespace
     ( @skip whitespace $_slf:ident )  => ();
-    ( @skip comment $slf:ident )      => ($slf.skip_ws());
+    ( @skip newline $_slf:ident )     => ();
+    ( @skip comment $slf:ident )      => ($crate::runtime::Parser::skip_ws($slf));
     ( @skip $_name:ident $slf:ident ) => {
         {
-            $slf.skip_com();
-            $slf.skip_ws();
+            $crate::runtime::Parser::skip_com($slf);
+            $crate::runtime::Parser::skip_ws($slf);
         }
     };
     // skip if not atomic
     ( @skip false $slf:ident ) => ();
     ( @skip true $slf:ident ) => {
         {
-            $slf.skip_com();
-            $slf.skip_ws();
+            $crate::runtime::Parser::skip_com($slf);
+            $crate::runtime::Parser::skip_ws($slf);
         }
     };
 
-    // whitespace is always atomic
-    ( @atomic whitespace $_atomic:tt $slf:ident $rules:tt ) => {
-        grammar!(@conv true $slf $rules [] [])
+    // split rules into top-level alternatives for coverage
+    ( @alts $name:ident $atomic:tt $slf:ident [ $( $alts:tt )* ] [ $( $cur:tt )* ]
+      | $( $ts:tt )* ) => {
+        $crate::grammar!(@alts $name $atomic $slf [ $( $alts )* [ $( $cur )* ] ] [] $( $ts )*)
     };
-    ( @atomic $_name:ident $atomic:tt $slf:ident $rules:tt ) => {
-        grammar!(@conv $atomic $slf $rules [] [])
+    ( @alts $name:ident $atomic:tt $slf:ident $alts:tt [ $( $cur:tt )* ]
+      $a:tt | $( $ts:tt )* ) => {
+        $crate::grammar!(@alts $name $atomic $slf $alts [ $( $cur )* $a ] | $( $ts )*)
+    };
+    ( @alts $name:ident $atomic:tt $slf:ident $alts:tt [ $( $cur:tt )* ]
+      $a:tt $b:tt | $( $ts:tt )* ) => {
+        $crate::grammar!(@alts $name $atomic $slf $alts [ $( $cur )* $a $b ] | $( $ts )*)
+    };
+    ( @alts $name:ident $atomic:tt $slf:ident $alts:tt [ $( $cur:tt )* ]
+      $a:tt $b:tt $c:tt | $( $ts:tt )* ) => {
+        $crate::grammar!(@alts $name $atomic $slf $alts [ $( $cur )* $a $b $c ] | $( $ts )*)
     };
+    // none of the next four tokens is a `|`, so they can all be moved at once
+    ( @alts $name:ident $atomic:tt $slf:ident $alts:tt [ $( $cur:tt )* ]
+      $a:tt $b:tt $c:tt $d:tt $( $ts:tt )* ) => {
+        $crate::grammar!(@alts $name $atomic $slf $alts [ $( $cur )* $a $b $c $d ] $( $ts )*)
+    };
+    ( @alts $name:ident $atomic:tt $slf:ident $alts:tt [ $( $cur:tt )* ]
+      $t:tt $( $ts:tt )* ) => {
+        $crate::grammar!(@alts $name $atomic $slf $alts [ $( $cur )* $t ] $( $ts )*)
+    };
+    ( @alts $name:ident $atomic:tt $slf:ident [ $( $alts:tt )* ] [ $( $cur:tt )* ] ) => {
+        $crate::grammar!(@cov $name $atomic $slf [ $( $alts )* [ $( $cur )* ] ])
+    };
+    // alternatives are counted at run-time rather than recursively in order to save recursion
+    ( @cov $name:ident $atomic:tt $slf:ident [ $( [ $( $alt:tt )* ] )* ] ) => {
+        {
+            let mut index = 0usize;
 
-    () => ();
+            $(
+                ({
+                    let result = $crate::grammar!(@atomic $name $atomic $slf [ $( $alt )* ]);
 
-    // normal rule
-    ( $name:ident = { $( $ts:tt )* } $( $tail:tt )* ) => {
-        #[allow(unused_parens, unused_variables)]
-        #[inline]
-        pub fn $name(&mut self) -> bool {
-            let slf = self;
-            grammar!(@skip $name slf);
+                    if result {
+                        $crate::runtime::Parser::cover($slf, stringify!($name), index);
+                    }
+
+                    index += 1;
 
-            let pos = slf.pos();
-            let len = slf.queue().len();
-            let tracked_len = slf.tracked_len();
+                    result
+                })
+            )||*
+        }
+    };
 
-            let result = grammar!(@atomic $name false slf [ $( $ts )* ]);
+    // collect the lint levels set with #![level(lint, ...)] at the start of the grammar
+    ( @levels $levels:ident # ! [ $level:ident ( $( $lint:ident ),* $( , )* ) ] $( $tail:tt )* )
+    => {
+        $( $levels.push((stringify!($lint), $crate::grammar!(@level $level))); )*
+        $crate::grammar!(@levels $levels $( $tail )*);
+    };
+    ( @levels $levels:ident $( $_ts:tt )* ) => ();
+    ( @level allow ) => ($crate::runtime::LintLevel::Allow);
+    ( @level warn )  => ($crate::runtime::LintLevel::Warn);
+    ( @level deny )  => ($crate::runtime::LintLevel::Deny);
+    // unknown levels are reported by @check_level
+    ( @level $_level:ident ) => ($crate::runtime::LintLevel::Warn);
+
+    // list top-level alternatives
+    ( @table $alternatives:ident ) => ();
+    ( @table $alternatives:ident $name:ident = { $( $ts:tt )* } $( $tail:tt )* ) => {
+        $crate::grammar!(@table_alts $alternatives $name (0usize) [] $( $ts )*);
+        $crate::grammar!(@table $alternatives $( $tail )*);
+    };
+    ( @table $alternatives:ident $name:ident = @{ $( $ts:tt )* } $( $tail:tt )* ) => {
+        $crate::grammar!(@table_alts $alternatives $name (0usize) [] $( $ts )*);
+        $crate::grammar!(@table $alternatives $( $tail )*);
+    };
+    ( @table $alternatives:ident $name:ident = _{ $( $ts:tt )* } $( $tail:tt )* ) => {
+        $crate::grammar!(@table_alts $alternatives $name (0usize) [] $( $ts )*);
+        $crate::grammar!(@table $alternatives $( $tail )*);
+    };
+    ( @table $alternatives:ident $name:ident = !{ $( $ts:tt )* } $( $tail:tt )* ) => {
+        $crate::grammar!(@table_alts $alternatives $name (0usize) [] $( $ts )*);
+        $crate::grammar!(@table $alternatives $( $tail )*);
+    };
+    ( @table $alternatives:ident $name:ident = flat { $( $ts:tt )* } $( $tail:tt )* ) => {
+        $crate::grammar!(@table_alts $alternatives $name (0usize) [] $( $ts )*);
+        $crate::grammar!(@table $alternatives $( $tail )*);
+    };
+    ( @table $alternatives:ident $_name:ident = keywords ( $_ident:ident ) { $( $_ts:tt )* }
+      $( $tail:tt )* ) => {
+        $crate::grammar!(@table $alternatives $( $tail )*);
+    };
+    ( @table $alternatives:ident $_name:ident = alias $_target:ident $( $tail:tt )* ) => {
+        $crate::grammar!(@table $alternatives $( $tail )*);
+    };
+    ( @table $alternatives:ident $_name:ident = external $_parser:ident :: $_rule:ident
+      $( $tail:tt )* ) => {
+        $crate::grammar!(@table $alternatives $( $tail )*);
+    };
+    ( @table $alternatives:ident $_name:ident = token $( $tail:tt )* ) => {
+        $crate::grammar!(@table $alternatives $( $tail )*);
+    };
+    ( @table $alternatives:ident $_head:tt $( $tail:tt )* ) => {
+        $crate::grammar!(@table $alternatives $( $tail )*);
+    };
+    ( @table_alts $alternatives:ident $name:ident ($index:expr) [ $( $cur:tt )* ]
+      | $( $ts:tt )* ) => {
+        $crate::grammar!(@table_alts $alternatives $name ($index) [ $( $cur )* ]);
+        $crate::grammar!(@table_alts $alternatives $name ($index + 1) [] $( $ts )*);
+    };
+    ( @table_alts $alternatives:ident $name:ident ($index:expr) [ $( $cur:tt )* ]
+      $a:tt | $( $ts:tt )* ) => {
+        $crate::grammar!(@table_alts $alternatives $name ($index) [ $( $cur )* $a ] | $( $ts )*)
+    };
+    ( @table_alts $alternatives:ident $name:ident ($index:expr) [ $( $cur:tt )* ]
+      $a:tt $b:tt | $( $ts:tt )* ) => {
+        $crate::grammar!(@table_alts $alternatives $name ($index) [ $( $cur )* $a $b ] | $( $ts )*)
+    };
+    ( @table_alts $alternatives:ident $name:ident ($index:expr) [ $( $cur:tt )* ]
+      $a:tt $b:tt $c:tt | $( $ts:tt )* ) => {
+        $crate::grammar!(@table_alts $alternatives $name ($index) [ $( $cur )* $a $b $c ]
+                         | $( $ts )*)
+    };
+    ( @table_alts $alternatives:ident $name:ident ($index:expr) [ $( $cur:tt )* ]
+      $a:tt $b:tt $c:tt $d:tt $( $ts:tt )* ) => {
+        $crate::grammar!(@table_alts $alternatives $name ($index) [ $( $cur )* $a $b $c $d ]
+                         $( $ts )*)
+    };
+    ( @table_alts $alternatives:ident $name:ident ($index:expr) [ $( $cur:tt )* ]
+      $t:tt $( $ts:tt )* ) => {
+        $crate::grammar!(@table_alts $alternatives $name ($index) [ $( $cur )* $t ] $( $ts )*)
+    };
+    ( @table_alts $alternatives:ident $name:ident ($index:expr) [ $( $cur:tt )* ] ) => {
+        $alternatives.push($crate::runtime::Alternative {
+            rule:  stringify!($name),
+            index: $index,
+            text:  stringify!($( $cur )*)
+        });
+    };
 
-            if result {
-                let new_pos = slf.pos();
+    // attach trivia to spans
+    ( @lead whitespace $_slf:ident $pos:ident ) => ($pos);
+    ( @lead newline $_slf:ident $pos:ident )    => ($pos);
+    ( @lead comment $_slf:ident $pos:ident )    => ($pos);
+    ( @lead $_name:ident $slf:ident $pos:ident ) => {
+        if $crate::runtime::Parser::trivia($slf) == $crate::runtime::Trivia::Leading {
+            $crate::runtime::Parser::trivia_start($slf, $pos)
+        } else {
+            $pos
+        }
+    };
+    ( @trail whitespace $_slf:ident )  => ();
+    ( @trail newline $_slf:ident )     => ();
+    ( @trail comment $_slf:ident )     => ();
+    ( @trail $_name:ident $slf:ident ) => {
+        if $crate::runtime::Parser::trivia($slf) == $crate::runtime::Trivia::Trailing {
+            $crate::runtime::Parser::skip_com($slf);
+            $crate::runtime::Parser::skip_ws($slf);
+        }
+    };
 
-                let token = Token {
-                    rule:  Rule::$name,
-                    start: pos,
-                    end:   new_pos
-                };
+    // check reserved words of identifier rules
+    ( @reserved $slf:ident $rule:ident $word:ident ) => ();
+    ( @reserved $slf:ident $rule:ident $word:ident
+      $_name:ident = keywords ( $ident:ident ) { $( $keyword:expr ),* } $( $tail:tt )* ) => {
+        if $rule == Rule::$ident &&
+           [$( $crate::runtime::Keywords::keywords(&$keyword) ),*]
+               .iter()
+               .any(|words| words.contains(&$word)) {
+            return true;
+        }
 
-                slf.queue_mut().insert(len, token);
-            } else {
-                slf.queue_mut().truncate(len);
+        $crate::grammar!(@reserved $slf $rule $word $( $tail )*);
+    };
+    ( @reserved $slf:ident $rule:ident $word:ident $_name:ident = alias $_target:ident
+      $( $tail:tt )* ) => {
+        $crate::grammar!(@reserved $slf $rule $word $( $tail )*);
+    };
+    ( @reserved $slf:ident $rule:ident $word:ident
+      $_name:ident = external $_parser:ident :: $_rule:ident $( $tail:tt )* ) => {
+        $crate::grammar!(@reserved $slf $rule $word $( $tail )*);
+    };
+    ( @reserved $slf:ident $rule:ident $word:ident $_name:ident = token $( $tail:tt )* ) => {
+        $crate::grammar!(@reserved $slf $rule $word $( $tail )*);
+    };
+    ( @reserved $slf:ident $rule:ident $word:ident $_name:ident = $_kind:tt { $( $_ts:tt )* }
+      $( $tail:tt )* ) => {
+        $crate::grammar!(@reserved $slf $rule $word $( $tail )*);
+    };
+    ( @reserved $slf:ident $rule:ident $word:ident $_name:ident = { $( $_ts:tt )* }
+      $( $tail:tt )* ) => {
+        $crate::grammar!(@reserved $slf $rule $word $( $tail )*);
+    };
+    ( @reserved $slf:ident $rule:ident $word:ident $_head:tt $( $tail:tt )* ) => {
+        $crate::grammar!(@reserved $slf $rule $word $( $tail )*);
+    };
 
-                if slf.tracked_len() == tracked_len {
-                    slf.track(Rule::$name, pos);
+    // return the reserved words of keywords rules
+    ( @keywords $rule:ident $visit:ident ) => ();
+    ( @keywords $rule:ident $visit:ident
+      $name:ident = keywords ( $_ident:ident ) { $( $keyword:expr ),* } $( $tail:tt )* ) => {
+        if $rule == Rule::$name {
+            $(
+                for &word in $crate::runtime::Keywords::keywords(&$keyword) {
+                    $visit(word);
                 }
-            }
+            )*
 
-            result
+            return;
         }
 
-        grammar!($( $tail )*);
+        $crate::grammar!(@keywords $rule $visit $( $tail )*);
+    };
+    ( @keywords $rule:ident $visit:ident $_name:ident = alias $_target:ident $( $tail:tt )* ) => {
+        $crate::grammar!(@keywords $rule $visit $( $tail )*);
+    };
+    ( @keywords $rule:ident $visit:ident $_name:ident = external $_parser:ident :: $_rule:ident
+      $( $tail:tt )* ) => {
+        $crate::grammar!(@keywords $rule $visit $( $tail )*);
+    };
+    ( @keywords $rule:ident $visit:ident $_name:ident = token $( $tail:tt )* ) => {
+        $crate::grammar!(@keywords $rule $visit $( $tail )*);
+    };
+    ( @keywords $rule:ident $visit:ident $_name:ident = $_kind:tt { $( $_ts:tt )* }
+      $( $tail:tt )* ) => {
+        $crate::grammar!(@keywords $rule $visit $( $tail )*);
+    };
+    ( @keywords $rule:ident $visit:ident $_name:ident = { $( $_ts:tt )* } $( $tail:tt )* ) => {
+        $crate::grammar!(@keywords $rule $visit $( $tail )*);
+    };
+    ( @keywords $rule:ident $visit:ident $_head:tt $( $tail:tt )* ) => {
+        $crate::grammar!(@keywords $rule $visit $( $tail )*);
     };
 
-    // atomic rule
-    ( $name:ident = @{ $( $ts:tt )* } $( $tail:tt )* ) => {
-        #[allow(unused_parens, unused_variables)]
-        #[inline]
-        pub fn $name(&mut self) -> bool {
-            let slf = self;
-            grammar!(@skip $name slf);
+    // check rule expressions, reporting mistakes with the name of the rule
+    ( @check_rule $name:ident { $( $primary:tt )* } $( $ts:tt )* ) => {
+        $crate::grammar!(@check $name operand [ $( $primary )* ]);
+        $crate::grammar!(@check_prec $name $( $ts )*);
+    };
+    ( @check_rule $name:ident $( $ts:tt )* ) => {
+        $crate::grammar!(@check $name operand [ $( $ts )* ]);
+    };
+    ( @check_prec $name:ident ) => ();
+    ( @check_prec $name:ident $op:ident = { $( $ts:tt )* } $( $tail:tt )* ) => {
+        $crate::grammar!(@check_assoc $op $( $ts )*);
+        $crate::grammar!(@check_prec $name $( $tail )*);
+    };
+    ( @check_prec $name:ident $op:ident = @{ $( $ts:tt )* } $( $tail:tt )* ) => {
+        $crate::grammar!(@check_assoc $op $( $ts )*);
+        $crate::grammar!(@check_prec $name $( $tail )*);
+    };
+    ( @check_prec $name:ident $op:ident = _{ $( $ts:tt )* } $( $tail:tt )* ) => {
+        $crate::grammar!(@check_assoc $op $( $ts )*);
+        $crate::grammar!(@check_prec $name $( $tail )*);
+    };
+    ( @check_prec $name:ident $op:ident = $( $_tail:tt )* ) => {
+        compile_error!(concat!("operator rule `", stringify!($op), "` of rule `", stringify!($name),
+                               "` needs to be defined as `{ ... }`, `@{ ... }`, or `_{ ... }`"));
+    };
+    ( @check_prec $name:ident $head:tt $( $_tail:tt )* ) => {
+        compile_error!(concat!("rule `", stringify!($name), "`: expected an operator rule like ",
+                               "`name = { ... }` after the primary expression, found `",
+                               stringify!($head), "`"));
+    };
+    ( @check_assoc $op:ident < $( $ts:tt )* ) => {
+        $crate::grammar!(@check $op operand [ $( $ts )* ]);
+    };
+    ( @check_assoc $op:ident $( $ts:tt )* ) => {
+        $crate::grammar!(@check $op operand [ $( $ts )* ]);
+    };
+    ( @check $name:ident operand [ & $( $ts:tt )* ] ) => {
+        $crate::grammar!(@check $name operand [ $( $ts )* ]);
+    };
+    ( @check $name:ident operand [ ! $( $ts:tt )* ] ) => {
+        $crate::grammar!(@check $name operand [ $( $ts )* ]);
+    };
+    ( @check $name:ident operand [ ( $( $inner:tt )* ) $( $ts:tt )* ] ) => {
+        $crate::grammar!(@check $name operand [ $( $inner )* ]);
+        $crate::grammar!(@check $name operator [ $( $ts )* ]);
+    };
+    ( @check $name:ident operand [ [ $( $_inner:tt )* ] $( $ts:tt )* ] ) => {
+        $crate::grammar!(@check $name operator [ $( $ts )* ]);
+    };
+    ( @check $name:ident operand [ nested_comment ( $_open:expr, $_close:expr ) $( $ts:tt )* ]
+    ) => {
+        $crate::grammar!(@check $name operator [ $( $ts )* ]);
+    };
+    ( @check $name:ident operand [ nested_comment $( $_ts:tt )* ] ) => {
+        compile_error!(concat!("rule `", stringify!($name), "`: expected ",
+                               "`nested_comment(open, close)`"));
+    };
+    ( @check $name:ident operand [ heredoc ( $_open:expr ) $( $ts:tt )* ] ) => {
+        $crate::grammar!(@check $name operator [ $( $ts )* ]);
+    };
+    ( @check $name:ident operand [ heredoc $( $_ts:tt )* ] ) => {
+        compile_error!(concat!("rule `", stringify!($name), "`: expected `heredoc(open)`"));
+    };
+    ( @check $name:ident operand [ with_prefix ( $_prefix:expr, $_rule:ident ) $( $ts:tt )* ] )
+    => {
+        $crate::grammar!(@check $name operator [ $( $ts )* ]);
+    };
+    ( @check $name:ident operand [ with_prefix $( $_ts:tt )* ] ) => {
+        compile_error!(concat!("rule `", stringify!($name), "`: expected ",
+                               "`with_prefix(prefix, rule)`"));
+    };
+    ( @check $name:ident operand [ skip_to ( $_rule:ident ) $( $ts:tt )* ] ) => {
+        $crate::grammar!(@check $name operator [ $( $ts )* ]);
+    };
+    ( @check $name:ident operand [ skip_to $( $_ts:tt )* ] ) => {
+        compile_error!(concat!("rule `", stringify!($name), "`: expected `skip_to(rule)`"));
+    };
+    ( @check $name:ident operand [ $_rule:ident $( $ts:tt )* ] ) => {
+        $crate::grammar!(@check $name operator [ $( $ts )* ]);
+    };
+    ( @check $name:ident operand [ $literal:literal $( $_ts:tt )* ] ) => {
+        compile_error!(concat!("rule `", stringify!($name), "`: literals need to be wrapped in ",
+                               "brackets, like `[\"a\"]` or `['a'..'z']`, found `",
+                               stringify!($literal), "`"));
+    };
+    ( @check $name:ident operand [ $head:tt $( $_ts:tt )* ] ) => {
+        compile_error!(concat!("rule `", stringify!($name), "`: expected a rule, `[\"...\"]`, ",
+                               "`['a'..'z']`, or `( ... )`, found `", stringify!($head), "`"));
+    };
+    ( @check $name:ident operand [] ) => {
+        compile_error!(concat!("rule `", stringify!($name), "`: expected an expression, found the ",
+                               "end of the rule"));
+    };
+    ( @check $name:ident operator [] ) => ();
+    ( @check $name:ident operator [ * $( $ts:tt )* ] ) => {
+        $crate::grammar!(@check $name operator [ $( $ts )* ]);
+    };
+    ( @check $name:ident operator [ + $( $ts:tt )* ] ) => {
+        $crate::grammar!(@check $name operator [ $( $ts )* ]);
+    };
+    ( @check $name:ident operator [ ? $( $ts:tt )* ] ) => {
+        $crate::grammar!(@check $name operator [ $( $ts )* ]);
+    };
+    ( @check $name:ident operator [ - $( $ts:tt )* ] ) => {
+        $crate::grammar!(@check $name operand [ $( $ts )* ]);
+    };
+    ( @check $name:ident operator [ & $( $ts:tt )* ] ) => {
+        $crate::grammar!(@check $name operand [ $( $ts )* ]);
+    };
+    ( @check $name:ident operator [ ~ $( $ts:tt )* ] ) => {
+        $crate::grammar!(@check $name operand [ $( $ts )* ]);
+    };
+    ( @check $name:ident operator [ | $( $ts:tt )* ] ) => {
+        $crate::grammar!(@check $name operand [ $( $ts )* ]);
+    };
+    ( @check $name:ident operator [ $head:tt $( $_ts:tt )* ] ) => {
+        compile_error!(concat!("rule `", stringify!($name), "`: expected `~`, `|`, `-`, `&`, `*`, ",
+                               "`+`, or `?`, found `", stringify!($head), "`"));
+    };
 
-            let pos = slf.pos();
-            let len = slf.queue().len();
+    // whitespace and comments do not count towards limits
+    ( @enter whitespace $_slf:ident ) => (true);
+    ( @enter newline $_slf:ident )    => (true);
+    ( @enter comment $_slf:ident )    => (true);
+    ( @enter $name:ident $slf:ident ) => {
+        $crate::runtime::Parser::enter_rule($slf, stringify!($name))
+    };
+    ( @exit whitespace $_slf:ident $_pos:ident $_result:ident ) => ();
+    ( @exit newline $_slf:ident $_pos:ident $_result:ident )    => ();
+    ( @exit comment $_slf:ident $_pos:ident $_result:ident )    => ();
+    ( @exit $name:ident $slf:ident $pos:ident $result:ident ) => {
+        $crate::runtime::Parser::exit_rule($slf, stringify!($name), $pos, $result)
+    };
+
+    // trivia is always atomic, cascading into the rules it calls, so that skipping trivia never
+    // reenters itself, e.g. for nested comments or comments in whitespace
+    ( @atomic whitespace $_atomic:tt $slf:ident $rules:tt ) => {
+        $crate::grammar!(@trivia $slf $rules)
+    };
+    ( @atomic newline $_atomic:tt $slf:ident $rules:tt ) => {
+        $crate::grammar!(@trivia $slf $rules)
+    };
+    ( @atomic comment $_atomic:tt $slf:ident $rules:tt ) => {
+        $crate::grammar!(@trivia $slf $rules)
+    };
+    ( @atomic $_name:ident $atomic:tt $slf:ident $rules:tt ) => {
+        $crate::grammar!(@conv $atomic $slf $rules [] [])
+    };
 
-            let toggled = slf.is_atomic();
+    ( @trivia $slf:ident $rules:tt ) => {
+        {
+            let toggled = $crate::runtime::Parser::is_atomic($slf);
 
             if !toggled {
-                slf.set_atomic(true);
+                $crate::runtime::Parser::set_atomic($slf, true);
             }
 
-            let result = grammar!(@conv true slf [ $( $ts )* ] [] []);
+            let result = $crate::grammar!(@conv true $slf $rules [] []);
 
             if !toggled {
-                slf.set_atomic(false);
+                $crate::runtime::Parser::set_atomic($slf, false);
             }
 
-            if result {
-                let new_pos = slf.pos();
+            result
+        }
+    };
 
-                let token = Token {
-                    rule:  Rule::$name,
-                    start: pos,
-                    end:   new_pos
-                };
+    // rules are inlined unless they come with their own attributes
+    ( @fn [] $( $item:tt )* ) => (#[inline] $( $item )*);
+    ( @fn [ $( $attr:meta ),* ] $( $item:tt )* ) => ($( #[$attr] )* $( $item )*);
+
+    // the method of every rule that pushes a Token, with $body matching the rule through $slf
+    ( @rule_body $kind:ident $name:ident [ $( $attr:meta ),* ] [ $slf:ident $start:ident ]
+      $body:expr ) => {
+        impl<T: $crate::runtime::Input> Rdp<T> {
+            $crate::grammar!(@fn [ $( $attr ),* ]
+                #[allow(unused_assignments, unused_parens, unused_variables)]
+                pub fn $name(&mut self) -> bool {
+                    let $slf = self;
+
+                    if !$crate::grammar!(@enter $name $slf) {
+                        return false;
+                    }
 
-                slf.queue_mut().insert(len, token);
-            } else {
-                slf.queue_mut().truncate(len);
+                    let (atomic, opens, flattens, always_tracks) = $crate::grammar!(@kind $kind);
+
+                    let entry = $crate::runtime::Parser::pos($slf);
+                    let is_atomic = $crate::runtime::Parser::is_atomic($slf);
+                    let enters_atomic = atomic == Some(true) && !is_atomic;
+                    let leaves_atomic = atomic == Some(false) && is_atomic;
+
+                    if leaves_atomic {
+                        $crate::runtime::Parser::set_atomic($slf, false);
+                    }
+
+                    $crate::grammar!(@skip $name $slf);
+
+                    let pos = $crate::runtime::Parser::pos($slf);
+                    let $start = $crate::grammar!(@lead $name $slf pos);
+                    let len = $crate::runtime::Parser::queue($slf).len();
+                    let tracked_len = $crate::runtime::Parser::tracked_len($slf);
+
+                    if enters_atomic {
+                        $crate::runtime::Parser::set_atomic($slf, true);
+                    }
+
+                    let parent = if opens {
+                        $crate::runtime::Parser::open_rule($slf, Some(Rule::$name))
+                    } else {
+                        None
+                    };
+
+                    let result = $body;
+
+                    if opens {
+                        $crate::runtime::Parser::close_rule($slf, parent);
+                    }
+
+                    if enters_atomic {
+                        $crate::runtime::Parser::set_atomic($slf, false);
+                    }
+
+                    let matched = result;
+                    let result = matched &&
+                                 !$crate::runtime::Parser::is_reserved($slf, Rule::$name, pos) &&
+                                 $crate::runtime::Parser::check_token_limit($slf);
+
+                    if result {
+                        $crate::grammar!(@trail $name $slf);
+
+                        // a flat rule directly nested in itself leaves its children to the outer
+                        // Token
+                        if !flattens || parent != Some(Rule::$name) {
+                            let new_pos = $crate::runtime::Parser::pos($slf);
+
+                            let token = $crate::runtime::TokenData::new(Rule::$name, $start,
+                                                                        new_pos);
+
+                            $crate::runtime::Parser::insert_parent($slf, len, token);
+                            $crate::runtime::Parser::stream($slf, len);
+                        }
+
+                        // the atomic caller continues right after the trivia that follows
+                        if leaves_atomic {
+                            $crate::runtime::Parser::skip_com($slf);
+                            $crate::runtime::Parser::skip_ws($slf);
+                        }
+                    } else {
+                        $crate::runtime::Parser::queue_mut($slf).truncate(len);
+
+                        // a match rejected as a reserved word or for its Tokens is undone as well
+                        if matched {
+                            $crate::runtime::Parser::set_pos($slf, pos);
+                        }
+
+                        if always_tracks ||
+                           $crate::runtime::Parser::tracked_len($slf) == tracked_len {
+                            $crate::runtime::Parser::track($slf, Rule::$name, pos);
+                        }
+
+                        // the atomic caller does not skip trivia, even if the rule fails
+                        if leaves_atomic {
+                            $crate::runtime::Parser::set_pos($slf, entry);
+                        }
+                    }
+
+                    if leaves_atomic {
+                        $crate::runtime::Parser::set_atomic($slf, true);
+                    }
+
+                    $crate::grammar!(@exit $name $slf pos result);
+
+                    result
+                }
+            );
+        }
+    };
+
+    // the atomicity inside rules of a kind, whether they open their Rule, whether they flatten
+    // into themselves, and whether they track every failure, even below failing children
+    ( @kind normal )     => ((None,        true,  false, false));
+    ( @kind atomic )     => ((Some(true),  true,  false, true));
+    ( @kind non_atomic ) => ((Some(false), true,  false, false));
+    ( @kind flat )       => ((None,        true,  true,  false));
+    ( @kind keywords )   => ((None,        false, false, true));
+    ( @kind external )   => ((None,        false, false, true));
+
+    () => ();
+
+    // lint levels, checked here and collected by Parser::lints
+    ( # ! [ $level:ident ( $( $lint:ident ),* $( , )* ) ] $( $tail:tt )* ) => {
+        $crate::grammar!(@check_level $level);
+        $( $crate::grammar!(@check_lint $lint); )*
+        $crate::grammar!($( $tail )*);
+    };
+    ( @check_level allow ) => ();
+    ( @check_level warn )  => ();
+    ( @check_level deny )  => ();
+    ( @check_level $level:ident ) => {
+        compile_error!(concat!("unknown lint level `", stringify!($level), "`, expected `allow`, ",
+                               "`warn`, or `deny`"));
+    };
+    ( @check_lint split_word )           => ();
+    ( @check_lint unused_rule )          => ();
+    ( @check_lint nullable_loop )        => ();
+    ( @check_lint shadowed_alternative ) => ();
+    ( @check_lint $lint:ident ) => {
+        compile_error!(concat!("unknown lint `", stringify!($lint), "`"));
+    };
+
+    // normal rule
+    ( $( #[ $attr:meta ] )* $name:ident = { $( $ts:tt )* } $( $tail:tt )* ) => {
+        $crate::grammar!(@check_rule $name $( $ts )*);
+        $crate::grammar!(@rule_body normal $name [ $( $attr ),* ] [ slf start ]
+                         $crate::grammar!(@alts $name false slf [] [] $( $ts )*));
+
+        $crate::grammar!($( $tail )*);
+    };
+
+    // atomic rule
+    ( $( #[ $attr:meta ] )* $name:ident = @{ $( $ts:tt )* } $( $tail:tt )* ) => {
+        $crate::grammar!(@check_rule $name $( $ts )*);
+        $crate::grammar!(@rule_body atomic $name [ $( $attr ),* ] [ slf start ]
+                         $crate::grammar!(@alts $name true slf [] [] $( $ts )*));
+
+        $crate::grammar!($( $tail )*);
+    };
+
+    // non-atomic rule
+    ( $( #[ $attr:meta ] )* $name:ident = !{ $( $ts:tt )* } $( $tail:tt )* ) => {
+        $crate::grammar!(@check_rule $name $( $ts )*);
+        $crate::grammar!(@rule_body non_atomic $name [ $( $attr ),* ] [ slf start ]
+                         $crate::grammar!(@alts $name false slf [] [] $( $ts )*));
+
+        $crate::grammar!($( $tail )*);
+    };
+
+    // flat rule
+    ( $( #[ $attr:meta ] )* $name:ident = flat { $( $ts:tt )* } $( $tail:tt )* ) => {
+        $crate::grammar!(@check_rule $name $( $ts )*);
+        $crate::grammar!(@rule_body flat $name [ $( $attr ),* ] [ slf start ]
+                         $crate::grammar!(@alts $name false slf [] [] $( $ts )*));
+
+        $crate::grammar!($( $tail )*);
+    };
 
-                slf.track(Rule::$name, pos);
+    // keyword rule
+    ( $( #[ $attr:meta ] )* $name:ident = keywords ( $ident:ident ) { $( $keyword:expr ),* }
+      $( $tail:tt )* ) => {
+        $crate::grammar!(@rule_body keywords $name [ $( $attr ),* ] [ slf start ] {
+            // the identifier rule fails exactly on reserved words
+            $crate::runtime::Parser::try(slf, true, |slf| !slf.$ident()) && {
+                let mut longest: Option<&str> = None;
+
+                let keywords = [$( $crate::runtime::Keywords::keywords(&$keyword) ),*];
+
+                for &keyword in keywords.iter().flat_map(|words| words.iter()) {
+                    if longest.map_or(true, |longest| keyword.len() > longest.len()) &&
+                       $crate::runtime::Parser::try(slf, true, |slf| {
+                           $crate::runtime::Parser::match_string(slf, keyword)
+                       }) {
+                        longest = Some(keyword);
+                    }
+                }
+
+                longest.map_or(false, |keyword| $crate::runtime::Parser::match_string(slf, keyword))
             }
+        });
 
-            result
+        $crate::grammar!($( $tail )*);
+    };
+
+    // alias
+    ( $( #[ $attr:meta ] )* $name:ident = alias $target:ident $( $tail:tt )* ) => {
+        impl<T: $crate::runtime::Input> Rdp<T> {
+            $crate::grammar!(@fn [ $( $attr ),* ]
+                #[allow(dead_code)]
+                pub fn $name(&mut self) -> bool {
+                    let len = $crate::runtime::Parser::queue(self).len();
+
+                    let result = self.$target();
+
+                    if result {
+                        if let Some(token) = $crate::runtime::Parser::queue_mut(self).get_mut(len) {
+                            if token.rule() == Rule::$target {
+                                *token = $crate::runtime::TokenData::new(Rule::$name,
+                                                                         token.start(),
+                                                                         token.end());
+                            }
+                        }
+                    }
+
+                    result
+                }
+            );
         }
 
-        grammar!($( $tail )*);
+        $crate::grammar!($( $tail )*);
+    };
+
+    // token rules only exist in Rule
+    ( $( #[ $_attr:meta ] )* $_name:ident = token $( $tail:tt )* ) => {
+        $crate::grammar!($( $tail )*);
+    };
+
+    // external rule
+    ( $( #[ $attr:meta ] )* $name:ident = external $parser:ident :: $rule:ident
+      $( $tail:tt )* ) => {
+        $crate::grammar!(@rule_body external $name [ $( $attr ),* ] [ slf start ] {
+            // the other parser borrows the input, so its Tokens are copied out first
+            let matched = {
+                let pos = $crate::runtime::Parser::pos(slf);
+                let len = $crate::runtime::Parser::input_len(slf);
+                let mut parser = $parser::new($crate::runtime::Parser::sub_input(slf, pos, len));
+
+                if parser.$rule() {
+                    let tokens: Vec<_> = $crate::runtime::Parser::queue(&parser)
+                                             .iter()
+                                             .map(|token| token.token())
+                                             .collect();
+
+                    Some(($crate::runtime::Parser::pos(&parser), tokens))
+                } else {
+                    None
+                }
+            };
+
+            match matched {
+                Some((end, tokens)) if $crate::runtime::Parser::check_token_limit(slf) => {
+                    $crate::runtime::Parser::set_pos(slf, end);
+                    $crate::runtime::Parser::embed(slf, Rule::$name, start, &tokens);
+
+                    true
+                },
+                _ => false
+            }
+        });
+
+        $crate::grammar!($( $tail )*);
     };
 
     // silent rule
-    ( $name:ident = _{ $( $ts:tt )* } $( $tail:tt )* ) => {
-        #[allow(unused_parens, unused_variables)]
-        #[inline]
-        pub fn $name(&mut self) -> bool {
-            let slf = self;
-            grammar!(@skip $name slf);
+    ( $( #[ $attr:meta ] )* $name:ident = _{ $( $ts:tt )* } $( $tail:tt )* ) => {
+        $crate::grammar!(@check_rule $name $( $ts )*);
 
-            let pos = slf.pos();
+        impl<T: $crate::runtime::Input> Rdp<T> {
+            $crate::grammar!(@fn [ $( $attr ),* ]
+                #[allow(unused_assignments, unused_parens, unused_variables)]
+                pub fn $name(&mut self) -> bool {
+                    let slf = self;
 
-            let result = grammar!(@atomic $name false slf [ $( $ts )* ]);
+                    if !$crate::grammar!(@enter $name slf) {
+                        return false;
+                    }
 
-            result
+                    $crate::grammar!(@skip $name slf);
+
+                    let pos = $crate::runtime::Parser::pos(slf);
+
+                    let result = $crate::grammar!(@alts $name false slf [] [] $( $ts )*);
+
+                    $crate::grammar!(@exit $name slf pos result);
+
+                    result
+                }
+            );
         }
 
-        grammar!($( $tail )*);
+        $crate::grammar!($( $tail )*);
+    };
+
+    // report malformed rules and carry on with the next one
+    ( $( #[ $_attr:meta ] )* $name:ident = $( $tail:tt )* ) => {
+        compile_error!(concat!("rule `", stringify!($name), "` needs to be defined as `{ ... }`, ",
+                               "`@{ ... }`, `!{ ... }`, `_{ ... }`, `flat { ... }`, ",
+                               "`keywords(rule) { ... }`, `alias rule`, ",
+                               "`external Parser::rule`, or `token`"));
+
+        $crate::grammar!(@recover $( $tail )*);
+    };
+    ( $name:ident $( $tail:tt )* ) => {
+        compile_error!(concat!("expected `=` after rule `", stringify!($name), "`"));
+
+        $crate::grammar!(@recover $( $tail )*);
     };
+    ( @recover ) => ();
+    ( @recover $name:ident = $( $tail:tt )* ) => ($crate::grammar!($name = $( $tail )*););
+    ( @recover # [ $attr:meta ] $( $tail:tt )* ) => ($crate::grammar!(#[$attr] $( $tail )*););
+    ( @recover $_head:tt $( $tail:tt )* ) => ($crate::grammar!(@recover $( $tail )*););
 }
@@ -0,0 +1,154 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+/// A `struct` representing a here-document, matched in grammars with the
+/// [`heredoc(open)`](macro.grammar!#here-documents) built-in.
+///
+/// A here-document starts with `open`, e.g. `<<`, an optional `~`, and a tag made of
+/// alphanumeric `char`s and `_`, followed by a line ending. Its body is every following line up
+/// to a line that only holds the tag. With `~`, the tag may be indented and the common
+/// indentation of the body is stripped by [`text`](#method.text).
+///
+/// # Examples
+///
+/// ```
+/// # use pest::Heredoc;
+/// let (heredoc, len) = Heredoc::parse("<<", "<<~EOF\n    a\n      b\n  EOF\nc").unwrap();
+///
+/// assert_eq!(heredoc.tag, "EOF");
+/// assert!(heredoc.strip);
+/// assert_eq!(heredoc.body, "    a\n      b\n");
+/// assert_eq!(heredoc.text(), "a\n  b\n");
+/// assert_eq!(len, 26);
+///
+/// assert!(Heredoc::parse("<<", "<<EOF\n  EOF\n").is_none());
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Heredoc<'a> {
+    /// tag closing the here-document
+    pub tag:   &'a str,
+    /// whether the here-document started with `~`
+    pub strip: bool,
+    /// lines between the opening line and the closing tag, including their line endings
+    pub body:  &'a str
+}
+
+impl<'a> Heredoc<'a> {
+    /// Parses a here-document starting with `open` at the start of `string` and returns it
+    /// together with its length, which ends right after the closing tag. Returns `None` if
+    /// `string` does not start with a here-document or if its tag is never closed.
+    pub fn parse(open: &str, string: &'a str) -> Option<(Heredoc<'a>, usize)> {
+        if open.is_empty() || !string.starts_with(open) {
+            return None;
+        }
+
+        let mut pos = open.len();
+        let strip = string[pos..].starts_with('~');
+
+        if strip {
+            pos += 1;
+        }
+
+        let tag_len = string[pos..].find(|c: char| !c.is_alphanumeric() && c != '_')
+                                   .unwrap_or(string.len() - pos);
+
+        if tag_len == 0 {
+            return None;
+        }
+
+        let tag = &string[pos..pos + tag_len];
+
+        pos += tag_len;
+        pos += line_ending(&string[pos..])?;
+
+        let start = pos;
+
+        while pos < string.len() {
+            let len = string[pos..].find(['\n', '\r']).unwrap_or(string.len() - pos);
+            let line = &string[pos..pos + len];
+            let line = if strip { line.trim_start_matches([' ', '\t']) } else { line };
+
+            if line == tag {
+                let heredoc = Heredoc {
+                    tag,
+                    strip,
+                    body: &string[start..pos]
+                };
+
+                return Some((heredoc, pos + len));
+            }
+
+            pos += len;
+            pos += line_ending(&string[pos..]).unwrap_or(0);
+        }
+
+        None
+    }
+
+    /// Returns the body with the common indentation of its non-blank lines stripped if the
+    /// here-document started with `~`, or as is otherwise.
+    pub fn text(&self) -> String {
+        if !self.strip {
+            return self.body.to_owned();
+        }
+
+        let indent = self.body.lines().filter(|line| !line.trim().is_empty()).map(|line| {
+            line.len() - line.trim_start_matches([' ', '\t']).len()
+        }).min().unwrap_or(0);
+
+        let mut text = String::with_capacity(self.body.len());
+
+        for line in self.body.split_inclusive('\n') {
+            let blank = line.len() - line.trim_start_matches([' ', '\t']).len();
+
+            text.push_str(&line[blank.min(indent)..]);
+        }
+
+        text
+    }
+}
+
+// returns the length of the line ending at the start of string
+fn line_ending(string: &str) -> Option<usize> {
+    if string.starts_with("\r\n") {
+        Some(2)
+    } else if string.starts_with('\n') || string.starts_with('\r') {
+        Some(1)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Heredoc;
+
+    #[test]
+    fn parse() {
+        let (heredoc, len) = Heredoc::parse("<<", "<<END_1\r\nEND\r\nEND_1\r\n").unwrap();
+
+        assert_eq!(heredoc, Heredoc { tag: "END_1", strip: false, body: "END\r\n" });
+        assert_eq!(len, 19);
+
+        assert_eq!(Heredoc::parse("<<", "<<EOF\nEOF").unwrap().0.body, "");
+        assert!(Heredoc::parse("<<", "<< EOF\nEOF").is_none());
+        assert!(Heredoc::parse("<<", "<<EOF a\nEOF").is_none());
+        assert!(Heredoc::parse("<<", "<<EOF\nEOFX\n").is_none());
+        assert!(Heredoc::parse("", "EOF\nEOF").is_none());
+    }
+
+    #[test]
+    fn text() {
+        let (heredoc, _) = Heredoc::parse("<<", "<<~X\n\t\ta\n\n\t b\n\t\t\tc\n X").unwrap();
+
+        assert_eq!(heredoc.text(), "a\n\nb\n\tc\n");
+
+        let (heredoc, _) = Heredoc::parse("<<", "<<X\n  a\nX").unwrap();
+
+        assert_eq!(heredoc.text(), "  a\n");
+    }
+}
@@ -7,21 +7,70 @@
 
 /// A `trait` that defines an input for a `Parser`.
 pub trait Input {
-    /// Returns length of an `Input`.
+    /// Returns length of an `Input`. Like all positions, it is counted from the beginning of the
+    /// whole `&str`, so it is the position where an `Input` ends, even if it does not start at
+    /// [`start`](#method.start).
     fn len(&self) -> usize;
 
-    /// Returns whether an `Input` is empty.
+    /// Returns whether an `Input` is empty, i.e. whether it ends where it starts, at
+    /// [`start`](#method.start).
     fn is_empty(&self) -> bool;
 
+    /// Returns the first position of an `Input`. This is `0` unless the `Input` only covers part of
+    /// a larger `&str`, like [`StringInput::with_range`](struct.StringInput#method.with_range).
+    fn start(&self) -> usize {
+        0
+    }
+
     /// Returns current position of an `Input`.
     fn pos(&self) -> usize;
 
-    /// Set current position of an `Input`.
+    /// Set current position of an `Input`. `pos` must be on a `char` boundary or past the end,
+    /// which the provided `Input`s check with debug assertions; use
+    /// [`advance_by_chars`](#method.advance_by_chars) to skip a number of `char`s instead of
+    /// computing byte positions by hand.
     fn set_pos(&mut self, pos: usize);
 
     /// Slices an `Input`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start` or `end` is not on a `char` boundary.
     fn slice(&self, start: usize, end: usize) -> &str;
 
+    /// Advances the position by `n` `char`s, stopping at the end of an `Input`, and returns how
+    /// many `char`s it advanced by.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::Input;
+    /// # use pest::StringInput;
+    /// let mut input = StringInput::new("aéb");
+    ///
+    /// assert_eq!(input.advance_by_chars(2), 2);
+    /// assert_eq!(input.pos(), 3);
+    /// assert_eq!(input.advance_by_chars(2), 1);
+    /// assert_eq!(input.pos(), 4);
+    /// ```
+    fn advance_by_chars(&mut self, n: usize) -> usize {
+        let pos = self.pos();
+
+        if pos >= self.len() {
+            return 0;
+        }
+
+        let (count, len) = self.slice(pos, self.len()).chars()
+                                                       .take(n)
+                                                       .fold((0, 0), |(count, len), c| {
+                                                           (count + 1, len + c.len_utf8())
+                                                       });
+
+        self.set_pos(pos + len);
+
+        count
+    }
+
     /// Returns the line and column of a position for an `Input`.
     fn line_col(&self, pos: usize) -> (usize, usize);
 
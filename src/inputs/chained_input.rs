@@ -0,0 +1,125 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::super::{Input, Origin, Preprocessed};
+use super::StringInput;
+
+/// A `struct` that matches the output of a [`Preprocessor`](trait.Preprocessor) as one `Input`,
+/// chaining together the parts that were copied from different files.
+///
+/// Positions of a `ChainedInput` are positions in the output, but
+/// [`line_col`](trait.Input#tymethod.line_col) returns the line and column in the file a
+/// position was copied from, so that errors point to the original files. Positions that were not
+/// copied from any file keep their line and column in the output.
+/// [`ChainedInput::origin`](#method.origin) also returns the name of the file.
+///
+/// # Examples
+///
+/// ```
+/// # use pest::{ChainedInput, Includer, Input, Preprocessor};
+/// let mut includer = Includer::new(|_: &str| Ok("int lib;\n".to_owned()));
+/// let preprocessed = includer.preprocess("main.c", "#include \"lib.h\"\nint main;\n").unwrap();
+///
+/// let mut input = ChainedInput::new(&preprocessed);
+///
+/// assert!(input.match_string("int lib;\nint "));
+/// assert_eq!(input.line_col(input.pos()), (2, 5));
+/// assert_eq!(input.origin(input.pos()).unwrap().file, "main.c");
+/// ```
+pub struct ChainedInput<'a> {
+    preprocessed: &'a Preprocessed,
+    input:        StringInput<'a>
+}
+
+impl<'a> ChainedInput<'a> {
+    /// Creates a new `ChainedInput` from the output of a `Preprocessor`.
+    pub fn new(preprocessed: &'a Preprocessed) -> ChainedInput<'a> {
+        ChainedInput {
+            preprocessed,
+            input: StringInput::new(preprocessed.as_str())
+        }
+    }
+
+    /// Returns the `Origin` of `pos`, like
+    /// [`Preprocessed::origin`](struct.Preprocessed#method.origin).
+    pub fn origin(&self, pos: usize) -> Option<Origin<'a>> {
+        self.preprocessed.origin(pos)
+    }
+}
+
+impl<'a> Input for ChainedInput<'a> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.input.len()
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.input.is_empty()
+    }
+
+    #[inline]
+    fn pos(&self) -> usize {
+        self.input.pos()
+    }
+
+    #[inline]
+    fn set_pos(&mut self, pos: usize) {
+        self.input.set_pos(pos)
+    }
+
+    #[inline]
+    fn slice(&self, start: usize, end: usize) -> &str {
+        self.input.slice(start, end)
+    }
+
+    #[inline]
+    fn line_col(&self, pos: usize) -> (usize, usize) {
+        match self.origin(pos) {
+            Some(origin) => (origin.line, origin.col),
+            None         => self.input.line_col(pos)
+        }
+    }
+
+    #[inline]
+    fn match_string(&mut self, string: &str) -> bool {
+        self.input.match_string(string)
+    }
+
+    #[inline]
+    fn match_range(&mut self, left: char, right: char) -> bool {
+        self.input.match_range(left, right)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::{Input, Preprocessed};
+    use super::ChainedInput;
+
+    #[test]
+    fn line_col() {
+        let mut preprocessed = Preprocessed::new();
+        let a = preprocessed.add_file("a", "a1\na2\n".to_owned());
+        let b = preprocessed.add_file("b", "b1\n".to_owned());
+
+        preprocessed.push_mapped(a, 0, 3);
+        preprocessed.push_mapped(b, 0, 3);
+        preprocessed.push_str("x\n");
+        preprocessed.push_mapped(a, 3, 6);
+
+        let input = ChainedInput::new(&preprocessed);
+
+        assert_eq!(input.slice(0, input.len()), "a1\nb1\nx\na2\n");
+        assert_eq!(input.line_col(1), (1, 2));
+        assert_eq!(input.line_col(4), (1, 2));
+        assert_eq!(input.origin(4).unwrap().file, "b");
+        assert_eq!(input.line_col(6), (3, 1));
+        assert_eq!(input.line_col(9), (2, 2));
+        assert_eq!(input.origin(9).unwrap().file, "a");
+    }
+}
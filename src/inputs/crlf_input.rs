@@ -0,0 +1,156 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::super::Input;
+use super::StringInput;
+
+/// A `struct` useful for matching `&str`s with Windows line endings using grammars written for
+/// `"\n"`.
+///
+/// Every `"\r\n"` in the `&str` is matched by a `'\n'` in a string or a range as a whole, while
+/// it can still be matched as two `char`s. Nothing is copied, so positions, and thus `Token`s and
+/// errors, always refer to the original `&str`.
+///
+/// # Examples
+///
+/// ```
+/// # use pest::Input;
+/// # use pest::CrlfInput;
+/// let mut input = CrlfInput::new("a\r\nb\nc");
+///
+/// assert!(input.match_string("a\nb"));
+/// assert_eq!(input.pos(), 4);
+/// assert!(input.match_range('\n', '\n'));
+/// assert!(input.match_string("c"));
+/// ```
+pub struct CrlfInput<'a> {
+    input: StringInput<'a>
+}
+
+impl<'a> CrlfInput<'a> {
+    /// Creates a new `CrlfInput` from a `&str`.
+    pub fn new(string: &'a str) -> CrlfInput<'a> {
+        CrlfInput {
+            input: StringInput::new(string)
+        }
+    }
+
+    // matches a line ending, "\r\n" or "\n", at pos and returns its length
+    #[inline]
+    fn line_ending(&self, pos: usize) -> Option<usize> {
+        let rest = self.input.slice(pos, self.input.len()).as_bytes();
+
+        match rest {
+            [b'\r', b'\n', ..] => Some(2),
+            [b'\n', ..]        => Some(1),
+            _                  => None
+        }
+    }
+}
+
+impl<'a> Input for CrlfInput<'a> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.input.len()
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.input.is_empty()
+    }
+
+    #[inline]
+    fn pos(&self) -> usize {
+        self.input.pos()
+    }
+
+    #[inline]
+    fn set_pos(&mut self, pos: usize) {
+        self.input.set_pos(pos)
+    }
+
+    #[inline]
+    fn slice(&self, start: usize, end: usize) -> &str {
+        self.input.slice(start, end)
+    }
+
+    #[inline]
+    fn line_col(&self, pos: usize) -> (usize, usize) {
+        self.input.line_col(pos)
+    }
+
+    #[inline]
+    fn match_string(&mut self, string: &str) -> bool {
+        let start = self.input.pos();
+
+        for (i, part) in string.split('\n').enumerate() {
+            if i > 0 {
+                let pos = self.input.pos();
+
+                match self.line_ending(pos) {
+                    Some(len) => self.input.set_pos(pos + len),
+                    None      => {
+                        self.input.set_pos(start);
+
+                        return false;
+                    }
+                }
+            }
+
+            if !self.input.match_string(part) {
+                self.input.set_pos(start);
+
+                return false;
+            }
+        }
+
+        true
+    }
+
+    #[inline]
+    fn match_range(&mut self, left: char, right: char) -> bool {
+        let pos = self.input.pos();
+
+        if left <= '\n' && '\n' <= right {
+            if let Some(len) = self.line_ending(pos) {
+                self.input.set_pos(pos + len);
+
+                return true;
+            }
+        }
+
+        self.input.match_range(left, right)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::Input;
+    use super::CrlfInput;
+
+    #[test]
+    fn match_string() {
+        let mut input = CrlfInput::new("a\r\n\nb\r\n");
+
+        assert!(!input.match_string("a\n\nc"));
+        assert_eq!(input.pos(), 0);
+
+        assert!(input.match_string("a\n\nb"));
+        assert!(input.match_string("\r\n"));
+        assert_eq!(input.pos(), 7);
+    }
+
+    #[test]
+    fn match_range() {
+        let mut input = CrlfInput::new("\r\n\r");
+
+        assert!(input.match_range('\0', '\n'));
+        assert_eq!(input.pos(), 2);
+        assert!(!input.match_range('\n', '\n'));
+        assert!(input.match_range('\r', '\r'));
+    }
+}
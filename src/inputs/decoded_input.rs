@@ -0,0 +1,198 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::error::Error;
+use std::fmt;
+
+use encoding_rs::{DecoderResult, Encoding, UTF_8};
+
+use super::super::Input;
+use super::OwnedInput;
+
+/// A `struct` describing bytes that are invalid in the detected `Encoding`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DecodeError {
+    /// `Encoding` used for decoding
+    pub encoding: &'static Encoding,
+    /// position of the first invalid byte in the undecoded bytes
+    pub pos:      usize
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid {} at byte {}", self.encoding.name(), self.pos)
+    }
+}
+
+impl Error for DecodeError {}
+
+/// A `struct` that decodes bytes to UTF-8 before matching them, picking the encoding from the
+/// byte order mark. Available with the `encoding` feature.
+///
+/// Positions of a `DecodedInput` are positions in the decoded UTF-8 `&str`.
+///
+/// # Examples
+///
+/// ```
+/// # use pest::Input;
+/// # use pest::DecodedInput;
+/// let bytes = [0xff, 0xfe, b'a', 0x00, b'b', 0x00]; // UTF-16LE with BOM
+/// let mut input = DecodedInput::new(&bytes).unwrap();
+///
+/// assert_eq!(input.encoding().name(), "UTF-16LE");
+/// assert!(input.match_string("ab"));
+/// ```
+pub struct DecodedInput {
+    input:    OwnedInput,
+    encoding: &'static Encoding
+}
+
+impl DecodedInput {
+    /// Decodes `bytes` as UTF-8, UTF-16LE, or UTF-16BE depending on their byte order mark,
+    /// defaulting to UTF-8 when there is none. The byte order mark is not part of the
+    /// `DecodedInput`.
+    pub fn new(bytes: &[u8]) -> Result<DecodedInput, DecodeError> {
+        DecodedInput::with_fallback(bytes, UTF_8)
+    }
+
+    /// Decodes `bytes` like [`DecodedInput::new`](#method.new), but uses `fallback` when there is
+    /// no byte order mark.
+    pub fn with_fallback(bytes: &[u8],
+                         fallback: &'static Encoding) -> Result<DecodedInput, DecodeError> {
+        let (encoding, bom_len) = Encoding::for_bom(bytes).unwrap_or((fallback, 0));
+        let bytes = &bytes[bom_len..];
+
+        let mut decoder = encoding.new_decoder_without_bom_handling();
+        let mut string = String::with_capacity(
+            decoder.max_utf8_buffer_length_without_replacement(bytes.len()).unwrap_or(0)
+        );
+        let mut read = 0;
+
+        loop {
+            let (result, len) = decoder.decode_to_string_without_replacement(&bytes[read..],
+                                                                             &mut string, true);
+            read += len;
+
+            match result {
+                DecoderResult::InputEmpty           => break,
+                DecoderResult::OutputFull           => {
+                    let len = decoder.max_utf8_buffer_length_without_replacement(bytes.len() -
+                                                                                 read);
+                    string.reserve(len.unwrap_or(4));
+                },
+                DecoderResult::Malformed(bad, extra) => {
+                    return Err(DecodeError {
+                        encoding,
+                        pos: bom_len + read - extra as usize - bad as usize
+                    })
+                }
+            }
+        }
+
+        Ok(DecodedInput {
+            input: OwnedInput::new(string),
+            encoding
+        })
+    }
+
+    /// Returns the `Encoding` the `DecodedInput` was decoded from.
+    pub fn encoding(&self) -> &'static Encoding {
+        self.encoding
+    }
+
+    /// Returns the decoded `&str`.
+    pub fn as_str(&self) -> &str {
+        self.input.as_str()
+    }
+}
+
+impl Input for DecodedInput {
+    #[inline]
+    fn len(&self) -> usize {
+        self.input.len()
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.input.is_empty()
+    }
+
+    #[inline]
+    fn pos(&self) -> usize {
+        self.input.pos()
+    }
+
+    #[inline]
+    fn set_pos(&mut self, pos: usize) {
+        self.input.set_pos(pos)
+    }
+
+    #[inline]
+    fn slice(&self, start: usize, end: usize) -> &str {
+        self.input.slice(start, end)
+    }
+
+    #[inline]
+    fn line_col(&self, pos: usize) -> (usize, usize) {
+        self.input.line_col(pos)
+    }
+
+    #[inline]
+    fn match_string(&mut self, string: &str) -> bool {
+        self.input.match_string(string)
+    }
+
+    #[inline]
+    fn match_range(&mut self, left: char, right: char) -> bool {
+        self.input.match_range(left, right)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use encoding_rs::WINDOWS_1252;
+
+    use super::super::super::Input;
+    use super::*;
+
+    #[test]
+    fn utf8() {
+        let mut input = DecodedInput::new(b"\xef\xbb\xbfa\xc4\x83").unwrap();
+
+        assert_eq!(input.encoding(), UTF_8);
+        assert_eq!(input.as_str(), "aă");
+        assert!(input.match_string("a"));
+        assert!(input.match_range('ă', 'ă'));
+        assert!(!input.is_empty() && input.pos() == input.len());
+    }
+
+    #[test]
+    fn utf16be() {
+        let input = DecodedInput::new(&[0xfe, 0xff, 0x00, b'a', 0xd8, 0x3d, 0xde, 0x00]).unwrap();
+
+        assert_eq!(input.as_str(), "a\u{1F600}");
+    }
+
+    #[test]
+    fn fallback() {
+        let input = DecodedInput::with_fallback(b"\xe9", WINDOWS_1252).unwrap();
+
+        assert_eq!(input.as_str(), "é");
+    }
+
+    #[test]
+    fn invalid() {
+        let error = DecodedInput::new(b"\xef\xbb\xbfab\xffc").err().unwrap();
+
+        assert_eq!(error.encoding, UTF_8);
+        assert_eq!(error.pos, 5);
+
+        let error = DecodedInput::new(&[0xff, 0xfe, b'a', 0x00, 0x00, 0xdc]).err().unwrap();
+
+        assert_eq!(error.pos, 4);
+    }
+}
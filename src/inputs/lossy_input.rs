@@ -0,0 +1,168 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::str;
+
+use super::super::Input;
+use super::OwnedInput;
+
+/// A `struct` useful for matching bytes that are mostly, but not entirely, valid UTF-8, like log
+/// files.
+///
+/// Every byte that is not part of a valid UTF-8 sequence is replaced by
+/// [`LossyInput::REPLACEMENT`](#associatedconstant.REPLACEMENT), a one-byte `char`, so positions
+/// are always the byte offsets of the original bytes. Replaced bytes are matched by `any` or by
+/// `['\u{1A}']`.
+///
+/// # Examples
+///
+/// ```
+/// # use pest::Input;
+/// # use pest::LossyInput;
+/// let mut input = LossyInput::new(b"a\xffb");
+///
+/// assert!(input.match_string("a"));
+/// assert!(input.match_string("\u{1A}"));
+/// assert!(input.match_string("b"));
+///
+/// assert!(input.is_replaced(1));
+/// assert_eq!(input.slice(1, 3), "\u{1A}b");
+/// ```
+pub struct LossyInput<'a> {
+    bytes: &'a [u8],
+    input: OwnedInput
+}
+
+impl<'a> LossyInput<'a> {
+    /// `char` that replaces every invalid byte: ASCII `SUB`.
+    pub const REPLACEMENT: char = '\u{1A}';
+
+    /// Creates a new `LossyInput` from `&[u8]`.
+    pub fn new(bytes: &'a [u8]) -> LossyInput<'a> {
+        let mut string = String::with_capacity(bytes.len());
+        let mut rest = bytes;
+
+        loop {
+            match str::from_utf8(rest) {
+                Ok(valid) => {
+                    string.push_str(valid);
+
+                    break;
+                },
+                Err(error) => {
+                    let (valid, invalid) = rest.split_at(error.valid_up_to());
+                    let len = error.error_len().unwrap_or(invalid.len());
+
+                    string.push_str(unsafe { str::from_utf8_unchecked(valid) });
+
+                    for _ in 0..len {
+                        string.push(LossyInput::REPLACEMENT);
+                    }
+
+                    rest = &invalid[len..];
+                }
+            }
+        }
+
+        LossyInput {
+            bytes,
+            input: OwnedInput::new(string)
+        }
+    }
+
+    /// Returns the original bytes.
+    pub fn bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Returns whether the byte at `pos` was invalid and got replaced, as opposed to being an
+    /// actual `SUB` byte.
+    pub fn is_replaced(&self, pos: usize) -> bool {
+        self.input.as_str().as_bytes().get(pos) != self.bytes.get(pos)
+    }
+}
+
+impl<'a> Input for LossyInput<'a> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.input.len()
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.input.is_empty()
+    }
+
+    #[inline]
+    fn pos(&self) -> usize {
+        self.input.pos()
+    }
+
+    #[inline]
+    fn set_pos(&mut self, pos: usize) {
+        self.input.set_pos(pos)
+    }
+
+    #[inline]
+    fn slice(&self, start: usize, end: usize) -> &str {
+        self.input.slice(start, end)
+    }
+
+    #[inline]
+    fn line_col(&self, pos: usize) -> (usize, usize) {
+        self.input.line_col(pos)
+    }
+
+    #[inline]
+    fn match_string(&mut self, string: &str) -> bool {
+        self.input.match_string(string)
+    }
+
+    #[inline]
+    fn match_range(&mut self, left: char, right: char) -> bool {
+        self.input.match_range(left, right)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::Input;
+    use super::LossyInput;
+
+    #[test]
+    fn valid() {
+        let mut input = LossyInput::new("aă".as_bytes());
+
+        assert_eq!(input.len(), 3);
+        assert!(input.match_string("a"));
+        assert!(input.match_range('ă', 'ă'));
+        assert!(!input.is_replaced(1));
+    }
+
+    #[test]
+    fn offsets() {
+        let bytes = b"\xc4a\xe2\x82\xacb\xe2\x82";
+        let input = LossyInput::new(bytes);
+
+        assert_eq!(input.len(), bytes.len());
+        assert_eq!(input.slice(0, 2), "\u{1A}a");
+        assert_eq!(input.slice(2, 6), "€b");
+        assert_eq!(input.slice(6, 8), "\u{1A}\u{1A}");
+        assert!(input.is_replaced(0));
+        assert!(!input.is_replaced(2));
+        assert!(input.is_replaced(7));
+    }
+
+    #[test]
+    fn sub() {
+        let input = LossyInput::new(b"\x1a\xff");
+
+        assert!(!input.is_replaced(0));
+        assert!(input.is_replaced(1));
+        assert_eq!(input.bytes(), b"\x1a\xff");
+    }
+}
@@ -5,6 +5,18 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
+mod chained_input;
+mod crlf_input;
+#[cfg(feature = "encoding")]
+mod decoded_input;
+mod lossy_input;
+mod owned_input;
 mod string_input;
 
+pub use self::chained_input::ChainedInput;
+pub use self::crlf_input::CrlfInput;
+#[cfg(feature = "encoding")]
+pub use self::decoded_input::{DecodeError, DecodedInput};
+pub use self::lossy_input::LossyInput;
+use self::owned_input::OwnedInput;
 pub use self::string_input::StringInput;
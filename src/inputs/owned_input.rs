@@ -0,0 +1,89 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::super::Input;
+use super::StringInput;
+
+/// A `struct` matching an owned `String` like a `StringInput`, shared by the inputs that have to
+/// convert their bytes to UTF-8 first.
+pub struct OwnedInput {
+    string: String,
+    pos:    usize
+}
+
+impl OwnedInput {
+    pub fn new(string: String) -> OwnedInput {
+        OwnedInput {
+            string,
+            pos: 0
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.string
+    }
+
+    #[inline]
+    fn with_string_input<F, R>(&mut self, f: F) -> R
+        where F: FnOnce(&mut StringInput) -> R {
+
+        let mut input = StringInput::new(&self.string);
+        input.set_pos(self.pos);
+
+        let result = f(&mut input);
+
+        self.pos = input.pos();
+
+        result
+    }
+}
+
+impl Input for OwnedInput {
+    #[inline]
+    fn len(&self) -> usize {
+        self.string.len()
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.string.is_empty()
+    }
+
+    #[inline]
+    fn pos(&self) -> usize {
+        self.pos
+    }
+
+    #[inline]
+    fn set_pos(&mut self, pos: usize) {
+        // positions past the end are allowed and simply never match
+        debug_assert!(pos > self.string.len() || self.string.is_char_boundary(pos),
+                      "position {} is not on a char boundary", pos);
+
+        self.pos = pos
+    }
+
+    #[inline]
+    fn slice(&self, start: usize, end: usize) -> &str {
+        &self.string[start..end]
+    }
+
+    #[inline]
+    fn line_col(&self, pos: usize) -> (usize, usize) {
+        StringInput::new(&self.string).line_col(pos)
+    }
+
+    #[inline]
+    fn match_string(&mut self, string: &str) -> bool {
+        self.with_string_input(|input| input.match_string(string))
+    }
+
+    #[inline]
+    fn match_range(&mut self, left: char, right: char) -> bool {
+        self.with_string_input(|input| input.match_range(left, right))
+    }
+}
@@ -6,7 +6,7 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use std::iter::Peekable;
-use std::str::{self, Chars};
+use std::str::Chars;
 
 use super::super::Input;
 
@@ -25,7 +25,9 @@ use super::super::Input;
 /// ```
 pub struct StringInput<'a> {
     string: &'a str,
-    pos: usize
+    pos: usize,
+    start: usize,
+    end: usize
 }
 
 impl<'a> StringInput<'a> {
@@ -42,8 +44,46 @@ impl<'a> StringInput<'a> {
     /// ```
     pub fn new(string: &'a str) -> StringInput<'a> {
         StringInput {
-            string: string,
-            pos : 0
+            string,
+            pos : 0,
+            start: 0,
+            end: string.len()
+        }
+    }
+
+    /// Creates a new `StringInput` that only matches `string` between `start` and `end`. All
+    /// positions, including those of `Token`s and `line_col`, are positions in the whole
+    /// `string`, so a nested `Parser` reports positions of the outer `Input`. This includes `len`,
+    /// which is `end`, while `is_empty` checks whether `start` is `end`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start..end` is not a valid range of `string`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::Input;
+    /// # use pest::StringInput;
+    /// let mut input = StringInput::with_range("a\nbcd", 3, 4);
+    ///
+    /// assert_eq!(input.pos(), 3);
+    /// assert!(input.match_string("c"));
+    /// assert!(!input.match_string("d"));
+    /// assert_eq!(input.line_col(4), (2, 3));
+    /// assert_eq!(input.len(), 4);
+    /// assert!(StringInput::with_range("a\nbcd", 3, 3).is_empty());
+    /// ```
+    pub fn with_range(string: &'a str, start: usize, end: usize) -> StringInput<'a> {
+        if string.get(start..end).is_none() {
+            panic!("invalid range {}..{}", start, end);
+        }
+
+        StringInput {
+            string,
+            pos: start,
+            start,
+            end
         }
     }
 }
@@ -51,12 +91,17 @@ impl<'a> StringInput<'a> {
 impl<'a> Input for StringInput<'a> {
     #[inline]
     fn len(&self) -> usize {
-        self.string.len()
+        self.end
     }
 
     #[inline]
     fn is_empty(&self) -> bool {
-        self.string.is_empty()
+        self.start() == self.len()
+    }
+
+    #[inline]
+    fn start(&self) -> usize {
+        self.start
     }
 
     #[inline]
@@ -66,6 +111,10 @@ impl<'a> Input for StringInput<'a> {
 
     #[inline]
     fn set_pos(&mut self, pos: usize) {
+        // positions past the end are allowed and simply never match
+        debug_assert!(pos > self.string.len() || self.string.is_char_boundary(pos),
+                      "position {} is not on a char boundary", pos);
+
         self.pos = pos
     }
 
@@ -96,7 +145,9 @@ impl<'a> Input for StringInput<'a> {
                         }
                     },
                     Some('\n') => find(chars, pos - 1, (current.0 + 1, 1)),
-                    Some(_)    => find(chars, pos - 1, (current.0, current.1 + 1)),
+                    Some(c)    => {
+                        find(chars, pos.saturating_sub(c.len_utf8()), (current.0, current.1 + 1))
+                    },
                     None       => unreachable!()
                 }
             }
@@ -111,10 +162,14 @@ impl<'a> Input for StringInput<'a> {
 
     #[inline]
     fn match_string(&mut self, string: &str) -> bool {
-        let to = self.pos + string.len();
-
-        if to <= self.string.len() {
-            let slice = unsafe { self.string.slice_unchecked(self.pos, to) };
+        // pos can be set to anything, so pos + len must not wrap around on 32-bit targets
+        let to = match self.pos.checked_add(string.len()) {
+            Some(to) => to,
+            None     => return false
+        };
+
+        if to <= self.end {
+            let slice = unsafe { self.string.get_unchecked(self.pos..to) };
             let result = slice == string;
 
             if result {
@@ -135,10 +190,13 @@ impl<'a> Input for StringInput<'a> {
             panic!("ranges should have same-sized UTF-8 limits");
         }
 
-        let to = self.pos + len;
+        let to = match self.pos.checked_add(len) {
+            Some(to) => to,
+            None     => return false
+        };
 
-        if to <= self.string.len() {
-            if let Ok(string) = str::from_utf8(&self.string.as_bytes()[self.pos..to]) {
+        if to <= self.end {
+            if let Some(string) = self.string.get(self.pos..to) {
                 let c = string.chars().next().unwrap();
 
                 let result = left <= c && c <= right;
@@ -202,6 +260,17 @@ mod tests {
         assert_eq!(input.pos(), 7);
     }
 
+    #[test]
+    fn overflow() {
+        let mut input = StringInput::new("asdasdf");
+
+        input.set_pos(usize::MAX);
+
+        assert!(!input.match_string("a"));
+        assert!(!input.match_range('a', 'z'));
+        assert_eq!(input.pos(), usize::MAX);
+    }
+
     #[test]
     fn slice() {
         let input = StringInput::new("asdasdf");
@@ -224,6 +293,54 @@ mod tests {
         assert_eq!(input.line_col(8), (4, 2));
     }
 
+    #[test]
+    fn with_range() {
+        let mut input = StringInput::with_range("abcdef", 2, 4);
+
+        assert_eq!(input.start(), 2);
+        assert_eq!(input.pos(), 2);
+        assert_eq!(input.len(), 4);
+        assert!(!input.is_empty());
+        assert!(!input.match_string("cde"));
+        assert!(input.match_string("cd"));
+        assert!(!input.match_range('a', 'z'));
+        assert_eq!(input.slice(0, 6), "abcdef");
+        assert!(StringInput::with_range("abc", 1, 1).is_empty());
+        assert_eq!(StringInput::with_range("abc", 1, 1).len(), 1);
+    }
+
+    #[test]
+    fn line_col_multibyte() {
+        let input = StringInput::new("aß\n€b");
+
+        assert_eq!(input.line_col(3), (1, 3));
+        assert_eq!(input.line_col(4), (2, 1));
+        assert_eq!(input.line_col(7), (2, 2));
+        assert_eq!(input.line_col(8), (2, 3));
+    }
+
+    #[test]
+    fn advance_by_chars() {
+        let mut input = StringInput::with_range("aßc€d", 1, 7);
+
+        assert_eq!(input.advance_by_chars(0), 0);
+        assert_eq!(input.advance_by_chars(2), 2);
+        assert_eq!(input.pos(), 4);
+        assert_eq!(input.advance_by_chars(5), 1);
+        assert_eq!(input.pos(), 7);
+
+        input.set_pos(usize::MAX);
+
+        assert_eq!(input.advance_by_chars(1), 0);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "position 2 is not on a char boundary")]
+    fn set_pos_inside_char() {
+        StringInput::new("aßc").set_pos(2);
+    }
+
     #[test]
     fn match_range() {
         let mut input = StringInput::new("bbbb");
@@ -0,0 +1,172 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// A `struct` representing a `&str` interned by an `Interner`. Two `Symbol`s from the same
+/// `Interner` are equal if and only if their `&str`s are equal.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Symbol(usize);
+
+impl Symbol {
+    /// Returns the index of a `Symbol`. `Symbol`s are numbered from `0` in the order they were
+    /// first interned.
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+/// A `struct` that stores every distinct `&str` only once and hands out `Symbol`s for them.
+///
+/// Every `Parser` generated by [`impl_rdp!`](macro.impl_rdp!) owns an `Interner` which it uses in
+/// [`Parser::intern`](trait.Parser#tymethod.intern). Since `intern` only needs `&self`, it can be
+/// called from `process!`.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::Symbol;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         idents = _{ ident* }
+///         ident  = @{ ['a'..'z']+ }
+///
+///         whitespace = _{ [" "] }
+///     }
+///
+///     process! {
+///         main(&self) -> Vec<Symbol> {
+///             (ident: ident, mut tail: main()) => {
+///                 tail.insert(0, self.intern(ident.start, ident.end));
+///
+///                 tail
+///             },
+///             () => Vec::new()
+///         }
+///     }
+/// }
+///
+/// let mut parser = Rdp::new(StringInput::new("a b a"));
+///
+/// assert!(parser.idents());
+///
+/// let symbols = parser.process();
+///
+/// assert_eq!(symbols[0], symbols[2]);
+/// assert!(symbols[0] != symbols[1]);
+/// assert_eq!(parser.interner().resolve(symbols[1]), "b");
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct Interner {
+    symbols: HashMap<Rc<str>, Symbol>,
+    strings: Vec<Rc<str>>
+}
+
+impl Interner {
+    /// Creates an empty `Interner`.
+    pub fn new() -> Interner {
+        Interner {
+            symbols: HashMap::new(),
+            strings: vec![]
+        }
+    }
+
+    /// Returns the `Symbol` of `string`, storing `string` if it was not interned before.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::Interner;
+    /// let mut interner = Interner::new();
+    ///
+    /// let a = interner.intern("a");
+    ///
+    /// assert_eq!(interner.intern("a"), a);
+    /// assert!(interner.intern("b") != a);
+    /// ```
+    pub fn intern(&mut self, string: &str) -> Symbol {
+        if let Some(&symbol) = self.symbols.get(string) {
+            return symbol;
+        }
+
+        let symbol = Symbol(self.strings.len());
+        let string: Rc<str> = Rc::from(string);
+
+        self.strings.push(string.clone());
+        self.symbols.insert(string, symbol);
+
+        symbol
+    }
+
+    /// Returns the `Symbol` of `string` without interning it.
+    pub fn get(&self, string: &str) -> Option<Symbol> {
+        self.symbols.get(string).cloned()
+    }
+
+    /// Returns the `&str` of `symbol`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `symbol` was not created by this `Interner`.
+    pub fn resolve(&self, symbol: Symbol) -> &str {
+        &self.strings[symbol.0]
+    }
+
+    /// Returns the number of distinct `&str`s that were interned.
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Returns whether nothing was interned yet.
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Interner;
+
+    #[test]
+    fn intern() {
+        let mut interner = Interner::new();
+
+        let a = interner.intern("a");
+        let b = interner.intern("b");
+
+        assert_eq!(interner.intern("a"), a);
+        assert_eq!(interner.intern("b"), b);
+        assert_eq!(interner.len(), 2);
+        assert_eq!(a.index(), 0);
+        assert_eq!(b.index(), 1);
+    }
+
+    #[test]
+    fn resolve() {
+        let mut interner = Interner::new();
+
+        let a = interner.intern("asd");
+
+        assert_eq!(interner.resolve(a), "asd");
+        assert_eq!(interner.get("asd"), Some(a));
+        assert_eq!(interner.get("f"), None);
+    }
+
+    #[test]
+    fn empty() {
+        let interner = Interner::new();
+
+        assert!(interner.is_empty());
+        assert_eq!(interner.len(), 0);
+    }
+}
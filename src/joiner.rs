@@ -0,0 +1,279 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fmt::Write;
+use std::ops::Range;
+
+use super::Token;
+
+/// A `struct` mapping a range of the output of a `StringJoiner` to the span of the `Input` it
+/// was generated from. Both ranges are byte offsets.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Mapping {
+    /// range in the output
+    pub output: Range<usize>,
+    /// span in the `Input`
+    pub input:  Range<usize>
+}
+
+/// A `struct` that builds a `String` while recording which parts of it were generated from which
+/// spans of the `Input`, e.g. in a transpiler's `process!` stage. The recorded `Mapping`s can be
+/// emitted as a [source map](https://sourcemaps.info/spec.html).
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::StringJoiner;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         assignment = { ident ~ ["="] ~ ident }
+///         ident      = @{ ['a'..'z']+ }
+///
+///         whitespace = _{ [" "] }
+///     }
+///
+///     process! {
+///         main(&self) -> StringJoiner {
+///             (_: assignment, left: ident, right: ident) => {
+///                 let mut joiner = StringJoiner::new();
+///
+///                 joiner.push_str("let ");
+///                 joiner.push_token(self.slice_input(left.start, left.end), &left);
+///                 joiner.push_str(" := ");
+///                 joiner.push_token(self.slice_input(right.start, right.end), &right);
+///                 joiner.push_str(";");
+///
+///                 joiner
+///             }
+///         }
+///     }
+/// }
+///
+/// let mut parser = Rdp::new(StringInput::new("a = bc"));
+///
+/// assert!(parser.assignment());
+///
+/// let joiner = parser.process();
+///
+/// assert_eq!(joiner.as_str(), "let a := bc;");
+/// assert_eq!(joiner.mappings()[1].output, 9..11);
+/// assert_eq!(joiner.mappings()[1].input, 4..6);
+/// assert_eq!(joiner.source_map("a = bc", "in.txt", "out.txt"),
+///            r#"{"version":3,"file":"out.txt","sources":["in.txt"],"names":[],"mappings":"IAAA,C,IAAI,E"}"#);
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct StringJoiner {
+    output:   String,
+    mappings: Vec<Mapping>
+}
+
+impl StringJoiner {
+    /// Creates an empty `StringJoiner`.
+    pub fn new() -> StringJoiner {
+        StringJoiner::default()
+    }
+
+    /// Appends `string` without mapping it to the `Input`.
+    pub fn push_str(&mut self, string: &str) {
+        self.output.push_str(string);
+    }
+
+    /// Appends `string`, mapping it to the span between `start` and `end` of the `Input`.
+    pub fn push_mapped(&mut self, string: &str, start: usize, end: usize) {
+        let output_start = self.output.len();
+
+        self.output.push_str(string);
+
+        self.mappings.push(Mapping {
+            output: output_start..self.output.len(),
+            input:  start..end
+        });
+    }
+
+    /// Appends `string`, usually the `&str` matched by `token`, mapping it to `token`'s span.
+    pub fn push_token<R>(&mut self, string: &str, token: &Token<R>) {
+        self.push_mapped(string, token.start, token.end);
+    }
+
+    /// Returns the `&str` built so far.
+    pub fn as_str(&self) -> &str {
+        &self.output
+    }
+
+    /// Returns the `Mapping`s in output order.
+    pub fn mappings(&self) -> &[Mapping] {
+        &self.mappings
+    }
+
+    /// Returns the built `String` and the `Mapping`s.
+    pub fn into_parts(self) -> (String, Vec<Mapping>) {
+        (self.output, self.mappings)
+    }
+
+    /// Returns a version 3 source map of the output as JSON. `source` is the `&str` that was
+    /// parsed, `source_name` its file name, and `file` the file name of the output. Columns are
+    /// counted in UTF-16 code units, as in JavaScript tooling.
+    pub fn source_map(&self, source: &str, source_name: &str, file: &str) -> String {
+        let mut mappings = String::new();
+
+        let mut line_start = 0;
+        let mut last_column = 0;
+        let mut last_source = (0, 0);
+        let mut first = true;
+
+        let mut segment = |mappings: &mut String, pos: usize, input: Option<usize>| {
+            let skipped = &self.output[line_start..pos];
+
+            if let Some(i) = skipped.rfind('\n') {
+                for _ in skipped.matches('\n') {
+                    mappings.push(';');
+                }
+
+                line_start += i + 1;
+                last_column = 0;
+                first = true;
+            }
+
+            let column = utf16_len(&self.output[line_start..pos]);
+
+            if !first {
+                mappings.push(',');
+            }
+
+            first = false;
+
+            vlq(mappings, column as i64 - last_column as i64);
+            last_column = column;
+
+            if let Some(input) = input {
+                let source_pos = line_col(source, input);
+
+                vlq(mappings, 0);
+                vlq(mappings, source_pos.0 as i64 - last_source.0 as i64);
+                vlq(mappings, source_pos.1 as i64 - last_source.1 as i64);
+
+                last_source = source_pos;
+            }
+        };
+
+        for (i, mapping) in self.mappings.iter().enumerate() {
+            segment(&mut mappings, mapping.output.start, Some(mapping.input.start));
+
+            let next = self.mappings.get(i + 1).map(|next| next.output.start);
+
+            if next != Some(mapping.output.end) && mapping.output.end < self.output.len() {
+                segment(&mut mappings, mapping.output.end, None);
+            }
+        }
+
+        let mut json = String::new();
+
+        json.push_str("{\"version\":3,\"file\":");
+        escape(&mut json, file);
+        json.push_str(",\"sources\":[");
+        escape(&mut json, source_name);
+        json.push_str("],\"names\":[],\"mappings\":");
+        escape(&mut json, &mappings);
+        json.push('}');
+
+        json
+    }
+}
+
+fn utf16_len(string: &str) -> usize {
+    string.chars().map(char::len_utf16).sum()
+}
+
+// 0-based line and UTF-16 column of pos
+fn line_col(source: &str, pos: usize) -> (usize, usize) {
+    let before = &source[..pos];
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+
+    (before.matches('\n').count(), utf16_len(&before[line_start..]))
+}
+
+fn vlq(string: &mut String, value: i64) {
+    const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut value = if value < 0 { ((-value) << 1) | 1 } else { value << 1 } as u64;
+
+    loop {
+        let mut digit = value & 0b11111;
+
+        value >>= 5;
+
+        if value > 0 {
+            digit |= 0b100000;
+        }
+
+        string.push(BASE64[digit as usize] as char);
+
+        if value == 0 {
+            break;
+        }
+    }
+}
+
+// writes string as a JSON string
+pub fn escape(json: &mut String, string: &str) {
+    json.push('"');
+
+    for c in string.chars() {
+        match c {
+            '"'  => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            },
+            c    => json.push(c)
+        }
+    }
+
+    json.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vlq_values() {
+        let mut string = String::new();
+
+        for &value in &[0, 1, -1, 15, 16, -17, 1000] {
+            vlq(&mut string, value);
+            string.push(' ');
+        }
+
+        assert_eq!(string, "A C D e gB jB w+B ");
+    }
+
+    #[test]
+    fn lines() {
+        let mut joiner = StringJoiner::new();
+
+        joiner.push_mapped("a", 0, 1);
+        joiner.push_str("\n\n  ");
+        joiner.push_mapped("bc", 3, 5);
+
+        assert_eq!(joiner.source_map("a\nxbc", "in", "out"),
+                   r#"{"version":3,"file":"out","sources":["in"],"names":[],"mappings":"AAAA,C;;EACC"}"#);
+    }
+
+    #[test]
+    fn escaped() {
+        let joiner = StringJoiner::new();
+
+        assert_eq!(joiner.source_map("", "a\"b", "c\\d"),
+                   r#"{"version":3,"file":"c\\d","sources":["a\"b"],"names":[],"mappings":""}"#);
+    }
+}
@@ -0,0 +1,65 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+/// A `trait` for the items of a `keywords` rule, so that a keyword can be given as a `&str` and a
+/// group of keywords as a slice or array of them. This lets keyword lists be Rust `const`s shared
+/// between the grammar and the rest of the crate, e.g. highlighting tables or completion lists.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # fn main() {
+/// const CONTROL: &[&str] = &["if", "else", "while"];
+/// const RETURN: &str = "return";
+///
+/// impl_rdp! {
+///     grammar! {
+///         keyword = keywords(ident) { CONTROL, RETURN, "let" }
+///         ident   = @{ ['a'..'z']+ }
+///     }
+/// }
+///
+/// let mut parser = Rdp::new(StringInput::new("while"));
+///
+/// assert!(parser.keyword());
+/// assert!(parser.end());
+///
+/// let mut parser = Rdp::new(StringInput::new("return"));
+///
+/// assert!(!parser.ident());
+/// # }
+/// ```
+pub trait Keywords {
+    /// Returns the keywords.
+    fn keywords(&self) -> &[&'static str];
+}
+
+impl Keywords for &'static str {
+    fn keywords(&self) -> &[&'static str] {
+        std::slice::from_ref(self)
+    }
+}
+
+impl Keywords for &[&'static str] {
+    fn keywords(&self) -> &[&'static str] {
+        self
+    }
+}
+
+impl<const N: usize> Keywords for [&'static str; N] {
+    fn keywords(&self) -> &[&'static str] {
+        self
+    }
+}
+
+impl<const N: usize> Keywords for &[&'static str; N] {
+    fn keywords(&self) -> &[&'static str] {
+        *self
+    }
+}
@@ -0,0 +1,348 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fmt;
+use std::vec::IntoIter;
+
+use super::Parser;
+
+/// An `enum` representing what a [`Lexeme`](struct.Lexeme) is made of.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum LexemeKind<R> {
+    /// text matched by a rule that produced a `Token` of this `Rule`
+    Rule(R),
+    /// text matched by a [silent](macro.grammar!#silent-rules-_) rule, e.g. whitespace
+    Trivia,
+    /// text that none of the rules matched
+    Error
+}
+
+/// A `struct` representing one piece of a flat token stream returned by
+/// [`Parser::lex`](trait.Parser#tymethod.lex). `Lexeme`s are gapless: every one of them starts
+/// where the previous one ended.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Lexeme<R> {
+    /// what the `Lexeme` is made of
+    pub kind:  LexemeKind<R>,
+    /// starting position in `Input`
+    pub start: usize,
+    /// ending position in `Input`
+    pub end:   usize
+}
+
+/// A `struct` representing a terminal handed to an external parser by
+/// [`LalrTokens`](struct.LalrTokens).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Terminal<'i, R> {
+    /// kind of the terminal; `Rule as usize` gives a stable numeric id
+    pub rule: R,
+    /// matched text
+    pub text: &'i str
+}
+
+/// A `struct` representing text that none of the lexical rules matched, i.e. a
+/// `LexemeKind::Error` `Lexeme`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct LexError {
+    /// starting position in `Input`
+    pub start: usize,
+    /// ending position in `Input`
+    pub end:   usize
+}
+
+/// An `Iterator` that turns the `Lexeme`s returned by [`Parser::lex`](trait.Parser#tymethod.lex)
+/// into the `Result<(start, Terminal, end), LexError>` triples external LALR parser generators
+/// such as LALRPOP expect from a hand-written lexer. `LexemeKind::Trivia` `Lexeme`s are dropped.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::{LalrTokens, LexError, Terminal};
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         number = @{ ['0'..'9']+ }
+///         plus   = @{ ["+"] }
+///         space  = _{ [" "]+ }
+///     }
+/// }
+///
+/// let source = "1 + 23 ?";
+/// let mut parser = Rdp::new(StringInput::new(source));
+/// let lexemes = parser.lex(&[Rdp::number, Rdp::plus, Rdp::space]);
+/// let tokens: Vec<_> = LalrTokens::new(source, lexemes).collect();
+///
+/// assert_eq!(tokens, vec![
+///     Ok((0, Terminal { rule: Rule::number, text: "1" }, 1)),
+///     Ok((2, Terminal { rule: Rule::plus, text: "+" }, 3)),
+///     Ok((4, Terminal { rule: Rule::number, text: "23" }, 6)),
+///     Err(LexError { start: 7, end: 8 })
+/// ]);
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct LalrTokens<'i, R> {
+    input:   &'i str,
+    lexemes: IntoIter<Lexeme<R>>
+}
+
+impl<'i, R> LalrTokens<'i, R> {
+    /// Creates a `LalrTokens` out of `lexemes` lexed from `input`.
+    pub fn new(input: &'i str, lexemes: Vec<Lexeme<R>>) -> LalrTokens<'i, R> {
+        LalrTokens {
+            input,
+            lexemes: lexemes.into_iter()
+        }
+    }
+}
+
+impl<'i, R> Iterator for LalrTokens<'i, R> {
+    type Item = Result<(usize, Terminal<'i, R>, usize), LexError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for Lexeme { kind, start, end } in self.lexemes.by_ref() {
+            match kind {
+                LexemeKind::Rule(rule) => {
+                    let terminal = Terminal {
+                        rule,
+                        text: &self.input[start..end]
+                    };
+
+                    return Some(Ok((start, terminal, end)));
+                },
+                LexemeKind::Error      => return Some(Err(LexError { start, end })),
+                LexemeKind::Trivia     => ()
+            }
+        }
+
+        None
+    }
+}
+
+/// A `struct` representing named groups of lexical rules, or modes, for
+/// [`Parser::lex_modes`](trait.Parser#tymethod.lex_modes), similar to the states of a lexer
+/// generator. Only the rules of the mode on top of a stack are tried at any point; matching a
+/// rule can [`push`](#method.push) another mode onto the stack or [`pop`](#method.pop) the current
+/// one. The first mode is the initial one and is never popped.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::{LexemeKind, Modes};
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         text  = @{ (!["{{"] ~ any)+ }
+///         open  = @{ ["{{"] }
+///         close = @{ ["}}"] }
+///         ident = @{ ['a'..'z']+ }
+///         space = _{ [" "]+ }
+///     }
+/// }
+///
+/// let modes = Modes::new().mode("text", &[Rdp::text, Rdp::open])
+///                         .push(Rule::open, "code")
+///                         .mode("code", &[Rdp::ident, Rdp::space, Rdp::close])
+///                         .pop(Rule::close);
+///
+/// let mut parser = Rdp::new(StringInput::new("hi {{ name }}!"));
+/// let kinds: Vec<_> = parser.lex_modes(&modes).into_iter()
+///                           .map(|lexeme| lexeme.kind)
+///                           .collect();
+///
+/// assert_eq!(kinds, vec![
+///     LexemeKind::Rule(Rule::text),
+///     LexemeKind::Rule(Rule::open),
+///     LexemeKind::Trivia,
+///     LexemeKind::Rule(Rule::ident),
+///     LexemeKind::Trivia,
+///     LexemeKind::Rule(Rule::close),
+///     LexemeKind::Rule(Rule::text)
+/// ]);
+/// # }
+/// ```
+#[allow(clippy::type_complexity)]
+pub struct Modes<P: Parser> {
+    modes:       Vec<(&'static str, Vec<fn(&mut P) -> bool>)>,
+    transitions: Vec<(usize, P::Rule, Option<&'static str>)>
+}
+
+impl<P: Parser> Modes<P> where P::Rule: PartialEq {
+    /// Creates a `Modes` without any mode.
+    pub fn new() -> Modes<P> {
+        Modes {
+            modes:       vec![],
+            transitions: vec![]
+        }
+    }
+
+    /// Adds a mode called `name` that tries `rules`, longest match first, like
+    /// [`Parser::lex`](trait.Parser#tymethod.lex).
+    pub fn mode(mut self, name: &'static str, rules: &[fn(&mut P) -> bool]) -> Modes<P> {
+        self.modes.push((name, rules.to_vec()));
+        self
+    }
+
+    /// Pushes the mode called `name`, which may be added later, after a `Lexeme` of `rule` in the
+    /// last added mode.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no mode was added yet.
+    pub fn push(self, rule: P::Rule, name: &'static str) -> Modes<P> {
+        self.transition(rule, Some(name))
+    }
+
+    /// Pops the current mode after a `Lexeme` of `rule` in the last added mode.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no mode was added yet.
+    pub fn pop(self, rule: P::Rule) -> Modes<P> {
+        self.transition(rule, None)
+    }
+
+    /// Returns the index of the mode called `name`.
+    pub fn index(&self, name: &str) -> Option<usize> {
+        self.modes.iter().position(|&(mode, _)| mode == name)
+    }
+
+    /// Returns the name of the mode at `index`.
+    pub fn name(&self, index: usize) -> &'static str {
+        self.modes[index].0
+    }
+
+    /// Returns the rules of the mode at `index`, or none if there is no such mode.
+    pub fn rules(&self, index: usize) -> &[fn(&mut P) -> bool] {
+        self.modes.get(index).map_or(&[], |(_, rules)| rules)
+    }
+
+    /// Applies the transition of a `Lexeme` of `rule` in the mode on top of `stack`, which holds
+    /// mode indices and is never emptied.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the transition pushes a mode that was never added.
+    pub fn apply(&self, rule: &P::Rule, stack: &mut Vec<usize>) {
+        let mode = match stack.last() {
+            Some(&mode) => mode,
+            None        => return
+        };
+        let transition = self.transitions.iter().find(|&&(other_mode, ref other, _)| {
+            other_mode == mode && other == rule
+        });
+
+        match transition {
+            Some(&(_, _, Some(name))) => {
+                let index = self.index(name)
+                                .unwrap_or_else(|| panic!("mode `{}` is not defined", name));
+
+                stack.push(index);
+            },
+            Some(&(_, _, None)) if stack.len() > 1 => {
+                stack.pop();
+            },
+            _ => ()
+        }
+    }
+
+    fn transition(mut self, rule: P::Rule, name: Option<&'static str>) -> Modes<P> {
+        let mode = self.modes.len().checked_sub(1).expect("transitions need a mode to start from");
+
+        self.transitions.push((mode, rule, name));
+        self
+    }
+}
+
+impl<P: Parser> Default for Modes<P> where P::Rule: PartialEq {
+    fn default() -> Modes<P> {
+        Modes::new()
+    }
+}
+
+impl<P: Parser> fmt::Debug for Modes<P> where P::Rule: fmt::Debug {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names: Vec<_> = self.modes.iter().map(|&(name, _)| name).collect();
+
+        f.debug_struct("Modes")
+         .field("modes", &names)
+         .field("transitions", &self.transitions)
+         .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::prelude::*;
+    use super::{LexemeKind, Modes};
+
+    impl_rdp! {
+        grammar! {
+            quote = @{ ["\""] }
+            chars = @{ (!["\""] ~ !["${"] ~ any)+ }
+            open  = @{ ["${"] }
+            close = @{ ["}"] }
+            ident = @{ ['a'..'z']+ }
+        }
+    }
+
+    #[test]
+    fn nested() {
+        let modes = Modes::new().mode("code", &[Rdp::quote, Rdp::ident, Rdp::close])
+                                .push(Rule::quote, "string")
+                                .pop(Rule::close)
+                                .mode("string", &[Rdp::chars, Rdp::open, Rdp::quote])
+                                .push(Rule::open, "code")
+                                .pop(Rule::quote);
+        let mut parser = Rdp::new(StringInput::new("\"a${b}c\"}d"));
+        let kinds: Vec<_> = parser.lex_modes(&modes).into_iter().map(|lexeme| lexeme.kind)
+                                  .collect();
+
+        // the last close is in the bottom mode and does not pop it
+        assert_eq!(kinds, vec![
+            LexemeKind::Rule(Rule::quote),
+            LexemeKind::Rule(Rule::chars),
+            LexemeKind::Rule(Rule::open),
+            LexemeKind::Rule(Rule::ident),
+            LexemeKind::Rule(Rule::close),
+            LexemeKind::Rule(Rule::chars),
+            LexemeKind::Rule(Rule::quote),
+            LexemeKind::Rule(Rule::close),
+            LexemeKind::Rule(Rule::ident)
+        ]);
+        assert!(parser.end());
+    }
+
+    #[test]
+    fn apply() {
+        let modes: Modes<Rdp<StringInput>> = Modes::new();
+        let modes = modes.mode("code", &[Rdp::ident, Rdp::close])
+                         .pop(Rule::close)
+                         .push(Rule::open, "string");
+        let mut stack = vec![0];
+
+        modes.apply(&Rule::close, &mut stack);
+        modes.apply(&Rule::ident, &mut stack);
+
+        assert_eq!(stack, vec![0]);
+        assert_eq!(modes.index("code"), Some(0));
+        assert_eq!(modes.name(0), "code");
+    }
+
+    #[test]
+    #[should_panic(expected = "mode `string` is not defined")]
+    fn undefined() {
+        let modes: Modes<Rdp<StringInput>> = Modes::new();
+        let modes = modes.mode("code", &[Rdp::open]).push(Rule::open, "string");
+
+        modes.apply(&Rule::open, &mut vec![0]);
+    }
+}
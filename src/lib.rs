@@ -49,7 +49,10 @@
 //! implemented.
 //!
 //! *Note:* `grammar!` may require you to increase the recursion limit of your create with
-//! `#![recursion_limit = "*"]` where * is the new limit.
+//! `#![recursion_limit = "*"]` where * is the new limit. Every rule is emitted into its own `impl`
+//! block, so the limit needed grows by roughly one per rule and with the length of the longest
+//! `~` sequence in a rule, while alternatives are cheap. The default limit of 128 fits around a
+//! hundred short rules or sequences of about 40 terms.
 //!
 //! When `impl_rdp!` is run, it implements an `enum` called `Rule` that has a value for all
 //! [non-silent](macro.grammar!#silent-rules-_) rules, but also for
@@ -566,19 +569,127 @@
 //! # }
 //! ```
 
+#[cfg(feature = "encoding")]
+pub extern crate encoding_rs;
+#[cfg(feature = "log")]
+#[macro_use]
+extern crate log;
+#[cfg(feature = "serde")]
+pub extern crate serde;
+
 #[macro_use]
 mod grammar;
 #[macro_use]
 mod process;
 #[macro_use]
 mod parsers;
+#[macro_use]
+mod pratt;
+#[doc(hidden)]
+#[macro_use]
+pub mod conformance;
+mod arena;
+mod attach;
+mod backtrack;
+mod batch;
+mod coverage;
+mod cursor;
+#[cfg(feature = "serde")]
+mod de;
+mod delimiter;
+mod document;
+mod edits;
+mod event;
+mod first_set;
+mod generated;
+mod golden;
+mod heredoc;
 mod input;
 mod inputs;
+mod interner;
+mod joiner;
+mod keywords;
+mod lexer;
+mod limits;
+mod line_index;
+mod lint;
+mod messages;
 mod parser;
+mod partial;
+mod preprocess;
+mod profile;
+mod progress;
+mod recording;
+mod recovery;
+mod reducer;
+mod result;
+mod rewrite;
+mod scanner;
+mod suggest;
+mod token_index;
+mod trace;
+mod tracking;
+mod trivia;
+#[macro_use]
+mod tree;
+mod validate;
 
+#[cfg(feature = "cli")]
+pub mod cli;
+#[cfg(feature = "formats")]
+pub mod formats;
 pub mod prelude;
+pub mod runtime;
+pub mod stdlib;
 
+pub use arena::Arena;
+pub use attach::{Attacher, Attachment};
+pub use backtrack::{Backtrack, BacktrackBounds, Bound};
+pub use batch::{BatchEntry, BatchError, BatchParser, BatchReport};
+pub use coverage::{Alternative, Coverage};
+pub use cursor::Cursor;
+#[cfg(feature = "serde")]
+pub use de::{from_tokens, DeserializeError, Deserializer, Model};
+pub use delimiter::Unclosed;
+pub use document::Document;
+pub use edits::{EditConflict, Edits};
+pub use event::{Event, Events};
+pub use first_set::FirstSet;
+pub use generated::{GeneratedParser, RuleKind};
+pub use golden::{Golden, GoldenChange, GoldenParseError, Snapshot, SnapshotToken};
+pub use heredoc::Heredoc;
 pub use input::Input;
-pub use inputs::StringInput;
+#[cfg(feature = "encoding")]
+pub use inputs::{DecodeError, DecodedInput};
+pub use inputs::{ChainedInput, CrlfInput, LossyInput, StringInput};
+pub use interner::{Interner, Symbol};
+pub use joiner::{Mapping, StringJoiner};
+pub use keywords::Keywords;
+pub use lexer::{LalrTokens, LexError, Lexeme, LexemeKind, Modes, Terminal};
+pub use limits::{LimitError, LimitKind, Limits};
+pub use line_index::LineIndex;
+pub use lint::{Lint, LintKind, LintLevel};
+pub use messages::{MessageKind, Messages};
+#[doc(hidden)]
+pub use parser::{EmbeddedTable, MemoTable, SavedState};
 pub use parser::Parser;
-pub use parsers::Token;
+pub use partial::Partial;
+pub use pratt::{Expr, Pratt, PrattError};
+pub use preprocess::{IncludeError, Includer, Origin, Preprocessed, Preprocessor};
+pub use profile::{Hint, Profile, RuleStats};
+pub use progress::Progress;
+pub use recording::{Recording, Replay, Step};
+pub use recovery::{Diagnostic, Recovery};
+pub use reducer::Reducer;
+pub use result::ParseResult;
+pub use rewrite::{Relabel, Rewriter};
+pub use scanner::{Scanner, ScannerError};
+pub use suggest::{edit_distance, Suggestion};
+pub use token_index::TokenIndex;
+#[doc(hidden)]
+pub use trace::{trace_enter, trace_exit};
+pub use tracking::{Expected, Tracking};
+pub use trivia::Trivia;
+pub use tree::children;
+pub use validate::{validate_queue, validate_spans, QueueError, QueueErrorKind, SpanError};
+pub use parsers::{OwnedToken, Token, TokenData};
@@ -0,0 +1,144 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+/// A `struct` of limits enforced by a `Parser` while matching untrusted input. No limits are set
+/// by default.
+///
+/// Once a limit is exceeded, every rule fails until the `Parser` is
+/// [reset](trait.Parser#tymethod.reset), and the error is available in
+/// [`Parser::limit_error`](trait.Parser#tymethod.limit_error).
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::{LimitError, LimitKind, Limits};
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         expression = { ["("] ~ expression ~ [")"] | ["a"] }
+///     }
+/// }
+///
+/// let mut parser = Rdp::new(StringInput::new("((((a))))"));
+///
+/// parser.set_limits(Limits::new().max_depth(3));
+///
+/// assert!(!parser.expression());
+/// assert_eq!(parser.limit_error(), Some(LimitError { kind: LimitKind::Depth, pos: 3 }));
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Limits {
+    max_line_len: Option<usize>,
+    max_tokens:   Option<usize>,
+    max_depth:    Option<usize>,
+    timeout:      Option<Duration>
+}
+
+impl Limits {
+    /// Creates `Limits` without any limit set.
+    pub fn new() -> Limits {
+        Limits::default()
+    }
+
+    /// Limits the length in bytes of every line. Lines are checked whenever a rule starts
+    /// matching.
+    pub fn max_line_len(mut self, value: usize) -> Limits {
+        self.max_line_len = Some(value);
+
+        self
+    }
+
+    /// Limits the number of `Token`s in the queue.
+    pub fn max_tokens(mut self, value: usize) -> Limits {
+        self.max_tokens = Some(value);
+
+        self
+    }
+
+    /// Limits how many rules, including silent rules but not `whitespace` and `comment`, can be
+    /// nested while matching.
+    pub fn max_depth(mut self, value: usize) -> Limits {
+        self.max_depth = Some(value);
+
+        self
+    }
+
+    /// Limits how long matching can take, measured from the first rule matched after the
+    /// `Parser` was created or [reset](trait.Parser#tymethod.reset). Time is checked whenever a
+    /// rule starts matching.
+    pub fn timeout(mut self, value: Duration) -> Limits {
+        self.timeout = Some(value);
+
+        self
+    }
+
+    /// Returns the maximum line length.
+    pub fn line_len(&self) -> Option<usize> {
+        self.max_line_len
+    }
+
+    /// Returns the maximum number of `Token`s.
+    pub fn tokens(&self) -> Option<usize> {
+        self.max_tokens
+    }
+
+    /// Returns the maximum nesting depth.
+    pub fn depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+
+    /// Returns the maximum matching time.
+    pub fn time(&self) -> Option<Duration> {
+        self.timeout
+    }
+}
+
+/// An `enum` describing which of the `Limits` was exceeded.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum LimitKind {
+    /// a line is longer than `Limits::max_line_len`
+    LineLength,
+    /// the queue has more `Token`s than `Limits::max_tokens`
+    TokenCount,
+    /// rules are nested deeper than `Limits::max_depth`
+    Depth,
+    /// matching took longer than `Limits::timeout`
+    Timeout,
+    /// matching was cancelled through
+    /// [`Parser::set_cancellation`](trait.Parser#tymethod.set_cancellation)
+    Cancelled
+}
+
+/// A `struct` describing a limit exceeded at position `pos`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LimitError {
+    /// exceeded limit
+    pub kind: LimitKind,
+    /// position where the limit was exceeded
+    pub pos:  usize
+}
+
+impl fmt::Display for LimitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            LimitKind::LineLength => write!(f, "line too long at {}", self.pos),
+            LimitKind::TokenCount => write!(f, "too many tokens at {}", self.pos),
+            LimitKind::Depth      => write!(f, "rules nested too deeply at {}", self.pos),
+            LimitKind::Timeout    => write!(f, "timed out at {}", self.pos),
+            LimitKind::Cancelled  => write!(f, "cancelled at {}", self.pos)
+        }
+    }
+}
+
+impl Error for LimitError {}
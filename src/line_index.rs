@@ -0,0 +1,109 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+/// A `struct` that stores where every line of a `&str` starts, turning positions into lines and
+/// columns in logarithmic time. Lines end with `\n`, `\r\n`, or `\r`, like in
+/// [`Input::line_col`](trait.Input#tymethod.line_col).
+///
+/// # Examples
+///
+/// ```
+/// # use pest::LineIndex;
+/// let string = "ab\ncd\r\ne";
+/// let index = LineIndex::new(string);
+///
+/// assert_eq!(index.len(), 3);
+/// assert_eq!(index.line_col(string, 4), (2, 2));
+/// assert_eq!(index.line_col(string, 7), (3, 1));
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LineIndex {
+    starts: Vec<usize>
+}
+
+impl LineIndex {
+    /// Creates a `LineIndex` of `string`.
+    pub fn new(string: &str) -> LineIndex {
+        let bytes = string.as_bytes();
+        let mut starts = vec![0];
+
+        for (i, &byte) in bytes.iter().enumerate() {
+            match byte {
+                b'\n'                                 => starts.push(i + 1),
+                b'\r' if bytes.get(i + 1) != Some(&b'\n') => starts.push(i + 1),
+                _                                     => ()
+            }
+        }
+
+        LineIndex {
+            starts
+        }
+    }
+
+    /// Returns the number of lines.
+    pub fn len(&self) -> usize {
+        self.starts.len()
+    }
+
+    /// Returns whether there are no lines, which is never the case.
+    pub fn is_empty(&self) -> bool {
+        self.starts.is_empty()
+    }
+
+    /// Returns the position where `line`, starting at `1`, starts.
+    pub fn line_start(&self, line: usize) -> Option<usize> {
+        line.checked_sub(1).and_then(|line| self.starts.get(line)).cloned()
+    }
+
+    /// Returns the line of `pos`, starting at `1`.
+    pub fn line(&self, pos: usize) -> usize {
+        match self.starts.binary_search(&pos) {
+            Ok(i)  => i + 1,
+            Err(i) => i
+        }
+    }
+
+    /// Returns the line and column of `pos` in `string`, the `&str` the `LineIndex` was created
+    /// from, both starting at `1`. Columns are counted in `char`s.
+    pub fn line_col(&self, string: &str, pos: usize) -> (usize, usize) {
+        let line = self.line(pos);
+        let start = self.starts[line - 1];
+
+        (line, string[start..pos].chars().count() + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LineIndex;
+
+    #[test]
+    fn line_col() {
+        let string = "a\rb\nc\r\nd";
+        let index = LineIndex::new(string);
+
+        assert_eq!(index.line_col(string, 0), (1, 1));
+        assert_eq!(index.line_col(string, 1), (1, 2));
+        assert_eq!(index.line_col(string, 2), (2, 1));
+        assert_eq!(index.line_col(string, 3), (2, 2));
+        assert_eq!(index.line_col(string, 4), (3, 1));
+        assert_eq!(index.line_col(string, 5), (3, 2));
+        assert_eq!(index.line_col(string, 7), (4, 1));
+        assert_eq!(index.line_col(string, 8), (4, 2));
+    }
+
+    #[test]
+    fn line_start() {
+        let index = LineIndex::new("ăb\nc");
+
+        assert_eq!(index.line_start(0), None);
+        assert_eq!(index.line_start(1), Some(0));
+        assert_eq!(index.line_start(2), Some(4));
+        assert_eq!(index.line_start(3), None);
+        assert_eq!(index.line_col("ăb\nc", 3), (1, 3));
+    }
+}
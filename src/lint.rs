@@ -0,0 +1,437 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use super::{Alternative, RuleKind};
+use super::backtrack::{Expr, ExprParser};
+
+/// An `enum` representing how much a [`Lint`](struct.Lint) matters, set for every
+/// [`LintKind`](enum.LintKind) with `#![allow(...)]`, `#![warn(...)]`, or `#![deny(...)]` in
+/// [`grammar!`](macro.grammar!#lints).
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum LintLevel {
+    /// the `Lint` is not reported
+    Allow,
+    /// the `Lint` is reported as a warning
+    Warn,
+    /// the `Lint` is reported as an error
+    Deny
+}
+
+/// An `enum` representing the kind of a [`Lint`](struct.Lint). Every kind is reported as a
+/// warning by default.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum LintKind {
+    /// `split_word`: a non-atomic rule only repeats characters, so trivia is skipped between them
+    /// and separate words, e.g. `"a a"` for `one = { ["a"]+ }`, match as a single `Token`
+    SplitWord,
+    /// `unused_rule`: a silent rule is never called by another rule; rules that create `Token`s
+    /// are not checked, since they can start a parse
+    UnusedRule,
+    /// `nullable_loop`: a rule repeats something that can match without making progress, which
+    /// loops forever
+    NullableLoop,
+    /// `shadowed_alternative`: a top-level alternative of a rule starts with a string of which an
+    /// earlier alternative `by` is a prefix, so alternative `index` never matches
+    ShadowedAlternative {
+        /// index of the alternative that never matches
+        index: usize,
+        /// index of the alternative that matches first
+        by:    usize
+    }
+}
+
+impl LintKind {
+    /// Returns the name of the `LintKind` as used in `grammar!` attributes.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            LintKind::SplitWord                  => "split_word",
+            LintKind::UnusedRule                 => "unused_rule",
+            LintKind::NullableLoop               => "nullable_loop",
+            LintKind::ShadowedAlternative { .. } => "shadowed_alternative"
+        }
+    }
+}
+
+/// A `struct` representing a likely mistake in a grammar, returned by
+/// [`Parser::lints`](trait.Parser#tymethod.lints).
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::{Lint, LintKind, LintLevel};
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         #![deny(split_word)]
+///
+///         sum    = { number ~ (["+"] ~ number)* }
+///         number = { ['0'..'9']+ }
+///
+///         whitespace = _{ [" "] }
+///     }
+/// }
+///
+/// let parser = Rdp::new(StringInput::new(""));
+///
+/// assert_eq!(parser.lints(), vec![
+///     Lint { rule: "number", kind: LintKind::SplitWord, level: LintLevel::Deny }
+/// ]);
+/// assert_eq!(parser.lints()[0].to_string(),
+///            "error: rule `number` only repeats characters, but is not atomic, so trivia is \
+///             skipped between them; define it with `@{ ... }`");
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Lint {
+    /// name of the rule
+    pub rule:  &'static str,
+    /// what is likely wrong with it
+    pub kind:  LintKind,
+    /// how much it matters; never `LintLevel::Allow`
+    pub level: LintLevel
+}
+
+impl Lint {
+    /// Checks the rules defined by `alternatives`, where `kinds` holds the `RuleKind` of every
+    /// rule that creates `Token`s, and returns the `Lint`s that `levels` do not allow, by rule in
+    /// grammar order. `levels` maps [`LintKind::name`](enum.LintKind#method.name)s to their
+    /// `LintLevel`, with later entries taking precedence.
+    pub fn check(alternatives: &[Alternative], kinds: &[(&str, RuleKind)],
+                 levels: &[(&str, LintLevel)]) -> Vec<Lint> {
+        let mut rules: Vec<(&'static str, Vec<Expr>)> = vec![];
+
+        for alternative in alternatives {
+            let expr = ExprParser::parse(alternative.text);
+
+            match rules.last_mut() {
+                Some(&mut (rule, ref mut exprs)) if rule == alternative.rule => exprs.push(expr),
+                _ => rules.push((alternative.rule, vec![expr]))
+            }
+        }
+
+        let nullable = nullable_rules(&rules);
+        let trivia = rules.iter().any(|&(rule, _)| TRIVIA.contains(&rule));
+        let mut lints = vec![];
+
+        for &(rule, ref exprs) in &rules {
+            let kind = kinds.iter().find(|&&(name, _)| name == rule).map(|&(_, kind)| kind);
+            let mut push = |kind: LintKind| {
+                let level = levels.iter().rev()
+                                  .find(|&&(name, _)| name == kind.name())
+                                  .map_or(LintLevel::Warn, |&(_, level)| level);
+
+                if level != LintLevel::Allow {
+                    lints.push(Lint {
+                        rule,
+                        kind,
+                        level
+                    });
+                }
+            };
+
+            let lexical = exprs.iter().all(|expr| is_lexical(expr, &nullable));
+
+            if trivia && kind == Some(RuleKind::Normal) && lexical && exprs.iter().any(repeats) {
+                push(LintKind::SplitWord);
+            }
+
+            let used = rules.iter().any(|&(other, ref exprs)| {
+                other != rule && exprs.iter().any(|expr| calls(expr, rule))
+            });
+
+            if kind.is_none() && !TRIVIA.contains(&rule) && !used {
+                push(LintKind::UnusedRule);
+            }
+
+            if exprs.iter().any(|expr| loops(expr, &nullable)) {
+                push(LintKind::NullableLoop);
+            }
+
+            for (index, expr) in exprs.iter().enumerate() {
+                let by = exprs[..index].iter().position(|earlier| match (earlier, first(expr)) {
+                    (Expr::Str(prefix), Some(string)) => string.starts_with(prefix.as_str()),
+                    _                                 => false
+                });
+
+                if let Some(by) = by {
+                    push(LintKind::ShadowedAlternative { index, by });
+                }
+            }
+        }
+
+        lints
+    }
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.level {
+            LintLevel::Deny => write!(f, "error: ")?,
+            _               => write!(f, "warning: ")?
+        }
+
+        match self.kind {
+            LintKind::SplitWord                         => {
+                write!(f, "rule `{}` only repeats characters, but is not atomic, so trivia is \
+                           skipped between them; define it with `@{{ ... }}`", self.rule)
+            },
+            LintKind::UnusedRule                        => {
+                write!(f, "silent rule `{}` is never used", self.rule)
+            },
+            LintKind::NullableLoop                      => {
+                write!(f, "rule `{}` repeats something that can match without making progress, \
+                           which loops forever", self.rule)
+            },
+            LintKind::ShadowedAlternative { index, by } => {
+                write!(f, "alternative {} of rule `{}` never matches, since alternative {} \
+                           matches a prefix of it first", index, self.rule, by)
+            }
+        }
+    }
+}
+
+const TRIVIA: [&str; 3] = ["whitespace", "newline", "comment"];
+
+// whether expr only matches characters, without calling rules; the grammar's rules are the keys
+// of nullable and take precedence over predefined ones of the same name
+fn is_lexical(expr: &Expr, nullable: &HashMap<&str, bool>) -> bool {
+    match *expr {
+        Expr::Rule(name) if nullable.contains_key(name) => false,
+        Expr::Str(_)                 |
+        Expr::Range(_)               |
+        Expr::Rule("any")            |
+        Expr::Rule("any_byte")       |
+        Expr::Rule("any_ascii")      |
+        Expr::Rule("line_ending")    => true,
+        Expr::Seq(ref exprs)         |
+        Expr::Choice(ref exprs)      => exprs.iter().all(|expr| is_lexical(expr, nullable)),
+        Expr::Opt(ref expr)          |
+        Expr::Rep(ref expr, _)       |
+        Expr::Look(ref expr)         => is_lexical(expr, nullable),
+        _                            => false
+    }
+}
+
+// whether expr repeats something, between which trivia would be skipped
+fn repeats(expr: &Expr) -> bool {
+    match *expr {
+        Expr::Rep(..)                => true,
+        Expr::Seq(ref exprs)         |
+        Expr::Choice(ref exprs)      => exprs.iter().any(repeats),
+        Expr::Opt(ref expr)          => repeats(expr),
+        _                            => false
+    }
+}
+
+// whether expr calls rule
+fn calls(expr: &Expr, rule: &str) -> bool {
+    match *expr {
+        Expr::Rule(name)             => name == rule,
+        Expr::Seq(ref exprs)         |
+        Expr::Choice(ref exprs)      => exprs.iter().any(|expr| calls(expr, rule)),
+        Expr::Opt(ref expr)          |
+        Expr::Rep(ref expr, _)       |
+        Expr::Look(ref expr)         => calls(expr, rule),
+        _                            => false
+    }
+}
+
+// the string that expr has to start with, if it is a literal
+fn first(expr: &Expr) -> Option<&str> {
+    match *expr {
+        Expr::Str(ref string)        => Some(string),
+        Expr::Seq(ref exprs)         => exprs.first().and_then(first),
+        _                            => None
+    }
+}
+
+// whether expr can match without making progress; unknown expressions are assumed not to
+fn is_nullable(expr: &Expr, nullable: &HashMap<&str, bool>) -> bool {
+    match *expr {
+        Expr::Str(ref string)        => string.is_empty(),
+        Expr::Rule(name) if nullable.contains_key(name) => nullable[name],
+        Expr::Rule("eoi")            |
+        Expr::Rule("terminator")     |
+        Expr::Rule("prolog")         |
+        Expr::Rule("prefixed")       => true,
+        Expr::Rule(_)                => false,
+        Expr::Seq(ref exprs)         => exprs.iter().all(|expr| is_nullable(expr, nullable)),
+        Expr::Choice(ref exprs)      => exprs.iter().any(|expr| is_nullable(expr, nullable)),
+        Expr::Opt(_)                 |
+        Expr::Rep(_, false)          |
+        Expr::Look(_)                => true,
+        Expr::Rep(ref expr, true)    => is_nullable(expr, nullable),
+        _                            => false
+    }
+}
+
+// the rules that can match without making progress, found by iterating to a fixed point
+fn nullable_rules<'a>(rules: &[(&'a str, Vec<Expr>)]) -> HashMap<&'a str, bool> {
+    let mut nullable: HashMap<&str, bool> = rules.iter().map(|&(rule, _)| (rule, false)).collect();
+    let mut changed = true;
+
+    while changed {
+        changed = false;
+
+        for &(rule, ref exprs) in rules {
+            if !nullable[rule] && exprs.iter().any(|expr| is_nullable(expr, &nullable)) {
+                nullable.insert(rule, true);
+                changed = true;
+            }
+        }
+    }
+
+    nullable
+}
+
+// whether expr repeats something nullable
+fn loops(expr: &Expr, nullable: &HashMap<&str, bool>) -> bool {
+    match *expr {
+        Expr::Rep(ref expr, _)       => is_nullable(expr, nullable) || loops(expr, nullable),
+        Expr::Seq(ref exprs)         |
+        Expr::Choice(ref exprs)      => exprs.iter().any(|expr| loops(expr, nullable)),
+        Expr::Opt(ref expr)          |
+        Expr::Look(ref expr)         => loops(expr, nullable),
+        _                            => false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Alternative, RuleKind};
+    use super::{Lint, LintKind, LintLevel};
+
+    fn alternative(rule: &'static str, index: usize, text: &'static str) -> Alternative {
+        Alternative {
+            rule,
+            index,
+            text
+        }
+    }
+
+    fn kinds(lints: Vec<Lint>) -> Vec<(&'static str, LintKind)> {
+        lints.into_iter().map(|lint| (lint.rule, lint.kind)).collect()
+    }
+
+    #[test]
+    fn split_word() {
+        let alternatives = [
+            alternative("one", 0, "[\"a\"]+"),
+            alternative("two", 0, "[\"a\"] ~ [\"b\"]"),
+            alternative("ident", 0, "['a'..'z'] ~ (['a'..'z'] | ['0'..'9'])*"),
+            alternative("list", 0, "ident ~ ([\",\"] ~ ident)*"),
+            alternative("whitespace", 0, "[\" \"]")
+        ];
+        let normal = [
+            ("one", RuleKind::Normal),
+            ("two", RuleKind::Normal),
+            ("ident", RuleKind::Normal),
+            ("list", RuleKind::Normal)
+        ];
+
+        assert_eq!(kinds(Lint::check(&alternatives, &normal, &[])), vec![
+            ("one", LintKind::SplitWord),
+            ("ident", LintKind::SplitWord)
+        ]);
+        assert!(Lint::check(&alternatives[..4], &normal, &[]).is_empty());
+
+        let atomic = [
+            ("one", RuleKind::Atomic),
+            ("two", RuleKind::Normal),
+            ("ident", RuleKind::Atomic),
+            ("list", RuleKind::Normal)
+        ];
+
+        assert!(Lint::check(&alternatives, &atomic, &[]).is_empty());
+    }
+
+    #[test]
+    fn unused_rule() {
+        let alternatives = [
+            alternative("list", 0, "item ~ ([\",\"] ~ item)*"),
+            alternative("item", 0, "digit+"),
+            alternative("digit", 0, "['0'..'9']"),
+            alternative("nested", 0, "[\"(\"] ~ nested? ~ [\")\"]"),
+            alternative("whitespace", 0, "[\" \"]")
+        ];
+        let kinds_ = [("list", RuleKind::Normal), ("item", RuleKind::Atomic)];
+
+        assert_eq!(kinds(Lint::check(&alternatives, &kinds_, &[])), vec![
+            ("nested", LintKind::UnusedRule)
+        ]);
+    }
+
+    #[test]
+    fn nullable_loop() {
+        let alternatives = [
+            alternative("a", 0, "([\"a\"]?)*"),
+            alternative("b", 0, "(c ~ d)+"),
+            alternative("c", 0, "[\"\"]"),
+            alternative("d", 0, "eoi | c*"),
+            alternative("e", 0, "([\"e\"] ~ c)*"),
+            alternative("f", 0, "prolog*"),
+            alternative("prolog", 0, "[\"#!\"]")
+        ];
+        let kinds_: Vec<_> = ["a", "b", "c", "d", "e", "f", "prolog"].iter().map(|&rule| {
+            (rule, RuleKind::Atomic)
+        }).collect();
+
+        assert_eq!(kinds(Lint::check(&alternatives, &kinds_, &[])), vec![
+            ("a", LintKind::NullableLoop),
+            ("b", LintKind::NullableLoop),
+            ("d", LintKind::NullableLoop)
+        ]);
+        // without the grammar's own prolog, the predefined one never makes progress
+        assert_eq!(kinds(Lint::check(&alternatives[5..6], &kinds_, &[])), vec![
+            ("f", LintKind::NullableLoop)
+        ]);
+    }
+
+    #[test]
+    fn shadowed_alternative() {
+        let alternatives = [
+            alternative("op", 0, "[\"=\"]"),
+            alternative("op", 1, "[\"==\"]"),
+            alternative("op", 2, "[\"!\"] ~ [\"=\"]"),
+            alternative("op", 3, "[\"=\"] ~ [\">\"]"),
+            alternative("op", 4, "[\"<=\"]")
+        ];
+
+        assert_eq!(kinds(Lint::check(&alternatives, &[("op", RuleKind::Atomic)], &[])), vec![
+            ("op", LintKind::ShadowedAlternative { index: 1, by: 0 }),
+            ("op", LintKind::ShadowedAlternative { index: 3, by: 0 })
+        ]);
+    }
+
+    #[test]
+    fn levels() {
+        let alternatives = [
+            alternative("a", 0, "[\"a\"]*"),
+            alternative("b", 0, "[\"b\"]?*"),
+            alternative("whitespace", 0, "[\" \"]")
+        ];
+        let kinds_ = [("a", RuleKind::Normal), ("b", RuleKind::Atomic)];
+        let levels = [
+            ("split_word", LintLevel::Deny),
+            ("nullable_loop", LintLevel::Deny),
+            ("nullable_loop", LintLevel::Allow)
+        ];
+        let lints = Lint::check(&alternatives, &kinds_, &levels);
+
+        assert_eq!(lints, vec![
+            Lint { rule: "a", kind: LintKind::SplitWord, level: LintLevel::Deny }
+        ]);
+        assert_eq!(lints[0].to_string(),
+                   "error: rule `a` only repeats characters, but is not atomic, so trivia is \
+                    skipped between them; define it with `@{ ... }`");
+    }
+}
@@ -0,0 +1,344 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use super::joiner::escape;
+use super::{GeneratedParser, LimitKind, ParseResult};
+
+/// An `enum` representing the kinds of messages rendered by [`Messages`](struct.Messages).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum MessageKind {
+    /// rules were expected; `{expected}` is their labels joined with the separator
+    Expected,
+    /// the input did not match and nothing in particular was expected
+    Unexpected,
+    /// a note pointing at an [unclosed](struct.Unclosed) delimiter; `{open}` and `{close}` are
+    /// the quoted delimiters
+    Unclosed,
+    /// a limit was exceeded
+    Limit(LimitKind)
+}
+
+/// A `struct` of message templates and rule labels used to render the errors in a
+/// `ParseResult`, so that applications can ship them in several languages. `Messages::new`
+/// returns English templates, and every template or label can be replaced with the builder
+/// methods.
+///
+/// Templates can contain placeholders in braces, which are replaced with the arguments of the
+/// message: `{line}` and `{col}` in every message, and the ones listed in
+/// [`MessageKind`](enum.MessageKind). Rules are rendered by their labels, which default to
+/// their names.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::{GeneratedParser, MessageKind, Messages};
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         list   = { ["("] ~ (number ~ ([","] ~ number)*)? ~ [")"] }
+///         number = @{ ['0'..'9']+ }
+///     }
+/// }
+///
+/// let english = Messages::new().label("number", "a number").label("list", "a list");
+/// let german = Messages::new().template(MessageKind::Expected, "{expected} erwartet")
+///                             .template(MessageKind::Unclosed, "{open} nicht geschlossen")
+///                             .label("number", "eine Zahl")
+///                             .label("list", "eine Liste");
+///
+/// let result = Rdp::<StringInput>::parse(Rule::list, "(1,");
+///
+/// assert_eq!(english.render::<Rdp<StringInput>>(&result), vec![
+///     (3, "expected a number".to_owned())
+/// ]);
+/// assert_eq!(german.render::<Rdp<StringInput>>(&result), vec![
+///     (3, "eine Zahl erwartet".to_owned())
+/// ]);
+///
+/// let result = Rdp::<StringInput>::parse(Rule::list, "(1,2");
+///
+/// assert_eq!(german.render::<Rdp<StringInput>>(&result), vec![
+///     (0, "eine Liste erwartet".to_owned()),
+///     (0, "\"(\" nicht geschlossen".to_owned())
+/// ]);
+/// # }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Messages {
+    templates: HashMap<MessageKind, String>,
+    labels:    HashMap<String, String>,
+    separator: String
+}
+
+impl Messages {
+    /// Creates `Messages` with English templates, rules labeled by their names, and `", "` as
+    /// separator.
+    pub fn new() -> Messages {
+        let templates = [
+            (MessageKind::Expected, "expected {expected}"),
+            (MessageKind::Unexpected, "unexpected input"),
+            (MessageKind::Unclosed, "unclosed {open} opened here"),
+            (MessageKind::Limit(LimitKind::LineLength), "line too long"),
+            (MessageKind::Limit(LimitKind::TokenCount), "too many tokens"),
+            (MessageKind::Limit(LimitKind::Depth), "rules nested too deeply"),
+            (MessageKind::Limit(LimitKind::Timeout), "timed out"),
+            (MessageKind::Limit(LimitKind::Cancelled), "cancelled")
+        ];
+
+        Messages {
+            templates: templates.iter().map(|&(kind, template)| {
+                (kind, template.to_owned())
+            }).collect(),
+            labels:    HashMap::new(),
+            separator: ", ".to_owned()
+        }
+    }
+
+    /// Replaces the template of `kind`.
+    pub fn template(mut self, kind: MessageKind, template: &str) -> Messages {
+        self.templates.insert(kind, template.to_owned());
+
+        self
+    }
+
+    /// Labels the rule named `rule` with `label`.
+    pub fn label(mut self, rule: &str, label: &str) -> Messages {
+        self.labels.insert(rule.to_owned(), label.to_owned());
+
+        self
+    }
+
+    /// Replaces the separator used to join lists of rules, e.g. with `" or "`.
+    pub fn separator(mut self, separator: &str) -> Messages {
+        self.separator = separator.to_owned();
+
+        self
+    }
+
+    /// Returns the label of the rule named `rule`.
+    pub fn label_of<'a>(&'a self, rule: &'a str) -> &'a str {
+        self.labels.get(rule).map_or(rule, |label| label.as_str())
+    }
+
+    /// Fills the template of `kind` with `args`, pairs of placeholder names and their values.
+    /// Unknown placeholders are kept as they are.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::{GeneratedParser, MessageKind, Messages};
+    /// let messages = Messages::new();
+    ///
+    /// assert_eq!(messages.format(MessageKind::Expected, &[("expected", "value")]),
+    ///            "expected value");
+    /// ```
+    pub fn format(&self, kind: MessageKind, args: &[(&str, &str)]) -> String {
+        let template = self.templates.get(&kind).map_or("", |template| template.as_str());
+        let mut message = String::with_capacity(template.len());
+        let mut rest = template;
+
+        while let Some(start) = rest.find('{') {
+            message.push_str(&rest[..start]);
+            rest = &rest[start..];
+
+            let arg = rest.find('}').and_then(|end| {
+                let name = &rest[1..end];
+
+                args.iter().find(|arg| arg.0 == name).map(|&(_, value)| (end, value))
+            });
+
+            match arg {
+                Some((end, value)) => {
+                    message.push_str(value);
+                    rest = &rest[end + 1..];
+                },
+                None               => {
+                    message.push('{');
+                    rest = &rest[1..];
+                }
+            }
+        }
+
+        message.push_str(rest);
+
+        message
+    }
+
+    /// Renders the errors of `result`, produced by `P`, as pairs of positions and messages: the
+    /// error itself and, if there is one, a note about the [unclosed](struct.Unclosed) delimiter.
+    /// Complete results have no errors.
+    pub fn render<P: GeneratedParser>(&self, result: &ParseResult<P::Rule>)
+                                      -> Vec<(usize, String)> {
+        if result.is_complete() {
+            return vec![];
+        }
+
+        let (ref expected, pos) = result.expected;
+        let mut messages = vec![];
+
+        let (pos, kind, expected) = match result.limit_error {
+            Some(error)                 => {
+                (error.pos, MessageKind::Limit(error.kind), String::new())
+            },
+            None if expected.is_empty() => (pos, MessageKind::Unexpected, String::new()),
+            None                        => {
+                let labels: Vec<_> = expected.iter().map(|&rule| {
+                    self.label_of(P::rule_name(rule))
+                }).collect();
+
+                (pos, MessageKind::Expected, labels.join(&self.separator))
+            }
+        };
+
+        messages.push((pos, self.format_at(result, kind, pos, &[("expected", &expected)])));
+
+        if let Some(unclosed) = result.unclosed {
+            let open = format!("{:?}", unclosed.open);
+            let close = format!("{:?}", unclosed.close);
+            let args = [("open", open.as_str()), ("close", close.as_str())];
+
+            messages.push((unclosed.start,
+                           self.format_at(result, MessageKind::Unclosed, unclosed.start, &args)));
+        }
+
+        messages
+    }
+
+    /// Serializes the errors of `result`, produced by `P` from `file`, to JSON for tools like CI
+    /// annotations and editor problem matchers. Every error [rendered](#method.render) becomes an
+    /// object with its `severity`, `"error"` or `"note"`, `message`, `start` and `end` positions,
+    /// 1-based `line` and `column`, and the `excerpt` of its line without line ending. Errors
+    /// also list the names and labels of the `expected` rules.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate pest;
+    /// # use pest::prelude::*;
+    /// # use pest::{GeneratedParser, Messages};
+    /// # fn main() {
+    /// impl_rdp! {
+    ///     grammar! {
+    ///         list   = { ["("] ~ (number ~ ([","] ~ number)*)? ~ [")"] }
+    ///         number = @{ ['0'..'9']+ }
+    ///     }
+    /// }
+    ///
+    /// let messages = Messages::new().label("number", "a number");
+    /// let result = Rdp::<StringInput>::parse(Rule::list, "(1,\n");
+    ///
+    /// assert_eq!(messages.to_json::<Rdp<StringInput>>("list.txt", &result),
+    ///            "{\"file\":\"list.txt\",\"errors\":[{\"severity\":\"error\",\
+    ///             \"message\":\"expected a number\",\"start\":3,\"end\":3,\"line\":1,\
+    ///             \"column\":4,\"excerpt\":\"(1,\",\"expected\":[{\"rule\":\"number\",\
+    ///             \"label\":\"a number\"}]}]}");
+    /// # }
+    /// ```
+    pub fn to_json<P: GeneratedParser>(&self, file: &str, result: &ParseResult<P::Rule>)
+                                       -> String {
+        let mut json = String::new();
+
+        json.push_str("{\"file\":");
+        escape(&mut json, file);
+        json.push_str(",\"errors\":[");
+
+        for (i, (pos, message)) in self.render::<P>(result).into_iter().enumerate() {
+            let (severity, end) = match result.unclosed {
+                Some(unclosed) if i > 0 => ("note", pos + unclosed.open.len()),
+                _                       => ("error", pos)
+            };
+            let (line, col) = result.line_col(pos);
+            let excerpt = result.line_index.line_start(line).map_or("", |start| {
+                let rest = &result.input[start..];
+
+                rest.lines().next().unwrap_or("")
+            });
+
+            if i > 0 {
+                json.push(',');
+            }
+
+            json.push_str("{\"severity\":");
+            escape(&mut json, severity);
+            json.push_str(",\"message\":");
+            escape(&mut json, &message);
+            let _ = write!(json, ",\"start\":{},\"end\":{},\"line\":{},\"column\":{}", pos, end,
+                           line, col);
+            json.push_str(",\"excerpt\":");
+            escape(&mut json, excerpt);
+
+            if i == 0 && result.limit_error.is_none() {
+                json.push_str(",\"expected\":[");
+
+                for (j, &rule) in result.expected.0.iter().enumerate() {
+                    if j > 0 {
+                        json.push(',');
+                    }
+
+                    json.push_str("{\"rule\":");
+                    escape(&mut json, P::rule_name(rule));
+                    json.push_str(",\"label\":");
+                    escape(&mut json, self.label_of(P::rule_name(rule)));
+                    json.push('}');
+                }
+
+                json.push(']');
+            }
+
+            json.push('}');
+        }
+
+        json.push_str("]}");
+
+        json
+    }
+
+    fn format_at<R>(&self, result: &ParseResult<R>, kind: MessageKind, pos: usize,
+                    args: &[(&str, &str)]) -> String {
+        let (line, col) = result.line_col(pos);
+        let (line, col) = (line.to_string(), col.to_string());
+        let mut args = args.to_vec();
+
+        args.push(("line", &line));
+        args.push(("col", &col));
+
+        self.format(kind, &args)
+    }
+}
+
+impl Default for Messages {
+    fn default() -> Messages {
+        Messages::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format() {
+        let messages = Messages::new().template(MessageKind::Unexpected, "{a}{b} {c} {}{{a}");
+
+        assert_eq!(messages.format(MessageKind::Unexpected, &[("a", "1"), ("b", "{a}")]),
+                   "1{a} {c} {}{1");
+    }
+
+    #[test]
+    fn labels() {
+        let messages = Messages::new().label("number", "Zahl").separator(" oder ");
+
+        assert_eq!(messages.label_of("number"), "Zahl");
+        assert_eq!(messages.label_of("string"), "string");
+        assert_eq!(messages.separator, " oder ");
+    }
+}
@@ -5,6 +5,57 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
+use std::any::{Any, TypeId};
+use std::cell::Ref;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::ops::Range;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+
+use super::{Alternative, Backtrack, BacktrackBounds, Coverage, Cursor, Event, Expected, Interner,
+            Lexeme, LimitError, Limits, Lint, Modes, ParseResult, Partial, Profile, Progress,
+            QueueError, Recording, StringInput, Suggestion, Symbol, Token, TokenIndex, Tracking,
+            Trivia, Unclosed};
+
+/// Cache of [`Parser::memoize`](trait.Parser#tymethod.memoize), mapping a `Rule`, a queue index,
+/// a key, and a result type to the queue index after processing and the result.
+#[doc(hidden)]
+pub type MemoTable<R> = HashMap<(R, usize, &'static str, TypeId), (usize, Box<dyn Any>)>;
+
+/// `Token`s of [`Parser::embed`](trait.Parser#tymethod.embed), mapping the `Rule` of an external
+/// rule and its start to the `Token`s of the other parser.
+#[doc(hidden)]
+pub type EmbeddedTable<R> = HashMap<(R, usize), Vec<Token<Symbol>>>;
+
+/// Everything matching can change in a parser generated by `impl_rdp!`, apart from what is only
+/// reported, like coverage or events. Saved before matching speculatively and restored when the
+/// match has to be undone.
+#[doc(hidden)]
+pub struct SavedState<R> {
+    pub pos:          usize,
+    pub queue_len:    usize,
+    pub operators:    usize,
+    pub limit_error:  Option<LimitError>,
+    pub partial:      Option<Partial<R>>,
+    pub depth:        usize,
+    pub line_start:   usize,
+    pub line_end:     usize,
+    pub trivia_run:   (usize, usize),
+    pub last_end:     usize,
+    pub open_rule:    Option<R>,
+    pub terminated:   usize,
+    pub comments:     Vec<Range<usize>>,
+    pub streaming:    usize,
+    pub failures:     Vec<R>,
+    pub fail_pos:     usize,
+    pub terminals:    Vec<Expected<R>>,
+    pub terminal_pos: usize,
+    pub delimiters:   Vec<(usize, usize)>,
+    pub unclosed:     Option<Unclosed>,
+    pub eoi_matched:  bool
+}
+
 /// A `trait` that defines a parser.
 pub trait Parser {
     type Rule;
@@ -18,23 +69,295 @@ pub trait Parser {
     /// it did.
     fn match_range(&mut self, left: char, right: char) -> bool;
 
+    /// Matches a comment that starts with `open` and ends with the matching `close`, counting
+    /// nested `open`s and `close`s in between, and advances a parser past it in case it did.
+    /// Unterminated comments do not match. Used by the `nested_comment(open, close)` built-in
+    /// of [`grammar!`](macro.grammar!).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate pest;
+    /// # use pest::prelude::*;
+    /// # fn main() {
+    /// impl_rdp! {
+    ///     grammar! {
+    ///         comment = _{ nested_comment("/*", "*/") }
+    ///     }
+    /// }
+    ///
+    /// let mut parser = Rdp::new(StringInput::new("/* a /* b */ c */ d"));
+    ///
+    /// assert!(parser.match_nested("/*", "*/"));
+    /// assert_eq!(parser.pos(), 17);
+    ///
+    /// let mut parser = Rdp::new(StringInput::new("/* a /* b */ c"));
+    ///
+    /// assert!(!parser.comment());
+    /// assert_eq!(parser.pos(), 0);
+    /// # }
+    /// ```
+    fn match_nested(&mut self, open: &str, close: &str) -> bool;
+
+    /// Matches `left` unless `right` matches exactly the same text, and advances a parser like
+    /// `left` in case it did. `Token`s queued by `right` are discarded. Used by the `left - right`
+    /// operator of [`grammar!`](macro.grammar!#character-class-operators).
+    fn match_difference<L, R>(&mut self, left: L, right: R) -> bool
+        where L: FnOnce(&mut Self) -> bool, R: FnOnce(&mut Self) -> bool;
+
+    /// Matches `left` only if `right` matches exactly the same text, and advances a parser like
+    /// `left` in case it did. `Token`s queued by `right` are discarded. Used by the
+    /// `left & right` operator of [`grammar!`](macro.grammar!#character-class-operators).
+    fn match_intersection<L, R>(&mut self, left: L, right: R) -> bool
+        where L: FnOnce(&mut Self) -> bool, R: FnOnce(&mut Self) -> bool;
+
+    /// Matches a [`Heredoc`](struct.Heredoc) starting with `open`, and advances a parser right
+    /// after its closing tag in case it did. Used by the `heredoc(open)` built-in of
+    /// [`grammar!`](macro.grammar!).
+    fn match_heredoc(&mut self, open: &str) -> bool;
+
+    /// Runs `rule` with `prefix` pushed onto a stack of line prefixes. After every line ending
+    /// matched by `line_ending` or skipped by `newline`, the registered prefixes are consumed
+    /// outermost first, up to the first one that is missing, which allows lazy continuation
+    /// lines. The predefined `prefixed` rule only matches on lines that start with all of them.
+    /// Used by the `with_prefix(prefix, rule)` built-in of [`grammar!`](macro.grammar!).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate pest;
+    /// # use pest::prelude::*;
+    /// # fn main() {
+    /// impl_rdp! {
+    ///     grammar! {
+    ///         lines = @{ line ~ (line_ending ~ prefixed ~ line)* }
+    ///         line  = @{ (!line_ending ~ any)+ }
+    ///     }
+    /// }
+    ///
+    /// let mut parser = Rdp::new(StringInput::new("a\n> b\nc"));
+    ///
+    /// assert!(parser.with_prefix("> ", |parser| parser.lines()));
+    /// assert_eq!(parser.pos(), 5);
+    /// assert_eq!(parser.slice_input(4, 5), "b");
+    /// # }
+    /// ```
+    fn with_prefix<F>(&mut self, prefix: &str, rule: F) -> bool
+        where F: FnOnce(&mut Self) -> bool;
+
+    /// Scans forward to the first position, starting with the current one, where `matcher`
+    /// matches, and returns whether there is one. The parser stops in front of the match, without
+    /// keeping its `Token`s, and does not move if there is none. Used by the `skip_to(rule)`
+    /// built-in of [`grammar!`](macro.grammar!#islands), which passes the name of `rule`.
+    ///
+    /// If the [`FirstSet`](struct.FirstSet) of `rule` is known, `matcher` is only tried in front
+    /// of the `char`s `rule` can start with; otherwise, it is tried at every position. Failures
+    /// while scanning are not tracked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate pest;
+    /// # use pest::prelude::*;
+    /// # fn main() {
+    /// impl_rdp! {
+    ///     grammar! {
+    ///         number = @{ ['0'..'9']+ }
+    ///     }
+    /// }
+    ///
+    /// let mut parser = Rdp::new(StringInput::new("abc 42"));
+    ///
+    /// assert!(parser.skip_to("number", |parser| parser.number()));
+    /// assert_eq!(parser.pos(), 4);
+    /// assert!(parser.queue().is_empty());
+    /// # }
+    /// ```
+    fn skip_to<F>(&mut self, rule: &'static str, matcher: F) -> bool
+        where F: FnMut(&mut Self) -> bool;
+
     /// Tries to match `rule`, returns whether it matched, and advances a parser with in case it
     /// did. If `revert` is `true`, the parser will not advance.
     fn try<F>(&mut self, revert: bool, rule: F) -> bool where F: FnOnce(&mut Self) -> bool;
 
+    /// Matches `rule` starting at `pos` instead of the current position and returns whether it
+    /// matched. On a match, a parser is left after the match with its `Token`s queued. Otherwise,
+    /// everything matching changed is restored, i.e. its position, queue,
+    /// [expected rules](trait.Parser#tymethod.expected),
+    /// [exceeded limits](trait.Parser#tymethod.limit_error), comments, and memoized results, so
+    /// that tooling can probe rules at arbitrary positions. A `pos` past the end of the input or
+    /// inside of a `char` doesn't match anything.
+    ///
+    /// Parsers generated by [`impl_rdp!`](macro.impl_rdp!) also have a `parse_rule_at` method
+    /// that does the same for a `Rule`, e.g. one picked at runtime.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate pest;
+    /// # use pest::prelude::*;
+    /// # fn main() {
+    /// impl_rdp! {
+    ///     grammar! {
+    ///         assignment = { ident ~ ["="] ~ number }
+    ///         ident      = { ['a'..'z']+ }
+    ///         number     = { ['0'..'9']+ }
+    ///     }
+    /// }
+    ///
+    /// let mut parser = Rdp::new(StringInput::new("x=42"));
+    ///
+    /// assert!(!parser.parse_at(0, Rdp::number));
+    /// assert_eq!(parser.pos(), 0);
+    /// assert!(parser.queue().is_empty());
+    ///
+    /// assert!(parser.parse_at(2, Rdp::number));
+    /// assert_eq!(parser.pos(), 4);
+    /// assert_eq!(parser.queue(), &vec![Token { rule: Rule::number, start: 2, end: 4 }]);
+    ///
+    /// let mut parser = Rdp::new(StringInput::new("x=42"));
+    ///
+    /// assert!(parser.parse_rule_at(2, Rule::number));
+    /// assert!(!parser.parse_rule_at(5, Rule::number));
+    /// # }
+    /// ```
+    fn parse_at<F>(&mut self, pos: usize, rule: F) -> bool where F: FnOnce(&mut Self) -> bool;
+
+    /// Matches `rule` like a start rule: it only matches if it is followed by trailing trivia and
+    /// the end of the input, so that garbage after an otherwise valid input is not silently
+    /// ignored. Otherwise, `eoi` is [expected](trait.Parser#tymethod.expected) where `rule`
+    /// stopped and a parser's position and queue are left as they were before the call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate pest;
+    /// # use pest::prelude::*;
+    /// # fn main() {
+    /// impl_rdp! {
+    ///     grammar! {
+    ///         sum    = { number ~ (["+"] ~ number)* }
+    ///         number = @{ ['0'..'9']+ }
+    ///
+    ///         whitespace = _{ [" "] }
+    ///     }
+    /// }
+    ///
+    /// let mut parser = Rdp::new(StringInput::new("1 + 2 "));
+    ///
+    /// assert!(parser.parse_complete(Rdp::sum));
+    ///
+    /// let mut parser = Rdp::new(StringInput::new("1 + 2)"));
+    ///
+    /// assert!(parser.sum());
+    /// parser.reset();
+    /// assert!(!parser.parse_complete(Rdp::sum));
+    /// assert_eq!(parser.expected(), (vec![Rule::eoi], 5));
+    /// assert!(parser.queue().is_empty());
+    /// # }
+    /// ```
+    fn parse_complete<F>(&mut self, rule: F) -> bool where F: FnOnce(&mut Self) -> bool;
+
     /// Uses the precendence climbing algorithm to match rules. `pos` is the current position
     /// of the queue. `left` is the left-most starting position of the current rule. `min_prec` is
     /// the currently processed precedence. `last_op` is the last greedily parsed infix operator.
     /// `primary` is a closure defined in `grammar!` that parses a primary expression. `climb` is a
     /// closure defined in `grammar!` that returns the first `Rule` that was parsed (provided it
-    /// was not silented) along with its precedence and right-associativity, or `None` if no
-    /// operator passes. This operator triplet is also returned by the function when it greedily
-    /// parses an operator useful for a higher precedence.
+    /// was not silented) along with its precedence, right-associativity, and the span of the
+    /// operator, or `None` if no operator passes. This operator is also returned by the function
+    /// when it greedily parses an operator useful for a higher precedence.
+    #[allow(clippy::type_complexity)]
     fn prec_climb<F, G>(&mut self, pos: usize, left: usize, min_prec: u8,
-                        last_op: Option<(Option<Self::Rule>, u8, bool)>, primary: &mut F,
-                        climb: &mut G) -> (Option<(Option<Self::Rule>, u8, bool)>, Option<usize>)
+                        last_op: Option<(Option<Self::Rule>, u8, bool, Range<usize>)>,
+                        primary: &mut F, climb: &mut G)
+                        -> (Option<(Option<Self::Rule>, u8, bool, Range<usize>)>, Option<usize>)
         where F: FnMut(&mut Self) -> bool,
-              G: FnMut(&mut Self) -> Option<(Option<Self::Rule>, u8, bool)>;
+              G: FnMut(&mut Self) -> Option<(Option<Self::Rule>, u8, bool, Range<usize>)>;
+
+    /// Adds `operator` to every [precedence climbing](macro.grammar!#precedence-climbing) rule,
+    /// replacing any operator with the same `&str`. It is tried after the rules of the grammar,
+    /// with the longest matching operator winning, and inserts a `Token` of `rule` like an
+    /// operator rule would, or none if `rule` is `None`. The rules of the grammar have
+    /// precedences starting at `0`, in the order in which they are declared.
+    ///
+    /// Operators stay registered after a [`reset`](#tymethod.reset) and are not removed when
+    /// the rule that added them backtracks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate pest;
+    /// # use pest::prelude::*;
+    /// # fn main() {
+    /// impl_rdp! {
+    ///     grammar! {
+    ///         expression = _{
+    ///             { number }
+    ///             addition       = { ["+"] }
+    ///             multiplication = { ["*"] }
+    ///         }
+    ///         number = @{ ['0'..'9']+ }
+    ///     }
+    /// }
+    ///
+    /// let mut parser = Rdp::new(StringInput::new("1<>2*3"));
+    ///
+    /// parser.add_operator("<>", Some(Rule::addition), 2, false);
+    ///
+    /// assert!(parser.expression());
+    /// assert_eq!(parser.queue()[0], Token { rule: Rule::multiplication, start: 0, end: 6 });
+    /// assert_eq!(parser.queue()[1], Token { rule: Rule::addition, start: 0, end: 4 });
+    /// # }
+    /// ```
+    fn add_operator(&mut self, operator: &str, rule: Option<Self::Rule>, prec: u8,
+                    right_assoc: bool);
+
+    /// Removes `operator` added with [`add_operator`](#tymethod.add_operator).
+    fn remove_operator(&mut self, operator: &str);
+
+    /// Matches the longest operator added with [`add_operator`](#tymethod.add_operator) and
+    /// returns it like the `climb` closure of [`prec_climb`](#tymethod.prec_climb). Used in
+    /// `grammar!`.
+    #[allow(clippy::type_complexity)]
+    fn match_operator(&mut self) -> Option<(Option<Self::Rule>, u8, bool, Range<usize>)>;
+
+    /// Returns the span of the operator matched between the operands of `token`, a `Token`
+    /// inserted for an operator rule by
+    /// [precedence climbing](macro.grammar!#precedence-climbing). The operator rule's own
+    /// children, if any, are still found between the operands in the queue, but this span also
+    /// covers operators matched by plain `&str`s.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate pest;
+    /// # use pest::prelude::*;
+    /// # fn main() {
+    /// impl_rdp! {
+    ///     grammar! {
+    ///         expression = _{
+    ///             { number }
+    ///             addition       = { ["+"] | ["-"] }
+    ///             multiplication = { ["*"] | ["/"] }
+    ///         }
+    ///         number = @{ ['0'..'9']+ }
+    ///
+    ///         whitespace = _{ [" "] }
+    ///     }
+    /// }
+    ///
+    /// let mut parser = Rdp::new(StringInput::new("1 - 2 * 3"));
+    ///
+    /// assert!(parser.expression());
+    ///
+    /// let operators: Vec<_> = parser.queue().iter().filter_map(|token| {
+    ///     parser.operator_of(token).map(|span| parser.slice_input(span.start, span.end))
+    /// }).collect();
+    ///
+    /// assert_eq!(operators, vec!["-", "*"]);
+    /// # }
+    /// ```
+    fn operator_of(&self, token: &Self::Token) -> Option<Range<usize>>;
 
     /// Returns the current position of a `Parser`.
     fn pos(&self) -> usize;
@@ -42,6 +365,48 @@ pub trait Parser {
     /// Sets the position of a `Parser`.
     fn set_pos(&mut self, pos: usize);
 
+    /// Skips exactly `n` `char`s and returns whether there were enough of them. The position is
+    /// left untouched if there were not.
+    fn skip(&mut self, n: usize) -> bool;
+
+    /// Returns whether a `Parser` is at `column`, starting at `1` and counted in `char`s, of its
+    /// current line. Lines are looked up in a `LineIndex` built on first use.
+    ///
+    /// Together with [`Parser::skip`](trait.Parser#tymethod.skip), this makes column-based formats
+    /// expressible by calling both from methods used as rules.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate pest;
+    /// # use pest::prelude::*;
+    /// # fn main() {
+    /// impl_rdp! {
+    ///     grammar! {
+    ///         cards = _{ card* ~ eoi }
+    ///         card  =  { label ~ column7 ~ statement ~ ["\n"] }
+    ///         label = @{ ['0'..'9']* ~ [" "]* }
+    ///         statement = @{ (!["\n"] ~ any)+ }
+    ///     }
+    /// }
+    ///
+    /// impl<T: Input> Rdp<T> {
+    ///     fn column7(&mut self) -> bool {
+    ///         self.at_column(7)
+    ///     }
+    /// }
+    ///
+    /// let mut parser = Rdp::new(StringInput::new("10    X = 1\n      Y = 2\n"));
+    ///
+    /// assert!(parser.cards());
+    ///
+    /// let mut parser = Rdp::new(StringInput::new("10   X = 1\n"));
+    ///
+    /// assert!(!parser.cards());
+    /// # }
+    /// ```
+    fn at_column(&mut self, column: usize) -> bool;
+
     /// Returns whether a `Parser` has reached its end.
     fn end(&self) -> bool;
 
@@ -51,23 +416,655 @@ pub trait Parser {
     /// Reset a `Parser`.
     fn reset(&mut self);
 
+    /// Matches `rule` from the current position and moves everything it produced into a
+    /// [`ParseResult`](struct.ParseResult), which owns a copy of the input. A `Parser` is
+    /// [reset](trait.Parser#tymethod.reset) afterwards, so it can be reused right away.
+    fn run<F>(&mut self, rule: F) -> ParseResult<Self::Rule> where F: FnOnce(&mut Self) -> bool;
+
+    /// Splits the rest of the input into a flat, gapless sequence of
+    /// [`Lexeme`](struct.Lexeme)s instead of a tree. At every position, all `rules` are tried and
+    /// the longest match wins, like in traditional lexers, rather than the first one like in an
+    /// ordered choice, so `<=` beats `<` no matter in which order they are given. Ties go to the
+    /// earlier rule, e.g. a keyword rule should come before an identifier rule. Text that no
+    /// rule matches is gathered into `LexemeKind::Error` `Lexeme`s. `rules` are usually small
+    /// atomic rules, since only the `Token` a rule produces first decides its `Lexeme`'s kind.
+    /// The queue is left untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate pest;
+    /// # use pest::prelude::*;
+    /// # use pest::{Lexeme, LexemeKind};
+    /// # fn main() {
+    /// impl_rdp! {
+    ///     grammar! {
+    ///         number = @{ ['0'..'9']+ }
+    ///         ident  = @{ ['a'..'z']+ }
+    ///         space  = _{ [" "]+ }
+    ///     }
+    /// }
+    ///
+    /// let mut parser = Rdp::new(StringInput::new("ab 12?"));
+    /// let kinds: Vec<_> = parser.lex(&[Rdp::number, Rdp::ident, Rdp::space]).into_iter()
+    ///                           .map(|lexeme| lexeme.kind)
+    ///                           .collect();
+    ///
+    /// assert_eq!(kinds, vec![
+    ///     LexemeKind::Rule(Rule::ident),
+    ///     LexemeKind::Trivia,
+    ///     LexemeKind::Rule(Rule::number),
+    ///     LexemeKind::Error
+    /// ]);
+    /// assert!(parser.end());
+    /// # }
+    /// ```
+    fn lex(&mut self, rules: &[fn(&mut Self) -> bool]) -> Vec<Lexeme<Self::Rule>>;
+
+    /// Lexes like [`lex`](#tymethod.lex), trying only the rules of the current mode of `modes`
+    /// and switching modes after the `Lexeme`s that [`Modes`](struct.Modes) says so.
+    fn lex_modes(&mut self, modes: &Modes<Self>) -> Vec<Lexeme<Self::Rule>> where Self: Sized;
+
+    /// Returns the length of a `Parser`'s `Input`.
+    fn input_len(&self) -> usize;
+
     /// Slices a `Parser`'s `Input`.
     fn slice_input(&self, start: usize, end: usize) -> &str;
 
+    /// Returns a `StringInput` that only matches a `Parser`'s `Input` between `start` and `end`,
+    /// but keeps its positions. This allows parsing the content of a `Token` with another rule
+    /// or another `Parser`, e.g. from `process!`, while its `Token`s and errors point into the
+    /// outer `Input`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate pest;
+    /// # use pest::prelude::*;
+    /// # fn main() {
+    /// mod expression {
+    ///     use pest::prelude::*;
+    ///
+    ///     impl_rdp! {
+    ///         grammar! {
+    ///             sum    = { number ~ (["+"] ~ number)* ~ eoi }
+    ///             number = @{ ['0'..'9']+ }
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// impl_rdp! {
+    ///     grammar! {
+    ///         string = @{ ["\""] ~ (interpolation | !["\""] ~ any)* ~ ["\""] }
+    ///         interpolation = @{ ["{"] ~ code ~ ["}"] }
+    ///         code = { (!["}"] ~ any)* }
+    ///     }
+    ///
+    ///     process! {
+    ///         main(&self) -> Vec<Token<expression::Rule>> {
+    ///             (_: string, _: interpolation, code: code) => {
+    ///                 let mut parser = expression::Rdp::new(self.sub_input(code.start, code.end));
+    ///
+    ///                 assert!(parser.sum());
+    ///
+    ///                 parser.queue().clone()
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut parser = Rdp::new(StringInput::new("\"a{1+2}\""));
+    ///
+    /// assert!(parser.string());
+    ///
+    /// let tokens = parser.process();
+    ///
+    /// assert_eq!(tokens[0], Token { rule: expression::Rule::sum, start: 3, end: 6 });
+    /// assert_eq!(parser.slice_input(tokens[2].start, tokens[2].end), "2");
+    /// # }
+    /// ```
+    fn sub_input(&self, start: usize, end: usize) -> StringInput<'_>;
+
+    /// Interns the `&str` sliced between `start` and `end` from a `Parser`'s `Input` and returns
+    /// its `Symbol`. Since it only needs `&self`, it can be used from `process!`.
+    fn intern(&self, start: usize, end: usize) -> Symbol;
+
+    /// Returns the `Interner` used by [`Parser::intern`](trait.Parser#tymethod.intern). It is
+    /// not cleared by [`Parser::reset`](trait.Parser#tymethod.reset), so `Symbol`s stay valid
+    /// across parses.
+    fn interner(&self) -> Ref<'_, Interner>;
+
+    /// Returns the farthest [`Backtrack`](struct.Backtrack) since the last
+    /// [`Parser::reset`](trait.Parser#tymethod.reset), i.e. how far behind the farthest position
+    /// read so far the `Parser` had to go back, along with the rule that did.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate pest;
+    /// # use pest::prelude::*;
+    /// # fn main() {
+    /// impl_rdp! {
+    ///     grammar! {
+    ///         statement = { call | assign }
+    ///         call      = { ident ~ ["("] ~ [")"] }
+    ///         assign    = { ident ~ ["="] ~ ident }
+    ///         ident     = @{ ['a'..'z']+ }
+    ///     }
+    /// }
+    ///
+    /// let mut parser = Rdp::new(StringInput::new("abc=d"));
+    ///
+    /// assert!(parser.statement());
+    ///
+    /// let backtrack = parser.max_backtrack().unwrap();
+    ///
+    /// assert_eq!((backtrack.distance, backtrack.from, backtrack.to), (3, 3, 0));
+    /// assert_eq!(backtrack.rule, Some(Rule::call));
+    /// # }
+    /// ```
+    fn max_backtrack(&self) -> Option<Backtrack<Self::Rule>>;
+
+    /// Returns a conservative static bound on how far every rule can
+    /// [backtrack](trait.Parser#tymethod.max_backtrack), computed from the grammar alone. Rules
+    /// whose `Bound` fits into a streaming input's retention window can be matched without ever
+    /// needing input that was already discarded. See
+    /// [`BacktrackBounds`](struct.BacktrackBounds) for what makes a rule unbounded.
+    fn backtrack_bounds(&self) -> BacktrackBounds;
+
+    /// Sets the `Limits` enforced while matching.
+    fn set_limits(&mut self, limits: Limits);
+
+    /// Returns the `LimitError` of the first exceeded limit, if any. It is cleared by
+    /// [`Parser::reset`](trait.Parser#tymethod.reset).
+    fn limit_error(&self) -> Option<LimitError>;
+
+    /// Makes every rule fail with a `LimitKind::Cancelled` `LimitError` once `flag` is set,
+    /// e.g. from another thread. The flag is checked whenever a rule starts matching.
+    fn set_cancellation(&mut self, flag: Arc<AtomicBool>);
+
+    /// Returns what a `Parser` had matched when a limit was first exceeded or it was cancelled,
+    /// if it was. It is cleared by [`Parser::reset`](trait.Parser#tymethod.reset).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate pest;
+    /// # use pest::prelude::*;
+    /// # use pest::{LimitKind, Limits};
+    /// # fn main() {
+    /// impl_rdp! {
+    ///     grammar! {
+    ///         list   = { ["["] ~ (number ~ ([","] ~ number)*)? ~ ["]"] }
+    ///         number = @{ ['0'..'9']+ }
+    ///     }
+    /// }
+    ///
+    /// let mut parser = Rdp::new(StringInput::new("[1,2,3,4]"));
+    ///
+    /// parser.set_limits(Limits::new().max_tokens(2));
+    ///
+    /// assert!(!parser.list());
+    /// assert!(parser.queue().is_empty());
+    ///
+    /// let partial = parser.partial().unwrap();
+    ///
+    /// assert_eq!(partial.error.kind, LimitKind::TokenCount);
+    /// assert_eq!(partial.tokens, vec![
+    ///     Token { rule: Rule::number, start: 1, end: 2 },
+    ///     Token { rule: Rule::number, start: 3, end: 4 }
+    /// ]);
+    /// # }
+    /// ```
+    fn partial(&self) -> Option<&Partial<Self::Rule>>;
+
+    /// Installs an `observer` that gets called with the `Progress` of a `Parser` whenever a rule
+    /// starts matching at least `interval` bytes after the last report. Without an observer, the
+    /// only overhead is one check per rule.
+    fn set_observer<F>(&mut self, interval: usize, observer: F) where F: FnMut(Progress) + 'static;
+
+    /// Removes the observer installed with
+    /// [`Parser::set_observer`](trait.Parser#tymethod.set_observer).
+    fn clear_observer(&mut self);
+
+    /// Installs a `hook` that gets called every time the `newline` rule is skipped, with the
+    /// `Rule` of the last `Token` in the queue. If it returns a `Rule`, a `Token` of that `Rule`
+    /// is [injected](trait.Parser#tymethod.inject) at the start of the newline, and the
+    /// predefined `terminator` rule matches once right after it. This implements automatic
+    /// semicolon insertion as in Go or JavaScript.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate pest;
+    /// # use pest::prelude::*;
+    /// # fn main() {
+    /// impl_rdp! {
+    ///     grammar! {
+    ///         statements = { statement* ~ eoi }
+    ///         statement  = { ident ~ (semicolon | terminator) }
+    ///         ident      = @{ ['a'..'z']+ }
+    ///         semicolon  = { [";"] }
+    ///
+    ///         whitespace = _{ [" "] }
+    ///         newline    = _{ ["\n"] }
+    ///     }
+    /// }
+    ///
+    /// let mut parser = Rdp::new(StringInput::new("a; b\nc\n"));
+    ///
+    /// parser.set_newline_hook(|last| {
+    ///     if last == Some(Rule::ident) {
+    ///         Some(Rule::semicolon)
+    ///     } else {
+    ///         None
+    ///     }
+    /// });
+    ///
+    /// assert!(parser.statements());
+    /// assert_eq!(parser.queue().iter().filter(|token| token.rule == Rule::semicolon).count(), 3);
+    /// # }
+    /// ```
+    fn set_newline_hook<F>(&mut self, hook: F)
+        where F: FnMut(Option<Self::Rule>) -> Option<Self::Rule> + 'static;
+
+    /// Streams the `Token`s of `rules` to `sink` as soon as they match, together with their
+    /// descendants, as `Event`s paired with the matched `&str`s, instead of keeping them in the
+    /// queue. This bounds the size of the queue while parsing large inputs.
+    ///
+    /// A streamed `Token` is only sent once no other rule from `rules` is still matching around
+    /// it, so that `Event`s always come in order; it is then removed from the queue. Since sent
+    /// `Event`s cannot be taken back, streamed rules should not be backtracked over once matched,
+    /// e.g. elements of a list, where a failure afterwards means the whole input is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate pest;
+    /// # use pest::prelude::*;
+    /// # use pest::Event;
+    /// # use std::cell::RefCell;
+    /// # use std::rc::Rc;
+    /// # fn main() {
+    /// impl_rdp! {
+    ///     grammar! {
+    ///         object = { ["{"] ~ (pair ~ ([","] ~ pair)*)? ~ ["}"] }
+    ///         pair   = { key ~ [":"] ~ value }
+    ///         key    = @{ ['a'..'z']+ }
+    ///         value  = @{ ['0'..'9']+ }
+    ///     }
+    /// }
+    ///
+    /// let pairs = Rc::new(RefCell::new(vec![]));
+    /// let sink = pairs.clone();
+    ///
+    /// let mut parser = Rdp::new(StringInput::new("{a:1,b:2}"));
+    ///
+    /// parser.set_stream(&[Rule::pair], move |event, string| {
+    ///     if let Event::Start(token) = event {
+    ///         if token.rule != Rule::pair {
+    ///             sink.borrow_mut().push(string.to_owned());
+    ///         }
+    ///     }
+    /// });
+    ///
+    /// assert!(parser.object());
+    /// assert_eq!(*pairs.borrow(), vec!["a", "1", "b", "2"]);
+    /// assert_eq!(parser.queue(), &vec![Token { rule: Rule::object, start: 0, end: 9 }]);
+    /// # }
+    /// ```
+    fn set_stream<F>(&mut self, rules: &[Self::Rule], sink: F)
+        where F: FnMut(Event<Self::Rule>, &str) + 'static;
+
+    /// Stops the stream started with [`Parser::set_stream`](trait.Parser#tymethod.set_stream).
+    fn clear_stream(&mut self);
+
+    /// Removes the hook installed with
+    /// [`Parser::set_newline_hook`](trait.Parser#tymethod.set_newline_hook).
+    fn clear_newline_hook(&mut self);
+
+    /// Returns every top-level `Alternative` of the rules defined in `grammar!`, in grammar
+    /// order.
+    fn alternatives(&self) -> Vec<Alternative>;
+
+    /// Returns the [`Lint`](struct.Lint)s of the grammar, i.e. likely mistakes that still
+    /// compile, by rule in grammar order and without the ones that the grammar's
+    /// [attributes](macro.grammar!#lints) allow.
+    fn lints(&self) -> Vec<Lint>;
+
+    /// Starts recording which `Alternative`s match in a `Coverage`. The `Coverage` is not
+    /// cleared by [`Parser::reset`](trait.Parser#tymethod.reset), so it can accumulate over
+    /// several inputs.
+    fn enable_coverage(&mut self);
+
+    /// Returns the `Coverage` recorded since
+    /// [`Parser::enable_coverage`](trait.Parser#tymethod.enable_coverage) was called.
+    fn coverage(&self) -> Option<&Coverage>;
+
+    /// Starts counting how many times every rule is tried, matched, and retried at the same
+    /// position. The counters are kept until the `Parser` is dropped, even across
+    /// [`Parser::reset`](trait.Parser#tymethod.reset).
+    ///
+    /// For a timeline instead of counters, the `log` feature logs a `trace` record with the
+    /// `pest` target whenever a rule is entered and exited, e.g. `enter key at 2 (depth 1)` and
+    /// `match key at 2..5 (depth 1)`, which can be turned into spans by any `log` backend.
+    fn enable_profiling(&mut self);
+
+    /// Returns the [`Profile`](struct.Profile) of all matches so far if
+    /// [`Parser::enable_profiling`](trait.Parser#tymethod.enable_profiling) was called.
+    fn profile(&self) -> Option<&Profile>;
+
+    /// Starts recording every rule tried, matched, and failed, and every backtrack in a
+    /// [`Recording`](struct.Recording), which can be replayed step by step. Unlike a `Profile`,
+    /// the `Recording` is cleared by [`Parser::reset`](trait.Parser#tymethod.reset), so that it
+    /// only ever covers one input.
+    fn enable_recording(&mut self);
+
+    /// Returns the `Recording` of the current input if
+    /// [`Parser::enable_recording`](trait.Parser#tymethod.enable_recording) was called.
+    fn recording(&self) -> Option<&Recording>;
+
+    /// Records that the `Alternative` `index` of `rule` matched. Used in `grammar!`.
+    fn cover(&mut self, rule: &'static str, index: usize);
+
+    /// Checks the `Limits`, reports `Progress`, and increments the nesting depth before matching
+    /// `rule`. Returns `false` if a limit was exceeded, in which case the rule should fail. Logs
+    /// the entry with the `log` feature. Used in `grammar!`.
+    fn enter_rule(&mut self, rule: &'static str) -> bool;
+
+    /// Decrements the nesting depth after matching `rule` from `pos`, records it in the
+    /// [`Profile`](struct.Profile) if enabled, and skips trailing trivia after a matched
+    /// top-level rule if enabled. Logs the exit with the `log` feature. Used in `grammar!`.
+    fn exit_rule(&mut self, rule: &'static str, pos: usize, matched: bool);
+
+    /// Checks whether another `Token` can be added to the queue. Used in `grammar!`.
+    fn check_token_limit(&mut self) -> bool;
+
+    /// Returns whether the match of `rule` from `start` to the current position is a reserved
+    /// word. Used in `grammar!`.
+    fn is_reserved(&self, rule: Self::Rule, start: usize) -> bool;
+
+    /// Sets the `Rule` of the innermost `Token` being matched and returns the previous one. Used
+    /// in `grammar!`.
+    fn open_rule(&mut self, rule: Option<Self::Rule>) -> Option<Self::Rule>;
+
+    /// Restores the `Rule` returned by [`Parser::open_rule`](trait.Parser#tymethod.open_rule)
+    /// once the innermost rule is done matching. Used in `grammar!`.
+    fn close_rule(&mut self, parent: Option<Self::Rule>);
+
+    /// Inserts `token` at `index` of the queue as the parent of all `Token`s after it and records
+    /// how many there are for the [queue searches](trait.Parser#tymethod.descendants_of). Used in
+    /// `grammar!`.
+    fn insert_parent(&mut self, index: usize, token: Self::Token);
+
+    /// Sends the `Token` at `index` of the queue and its descendants to the
+    /// [stream](trait.Parser#tymethod.set_stream) if its `Rule` is streamed. Used in `grammar!`.
+    fn stream(&mut self, index: usize);
+
+    /// Stores the `Token`s another parser matched for the [external](macro.grammar!#external-rules)
+    /// rule `rule` starting at `start`, naming their rules `rule::inner_rule`. Used in
+    /// `grammar!`.
+    fn embed<R: Debug>(&mut self, rule: Self::Rule, start: usize, tokens: &[Token<R>]);
+
     /// Returns the queue of all matched `Token`s.
+    ///
+    /// The queue is always in pre-order: `Token`s are sorted by their `start` and every `Token`
+    /// comes before all of the `Token`s nested inside of it. This also holds for the `Token`s
+    /// inserted by precedence climbing, which are inserted before their left-hand operand once
+    /// the right-hand side has been parsed. An ancestor can thus always be found before its
+    /// descendants with [`Token::is_ancestor_of`](struct.Token#method.is_ancestor_of).
     fn queue(&self) -> &Vec<Self::Token>;
 
     /// Returns the mutable queue of all matched `Token`s.
     fn queue_mut(&mut self) -> &mut Vec<Self::Token>;
 
-    /// Returns the current index within the queue. Used in `process!`.
-    fn queue_index(&self) -> usize;
+    /// Inserts a synthetic, zero-width `Token` of `rule` at `pos` into the queue. It is ordered
+    /// like a `Token` of a rule matched at that point: it becomes a child of the rules currently
+    /// being matched and is removed along with their other `Token`s if they fail. Useful in hooks
+    /// and preprocessing tricks that add `Token`s which don't appear in the input.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pos` comes before the end of the last `Token` in the queue or after the current
+    /// position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate pest;
+    /// # use pest::prelude::*;
+    /// # fn main() {
+    /// impl_rdp! {
+    ///     grammar! {
+    ///         block = { ["{"] ~ ["}"] }
+    ///         empty = token
+    ///     }
+    /// }
+    ///
+    /// let mut parser = Rdp::new(StringInput::new("{}"));
+    ///
+    /// assert!(parser.match_string("{"));
+    ///
+    /// let pos = parser.pos();
+    /// parser.inject(Rule::empty, pos);
+    ///
+    /// assert_eq!(parser.queue(), &vec![Token { rule: Rule::empty, start: 1, end: 1 }]);
+    /// # }
+    /// ```
+    fn inject(&mut self, rule: Self::Rule, pos: usize);
+
+    /// Returns the index of `token` in the queue, found with a binary search over the starts of
+    /// `Token`s. If several `Token`s are equal to `token`, it returns the one `token` refers to,
+    /// or the first one if `token` is not part of the queue.
+    fn index_of(&self, token: &Self::Token) -> Option<usize>;
+
+    /// Returns a [`TokenIndex`](struct.TokenIndex) of the queue, mapping every `Rule` to the
+    /// indices of its `Token`s. It is built on demand in one pass over the queue, so it is worth
+    /// keeping around when several analyses query it.
+    fn token_index(&self) -> TokenIndex<Self::Rule>;
+
+    /// Returns the `Token`s nested inside of `token` in the queue. Since the queue is in
+    /// pre-order, they come right after `token`.
+    ///
+    /// How many `Token`s a rule nested is recorded when its `Token` is inserted, so this is exact
+    /// even for empty `Token`s, which cannot be placed by their spans alone: an empty `Token` at
+    /// the end of another one could either be its last child or its next sibling. `Token`s
+    /// pushed or changed by hand, e.g. with [`Parser::inject`](trait.Parser#tymethod.inject),
+    /// fall back to [`Token::is_ancestor_of`](struct.Token#method.is_ancestor_of).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate pest;
+    /// # use pest::prelude::*;
+    /// # fn main() {
+    /// impl_rdp! {
+    ///     grammar! {
+    ///         list = { ["["] ~ (item ~ ([","] ~ item)*)? ~ ["]"] }
+    ///         item = { list | word }
+    ///         word = @{ ['a'..'z']+ }
+    ///     }
+    /// }
+    ///
+    /// let mut parser = Rdp::new(StringInput::new("[a,[b],c]"));
+    ///
+    /// assert!(parser.list());
+    ///
+    /// let second = parser.queue()[3];
+    ///
+    /// assert_eq!(parser.descendants_of(&second).len(), 3);
+    /// assert_eq!(parser.children_of(&second),
+    ///            vec![&Token { rule: Rule::list, start: 3, end: 6 }]);
+    /// assert_eq!(parser.next_sibling(&second),
+    ///            Some(&Token { rule: Rule::item, start: 7, end: 8 }));
+    /// assert_eq!(parser.parent_of(&second), Some(&parser.queue()[0]));
+    /// # }
+    /// ```
+    fn descendants_of(&self, token: &Self::Token) -> &[Self::Token];
 
-    /// Increments the current index within the queue. Used in `process!`.
-    fn inc_queue_index(&self);
+    /// Returns the `Token`s directly nested inside of `token` in the queue, skipping over their
+    /// descendants.
+    fn children_of(&self, token: &Self::Token) -> Vec<&Self::Token>;
 
-    /// Set the current index within the queue. Used in `process!`.
-    fn set_queue_index(&self, index: usize);
+    /// Returns the `Token` that directly encloses `token` in the queue, if any. Unlike the other
+    /// queue searches, it walks back over the `Token`s preceding `token`, up to the first one
+    /// whose descendants include it.
+    fn parent_of(&self, token: &Self::Token) -> Option<&Self::Token>;
+
+    /// Returns the `Token` that follows `token` in the queue with the same parent, if any.
+    fn next_sibling(&self, token: &Self::Token) -> Option<&Self::Token>;
+
+    /// Returns the `Token`s the other parser of the [external](macro.grammar!#external-rules)
+    /// rule of `token` matched, in pre-order. Their positions point into a `Parser`'s `Input` and
+    /// their rules are interned as `rule::inner_rule`, e.g. `embedded_sql::select`, so they can be
+    /// told apart from the rules of other external rules with
+    /// [`interner`](#tymethod.interner). Any other `Token` has no embedded `Token`s.
+    fn embedded(&self, token: &Self::Token) -> &[Token<Symbol>];
+
+    /// Checks that the queue is a well-nested tree in pre-order. See
+    /// [`validate_queue`](fn.validate_queue).
+    fn validate(&self) -> Result<(), QueueError>;
+
+    /// Sets whether the queue is validated every time a top-level rule matches. An invalid queue
+    /// panics with the offending `Token`s, catching `grammar!` bugs and hand-made queue
+    /// modifications before they reach `process!`. Disabled by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate pest;
+    /// # use pest::prelude::*;
+    /// # fn main() {
+    /// impl_rdp! {
+    ///     grammar! {
+    ///         expression = _{
+    ///             { number }
+    ///             sum     = { plus }
+    ///             product = { times }
+    ///         }
+    ///         number = @{ ['0'..'9']+ }
+    ///         plus   =  { ["+"] }
+    ///         times  =  { ["*"] }
+    ///     }
+    /// }
+    ///
+    /// let mut parser = Rdp::new(StringInput::new("1+2*3"));
+    ///
+    /// parser.set_validation(true);
+    ///
+    /// assert!(parser.expression());
+    /// assert_eq!(parser.validate(), Ok(()));
+    /// # }
+    /// ```
+    fn set_validation(&mut self, enabled: bool);
+
+    /// Returns the `Token` `cursor` points at without consuming it. Useful in `process!` for
+    /// deciding which matcher to call.
+    fn peek_token(&self, cursor: Cursor) -> Option<&Self::Token>;
+
+    /// Returns the `Rule` of the `Token` `cursor` points at without consuming it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate pest;
+    /// # use pest::prelude::*;
+    /// # fn main() {
+    /// impl_rdp! {
+    ///     grammar! {
+    ///         value  = _{ number | word }
+    ///         number = @{ ['0'..'9']+ }
+    ///         word   = @{ ['a'..'z']+ }
+    ///     }
+    ///
+    ///     process! {
+    ///         main(&self, &mut cursor) -> String {
+    ///             () => {
+    ///                 match self.peek_rule(*cursor) {
+    ///                     Some(Rule::number) => self._number(cursor),
+    ///                     _                  => self._word(cursor)
+    ///                 }
+    ///             }
+    ///         }
+    ///
+    ///         _number(&self) -> String {
+    ///             (&number: number) => format!("number {}", number)
+    ///         }
+    ///
+    ///         _word(&self) -> String {
+    ///             (&word: word) => format!("word {}", word)
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut parser = Rdp::new(StringInput::new("42"));
+    ///
+    /// assert!(parser.value());
+    /// assert_eq!(parser.process(), "number 42");
+    /// # }
+    /// ```
+    fn peek_rule(&self, cursor: Cursor) -> Option<Self::Rule>;
+
+    /// Calls `process` with `cursor` and caches its result by the `Rule` and index of the `Token`
+    /// `cursor` points at, by `key`, and by the type `V`. When called again at the same `Token`
+    /// with the same `key`, e.g. in another pass over the queue, the cached result is cloned and
+    /// `cursor` skips over the `Token`s that `process` consumed. `key` tells apart different
+    /// analyses of the same `Token`s, so every matcher using `memoize` needs its own, usually its
+    /// name. The cache is cleared by [`Parser::reset`](trait.Parser#tymethod.reset) and
+    /// [`Parser::clear_memo`](trait.Parser#tymethod.clear_memo).
+    ///
+    /// Since results are cloned, expensive values are best wrapped in an `Rc`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate pest;
+    /// # use pest::prelude::*;
+    /// # use std::cell::Cell;
+    /// # fn main() {
+    /// thread_local!(static CALLS: Cell<usize> = Cell::new(0));
+    ///
+    /// impl_rdp! {
+    ///     grammar! {
+    ///         list   = { number* }
+    ///         number = @{ ['0'..'9']+ }
+    ///
+    ///         whitespace = _{ [" "] }
+    ///     }
+    ///
+    ///     process! {
+    ///         main(&self) -> u32 {
+    ///             (_: list, sum: _sum()) => sum
+    ///         }
+    ///
+    ///         _sum(&self, &mut cursor) -> u32 {
+    ///             () => self.memoize("sum", cursor, |cursor| self._sum_uncached(cursor))
+    ///         }
+    ///
+    ///         _sum_uncached(&self) -> u32 {
+    ///             (&number: number, tail: _sum()) => {
+    ///                 CALLS.with(|calls| calls.set(calls.get() + 1));
+    ///
+    ///                 number.parse::<u32>().unwrap() + tail
+    ///             },
+    ///             () => 0
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut parser = Rdp::new(StringInput::new("1 2 3"));
+    ///
+    /// assert!(parser.list());
+    /// assert_eq!(parser.process(), 6);
+    /// assert_eq!(parser.process(), 6);
+    /// assert_eq!(CALLS.with(|calls| calls.get()), 3);
+    /// # }
+    /// ```
+    fn memoize<V, F>(&self, key: &'static str, cursor: &mut Cursor, process: F) -> V
+        where V: Any + Clone, F: FnOnce(&mut Cursor) -> V;
+
+    /// Clears the cache of [`Parser::memoize`](trait.Parser#tymethod.memoize).
+    fn clear_memo(&self);
 
     /// Skips white-space.
     fn skip_ws(&mut self);
@@ -75,12 +1072,113 @@ pub trait Parser {
     /// Skips comments.
     fn skip_com(&mut self);
 
+    /// Returns the spans of all `comment`s skipped so far, sorted by position. Pass them to an
+    /// [`Attacher`](struct.Attacher) to find the `Token`s they document.
+    fn comments(&self) -> &[Range<usize>];
+
+    /// Sets whether `Token` spans include surrounding `Trivia`.
+    fn set_trivia(&mut self, trivia: Trivia);
+
+    /// Returns the `Trivia` setting of a `Parser`.
+    fn trivia(&self) -> Trivia;
+
+    /// Returns the start of the trivia that was skipped right before `pos`, or `pos` if there is
+    /// none. Used in `grammar!`.
+    fn trivia_start(&self, pos: usize) -> usize;
+
+    /// Sets whether a matched top-level rule, i.e. a rule not called from another rule, also
+    /// skips the `whitespace` and `comment`s that follow it, so that
+    /// [`Parser::end`](trait.Parser#tymethod.end) holds for inputs with trailing trivia without
+    /// having to append `eoi` to the rule.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate pest;
+    /// # use pest::prelude::*;
+    /// # fn main() {
+    /// impl_rdp! {
+    ///     grammar! {
+    ///         word = @{ ['a'..'z']+ }
+    ///
+    ///         whitespace = _{ [" "] | ["\n"] }
+    ///     }
+    /// }
+    ///
+    /// let mut parser = Rdp::new(StringInput::new("word \n"));
+    ///
+    /// assert!(parser.word());
+    /// assert!(!parser.end());
+    ///
+    /// parser.reset();
+    /// parser.skip_trailing_trivia(true);
+    ///
+    /// assert!(parser.word());
+    /// assert!(parser.end());
+    /// assert_eq!(parser.significant_end(), 4);
+    /// # }
+    /// ```
+    fn skip_trailing_trivia(&mut self, value: bool);
+
+    /// Returns the position where the last matched top-level rule ended, excluding any trailing
+    /// trivia.
+    fn significant_end(&self) -> usize;
+
     /// Returns whether a `Parser` is currently inside an atomic rule.
     fn is_atomic(&self) -> bool;
 
     /// Sets a `Parser` to atomic rule mode, barring comment & white-space skipping.
     fn set_atomic(&mut self, value: bool);
 
+    /// Sets how much is recorded about failed rules. See [`Tracking`](enum.Tracking).
+    fn set_tracking(&mut self, tracking: Tracking);
+
+    /// Returns the `Tracking` setting of a `Parser`.
+    fn tracking(&self) -> Tracking;
+
+    /// Sets whether failures are tracked at the end of the `whitespace` and `comment`s at the
+    /// position they happened at, instead of at the position itself. Rules skip all trivia before
+    /// them, but strings and ranges in a sequence only skip `whitespace`, so a string fails in
+    /// front of a `comment` while a rule matching the same string fails after it. With error
+    /// normalization, refactoring a grammar like this, or making rules atomic or non-atomic, does
+    /// not shift the positions returned by [`Parser::expected`](trait.Parser#tymethod.expected)
+    /// and [`Parser::expectations`](trait.Parser#tymethod.expectations).
+    ///
+    /// The trivia is only looked at: it is not added to the queue or to
+    /// [`Parser::comments`](trait.Parser#tymethod.comments). The setting is kept across
+    /// [`Parser::reset`](trait.Parser#tymethod.reset).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate pest;
+    /// # use pest::prelude::*;
+    /// # use pest::Expected;
+    /// # fn main() {
+    /// impl_rdp! {
+    ///     grammar! {
+    ///         statement = { word ~ [";"] }
+    ///         word      = @{ ['a'..'z']+ }
+    ///
+    ///         whitespace = _{ [" "] }
+    ///         comment    = _{ ["#"] ~ (!["\n"] ~ any)* ~ ["\n"] }
+    ///     }
+    /// }
+    ///
+    /// let mut parser = Rdp::new(StringInput::new("a #\n ?"));
+    ///
+    /// assert!(!parser.statement());
+    /// assert_eq!(parser.expectations(), (vec![Expected::String(";".to_owned())], 2));
+    ///
+    /// parser.reset();
+    /// parser.set_error_normalization(true);
+    ///
+    /// assert!(!parser.statement());
+    /// assert_eq!(parser.expectations(), (vec![Expected::String(";".to_owned())], 5));
+    /// # }
+    /// ```
+    fn set_error_normalization(&mut self, enabled: bool);
+
     /// Keeps track of rule failures. It gets called when a `Rule` fails at `pos`.
     fn track(&mut self, failed: Self::Rule, pos: usize);
 
@@ -90,4 +1188,81 @@ pub trait Parser {
     /// Retuns a `Vec` of all expected `Rule`s at the deepest position where the parsing last
     /// stopped. It only returns leafs from the rule tree. Used for error reporting.
     fn expected(&mut self) -> (Vec<Self::Rule>, usize);
+
+    /// Returns everything that was expected at the farthest position where the parsing failed,
+    /// i.e. the `Rule`s that [`Parser::expected`](trait.Parser#tymethod.expected) returns and the
+    /// strings and ranges that failed outside of atomic rules and trivia, deduplicated. Rules
+    /// come first, sorted, followed by strings and ranges in the order they failed. Strings and
+    /// ranges are recorded according to the same [`Tracking`](enum.Tracking) as rules.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate pest;
+    /// # use pest::prelude::*;
+    /// # use pest::Expected;
+    /// # fn main() {
+    /// impl_rdp! {
+    ///     grammar! {
+    ///         list   = { ["("] ~ number ~ ([","] ~ number)* ~ [")"] }
+    ///         number = @{ ['0'..'9']+ }
+    ///     }
+    /// }
+    ///
+    /// let mut parser = Rdp::new(StringInput::new("(1;"));
+    ///
+    /// assert!(!parser.list());
+    /// assert_eq!(parser.expected(), (vec![Rule::list], 0));
+    ///
+    /// let (expected, pos) = parser.expectations();
+    /// let names: Vec<_> = expected.iter().map(|expected| expected.to_string()).collect();
+    ///
+    /// assert_eq!(expected, vec![
+    ///     Expected::String(",".to_owned()),
+    ///     Expected::String(")".to_owned())
+    /// ]);
+    /// assert_eq!(format!("expected {} at {}", names.join(" or "), pos),
+    ///            "expected \",\" or \")\" at 2");
+    /// # }
+    /// ```
+    fn expectations(&mut self) -> (Vec<Expected<Self::Rule>>, usize);
+
+    /// Returns a [`Suggestion`](struct.Suggestion) when the word at the position returned by
+    /// [`Parser::expectations`](trait.Parser#tymethod.expectations) is a near miss of an expected
+    /// string or of a reserved word of an expected [keywords](macro.grammar!#keywords) rule.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate pest;
+    /// # use pest::prelude::*;
+    /// # fn main() {
+    /// impl_rdp! {
+    ///     grammar! {
+    ///         statement = { keyword ~ ident | ["print"] ~ ident }
+    ///         keyword   = keywords(ident) { "return", "break" }
+    ///         ident     = @{ ['a'..'z']+ }
+    ///
+    ///         whitespace = _{ [" "] }
+    ///     }
+    /// }
+    ///
+    /// let mut parser = Rdp::new(StringInput::new("retrun x"));
+    ///
+    /// assert!(!parser.statement());
+    /// assert_eq!(parser.suggestion().unwrap().to_string(),
+    ///            "found \"retrun\", did you mean \"return\"?");
+    ///
+    /// let mut parser = Rdp::new(StringInput::new("prnt x"));
+    ///
+    /// assert!(!parser.statement());
+    /// assert_eq!(parser.suggestion().unwrap().expected, "print");
+    /// # }
+    /// ```
+    fn suggestion(&mut self) -> Option<Suggestion>;
+
+    /// Returns the innermost opening delimiter that was still open where its closing delimiter
+    /// failed, if that happened at the farthest position where the parsing failed. See
+    /// [`Unclosed`](struct.Unclosed).
+    fn unclosed(&self) -> Option<Unclosed>;
 }
@@ -10,4 +10,4 @@ mod rdp;
 
 mod token;
 
-pub use self::token::Token;
+pub use self::token::{OwnedToken, Token, TokenData};
@@ -6,7 +6,8 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 /// A `macro` useful for implementing the `Parser` `trait` as a recursive descent parser. It only
-/// accepts `grammar!` and `process!` calls that get implemented on `self`.
+/// accepts `grammar!` and `process!` calls that get implemented on `self`, and an optional
+/// [`functions!`](#rule-functions) call.
 ///
 /// # Rule
 ///
@@ -15,6 +16,188 @@
 /// [`any` and `eoi`](macro.grammar!). These `Rule`s are used within `Token`s to specify the type
 /// of rule that matched.
 ///
+/// `Rule`'s discriminants are stable: `any` is always `0`, `eoi` is always `1`, and every other
+/// rule follows in the order it is defined in `grammar!`, with the rules of a
+/// [precedence climbing](macro.grammar!#precedence-climbing) rule following the rule itself. The
+/// remaining predefined rules, `any_byte`, `any_ascii`, and `line_ending`, always come last, as
+/// `0xfffd`, `0xfffe`, and `0xffff`, unless the grammar defines a rule of the same name. This also
+/// defines `Rule`'s `Ord`, so reordering rules is the only change that can renumber them.
+///
+/// `Rule::category` returns the [`RuleKind`](enum.RuleKind) a rule is defined as, and
+/// `Rule::is_terminal` whether it is lexical, so that generic passes can treat lexical and
+/// structural rules differently without listing them by hand.
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         expression = _{ paren ~ expression? }
+///         paren      =  { ["("] ~ expression? ~ [")"] }
+///         letter     =  { ['a'..'z'] }
+///     }
+/// }
+///
+/// assert_eq!(Rule::any as usize, 0);
+/// assert_eq!(Rule::eoi as usize, 1);
+/// assert_eq!(Rule::paren as usize, 2);
+/// assert_eq!(Rule::letter as usize, 3);
+/// assert_eq!(Rule::line_ending as usize, 0xffff);
+/// # }
+/// ```
+///
+/// # Names
+///
+/// Since `impl_rdp!` always defines `Rdp` and `Rule`, a leading `names!` call is needed to use more
+/// than one parser in the same module. It puts everything generated into a new module, which
+/// imports everything from its parent module, and defines type aliases of `Rdp` and `Rule` with the
+/// given names. `Rule`s and methods keep their names, and other generated items, like
+/// [rule functions](#rule-functions), stay inside of the module. Since `process!` methods are
+/// private, only `process` can be called from outside of the module.
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # fn main() {
+/// impl_rdp! {
+///     names! { numbers: NumberParser, NumberRule }
+///
+///     grammar! {
+///         number = @{ ['0'..'9']+ }
+///     }
+/// }
+///
+/// impl_rdp! {
+///     names! { words: WordParser, WordRule }
+///
+///     grammar! {
+///         word = @{ ['a'..'z']+ }
+///     }
+/// }
+///
+/// let mut parser = NumberParser::new(StringInput::new("42"));
+///
+/// assert!(parser.number());
+/// assert_eq!(parser.queue()[0].rule, NumberRule::number);
+///
+/// let mut parser = WordParser::new(StringInput::new("pest"));
+///
+/// assert!(parser.word());
+/// assert_eq!(parser.queue()[0].rule, WordRule::word);
+/// # }
+/// ```
+///
+/// # Scope
+///
+/// The expansion refers to everything by its full path and imports nothing by name, so it
+/// neither needs `pest::prelude::*` nor collides with items of the module it is expanded in.
+/// `impl_rdp!` can also be imported like any other item and be expanded inside of functions,
+/// e.g. in tests. `names!` needs a module to expand into, so it only works outside of
+/// functions.
+///
+/// ```
+/// extern crate pest;
+///
+/// use pest::impl_rdp;
+/// use pest::prelude::parser::*;
+///
+/// fn main() {
+///     impl_rdp! {
+///         grammar! {
+///             number = @{ ['0'..'9']+ }
+///         }
+///     }
+///
+///     let mut parser = Rdp::new(StringInput::new("42"));
+///
+///     assert!(parser.number());
+///     assert!(parser.end());
+/// }
+/// ```
+///
+/// # Token type
+///
+/// The queue holds [`Token`](struct.Token)s by default. A leading `token!` call, after `names!`
+/// if there is one, replaces them with a type implementing [`TokenData`](trait.TokenData), so
+/// that extra data can be stored with every `Token`. The queue, `Parser::Token`, and the items
+/// of [`process!`](macro.process!) all use that type; results that outlive the parser, like
+/// `ParseResult`, `Partial`, and streamed `Event`s, hold plain `Token`s.
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::TokenData;
+/// #[derive(Clone, Copy, Debug, PartialEq)]
+/// pub struct Sourced {
+///     token: Token<Rule>,
+///     file:  u32
+/// }
+///
+/// impl TokenData<Rule> for Sourced {
+///     fn new(rule: Rule, start: usize, end: usize) -> Sourced {
+///         Sourced { token: Token { rule, start, end }, file: 0 }
+///     }
+///
+///     fn rule(&self) -> Rule { self.token.rule }
+///     fn start(&self) -> usize { self.token.start }
+///     fn end(&self) -> usize { self.token.end }
+/// }
+///
+/// impl_rdp! {
+///     token! { Sourced }
+///
+///     grammar! {
+///         number = @{ ['0'..'9']+ }
+///     }
+/// }
+///
+/// # fn main() {
+/// let mut parser = Rdp::new(StringInput::new("42"));
+///
+/// assert!(parser.number());
+///
+/// for token in parser.queue_mut() {
+///     token.file = 7;
+/// }
+///
+/// assert_eq!(parser.queue()[0].file, 7);
+/// assert_eq!(parser.queue()[0].token(), Token { rule: Rule::number, start: 0, end: 2 });
+/// # }
+/// ```
+///
+/// # Rule functions
+///
+/// A `functions!` call lists rules that additionally get a free function taking the parser,
+/// e.g. `pub fn paren<T: Input>(parser: &mut Rdp<T>) -> bool`, next to `Rdp`. This way, unit
+/// tests and benchmarks can pass a single rule around, e.g. to a shared test helper, without
+/// wrapping it in a closure or an artificial grammar.
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         paren  = { ["("] ~ paren? ~ [")"] }
+///         letter = { ['a'..'z'] }
+///     }
+///
+///     functions! { paren, letter }
+/// }
+///
+/// fn parses<'a>(rule: fn(&mut Rdp<StringInput<'a>>) -> bool, source: &'a str) -> bool {
+///     let mut parser = Rdp::new(StringInput::new(source));
+///
+///     rule(&mut parser) && parser.end()
+/// }
+///
+/// assert!(parses(paren, "(())"));
+/// assert!(parses(letter, "a"));
+/// assert!(!parses(letter, "ab"));
+/// # }
+/// ```
+///
 /// # Examples
 ///
 /// ```
@@ -36,41 +219,342 @@
 /// ```
 #[macro_export]
 macro_rules! impl_rdp {
-    // implement rules
-    ( @rules $( $name:ident )* ) => {
+    // implement rules; callable rules are the ones that have a method
+    ( @rules [ $( $name:ident )* ] [ $( $callable:ident )* ]
+      [ $( ( $kind_name:ident => $( $kind:tt )* ) )* ]
+      [ [ $( ( $builtin:ident $value:expr ) )* ] [ $( $helper:ident )* ] ] ) => {
         #[allow(dead_code, non_camel_case_types)]
         #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
         pub enum Rule {
             any,
             eoi,
-            $( $name ),*
+            $( $name, )*
+            $( $builtin = $value, )*
+        }
+
+        impl Rule {
+            /// Returns what kind of rule a `Rule` is defined as in the grammar.
+            #[allow(dead_code)]
+            pub fn category(&self) -> $crate::runtime::RuleKind {
+                match *self {
+                    $( Rule::$kind_name => $( $kind )*, )*
+                    Rule::any | Rule::eoi $( | Rule::$builtin )* => {
+                        $crate::runtime::RuleKind::Builtin
+                    }
+                }
+            }
+
+            /// Returns whether a `Rule` is lexical, i.e. a built-in, atomic, or keywords rule,
+            /// rather than structural.
+            #[allow(dead_code)]
+            pub fn is_terminal(&self) -> bool {
+                self.category().is_terminal()
+            }
+        }
+
+        impl<T: $crate::runtime::Input> Rdp<T> {
+            /// Matches the method of `rule` starting at `pos` like `Parser::parse_at`, for tools
+            /// that only know which `Rule` to probe at runtime. `Rule`s without a method never
+            /// match.
+            #[allow(dead_code, unreachable_patterns)]
+            pub fn parse_rule_at(&mut self, pos: usize, rule: Rule) -> bool {
+                $crate::runtime::Parser::parse_at(self, pos, |parser| {
+                    match rule {
+                        $( Rule::$callable => parser.$callable(), )*
+                        Rule::any => parser.any(),
+                        Rule::eoi => parser.eoi(),
+                        $( Rule::$builtin => parser.$builtin(), )*
+                        _         => false
+                    }
+                })
+            }
+
+            $( $crate::impl_rdp!(@builtin $builtin); )*
+            $( $crate::impl_rdp!(@builtin $helper); )*
+        }
+
+        impl<'a> $crate::runtime::GeneratedParser for Rdp<$crate::runtime::StringInput<'a>> {
+            type Rule = Rule;
+
+            fn rules() -> &'static [Rule] {
+                &[Rule::any, Rule::eoi, $( Rule::$name, )* $( Rule::$builtin, )*]
+            }
+
+            fn rule_name(rule: Rule) -> &'static str {
+                match rule {
+                    Rule::any => "any",
+                    Rule::eoi => "eoi",
+                    $( Rule::$name => stringify!($name), )*
+                    $( Rule::$builtin => stringify!($builtin), )*
+                }
+            }
+
+            #[allow(unreachable_patterns)]
+            fn is_callable(rule: Rule) -> bool {
+                match rule {
+                    $( Rule::$callable => true, )*
+                    Rule::any | Rule::eoi $( | Rule::$builtin )* => true,
+                    _ => false
+                }
+            }
+
+            fn rule_kind(rule: Rule) -> $crate::runtime::RuleKind {
+                rule.category()
+            }
+
+            fn alternatives() -> Vec<$crate::runtime::Alternative> {
+                let parser = Rdp::new($crate::runtime::StringInput::new(""));
+
+                $crate::runtime::Parser::alternatives(&parser)
+            }
+
+            #[allow(unreachable_patterns)]
+            fn parse(rule: Rule, input: &str) -> $crate::runtime::ParseResult<Rule> {
+                let mut parser = Rdp::new($crate::runtime::StringInput::new(input));
+
+                $crate::runtime::Parser::run(&mut parser, |parser| {
+                    $crate::runtime::Parser::parse_complete(parser, |parser| {
+                        match rule {
+                            $( Rule::$callable => parser.$callable(), )*
+                            Rule::any => parser.any(),
+                            Rule::eoi => parser.eoi(),
+                            $( Rule::$builtin => parser.$builtin(), )*
+                            _         => false
+                        }
+                    })
+                })
+            }
         }
     };
 
-    // filter out silent rules
-    ( @filter [  ] [ $( $rules:tt )* ] ) => {
-        impl_rdp!(@rules $( $rules )*);
+    // filter out silent rules; rules are kept in grammar order, while predefined rules that the
+    // grammar does not define itself get discriminants past them, so neither renumbers the other
+    ( @filter [  ] $rules:tt $callable:tt $kinds:tt
+      ([ $( $byte:ident )* ] [ $( $ascii:ident )* ] [ $( $ending:ident )* ]
+       [ $( $prefixed:ident )* ] [ $( $prolog:ident )* ]) ) => {
+        $crate::impl_rdp!(@rules $rules $callable $kinds
+                          [ [ $( ($byte 0xfffd) )* $( ($ascii 0xfffe) )* $( ($ending 0xffff) )* ]
+                            [ $( $prefixed )* $( $prolog )* ] ]);
+    };
+    // rules of the grammar replace predefined rules of the same name
+    ( @filter [ any_byte = $( $tail:tt )* ] $rules:tt $callable:tt $kinds:tt
+      ([any_byte] $ascii:tt $ending:tt $prefixed:tt $prolog:tt) ) => {
+        $crate::impl_rdp!(@filter [ any_byte = $( $tail )* ] $rules $callable $kinds
+                          ([] $ascii $ending $prefixed $prolog));
+    };
+    ( @filter [ any_ascii = $( $tail:tt )* ] $rules:tt $callable:tt $kinds:tt
+      ($byte:tt [any_ascii] $ending:tt $prefixed:tt $prolog:tt) ) => {
+        $crate::impl_rdp!(@filter [ any_ascii = $( $tail )* ] $rules $callable $kinds
+                          ($byte [] $ending $prefixed $prolog));
+    };
+    ( @filter [ line_ending = $( $tail:tt )* ] $rules:tt $callable:tt $kinds:tt
+      ($byte:tt $ascii:tt [line_ending] $prefixed:tt $prolog:tt) ) => {
+        $crate::impl_rdp!(@filter [ line_ending = $( $tail )* ] $rules $callable $kinds
+                          ($byte $ascii [] $prefixed $prolog));
+    };
+    ( @filter [ prefixed = $( $tail:tt )* ] $rules:tt $callable:tt $kinds:tt
+      ($byte:tt $ascii:tt $ending:tt [prefixed] $prolog:tt) ) => {
+        $crate::impl_rdp!(@filter [ prefixed = $( $tail )* ] $rules $callable $kinds
+                          ($byte $ascii $ending [] $prolog));
+    };
+    ( @filter [ prolog = $( $tail:tt )* ] $rules:tt $callable:tt $kinds:tt
+      ($byte:tt $ascii:tt $ending:tt $prefixed:tt [prolog]) ) => {
+        $crate::impl_rdp!(@filter [ prolog = $( $tail )* ] $rules $callable $kinds
+                          ($byte $ascii $ending $prefixed []));
     };
     ( @filter [ $name:ident = { { $( $_primary:tt )* } $( $ts:tt )* } $( $tail:tt )* ]
-      [ $( $rules:tt )* ] ) => {
-        impl_rdp!(@filter [ $( $tail )* $( $ts )* ] [ $name $( $rules )* ]);
+      [ $( $rules:tt )* ] [ $( $callable:tt )* ] [ $( $kinds:tt )* ] $builtins:tt ) => {
+        $crate::impl_rdp!(@filter [ (ops $( $ts )*) $( $tail )* ] [ $( $rules )* $name ]
+                          [ $( $callable )* $name ]
+                          [ $( $kinds )* ($name => $crate::runtime::RuleKind::Normal) ] $builtins);
     };
     ( @filter [ $name:ident = @{ { $( $_primary:tt )* } $( $ts:tt )* } $( $tail:tt )* ]
-      [ $( $rules:tt )* ] ) => {
-        impl_rdp!(@filter [ $( $tail )* $( $ts )* ] [ $name $( $rules )* ]);
+      [ $( $rules:tt )* ] [ $( $callable:tt )* ] [ $( $kinds:tt )* ] $builtins:tt ) => {
+        $crate::impl_rdp!(@filter [ (ops $( $ts )*) $( $tail )* ] [ $( $rules )* $name ]
+                          [ $( $callable )* $name ]
+                          [ $( $kinds )* ($name => $crate::runtime::RuleKind::Atomic) ] $builtins);
     };
     ( @filter [ $name:ident = _{ { $( $_primary:tt )* } $( $ts:tt )* } $( $tail:tt )* ]
-      [ $( $rules:tt )* ] ) => {
-        impl_rdp!(@filter [ $( $tail )* $( $ts )* ] [ $name $( $rules )* ]);
+      [ $( $rules:tt )* ] [ $( $callable:tt )* ] [ $( $kinds:tt )* ] $builtins:tt ) => {
+        $crate::impl_rdp!(@filter [ (ops $( $ts )*) $( $tail )* ] [ $( $rules )* $name ]
+                          [ $( $callable )* $name ]
+                          [ $( $kinds )* ($name => $crate::runtime::RuleKind::Normal) ] $builtins);
+    };
+    // operators of precedence climbing rules don't have methods
+    ( @filter [ (ops) $( $tail:tt )* ] $rules:tt $callable:tt $kinds:tt $builtins:tt ) => {
+        $crate::impl_rdp!(@filter [ $( $tail )* ] $rules $callable $kinds $builtins);
+    };
+    ( @filter [ (ops $name:ident = { $( $_ts:tt )* } $( $ops:tt )*) $( $tail:tt )* ]
+      [ $( $rules:tt )* ] $callable:tt [ $( $kinds:tt )* ] $builtins:tt ) => {
+        $crate::impl_rdp!(@filter [ (ops $( $ops )*) $( $tail )* ] [ $( $rules )* $name ] $callable
+                          [ $( $kinds )* ($name => $crate::runtime::RuleKind::Operator) ]
+                          $builtins);
+    };
+    ( @filter [ (ops $name:ident = @{ $( $_ts:tt )* } $( $ops:tt )*) $( $tail:tt )* ]
+      [ $( $rules:tt )* ] $callable:tt [ $( $kinds:tt )* ] $builtins:tt ) => {
+        $crate::impl_rdp!(@filter [ (ops $( $ops )*) $( $tail )* ] [ $( $rules )* $name ] $callable
+                          [ $( $kinds )* ($name => $crate::runtime::RuleKind::Operator) ]
+                          $builtins);
+    };
+    ( @filter [ (ops $_head:tt $( $ops:tt )*) $( $tail:tt )* ] $rules:tt $callable:tt
+      $kinds:tt $builtins:tt ) => {
+        $crate::impl_rdp!(@filter [ (ops $( $ops )*) $( $tail )* ] $rules $callable $kinds
+                          $builtins);
+    };
+    ( @filter [ $name:ident = { $( $_ts:tt )* } $( $tail:tt )* ] [ $( $rules:tt )* ]
+      [ $( $callable:tt )* ] [ $( $kinds:tt )* ] $builtins:tt ) => {
+        $crate::impl_rdp!(@filter [ $( $tail )* ] [ $( $rules )* $name ] [ $( $callable )* $name ]
+                          [ $( $kinds )* ($name => $crate::runtime::RuleKind::Normal) ] $builtins);
+    };
+    ( @filter [ $name:ident = @{ $( $_ts:tt )* } $( $tail:tt )* ] [ $( $rules:tt )* ]
+      [ $( $callable:tt )* ] [ $( $kinds:tt )* ] $builtins:tt ) => {
+        $crate::impl_rdp!(@filter [ $( $tail )* ] [ $( $rules )* $name ] [ $( $callable )* $name ]
+                          [ $( $kinds )* ($name => $crate::runtime::RuleKind::Atomic) ] $builtins);
+    };
+    ( @filter [ $name:ident = _{ $( $_ts:tt )* } $( $tail:tt )* ] $rules:tt $callable:tt
+      $kinds:tt $builtins:tt ) => {
+        $crate::impl_rdp!(@filter [ $( $tail )* ] $rules $callable $kinds $builtins);
+    };
+    ( @filter [ $name:ident = !{ $( $_ts:tt )* } $( $tail:tt )* ] [ $( $rules:tt )* ]
+      [ $( $callable:tt )* ] [ $( $kinds:tt )* ] $builtins:tt ) => {
+        $crate::impl_rdp!(@filter [ $( $tail )* ] [ $( $rules )* $name ] [ $( $callable )* $name ]
+                          [ $( $kinds )* ($name => $crate::runtime::RuleKind::Normal) ] $builtins);
+    };
+    ( @filter [ $name:ident = flat { $( $_ts:tt )* } $( $tail:tt )* ] [ $( $rules:tt )* ]
+      [ $( $callable:tt )* ] [ $( $kinds:tt )* ] $builtins:tt ) => {
+        $crate::impl_rdp!(@filter [ $( $tail )* ] [ $( $rules )* $name ] [ $( $callable )* $name ]
+                          [ $( $kinds )* ($name => $crate::runtime::RuleKind::Normal) ] $builtins);
+    };
+    ( @filter [ $name:ident = keywords ( $_ident:ident ) { $( $_ts:tt )* } $( $tail:tt )* ]
+      [ $( $rules:tt )* ] [ $( $callable:tt )* ] [ $( $kinds:tt )* ] $builtins:tt ) => {
+        $crate::impl_rdp!(@filter [ $( $tail )* ] [ $( $rules )* $name ] [ $( $callable )* $name ]
+                          [ $( $kinds )* ($name => $crate::runtime::RuleKind::Atomic) ] $builtins);
+    };
+    // aliases are of the same kind as their target
+    ( @filter [ $name:ident = alias $target:ident $( $tail:tt )* ] [ $( $rules:tt )* ]
+      [ $( $callable:tt )* ] [ $( $kinds:tt )* ] $builtins:tt ) => {
+        $crate::impl_rdp!(@filter [ $( $tail )* ] [ $( $rules )* $name ] [ $( $callable )* $name ]
+                          [ $( $kinds )* ($name => Rule::$target.category()) ] $builtins);
+    };
+    ( @filter [ $name:ident = external $_parser:ident :: $_rule:ident $( $tail:tt )* ]
+      [ $( $rules:tt )* ] [ $( $callable:tt )* ] [ $( $kinds:tt )* ] $builtins:tt ) => {
+        $crate::impl_rdp!(@filter [ $( $tail )* ] [ $( $rules )* $name ] [ $( $callable )* $name ]
+                          [ $( $kinds )* ($name => $crate::runtime::RuleKind::Atomic) ] $builtins);
+    };
+    // token rules only name Tokens pushed by hand and don't have methods
+    ( @filter [ $name:ident = token $( $tail:tt )* ] [ $( $rules:tt )* ] $callable:tt
+      [ $( $kinds:tt )* ] $builtins:tt ) => {
+        $crate::impl_rdp!(@filter [ $( $tail )* ] [ $( $rules )* $name ] $callable
+                          [ $( $kinds )* ($name => $crate::runtime::RuleKind::Token) ] $builtins);
+    };
+    ( @filter [ $_head:tt $( $tail:tt )* ] $rules:tt $callable:tt $kinds:tt $builtins:tt ) => {
+        $crate::impl_rdp!(@filter [ $( $tail )* ] $rules $callable $kinds $builtins);
+    };
+
+    // implement predefined rules
+    ( @builtin any_byte ) => {
+        #[allow(dead_code)]
+        #[inline]
+        pub fn any_byte(&mut self) -> bool {
+            let pos = $crate::runtime::Parser::pos(self);
+
+            if $crate::runtime::Parser::end(self) {
+                $crate::runtime::Parser::track(self, Rule::any_byte, pos);
+
+                false
+            } else {
+                $crate::runtime::Parser::set_pos(self, pos + 1);
+
+                true
+            }
+        }
+    };
+    ( @builtin any_ascii ) => {
+        #[allow(dead_code)]
+        #[inline]
+        pub fn any_ascii(&mut self) -> bool {
+            let pos = $crate::runtime::Parser::pos(self);
+            let result = $crate::runtime::Parser::match_range(self, '\0', '\x7f');
+
+            if !result {
+                $crate::runtime::Parser::track(self, Rule::any_ascii, pos);
+            }
+
+            result
+        }
     };
-    ( @filter [ $name:ident = { $( $_ts:tt )* } $( $tail:tt )* ] [ $( $rules:tt )* ] ) => {
-        impl_rdp!(@filter [ $( $tail )* ] [ $name $( $rules )* ]);
+    ( @builtin line_ending ) => {
+        #[allow(dead_code)]
+        #[inline]
+        pub fn line_ending(&mut self) -> bool {
+            let pos = $crate::runtime::Parser::pos(self);
+            let result = $crate::runtime::Parser::match_string(self, "\r\n") ||
+                         $crate::runtime::Parser::match_string(self, "\n") ||
+                         $crate::runtime::Parser::match_string(self, "\r");
+
+            if result {
+                self.skip_prefixes();
+            } else {
+                $crate::runtime::Parser::track(self, Rule::line_ending, pos);
+            }
+
+            result
+        }
     };
-    ( @filter [ $name:ident = @{ $( $_ts:tt )* } $( $tail:tt )* ] [ $( $rules:tt )* ] ) => {
-        impl_rdp!(@filter [ $( $tail )* ] [ $name $( $rules )* ]);
+    ( @builtin prefixed ) => {
+        #[allow(dead_code)]
+        pub fn prefixed(&mut self) -> bool {
+            let pos = self.input.pos();
+            let start = self.input.slice(0, pos).rfind(['\n', '\r']).map_or(0, |i| i + 1);
+            let line = self.input.slice(start, pos);
+            let mut len = 0;
+
+            for prefix in &self.prefixes {
+                if !line[len..].starts_with(prefix.as_str()) {
+                    return false;
+                }
+
+                len += prefix.len();
+            }
+
+            true
+        }
     };
-    ( @filter [ $name:ident = _{ $( $_ts:tt )* } $( $tail:tt )* ] [ $( $rules:tt )* ] ) => {
-        impl_rdp!(@filter [ $( $tail )* ] [ $( $rules )* ]);
+    ( @builtin prolog ) => {
+        #[allow(dead_code)]
+        pub fn prolog(&mut self) -> bool {
+            let pos = self.input.pos();
+
+            if pos != 0 {
+                return true;
+            }
+
+            self.input.match_string("\u{feff}");
+
+            let start = self.input.pos();
+            let shebang = {
+                let rest = self.input.slice(start, self.input.len());
+
+                // `#![` starts an inner attribute in Rust rather than a shebang
+                if rest.starts_with("#!") && !rest.starts_with("#![") {
+                    Some(rest.find(|c| c == '\n' || c == '\r').unwrap_or(rest.len()))
+                } else {
+                    None
+                }
+            };
+
+            if let Some(len) = shebang {
+                self.input.set_pos(start + len);
+                self.record_comment(start);
+
+                let _ = self.input.match_string("\r\n") || self.input.match_string("\n") ||
+                        self.input.match_string("\r");
+            }
+
+            self.mark_trivia(pos);
+
+            true
+        }
     };
 
     // implement empty whitespace rule
@@ -83,13 +567,74 @@ macro_rules! impl_rdp {
     };
     ( @ws whitespace = $( $_ts:tt )* ) => ();
     ( @ws $_name:ident = { $( $_ts:tt )* } $( $tail:tt )* ) => {
-        impl_rdp!(@ws $( $tail )*);
+        $crate::impl_rdp!(@ws $( $tail )*);
     };
     ( @ws $_name:ident = @{ $( $_ts:tt )* } $( $tail:tt )* ) => {
-        impl_rdp!(@ws $( $tail )*);
+        $crate::impl_rdp!(@ws $( $tail )*);
     };
     ( @ws $_name:ident = _{ $( $_ts:tt )* } $( $tail:tt )* ) => {
-        impl_rdp!(@ws $( $tail )*);
+        $crate::impl_rdp!(@ws $( $tail )*);
+    };
+    ( @ws $_name:ident = !{ $( $_ts:tt )* } $( $tail:tt )* ) => {
+        $crate::impl_rdp!(@ws $( $tail )*);
+    };
+    ( @ws $_name:ident = flat { $( $_ts:tt )* } $( $tail:tt )* ) => {
+        $crate::impl_rdp!(@ws $( $tail )*);
+    };
+    ( @ws $_name:ident = keywords ( $_ident:ident ) { $( $_ts:tt )* } $( $tail:tt )* ) => {
+        $crate::impl_rdp!(@ws $( $tail )*);
+    };
+    ( @ws $_name:ident = alias $_target:ident $( $tail:tt )* ) => {
+        $crate::impl_rdp!(@ws $( $tail )*);
+    };
+    ( @ws $_name:ident = external $_parser:ident :: $_rule:ident $( $tail:tt )* ) => {
+        $crate::impl_rdp!(@ws $( $tail )*);
+    };
+    ( @ws $_name:ident = token $( $tail:tt )* ) => {
+        $crate::impl_rdp!(@ws $( $tail )*);
+    };
+    ( @ws $_head:tt $( $tail:tt )* ) => {
+        $crate::impl_rdp!(@ws $( $tail )*);
+    };
+
+    // implement empty newline rule
+    ( @nl ) => {
+        #[allow(dead_code)]
+        #[inline]
+        pub fn newline(&mut self) -> bool {
+            false
+        }
+    };
+    ( @nl newline = $( $_ts:tt )* ) => ();
+    ( @nl $_name:ident = { $( $_ts:tt )* } $( $tail:tt )* ) => {
+        $crate::impl_rdp!(@nl $( $tail )*);
+    };
+    ( @nl $_name:ident = @{ $( $_ts:tt )* } $( $tail:tt )* ) => {
+        $crate::impl_rdp!(@nl $( $tail )*);
+    };
+    ( @nl $_name:ident = _{ $( $_ts:tt )* } $( $tail:tt )* ) => {
+        $crate::impl_rdp!(@nl $( $tail )*);
+    };
+    ( @nl $_name:ident = !{ $( $_ts:tt )* } $( $tail:tt )* ) => {
+        $crate::impl_rdp!(@nl $( $tail )*);
+    };
+    ( @nl $_name:ident = flat { $( $_ts:tt )* } $( $tail:tt )* ) => {
+        $crate::impl_rdp!(@nl $( $tail )*);
+    };
+    ( @nl $_name:ident = keywords ( $_ident:ident ) { $( $_ts:tt )* } $( $tail:tt )* ) => {
+        $crate::impl_rdp!(@nl $( $tail )*);
+    };
+    ( @nl $_name:ident = alias $_target:ident $( $tail:tt )* ) => {
+        $crate::impl_rdp!(@nl $( $tail )*);
+    };
+    ( @nl $_name:ident = external $_parser:ident :: $_rule:ident $( $tail:tt )* ) => {
+        $crate::impl_rdp!(@nl $( $tail )*);
+    };
+    ( @nl $_name:ident = token $( $tail:tt )* ) => {
+        $crate::impl_rdp!(@nl $( $tail )*);
+    };
+    ( @nl $_head:tt $( $tail:tt )* ) => {
+        $crate::impl_rdp!(@nl $( $tail )*);
     };
 
     // implement empty comment rule
@@ -102,107 +647,850 @@ macro_rules! impl_rdp {
     };
     ( @com comment = $( $_ts:tt )* ) => ();
     ( @com $_name:ident = { $( $_ts:tt )* } $( $tail:tt )* ) => {
-        impl_rdp!(@com $( $tail )*);
+        $crate::impl_rdp!(@com $( $tail )*);
     };
     ( @com $_name:ident = @{ $( $_ts:tt )* } $( $tail:tt )* ) => {
-        impl_rdp!(@com $( $tail )*);
+        $crate::impl_rdp!(@com $( $tail )*);
     };
     ( @com $_name:ident = _{ $( $_ts:tt )* } $( $tail:tt )* ) => {
-        impl_rdp!(@com $( $tail )*);
+        $crate::impl_rdp!(@com $( $tail )*);
+    };
+    ( @com $_name:ident = !{ $( $_ts:tt )* } $( $tail:tt )* ) => {
+        $crate::impl_rdp!(@com $( $tail )*);
+    };
+    ( @com $_name:ident = flat { $( $_ts:tt )* } $( $tail:tt )* ) => {
+        $crate::impl_rdp!(@com $( $tail )*);
+    };
+    ( @com $_name:ident = keywords ( $_ident:ident ) { $( $_ts:tt )* } $( $tail:tt )* ) => {
+        $crate::impl_rdp!(@com $( $tail )*);
+    };
+    ( @com $_name:ident = alias $_target:ident $( $tail:tt )* ) => {
+        $crate::impl_rdp!(@com $( $tail )*);
+    };
+    ( @com $_name:ident = external $_parser:ident :: $_rule:ident $( $tail:tt )* ) => {
+        $crate::impl_rdp!(@com $( $tail )*);
+    };
+    ( @com $_name:ident = token $( $tail:tt )* ) => {
+        $crate::impl_rdp!(@com $( $tail )*);
+    };
+    ( @com $_head:tt $( $tail:tt )* ) => {
+        $crate::impl_rdp!(@com $( $tail )*);
+    };
+
+    // functions! is expanded next to the impl, everything else inside of it
+    ( @mac functions { $( $_rest:tt )* } ) => ();
+    ( @mac process { $( $rest:tt )* } ) => {
+        $crate::process! {
+            $( $rest )*
+        }
+    };
+    ( @mac $mac:ident { $( $rest:tt )* } ) => {
+        $mac! {
+            $( $rest )*
+        }
+    };
+    ( @fns functions { $( $name:ident ),* $( , )* } ) => {
+        $(
+            #[allow(dead_code)]
+            pub fn $name<T: $crate::runtime::Input>(parser: &mut Rdp<T>) -> bool {
+                parser.$name()
+            }
+        )*
+    };
+    ( @fns $_mac:ident { $( $_rest:tt )* } ) => ();
+
+    // generate everything inside of its own module and alias Rdp and Rule
+    ( names! { $module:ident : $rdp:ident, $rule:ident $( , )* } $( $rest:tt )* ) => {
+        mod $module {
+            #![allow(unused_imports)]
+
+            use super::*;
+
+            $crate::impl_rdp! {
+                $( $rest )*
+            }
+        }
+
+        #[allow(dead_code)]
+        pub type $rdp<T> = $module::Rdp<T>;
+        #[allow(dead_code)]
+        pub type $rule = $module::Rule;
     };
 
+    ( token! { $tok:ty } grammar! { $( $ts:tt )* } $( $mac:ident! { $( $rest:tt )* } )* ) => {
+        $crate::impl_rdp! {
+            @rdp ($tok) grammar! { $( $ts )* } $( $mac! { $( $rest )* } )*
+        }
+    };
     ( grammar! { $( $ts:tt )* } $( $mac:ident! { $( $rest:tt )* } )* ) => {
-        use std::cell::Cell;
-        use std::cmp;
+        $crate::impl_rdp! {
+            @rdp ($crate::runtime::Token<Rule>) grammar! { $( $ts )* } $( $mac! { $( $rest )* } )*
+        }
+    };
 
-        pub struct Rdp<T: Input> {
+    ( @rdp ($tok:ty) grammar! { $( $ts:tt )* } $( $mac:ident! { $( $rest:tt )* } )* ) => {
+        // rules call the methods of these traits, so they need to be in scope without a glob
+        // import
+        #[allow(unused_imports)]
+        use $crate::runtime::Input as _;
+        #[allow(unused_imports)]
+        use $crate::runtime::Parser as _;
+        #[allow(unused_imports)]
+        use $crate::runtime::TokenData as _;
+
+        pub struct Rdp<T: $crate::runtime::Input> {
             input:       T,
-            queue:       Vec<Token<Rule>>,
-            queue_index: Cell<usize>,
+            queue:       Vec<$tok>,
+            subtrees:    Vec<Option<(usize, usize, usize)>>,
+            interner:    ::std::cell::RefCell<$crate::runtime::Interner>,
+            limits:      $crate::runtime::Limits,
+            limit_error: Option<$crate::runtime::LimitError>,
+            depth:       usize,
+            line_start:  usize,
+            line_end:    usize,
+            observer:    Option<(usize, Box<dyn FnMut($crate::runtime::Progress)>)>,
+            next_report: usize,
+            coverage:    Option<$crate::runtime::Coverage>,
+            profile:     Option<$crate::runtime::Profile>,
+            recording:   Option<$crate::runtime::Recording>,
+            memo:        ::std::cell::RefCell<$crate::runtime::MemoTable<Rule>>,
+            trivia:      $crate::runtime::Trivia,
+            trivia_run:  (usize, usize),
+            skip_trail:  bool,
+            last_end:    usize,
+            line_index:  Option<$crate::runtime::LineIndex>,
+            open_rule:   Option<Rule>,
+            high_water:  usize,
+            backtrack:   Option<$crate::runtime::Backtrack<Rule>>,
+            validation:  bool,
+            newline_hook: Option<Box<dyn FnMut(Option<Rule>) -> Option<Rule>>>,
+            terminated:  usize,
+            comments:    Vec<::std::ops::Range<usize>>,
+            started:     Option<::std::time::Instant>,
+            cancel:      Option<::std::sync::Arc<::std::sync::atomic::AtomicBool>>,
+            partial:     Option<$crate::runtime::Partial<Rule>>,
+            stream:      Option<(Vec<Rule>, Box<dyn FnMut($crate::runtime::Event<Rule>, &str)>)>,
+            streaming:   usize,
+            operators:   Vec<($tok, ::std::ops::Range<usize>)>,
+            embedded:    $crate::runtime::EmbeddedTable<Rule>,
+            first_sets:  ::std::collections::HashMap<&'static str,
+                                                     Option<$crate::runtime::FirstSet>>,
+            fixities:    Vec<(String, Option<Rule>, u8, bool)>,
+            tracking:    $crate::runtime::Tracking,
+            normalize:   bool,
             failures:    Vec<Rule>,
             fail_pos:    usize,
+            terminals:   Vec<$crate::runtime::Expected<Rule>>,
+            terminal_pos: usize,
+            delimiters:  Vec<(usize, usize)>,
+            unclosed:    Option<$crate::runtime::Unclosed>,
+            prefixes:    Vec<String>,
+            skipping_ws: bool,
             atomic:      bool,
             comment:     bool,
             eoi_matched: bool
         }
 
-        impl_rdp!(@filter [ $( $ts )* ] []);
+        $crate::impl_rdp!(@filter [ $( $ts )* ] [] [] []
+                          ([any_byte] [any_ascii] [line_ending] [prefixed] [prolog]));
 
-        impl<T: Input> Rdp<T> {
+        impl<T: $crate::runtime::Input> Rdp<T> {
             pub fn new(input: T) -> Rdp<T> {
                 Rdp {
-                    input:       input,
+                    input,
                     queue:       vec![],
-                    queue_index: Cell::new(0),
+                    subtrees:    vec![],
+                    interner:    ::std::cell::RefCell::new($crate::runtime::Interner::new()),
+                    limits:      $crate::runtime::Limits::new(),
+                    limit_error: None,
+                    depth:       0,
+                    line_start:  0,
+                    line_end:    0,
+                    observer:    None,
+                    next_report: 0,
+                    coverage:    None,
+                    profile:     None,
+                    recording:   None,
+                    memo:        ::std::cell::RefCell::new(::std::collections::HashMap::new()),
+                    trivia:      $crate::runtime::Trivia::Detached,
+                    trivia_run:  (0, 0),
+                    skip_trail:  false,
+                    last_end:    0,
+                    line_index:  None,
+                    open_rule:   None,
+                    high_water:  0,
+                    backtrack:   None,
+                    validation:  false,
+                    newline_hook: None,
+                    terminated:  0,
+                    comments:    vec![],
+                    started:     None,
+                    cancel:      None,
+                    partial:     None,
+                    stream:      None,
+                    streaming:   0,
+                    operators:   vec![],
+                    embedded:    ::std::collections::HashMap::new(),
+                    first_sets:  ::std::collections::HashMap::new(),
+                    fixities:    vec![],
+                    tracking:    $crate::runtime::Tracking::Full,
+                    normalize:   false,
                     failures:    vec![],
                     fail_pos:    0,
+                    terminals:   vec![],
+                    terminal_pos: 0,
+                    delimiters:  vec![],
+                    unclosed:    None,
+                    prefixes:    vec![],
+                    skipping_ws: false,
                     atomic:      false,
                     comment:     false,
                     eoi_matched: false
                 }
             }
 
-            impl_rdp!(@ws $( $ts )*);
-            impl_rdp!(@com $( $ts )*);
+            // number of Tokens nested inside of the one at index, as recorded when it was inserted,
+            // or by their spans if it was pushed or changed by hand
+            fn descendants_len(&self, index: usize) -> usize {
+                let token = &self.queue[index];
+                let rest = &self.queue[index + 1..];
+
+                match self.subtrees.get(index) {
+                    Some(&Some((start, end, len))) if start == token.start() &&
+                                                      end == token.end() => {
+                        ::std::cmp::min(len, rest.len())
+                    },
+                    _ => rest.iter().take_while(|other| {
+                        token.token().is_ancestor_of(&other.token())
+                    }).count()
+                }
+            }
 
-            #[allow(dead_code)]
-            #[inline]
-            pub fn any(&mut self) -> bool {
-                if self.end() {
-                    let pos = self.pos();
+            // index of the closest Token before the one at index that has it as a descendant
+            fn parent_index(&self, index: usize) -> Option<usize> {
+                (0..index).rev().find(|&i| i + self.descendants_len(i) >= index)
+            }
 
-                    self.track(Rule::any, pos);
+            // matches left, then checks whether right matches the same text at the same position
+            fn match_class<L, R>(&mut self, left: L, right: R, same: bool) -> bool
+                where L: FnOnce(&mut Self) -> bool, R: FnOnce(&mut Self) -> bool {
 
-                    false
-                } else {
-                    let next = self.pos() + 1;
-                    self.set_pos(next);
+                let pos = self.input.pos();
+                let len = self.queue.len();
 
-                    true
+                if !left(self) {
+                    return false;
                 }
-            }
 
-            #[allow(dead_code)]
-            #[inline]
-            pub fn eoi(&mut self) -> bool {
-                let result = self.end();
+                let end = self.input.pos();
+                let queued = self.queue.len();
 
-                if !result {
-                    let pos = self.pos();
+                self.input.set_pos(pos);
 
-                    self.track(Rule::eoi, pos);
-                } else {
+                let result = right(self) && self.input.pos() == end;
+
+                self.queue.truncate(queued);
+
+                if result == same {
+                    self.input.set_pos(end);
+
+                    true
+                } else {
+                    self.input.set_pos(pos);
+                    self.queue.truncate(len);
+
+                    false
+                }
+            }
+
+            // records trivia skipped from pos to the current position
+            #[inline]
+            fn mark_trivia(&mut self, pos: usize) {
+                let new_pos = self.input.pos();
+
+                if new_pos > pos {
+                    if pos != self.trivia_run.1 {
+                        self.trivia_run.0 = pos;
+                    }
+
+                    self.trivia_run.1 = new_pos;
+                }
+            }
+
+            // saves what matching can change, so that a speculative match can be undone
+            fn save(&self) -> $crate::runtime::SavedState<Rule> {
+                $crate::runtime::SavedState {
+                    pos:          self.input.pos(),
+                    queue_len:    self.queue.len(),
+                    operators:    self.operators.len(),
+                    limit_error:  self.limit_error,
+                    partial:      self.partial.clone(),
+                    depth:        self.depth,
+                    line_start:   self.line_start,
+                    line_end:     self.line_end,
+                    trivia_run:   self.trivia_run,
+                    last_end:     self.last_end,
+                    open_rule:    self.open_rule,
+                    terminated:   self.terminated,
+                    comments:     self.comments.clone(),
+                    streaming:    self.streaming,
+                    failures:     self.failures.clone(),
+                    fail_pos:     self.fail_pos,
+                    terminals:    self.terminals.clone(),
+                    terminal_pos: self.terminal_pos,
+                    delimiters:   self.delimiters.clone(),
+                    unclosed:     self.unclosed,
+                    eoi_matched:  self.eoi_matched
+                }
+            }
+
+            // undoes everything matched since state was saved; memoized results of the Tokens
+            // that are dropped are dropped as well
+            fn restore(&mut self, state: $crate::runtime::SavedState<Rule>) {
+                self.input.set_pos(state.pos);
+                self.queue.truncate(state.queue_len);
+                self.subtrees.truncate(state.queue_len);
+                self.operators.truncate(state.operators);
+                self.memo.borrow_mut().retain(|&(_, index, _, _), _| index < state.queue_len);
+
+                self.limit_error = state.limit_error;
+                self.partial = state.partial;
+                self.depth = state.depth;
+                self.line_start = state.line_start;
+                self.line_end = state.line_end;
+                self.trivia_run = state.trivia_run;
+                self.last_end = state.last_end;
+                self.open_rule = state.open_rule;
+                self.terminated = state.terminated;
+                self.comments = state.comments;
+                self.streaming = state.streaming;
+                self.failures = state.failures;
+                self.fail_pos = state.fail_pos;
+                self.terminals = state.terminals;
+                self.terminal_pos = state.terminal_pos;
+                self.delimiters = state.delimiters;
+                self.unclosed = state.unclosed;
+                self.eoi_matched = state.eoi_matched;
+            }
+
+            // returns the end of the trivia at pos, where failures are tracked with error
+            // normalization; the trivia is only looked at, so nothing it matches is kept
+            fn trivia_end(&mut self, pos: usize) -> usize {
+                if self.skipping_ws || self.comment {
+                    return pos;
+                }
+
+                if self.trivia_run.0 == pos && self.trivia_run.1 > pos {
+                    return self.trivia_run.1;
+                }
+
+                let state = self.save();
+                let newline_hook = self.newline_hook.take();
+                let stream = self.stream.take();
+                let coverage = self.coverage.take();
+                let profile = self.profile.take();
+                let recording = self.recording.take();
+                let tracking = ::std::mem::replace(&mut self.tracking,
+                                                   $crate::runtime::Tracking::Off);
+
+                self.input.set_pos(pos);
+                $crate::runtime::Parser::skip_com(self);
+                $crate::runtime::Parser::skip_ws(self);
+
+                let end = self.input.pos();
+
+                self.restore(state);
+                self.newline_hook = newline_hook;
+                self.stream = stream;
+                self.coverage = coverage;
+                self.profile = profile;
+                self.recording = recording;
+                self.tracking = tracking;
+
+                end
+            }
+
+            // whether strings and ranges are tracked; trivia is skipped since its strings are
+            // expected everywhere
+            #[inline]
+            fn tracks_terminals(&self) -> bool {
+                !self.atomic && !self.comment && !self.skipping_ws &&
+                self.tracking != $crate::runtime::Tracking::Off
+            }
+
+            // records a string or range that failed at pos like `track` records rules
+            fn track_terminal<F, G>(&mut self, pos: usize, same: F, expected: G)
+                where F: Fn(&$crate::runtime::Expected<Rule>) -> bool,
+                      G: FnOnce() -> $crate::runtime::Expected<Rule> {
+
+                if !self.tracks_terminals() {
+                    return
+                }
+
+                let pos = if self.normalize { self.trivia_end(pos) } else { pos };
+
+                let cap = match self.tracking {
+                    $crate::runtime::Tracking::Capped(n) => n,
+                    _                           => usize::MAX
+                };
+
+                if pos > self.terminal_pos {
+                    self.terminals.clear();
+
+                    self.terminal_pos = pos;
+                } else if pos < self.terminal_pos {
+                    return
+                }
+
+                if self.terminals.len() < cap && !self.terminals.iter().any(same) {
+                    self.terminals.push(expected());
+                }
+            }
+
+            // opens or closes a delimiter matched at pos, or records the innermost open one in case
+            // its closing delimiter failed at the farthest position so far
+            fn track_delimiter(&mut self, string: &str, pos: usize, matched: bool) {
+                let (pair, opens) = match $crate::runtime::Unclosed::delimiter(string) {
+                    Some(delimiter) if self.tracks_terminals() => delimiter,
+                    _                                          => return
+                };
+
+                let open = self.delimiters.iter().rposition(|&(other, _)| other == pair);
+
+                let farther = self.unclosed.is_none_or(|unclosed| pos > unclosed.pos);
+
+                match (matched, opens, open) {
+                    (true, true, _)                    => self.delimiters.push((pair, pos)),
+                    (true, false, Some(i))             => self.delimiters.truncate(i),
+                    (false, false, Some(i)) if farther => {
+                        let start = self.delimiters[i].1;
+
+                        self.unclosed = Some($crate::runtime::Unclosed::new(pair, start, pos));
+                    },
+                    _                                  => ()
+                }
+            }
+
+            // moves back to pos and records how far behind the farthest position read so far it is
+            #[inline]
+            fn rewind(&mut self, pos: usize) {
+                self.high_water = ::std::cmp::max(self.high_water, self.input.pos());
+
+                if let Some(ref mut recording) = self.recording {
+                    let from = self.input.pos();
+
+                    if pos < from {
+                        recording.record($crate::runtime::Step::Backtrack { from, to: pos });
+                    }
+                }
+
+                if pos < self.high_water {
+                    let distance = self.high_water - pos;
+
+                    if self.backtrack.map_or(true, |backtrack| distance > backtrack.distance) {
+                        self.backtrack = Some($crate::runtime::Backtrack {
+                            distance,
+                            from: self.high_water,
+                            to:   pos,
+                            rule: self.open_rule
+                        });
+                    }
+                }
+
+                while self.delimiters.last().is_some_and(|&(_, start)| start >= pos) {
+                    self.delimiters.pop();
+                }
+
+                self.input.set_pos(pos);
+            }
+
+            // calls visit with every reserved word of a keywords rule
+            #[allow(unused_mut, unused_variables)]
+            fn reserved_words<F>(rule: Rule, mut visit: F) where F: FnMut(&'static str) {
+                $crate::grammar!(@keywords rule visit $( $ts )*);
+            }
+
+            // records the first exceeded limit along with what was matched so far
+            fn exceed(&mut self, kind: $crate::runtime::LimitKind, pos: usize) {
+                let error = $crate::runtime::LimitError { kind, pos };
+
+                self.limit_error = Some(error);
+                self.partial = Some($crate::runtime::Partial {
+                    error,
+                    tokens:   self.queue.iter().map(|token| token.token()).collect(),
+                    expected: $crate::runtime::Parser::expected(self)
+                });
+            }
+
+            // lexes the rest of the input, longest match first, with the rules returned for the
+            // Rule of the previous Lexeme
+            fn lex_with<'r, F>(&mut self, mut rules_after: F) -> Vec<$crate::runtime::Lexeme<Rule>>
+                where F: FnMut(Option<Rule>) -> &'r [fn(&mut Self) -> bool], Self: 'r {
+
+                let len = self.queue.len();
+                let end = self.input.len();
+                let mut pos = self.input.pos();
+                let mut error = None;
+                let mut last = None;
+                let mut lexemes = vec![];
+
+                while pos < end {
+                    let mut longest: Option<($crate::runtime::LexemeKind<Rule>, usize)> = None;
+
+                    for rule in rules_after(last.take()) {
+                        self.input.set_pos(pos);
+
+                        if rule(self) && self.input.pos() > longest.map_or(pos, |l| l.1) {
+                            let kind = match self.queue.get(len) {
+                                Some(token) => $crate::runtime::LexemeKind::Rule(token.rule()),
+                                None        => $crate::runtime::LexemeKind::Trivia
+                            };
+
+                            longest = Some((kind, self.input.pos()));
+                        }
+
+                        self.queue.truncate(len);
+                    }
+
+                    match longest {
+                        Some((kind, new_pos)) => {
+                            if let Some(start) = error.take() {
+                                lexemes.push($crate::runtime::Lexeme {
+                                    kind:  $crate::runtime::LexemeKind::Error,
+                                    start,
+                                    end:   pos
+                                });
+                            }
+
+                            if let $crate::runtime::LexemeKind::Rule(rule) = kind {
+                                last = Some(rule);
+                            }
+
+                            lexemes.push($crate::runtime::Lexeme {
+                                kind,
+                                start: pos,
+                                end:   new_pos
+                            });
+
+                            pos = new_pos;
+                        },
+                        None => {
+                            if error.is_none() {
+                                error = Some(pos);
+                            }
+
+                            self.input.set_pos(pos);
+                            $crate::runtime::Parser::skip(self, 1);
+
+                            pos = self.input.pos();
+                        }
+                    }
+                }
+
+                if let Some(start) = error {
+                    lexemes.push($crate::runtime::Lexeme {
+                        kind: $crate::runtime::LexemeKind::Error,
+                        start,
+                        end:  pos
+                    });
+                }
+
+                self.input.set_pos(pos);
+
+                lexemes
+            }
+
+            // records the span of a skipped comment once, even after backtracking
+            #[inline]
+            fn record_comment(&mut self, start: usize) {
+                let end = self.input.pos();
+
+                if let Err(i) = self.comments.binary_search_by_key(&start, |span| span.start) {
+                    self.comments.insert(i, start..end);
+                }
+            }
+
+            // skips the registered line prefixes after a line ending, outermost first, stopping at
+            // the first missing one so that lazy continuation lines still match
+            fn skip_prefixes(&mut self) {
+                for prefix in &self.prefixes {
+                    if !self.input.match_string(prefix) {
+                        break;
+                    }
+                }
+            }
+
+            // lets the newline hook insert a terminator Token at pos
+            #[inline]
+            fn insert_terminator(&mut self, pos: usize) {
+                let last = self.queue.last().map(|token| token.rule());
+
+                if let Some(rule) = self.newline_hook.as_mut().and_then(|hook| hook(last)) {
+                    $crate::runtime::Parser::inject(self, rule, pos);
+                    self.terminated = self.queue.len();
+                }
+            }
+
+            $crate::impl_rdp!(@ws $( $ts )*);
+            $crate::impl_rdp!(@nl $( $ts )*);
+            $crate::impl_rdp!(@com $( $ts )*);
+
+            #[allow(dead_code)]
+            #[inline]
+            pub fn any(&mut self) -> bool {
+                if $crate::runtime::Parser::end(self) {
+                    let pos = $crate::runtime::Parser::pos(self);
+
+                    $crate::runtime::Parser::track(self, Rule::any, pos);
+
+                    false
+                } else {
+                    let pos = $crate::runtime::Parser::pos(self);
+                    let len = self.input.slice(pos, self.input.len()).chars().next()
+                                        .map_or(1, |c| c.len_utf8());
+
+                    $crate::runtime::Parser::set_pos(self, pos + len);
+
+                    true
+                }
+            }
+
+            #[allow(dead_code)]
+            #[inline]
+            pub fn eoi(&mut self) -> bool {
+                let result = $crate::runtime::Parser::end(self);
+
+                if !result {
+                    let pos = $crate::runtime::Parser::pos(self);
+
+                    $crate::runtime::Parser::track(self, Rule::eoi, pos);
+                } else {
                     self.eoi_matched = true;
                 }
 
                 result
             }
 
-            grammar! {
-                $( $ts )*
+            #[allow(dead_code)]
+            #[inline]
+            pub fn terminator(&mut self) -> bool {
+                let result = self.terminated != 0 && self.terminated == self.queue.len();
+
+                if result {
+                    self.terminated = 0;
+                }
+
+                result
             }
 
             $(
-                $mac! {
-                    $( $rest )*
-                }
+                $crate::impl_rdp!(@mac $mac { $( $rest )* });
             )*
         }
 
-        impl<T: Input> Parser for Rdp<T> {
+        $crate::grammar! {
+            $( $ts )*
+        }
+
+        $(
+            $crate::impl_rdp!(@fns $mac { $( $rest )* });
+        )*
+
+        impl<T: $crate::runtime::Input> $crate::runtime::Parser for Rdp<T> {
             type Rule = Rule;
-            type Token = Token<Rule>;
+            type Token = $tok;
 
             #[inline]
             fn match_string(&mut self, string: &str) -> bool {
-                self.input.match_string(string)
+                let pos = self.input.pos();
+                let result = self.input.match_string(string);
+
+                if !result {
+                    self.track_terminal(pos, |expected| match *expected {
+                        $crate::runtime::Expected::String(ref other) => other == string,
+                        _                                   => false
+                    }, || $crate::runtime::Expected::String(string.to_owned()));
+                }
+
+                self.track_delimiter(string, pos, result);
+
+                result
             }
 
             #[inline]
             fn match_range(&mut self, left: char, right: char) -> bool {
-                self.input.match_range(left, right)
+                let result = self.input.match_range(left, right);
+
+                if !result {
+                    let pos = self.input.pos();
+                    let range = $crate::runtime::Expected::Range(left, right);
+
+                    self.track_terminal(pos, |expected| *expected == range, || range.clone());
+                }
+
+                result
+            }
+
+            fn match_difference<L, R>(&mut self, left: L, right: R) -> bool
+                where L: FnOnce(&mut Self) -> bool, R: FnOnce(&mut Self) -> bool {
+
+                self.match_class(left, right, false)
+            }
+
+            fn match_intersection<L, R>(&mut self, left: L, right: R) -> bool
+                where L: FnOnce(&mut Self) -> bool, R: FnOnce(&mut Self) -> bool {
+
+                self.match_class(left, right, true)
+            }
+
+            fn match_nested(&mut self, open: &str, close: &str) -> bool {
+                let pos = self.input.pos();
+
+                if open.is_empty() || close.is_empty() || !self.input.match_string(open) {
+                    self.track_terminal(pos, |expected| match *expected {
+                        $crate::runtime::Expected::String(ref other) => other == open,
+                        _                                   => false
+                    }, || $crate::runtime::Expected::String(open.to_owned()));
+
+                    return false;
+                }
+
+                let start = pos + open.len();
+                let end = {
+                    // both delimiters start at char boundaries, so scanning bytes is safe
+                    let rest = self.input.slice(start, self.input.len()).as_bytes();
+                    let mut depth = 1usize;
+                    let mut i = 0;
+
+                    loop {
+                        if i >= rest.len() {
+                            break None;
+                        }
+
+                        if rest[i..].starts_with(close.as_bytes()) {
+                            depth -= 1;
+                            i += close.len();
+
+                            if depth == 0 {
+                                break Some(start + i);
+                            }
+                        } else if rest[i..].starts_with(open.as_bytes()) {
+                            depth += 1;
+                            i += open.len();
+                        } else {
+                            i += 1;
+                        }
+                    }
+                };
+
+                match end {
+                    Some(end) => {
+                        self.input.set_pos(end);
+
+                        true
+                    },
+                    None      => {
+                        let len = self.input.len();
+
+                        self.track_terminal(len, |expected| match *expected {
+                            $crate::runtime::Expected::String(ref other) => other == close,
+                            _                                   => false
+                        }, || $crate::runtime::Expected::String(close.to_owned()));
+                        self.input.set_pos(pos);
+
+                        false
+                    }
+                }
             }
 
+            fn with_prefix<F>(&mut self, prefix: &str, rule: F) -> bool
+                where F: FnOnce(&mut Self) -> bool {
+
+                self.prefixes.push(prefix.to_owned());
+
+                let result = rule(self);
+
+                self.prefixes.pop();
+
+                result
+            }
+
+            fn skip_to<F>(&mut self, rule: &'static str, mut matcher: F) -> bool
+                where F: FnMut(&mut Self) -> bool {
+
+                if !self.first_sets.contains_key(rule) {
+                    let alternatives = $crate::runtime::Parser::alternatives(self);
+                    let first = $crate::runtime::FirstSet::of(&alternatives, rule);
+
+                    self.first_sets.insert(rule, first);
+                }
+
+                let start = self.input.pos();
+                let end = self.input.len();
+                let len = self.queue.len();
+                let tracking = self.tracking;
+                let mut pos = start;
+                let mut found = false;
+
+                self.tracking = $crate::runtime::Tracking::Off;
+
+                loop {
+                    if let Some(ref first) = self.first_sets[rule] {
+                        match first.find(self.input.slice(pos, end)) {
+                            Some(offset) => pos += offset,
+                            None         => break
+                        }
+                    }
+
+                    self.input.set_pos(pos);
+
+                    if $crate::runtime::Parser::try(self, true, &mut matcher) {
+                        self.queue.truncate(len);
+
+                        found = true;
+
+                        break;
+                    }
+
+                    match self.input.slice(pos, end).chars().next() {
+                        Some(c) => pos += c.len_utf8(),
+                        None    => break
+                    }
+                }
+
+                self.tracking = tracking;
+                self.input.set_pos(if found { pos } else { start });
+
+                found
+            }
+
+            fn match_heredoc(&mut self, open: &str) -> bool {
+                let pos = self.input.pos();
+                let len = {
+                    let rest = self.input.slice(pos, self.input.len());
+
+                    $crate::runtime::Heredoc::parse(open, rest).map(|(_, len)| len)
+                };
+
+                match len {
+                    Some(len) => {
+                        self.input.set_pos(pos + len);
+
+                        true
+                    },
+                    None      => {
+                        self.track_terminal(pos, |expected| match *expected {
+                            $crate::runtime::Expected::String(ref other) => other == open,
+                            _                                   => false
+                        }, || $crate::runtime::Expected::String(open.to_owned()));
+
+                        false
+                    }
+                }
+            }
+
+            // unlike parse_at, only the position and the queue are undone, since the failures
+            // of alternatives have to stay tracked and exceeded limits have to stop the parse
             #[inline]
             fn try<F>(&mut self, revert: bool, rule: F) -> bool
                 where F: FnOnce(&mut Self) -> bool {
@@ -213,7 +1501,7 @@ macro_rules! impl_rdp {
                 let result = rule(self);
 
                 if revert || !result {
-                    self.input.set_pos(pos);
+                    self.rewind(pos);
                 }
 
                 if !result {
@@ -223,126 +1511,815 @@ macro_rules! impl_rdp {
                 result
             }
 
-            fn prec_climb<F, G>(&mut self, pos: usize, left: usize, min_prec: u8,
-                                last_op: Option<(Option<Rule>, u8, bool)>, primary: &mut F,
-                                climb: &mut G) -> (Option<(Option<Rule>, u8, bool)>, Option<usize>)
-                where F: FnMut(&mut Self) -> bool,
-                      G: FnMut(&mut Self) -> Option<(Option<Rule>, u8, bool)> {
+            fn parse_at<F>(&mut self, pos: usize, rule: F) -> bool
+                where F: FnOnce(&mut Self) -> bool {
 
-                let mut op = if last_op.is_some() {
-                    last_op
-                } else {
-                    climb(self)
-                };
-                let mut last_right = None;
+                let len = self.input.len();
 
-                while let Some((rule, prec, _)) = op {
-                    if prec >= min_prec {
-                        let mut new_pos = self.pos();
-                        let mut right = self.pos();
-                        let queue_pos = self.queue.len();
+                if !self.input.slice(0, len).is_char_boundary(pos) {
+                    return false;
+                }
 
-                        primary(self);
+                let state = self.save();
 
-                        if let Some(token) = self.queue.get(queue_pos) {
-                            new_pos = token.start;
-                            right   = token.end;
-                        }
+                self.input.set_pos(pos);
 
-                        op = climb(self);
+                let result = rule(self);
 
-                        while let Some((_, new_prec, right_assoc)) = op {
-                            if new_prec > prec || right_assoc && new_prec == prec {
-                                let (new_op, new_lr) = self.prec_climb(queue_pos, new_pos,
-                                                                       new_prec, op, primary,
-                                                                       climb);
+                if !result {
+                    self.restore(state);
+                }
+
+                result
+            }
+
+            fn parse_complete<F>(&mut self, rule: F) -> bool
+                where F: FnOnce(&mut Self) -> bool {
+
+                let pos = self.input.pos();
+                let len = self.queue.len();
+
+                let result = rule(self) && {
+                    $crate::runtime::Parser::skip_com(self);
+                    $crate::runtime::Parser::skip_ws(self);
+
+                    self.eoi()
+                };
+
+                if !result {
+                    self.input.set_pos(pos);
+                    self.queue.truncate(len);
+                }
+
+                result
+            }
+
+            fn prec_climb<F, G>(&mut self, pos: usize, left: usize, min_prec: u8,
+                                last_op: Option<(Option<Rule>, u8, bool, ::std::ops::Range<usize>)>,
+                                primary: &mut F, climb: &mut G)
+                                -> (Option<(Option<Rule>, u8, bool, ::std::ops::Range<usize>)>,
+                                    Option<usize>)
+                where F: FnMut(&mut Self) -> bool,
+                      G: FnMut(&mut Self)
+                                -> Option<(Option<Rule>, u8, bool, ::std::ops::Range<usize>)> {
+
+                let mut op = if last_op.is_some() {
+                    last_op
+                } else {
+                    climb(self)
+                };
+                let mut last_right = None;
+
+                while let Some((rule, prec, _, operator)) = op.clone() {
+                    if prec >= min_prec {
+                        let mut new_pos = $crate::runtime::Parser::pos(self);
+                        let mut right = $crate::runtime::Parser::pos(self);
+                        let queue_pos = self.queue.len();
+
+                        primary(self);
+
+                        if let Some(token) = self.queue.get(queue_pos) {
+                            new_pos = token.start();
+                            right   = token.end();
+                        }
+
+                        op = climb(self);
+
+                        while let Some((_, new_prec, right_assoc, _)) = op {
+                            if new_prec > prec || right_assoc && new_prec == prec {
+                                let (new_op, new_lr) =
+                                    $crate::runtime::Parser::prec_climb(self, queue_pos, new_pos,
+                                                                        new_prec, op, primary,
+                                                                        climb);
+
+                                op = new_op;
+                                last_right = new_lr;
+                            } else {
+                                break
+                            }
+                        }
+
+                        if let Some(pos) = last_right {
+                            right = ::std::cmp::max(pos, right);
+                        } else {
+                            last_right = Some(right);
+                        }
+
+                        if let Some(rule) = rule {
+                            let token = <$tok as $crate::runtime::TokenData<Rule>>::new(rule, left,
+                                                                                      right);
+
+                            $crate::runtime::Parser::insert_parent(self, pos, token);
+                            self.operators.push((token, operator));
+                        }
+                    } else {
+                        return (op, last_right)
+                    }
+                }
+
+                (op, last_right)
+            }
+
+            fn add_operator(&mut self, operator: &str, rule: Option<Rule>, prec: u8,
+                            right_assoc: bool) {
+                self.fixities.retain(|fixity| fixity.0 != operator);
+                self.fixities.push((operator.to_owned(), rule, prec, right_assoc));
+            }
+
+            fn remove_operator(&mut self, operator: &str) {
+                self.fixities.retain(|fixity| fixity.0 != operator);
+            }
+
+            fn match_operator(&mut self)
+                              -> Option<(Option<Rule>, u8, bool, ::std::ops::Range<usize>)> {
+                let pos = self.input.pos();
+                let fixity = {
+                    let rest = self.input.slice(pos, self.input.len());
+
+                    self.fixities.iter().filter(|fixity| {
+                        !fixity.0.is_empty() && rest.starts_with(&fixity.0[..])
+                    }).max_by_key(|fixity| fixity.0.len()).cloned()
+                };
+
+                fixity.map(|(operator, rule, prec, right_assoc)| {
+                    let end = pos + operator.len();
+
+                    self.input.set_pos(end);
+
+                    (rule, prec, right_assoc, pos..end)
+                })
+            }
+
+            fn operator_of(&self, token: &Self::Token) -> Option<::std::ops::Range<usize>> {
+                self.operators.iter().rev().find(|&&(ref other, _)| other == token)
+                              .map(|&(_, ref operator)| operator.clone())
+            }
+
+            #[inline]
+            fn pos(&self) -> usize {
+                self.input.pos()
+            }
+
+            #[inline]
+            fn set_pos(&mut self, pos: usize) {
+                self.rewind(pos);
+            }
+
+            fn skip(&mut self, n: usize) -> bool {
+                let pos = self.input.pos();
+                let len = self.input.len();
+
+                let to = {
+                    let rest = self.input.slice(pos, len);
+
+                    if n == 0 {
+                        Some(0)
+                    } else {
+                        rest.char_indices().nth(n - 1).map(|(i, c)| i + c.len_utf8())
+                    }
+                };
+
+                match to {
+                    Some(to) => {
+                        self.input.set_pos(pos + to);
+
+                        true
+                    },
+                    None => false
+                }
+            }
+
+            fn at_column(&mut self, column: usize) -> bool {
+                let pos = self.input.pos();
+                let string = self.input.slice(0, self.input.len());
+
+                if self.line_index.is_none() {
+                    self.line_index = Some($crate::runtime::LineIndex::new(string));
+                }
+
+                self.line_index.as_ref().unwrap().line_col(string, pos).1 == column
+            }
+
+            #[inline]
+            fn end(&self) -> bool {
+                self.input.len() == self.input.pos()
+            }
+
+            #[inline]
+            fn eoi_matched(&self) -> bool {
+                self.eoi_matched
+            }
+
+            #[inline]
+            fn reset(&mut self) {
+                let start = self.input.start();
+
+                self.input.set_pos(start);
+                self.queue.clear();
+                self.subtrees.clear();
+                self.failures.clear();
+                self.fail_pos = 0;
+                self.terminals.clear();
+                self.terminal_pos = 0;
+                self.delimiters.clear();
+                self.unclosed = None;
+                self.limit_error = None;
+                self.depth = 0;
+                self.line_start = 0;
+                self.line_end = 0;
+                self.next_report = 0;
+                self.memo.borrow_mut().clear();
+                self.trivia_run = (0, 0);
+                self.last_end = 0;
+                self.open_rule = None;
+                self.high_water = 0;
+                self.backtrack = None;
+
+                if let Some(ref mut recording) = self.recording {
+                    recording.clear();
+                }
+
+                self.terminated = 0;
+                self.comments.clear();
+                self.started = None;
+                self.partial = None;
+                self.streaming = 0;
+                self.operators.clear();
+                self.embedded.clear();
+            }
+
+            fn run<F>(&mut self, rule: F) -> $crate::runtime::ParseResult<Rule>
+                where F: FnOnce(&mut Self) -> bool {
+
+                let matched = rule(self);
+                let input: ::std::sync::Arc<str> = self.input.slice(0, self.input.len()).into();
+
+                let result = $crate::runtime::ParseResult {
+                    matched,
+                    end:         self.input.pos(),
+                    tokens:      self.queue.iter().map(|token| token.token()).collect(),
+                    line_index:  $crate::runtime::LineIndex::new(&input),
+                    input,
+                    expected:    $crate::runtime::Parser::expected(self),
+                    unclosed:    $crate::runtime::Parser::unclosed(self),
+                    limit_error: self.limit_error
+                };
+
+                $crate::runtime::Parser::reset(self);
+
+                result
+            }
+
+            fn lex(&mut self,
+                   rules: &[fn(&mut Self) -> bool]) -> Vec<$crate::runtime::Lexeme<Rule>> {
+                self.lex_with(|_| rules)
+            }
+
+            fn lex_modes(&mut self, modes: &$crate::runtime::Modes<Self>)
+                         -> Vec<$crate::runtime::Lexeme<Rule>> {
+                let mut stack = vec![0];
+
+                self.lex_with(|last| {
+                    if let Some(rule) = last {
+                        modes.apply(&rule, &mut stack);
+                    }
+
+                    modes.rules(stack[stack.len() - 1])
+                })
+            }
+
+            #[inline]
+            fn input_len(&self) -> usize {
+                self.input.len()
+            }
+
+            #[inline]
+            fn slice_input(&self, start: usize, end: usize) -> &str {
+                self.input.slice(start, end)
+            }
+
+            #[inline]
+            fn sub_input(&self, start: usize, end: usize) -> $crate::runtime::StringInput<'_> {
+                let string = self.input.slice(0, self.input.len());
+
+                $crate::runtime::StringInput::with_range(string, start, end)
+            }
+
+            #[inline]
+            fn intern(&self, start: usize, end: usize) -> $crate::runtime::Symbol {
+                self.interner.borrow_mut().intern(self.input.slice(start, end))
+            }
+
+            #[inline]
+            fn interner(&self) -> ::std::cell::Ref<'_, $crate::runtime::Interner> {
+                self.interner.borrow()
+            }
+
+            fn set_limits(&mut self, limits: $crate::runtime::Limits) {
+                self.limits = limits;
+            }
+
+            fn max_backtrack(&self) -> Option<$crate::runtime::Backtrack<Rule>> {
+                self.backtrack
+            }
+
+            fn backtrack_bounds(&self) -> $crate::runtime::BacktrackBounds {
+                type Generated<'a> = Rdp<$crate::runtime::StringInput<'a>>;
+
+                let rules = <Generated as $crate::runtime::GeneratedParser>::rules();
+                let atomic: Vec<_> = rules.iter().filter(|rule| {
+                    rule.category() == $crate::runtime::RuleKind::Atomic
+                }).map(|&rule| {
+                    <Generated as $crate::runtime::GeneratedParser>::rule_name(rule)
+                }).collect();
+
+                let alternatives = $crate::runtime::Parser::alternatives(self);
+
+                $crate::runtime::BacktrackBounds::new(&alternatives, &atomic)
+            }
+
+            fn limit_error(&self) -> Option<$crate::runtime::LimitError> {
+                self.limit_error
+            }
+
+            fn set_cancellation(&mut self,
+                                flag: ::std::sync::Arc<::std::sync::atomic::AtomicBool>) {
+                self.cancel = Some(flag);
+            }
+
+            fn partial(&self) -> Option<&$crate::runtime::Partial<Rule>> {
+                self.partial.as_ref()
+            }
+
+            fn set_observer<F>(&mut self, interval: usize, observer: F)
+                where F: FnMut($crate::runtime::Progress) + 'static {
+
+                self.observer = Some((interval, Box::new(observer)));
+                self.next_report = self.input.pos();
+            }
+
+            fn clear_observer(&mut self) {
+                self.observer = None;
+            }
+
+            fn set_newline_hook<F>(&mut self, hook: F)
+                where F: FnMut(Option<Rule>) -> Option<Rule> + 'static {
+
+                self.newline_hook = Some(Box::new(hook));
+            }
+
+            fn clear_newline_hook(&mut self) {
+                self.newline_hook = None;
+            }
+
+            fn set_stream<F>(&mut self, rules: &[Rule], sink: F)
+                where F: FnMut($crate::runtime::Event<Rule>, &str) + 'static {
+
+                self.stream = Some((rules.to_vec(), Box::new(sink)));
+                self.streaming = 0;
+            }
+
+            fn clear_stream(&mut self) {
+                self.stream = None;
+                self.streaming = 0;
+            }
+
+            #[allow(clippy::vec_init_then_push)]
+            fn alternatives(&self) -> Vec<$crate::runtime::Alternative> {
+                let mut alternatives = vec![];
+
+                $crate::grammar!(@table alternatives $( $ts )*);
+
+                alternatives
+            }
+
+            fn lints(&self) -> Vec<$crate::runtime::Lint> {
+                type Generated<'a> = Rdp<$crate::runtime::StringInput<'a>>;
+
+                let rules = <Generated as $crate::runtime::GeneratedParser>::rules();
+                let kinds: Vec<_> = rules.iter().map(|&rule| {
+                    let name = <Generated as $crate::runtime::GeneratedParser>::rule_name(rule);
+
+                    (name, rule.category())
+                }).collect();
+                #[allow(unused_mut)]
+                let mut levels = vec![];
+
+                $crate::grammar!(@levels levels $( $ts )*);
+
+                let alternatives = $crate::runtime::Parser::alternatives(self);
+
+                $crate::runtime::Lint::check(&alternatives, &kinds, &levels)
+            }
+
+            fn enable_coverage(&mut self) {
+                if self.coverage.is_none() {
+                    let alternatives = $crate::runtime::Parser::alternatives(self);
+
+                    self.coverage = Some($crate::runtime::Coverage::new(alternatives));
+                }
+            }
+
+            fn coverage(&self) -> Option<&$crate::runtime::Coverage> {
+                self.coverage.as_ref()
+            }
+
+            #[inline]
+            fn cover(&mut self, rule: &'static str, index: usize) {
+                if let Some(ref mut coverage) = self.coverage {
+                    coverage.hit(rule, index);
+                }
+            }
+
+            fn enable_profiling(&mut self) {
+                if self.profile.is_none() {
+                    let alternatives = $crate::runtime::Parser::alternatives(self);
+
+                    self.profile = Some($crate::runtime::Profile::new(alternatives));
+                }
+            }
+
+            fn profile(&self) -> Option<&$crate::runtime::Profile> {
+                self.profile.as_ref()
+            }
+
+            fn enable_recording(&mut self) {
+                if self.recording.is_none() {
+                    self.recording = Some($crate::runtime::Recording::new());
+                }
+            }
+
+            fn recording(&self) -> Option<&$crate::runtime::Recording> {
+                self.recording.as_ref()
+            }
+
+            #[inline]
+            fn enter_rule(&mut self, rule: &'static str) -> bool {
+                if self.limit_error.is_some() {
+                    return false;
+                }
+
+                let pos = self.input.pos();
+
+                if let Some((interval, ref mut observer)) = self.observer {
+                    if pos >= self.next_report {
+                        observer($crate::runtime::Progress {
+                            pos,
+                            len:    self.input.len(),
+                            tokens: self.queue.len()
+                        });
+
+                        self.next_report = pos.saturating_add(::std::cmp::max(interval, 1));
+                    }
+                }
+
+                if let Some(ref flag) = self.cancel {
+                    if flag.load(::std::sync::atomic::Ordering::Relaxed) {
+                        self.exceed($crate::runtime::LimitKind::Cancelled, pos);
+
+                        return false;
+                    }
+                }
+
+                if let Some(timeout) = self.limits.time() {
+                    let started = *self.started.get_or_insert_with(::std::time::Instant::now);
+
+                    if started.elapsed() >= timeout {
+                        self.exceed($crate::runtime::LimitKind::Timeout, pos);
+
+                        return false;
+                    }
+                }
+
+                if let Some(max) = self.limits.depth() {
+                    if self.depth >= max {
+                        self.exceed($crate::runtime::LimitKind::Depth, pos);
+
+                        return false;
+                    }
+                }
+
+                // line_start <= line_end < pos, so measuring lines never wraps around
+                if let Some(max) = self.limits.line_len() {
+                    if pos > self.line_end {
+                        let len = self.input.len();
+                        let bytes = &self.input.slice(0, len).as_bytes()[..pos];
+
+                        let mut exceeded = None;
+
+                        for (i, &byte) in bytes.iter().enumerate().skip(self.line_end) {
+                            if byte == b'\n' {
+                                if i - self.line_start > max {
+                                    exceeded = Some(self.line_start + max);
+
+                                    break;
+                                }
+
+                                self.line_start = i + 1;
+                            }
+                        }
+
+                        if exceeded.is_none() && pos - self.line_start > max {
+                            exceeded = Some(self.line_start + max);
+                        }
+
+                        self.line_end = pos;
+
+                        if let Some(pos) = exceeded {
+                            self.exceed($crate::runtime::LimitKind::LineLength, pos);
+
+                            return false;
+                        }
+                    }
+                }
+
+                $crate::runtime::trace_enter(rule, pos, self.depth);
+
+                if let Some(ref mut recording) = self.recording {
+                    recording.record($crate::runtime::Step::Enter { rule, pos });
+                }
+
+                self.depth += 1;
+
+                true
+            }
+
+            #[inline]
+            fn exit_rule(&mut self, rule: &'static str, pos: usize, matched: bool) {
+                self.depth -= 1;
+
+                $crate::runtime::trace_exit(rule, pos, self.input.pos(), self.depth, matched);
+
+                if let Some(ref mut profile) = self.profile {
+                    profile.record(rule, pos, matched);
+                }
+
+                if let Some(ref mut recording) = self.recording {
+                    recording.record(if matched {
+                        $crate::runtime::Step::Match { rule, start: pos, end: self.input.pos() }
+                    } else {
+                        $crate::runtime::Step::Fail { rule, pos }
+                    });
+                }
+
+                if matched && self.depth == 0 {
+                    let pos = self.input.pos();
+
+                    self.last_end = if self.trivia == $crate::runtime::Trivia::Trailing {
+                        $crate::runtime::Parser::trivia_start(self, pos)
+                    } else {
+                        pos
+                    };
+
+                    if self.skip_trail {
+                        $crate::runtime::Parser::skip_com(self);
+                        $crate::runtime::Parser::skip_ws(self);
+                    }
+
+                    if self.validation {
+                        if let Err(error) = $crate::runtime::Parser::validate(self) {
+                            panic!("invalid queue: {}; {:?}", error, self.queue[error.index]);
+                        }
+                    }
+                }
+            }
+
+            #[inline]
+            fn check_token_limit(&mut self) -> bool {
+                if let Some(max) = self.limits.tokens() {
+                    if self.queue.len() >= max {
+                        if self.limit_error.is_none() {
+                            let pos = self.input.pos();
+
+                            self.exceed($crate::runtime::LimitKind::TokenCount, pos);
+                        }
+
+                        return false;
+                    }
+                }
+
+                true
+            }
+
+            #[allow(unused_variables)]
+            #[inline]
+            fn is_reserved(&self, rule: Rule, start: usize) -> bool {
+                let word = self.input.slice(start, self.input.pos());
+
+                $crate::grammar!(@reserved self rule word $( $ts )*);
+
+                false
+            }
+
+            #[inline]
+            fn open_rule(&mut self, rule: Option<Rule>) -> Option<Rule> {
+                if let Some((ref rules, _)) = self.stream {
+                    if rule.map_or(false, |rule| rules.contains(&rule)) {
+                        self.streaming += 1;
+                    }
+                }
+
+                ::std::mem::replace(&mut self.open_rule, rule)
+            }
+
+            #[inline]
+            fn close_rule(&mut self, parent: Option<Rule>) {
+                let rule = ::std::mem::replace(&mut self.open_rule, parent);
+
+                if let Some((ref rules, _)) = self.stream {
+                    if rule.map_or(false, |rule| rules.contains(&rule)) {
+                        self.streaming -= 1;
+                    }
+                }
+            }
+
+            fn insert_parent(&mut self, index: usize, token: Self::Token) {
+                let descendants = self.queue.len() - index;
+
+                // entries past the end belong to Tokens that have been truncated since
+                self.subtrees.resize(self.queue.len(), None);
+                self.subtrees.insert(index, Some((token.start(), token.end(), descendants)));
+                self.queue.insert(index, token);
+            }
+
+            #[inline]
+            fn stream(&mut self, index: usize) {
+                if self.streaming > 0 {
+                    return;
+                }
+
+                if let Some((ref rules, ref mut sink)) = self.stream {
+                    if !rules.contains(&self.queue[index].rule()) {
+                        return;
+                    }
+
+                    let tokens: Vec<_> = self.queue[index..].iter().map(|token| token.token())
+                                                                .collect();
+
+                    for event in $crate::runtime::Events::new(&tokens) {
+                        let token = match event {
+                            $crate::runtime::Event::Start(token) |
+                            $crate::runtime::Event::End(token)   => token
+                        };
+
+                        sink(event, self.input.slice(token.start, token.end));
+                    }
+                } else {
+                    return;
+                }
+
+                self.queue.truncate(index);
+            }
+
+            #[inline]
+            fn queue(&self) -> &Vec<Self::Token>{
+                &self.queue
+            }
+
+            #[inline]
+            fn peek_token(&self, cursor: $crate::runtime::Cursor) -> Option<&Self::Token> {
+                self.queue.get(cursor.index())
+            }
+
+            #[inline]
+            fn peek_rule(&self, cursor: $crate::runtime::Cursor) -> Option<Rule> {
+                $crate::runtime::Parser::peek_token(self, cursor).map(|token| token.rule())
+            }
 
-                                op = new_op;
-                                last_right = new_lr;
-                            } else {
-                                break
-                            }
-                        }
+            fn memoize<V, F>(&self, key: &'static str, cursor: &mut $crate::runtime::Cursor,
+                             process: F) -> V
+                where V: ::std::any::Any + Clone, F: FnOnce(&mut $crate::runtime::Cursor) -> V {
 
-                        if let Some(pos) = last_right {
-                            right = cmp::max(pos, right);
-                        } else {
-                            last_right = Some(right);
-                        }
+                let index = cursor.index();
+                let key = match self.queue.get(index) {
+                    Some(token) => (token.rule(), index, key, ::std::any::TypeId::of::<V>()),
+                    None        => return process(cursor)
+                };
 
-                        if let Some(rule) = rule {
-                            let token = Token {
-                                rule:  rule,
-                                start: left,
-                                end:   right
-                            };
+                if let Some(&(end, ref value)) = self.memo.borrow().get(&key) {
+                    if let Some(value) = value.downcast_ref::<V>() {
+                        *cursor = $crate::runtime::Cursor::new(end);
 
-                            self.queue.insert(pos, token);
-                        }
-                    } else {
-                        return (op, last_right)
+                        return value.clone();
                     }
                 }
 
-                (op, last_right)
+                let value = process(cursor);
+
+                self.memo.borrow_mut().insert(key, (cursor.index(), Box::new(value.clone())));
+
+                value
             }
 
-            #[inline]
-            fn pos(&self) -> usize {
-                self.input.pos()
+            fn clear_memo(&self) {
+                self.memo.borrow_mut().clear();
             }
 
             #[inline]
-            fn set_pos(&mut self, pos: usize) {
-                self.input.set_pos(pos);
+            fn queue_mut(&mut self) -> &mut Vec<Self::Token>{
+                &mut self.queue
             }
 
-            #[inline]
-            fn end(&self) -> bool {
-                self.input.len() == self.input.pos()
+            fn inject(&mut self, rule: Rule, pos: usize) {
+                let min = self.queue.last().map_or(self.input.start(), |token| token.end());
+
+                assert!(pos >= min && pos <= self.input.pos(),
+                        "cannot inject a Token at {}, outside of {}..{}", pos, min,
+                        self.input.pos());
+
+                self.queue.push(<$tok as $crate::runtime::TokenData<Rule>>::new(rule, pos, pos));
             }
 
-            #[inline]
-            fn eoi_matched(&self) -> bool {
-                self.eoi_matched
+            fn index_of(&self, token: &Self::Token) -> Option<usize> {
+                // empty Tokens can come before longer ones with the same start, so only the starts
+                // are sorted
+                let first = self.queue.partition_point(|other| other.start() < token.start());
+                let candidates = || {
+                    self.queue[first..].iter().take_while(|other| other.start() == token.start())
+                };
+
+                candidates().position(|other| ::std::ptr::eq(other, token))
+                            .or_else(|| candidates().position(|other| other == token))
+                            .map(|i| first + i)
             }
 
-            #[inline]
-            fn reset(&mut self) {
-                self.input.set_pos(0);
-                self.queue.clear();
-                self.failures.clear();
-                self.fail_pos = 0;
+            fn token_index(&self) -> $crate::runtime::TokenIndex<Rule> {
+                $crate::runtime::TokenIndex::new(&self.queue)
             }
 
-            #[inline]
-            fn slice_input(&self, start: usize, end: usize) -> &str {
-                self.input.slice(start, end)
+            fn descendants_of(&self, token: &Self::Token) -> &[Self::Token] {
+                match $crate::runtime::Parser::index_of(self, token) {
+                    Some(i) => &self.queue[i + 1..i + 1 + self.descendants_len(i)],
+                    None    => &[]
+                }
             }
 
-            #[inline]
-            fn queue(&self) -> &Vec<Token<Rule>>{
-                &self.queue
+            fn children_of(&self, token: &Self::Token) -> Vec<&Self::Token> {
+                let i = match $crate::runtime::Parser::index_of(self, token) {
+                    Some(i) => i,
+                    None    => return vec![]
+                };
+
+                let end = i + 1 + self.descendants_len(i);
+                let mut children = vec![];
+                let mut child = i + 1;
+
+                while child < end {
+                    children.push(&self.queue[child]);
+                    child += 1 + self.descendants_len(child);
+                }
+
+                children
             }
 
-            #[inline]
-            fn queue_index(&self) -> usize {
-                self.queue_index.get()
+            fn parent_of(&self, token: &Self::Token) -> Option<&Self::Token> {
+                let i = $crate::runtime::Parser::index_of(self, token)?;
+
+                self.parent_index(i).map(|parent| &self.queue[parent])
             }
 
-            #[inline]
-            fn inc_queue_index(&self) {
-                self.queue_index.set(self.queue_index.get() + 1);
+            fn next_sibling(&self, token: &Self::Token) -> Option<&Self::Token> {
+                let i = $crate::runtime::Parser::index_of(self, token)?;
+                let next = i + 1 + self.descendants_len(i);
+
+                match self.parent_index(i) {
+                    Some(parent) if parent + self.descendants_len(parent) < next => None,
+                    _                                                         => {
+                        self.queue.get(next)
+                    }
+                }
+            }
+
+            fn embed<R: ::std::fmt::Debug>(&mut self, rule: Rule, start: usize,
+                                           tokens: &[$crate::runtime::Token<R>]) {
+                let tokens = tokens.iter().map(|token| {
+                    let name = format!("{:?}::{:?}", rule, token.rule);
+
+                    $crate::runtime::Token {
+                        rule:  self.interner.borrow_mut().intern(&name),
+                        start: token.start,
+                        end:   token.end
+                    }
+                }).collect();
+
+                self.embedded.insert((rule, start), tokens);
+            }
+
+            fn embedded(&self, token: &Self::Token)
+                        -> &[$crate::runtime::Token<$crate::runtime::Symbol>] {
+                self.embedded.get(&(token.rule(), token.start())).map_or(&[], |tokens| &tokens[..])
             }
 
             #[inline]
-            fn set_queue_index(&self, index: usize) {
-                self.queue_index.set(index);
+            fn validate(&self) -> Result<(), $crate::runtime::QueueError> {
+                let queue: Vec<_> = self.queue.iter().map(|token| token.token()).collect();
+
+                $crate::runtime::validate_queue(&queue)
             }
 
             #[inline]
-            fn queue_mut(&mut self) -> &mut Vec<Token<Rule>>{
-                &mut self.queue
+            fn set_validation(&mut self, enabled: bool) {
+                self.validation = enabled;
             }
 
             #[inline]
@@ -351,11 +2328,26 @@ macro_rules! impl_rdp {
                     return
                 }
 
+                let pos = self.input.pos();
+                let skipping = ::std::mem::replace(&mut self.skipping_ws, true);
+
                 loop {
-                    if !self.whitespace() {
+                    if self.whitespace() {
+                        continue
+                    }
+
+                    let start = self.input.pos();
+
+                    if !self.newline() {
                         break
                     }
+
+                    self.insert_terminator(start);
+                    self.skip_prefixes();
                 }
+
+                self.skipping_ws = skipping;
+                self.mark_trivia(pos);
             }
 
             fn skip_com(&mut self) {
@@ -366,16 +2358,57 @@ macro_rules! impl_rdp {
                 if !self.comment {
                     self.comment = true;
 
+                    let pos = self.input.pos();
+
                     loop {
+                        // comments skip preceding whitespace themselves
+                        $crate::runtime::Parser::skip_ws(self);
+
+                        let start = self.input.pos();
+
                         if !self.comment() {
                             break
                         }
+
+                        self.record_comment(start);
                     }
 
+                    self.mark_trivia(pos);
+
                     self.comment = false;
                 }
             }
 
+            fn skip_trailing_trivia(&mut self, value: bool) {
+                self.skip_trail = value;
+            }
+
+            fn significant_end(&self) -> usize {
+                self.last_end
+            }
+
+            fn comments(&self) -> &[::std::ops::Range<usize>] {
+                &self.comments
+            }
+
+            fn set_trivia(&mut self, trivia: $crate::runtime::Trivia) {
+                self.trivia = trivia;
+            }
+
+            #[inline]
+            fn trivia(&self) -> $crate::runtime::Trivia {
+                self.trivia
+            }
+
+            #[inline]
+            fn trivia_start(&self, pos: usize) -> usize {
+                if self.trivia_run.1 == pos {
+                    self.trivia_run.0
+                } else {
+                    pos
+                }
+            }
+
             fn is_atomic(&self) -> bool {
                 self.atomic
             }
@@ -384,24 +2417,44 @@ macro_rules! impl_rdp {
                 self.atomic = value;
             }
 
+            fn set_tracking(&mut self, tracking: $crate::runtime::Tracking) {
+                self.tracking = tracking;
+            }
+
+            fn tracking(&self) -> $crate::runtime::Tracking {
+                self.tracking
+            }
+
+            fn set_error_normalization(&mut self, enabled: bool) {
+                self.normalize = enabled;
+            }
+
             fn track(&mut self, failed: Rule, pos: usize) {
                 if self.atomic {
                     return
                 }
 
-                if self.failures.is_empty() {
-                    self.failures.push(failed);
+                let cap = match self.tracking {
+                    $crate::runtime::Tracking::Full      => None,
+                    $crate::runtime::Tracking::Capped(n) => Some(n),
+                    $crate::runtime::Tracking::Off       => return
+                };
+
+                let pos = if self.normalize { self.trivia_end(pos) } else { pos };
+
+                if pos > self.fail_pos {
+                    self.failures.clear();
 
                     self.fail_pos = pos;
-                } else {
-                    if pos == self.fail_pos {
-                        self.failures.push(failed);
-                    } else if pos > self.fail_pos {
-                        self.failures.clear();
-                        self.failures.push(failed);
+                } else if pos < self.fail_pos {
+                    return
+                }
 
-                        self.fail_pos = pos;
-                    }
+                match cap {
+                    None                                     => self.failures.push(failed),
+                    Some(n) if self.failures.len() < n &&
+                               !self.failures.contains(&failed) => self.failures.push(failed),
+                    Some(_)                                  => ()
                 }
             }
 
@@ -415,6 +2468,57 @@ macro_rules! impl_rdp {
 
                 (self.failures.iter().cloned().collect(), self.fail_pos)
             }
+
+            fn unclosed(&self) -> Option<$crate::runtime::Unclosed> {
+                let pos = ::std::cmp::max(self.fail_pos, self.terminal_pos);
+
+                self.unclosed.filter(|unclosed| unclosed.pos == pos)
+            }
+
+            fn suggestion(&mut self) -> Option<$crate::runtime::Suggestion> {
+                let (expected, pos) = $crate::runtime::Parser::expectations(self);
+                let len = self.input.len();
+                let word = {
+                    let rest = self.input.slice(pos, len);
+                    let end = rest.find(|c: char| !c.is_alphanumeric() && c != '_')
+                                  .unwrap_or(rest.len());
+
+                    &rest[..end]
+                };
+
+                if word.is_empty() {
+                    return None;
+                }
+
+                let mut candidates: Vec<&str> = vec![];
+
+                for expected in &expected {
+                    match *expected {
+                        $crate::runtime::Expected::Rule(rule)         => {
+                            Self::reserved_words(rule, |word| candidates.push(word))
+                        },
+                        $crate::runtime::Expected::String(ref string) => candidates.push(string),
+                        $crate::runtime::Expected::Range(..)          => ()
+                    }
+                }
+
+                $crate::runtime::Suggestion::new(word, pos, candidates)
+            }
+
+            fn expectations(&mut self) -> (Vec<$crate::runtime::Expected<Rule>>, usize) {
+                let (rules, pos) = $crate::runtime::Parser::expected(self);
+                let mut expected = vec![];
+
+                if pos >= self.terminal_pos {
+                    expected.extend(rules.into_iter().map($crate::runtime::Expected::Rule));
+                }
+
+                if self.terminal_pos >= pos {
+                    expected.extend(self.terminals.iter().cloned());
+                }
+
+                (expected, ::std::cmp::max(pos, self.terminal_pos))
+            }
         }
     };
 }
@@ -422,6 +2526,7 @@ macro_rules! impl_rdp {
 #[cfg(test)]
 mod tests {
     use super::super::super::prelude::*;
+    use super::super::super::{Cursor, Expected, Lexeme, LexemeKind, Limits, Tracking, Unclosed};
 
     impl_rdp! {
         grammar! {
@@ -461,6 +2566,134 @@ mod tests {
         }));
     }
 
+    #[test]
+    fn parse_at() {
+        let mut parser = Rdp::new(StringInput::new("b(a"));
+
+        assert!(!parser.parse_at(1, Rdp::paren));
+        assert!(!parser.parse_at(usize::MAX, Rdp::paren));
+        assert_eq!(parser.pos(), 0);
+        assert!(parser.queue().is_empty());
+        assert_eq!(parser.expected(), (vec![], 0));
+
+        assert!(parser.parse_at(2, Rdp::one));
+        assert!(parser.end());
+        assert_eq!(parser.queue(), &vec![Token { rule: Rule::one, start: 2, end: 3 }]);
+    }
+
+    #[test]
+    fn parse_at_limits() {
+        let mut parser = Rdp::new(StringInput::new("((a"));
+
+        parser.set_limits(Limits::new().max_depth(2));
+
+        assert!(!parser.parse_at(0, Rdp::paren));
+        assert_eq!(parser.limit_error(), None);
+        assert!(parser.partial().is_none());
+        assert!(!parser.parse_at(4, Rdp::one));
+        assert!(!parser.parse_rule_at(1, Rule::one));
+
+        assert!(parser.parse_rule_at(2, Rule::one));
+        assert_eq!(parser.queue(), &vec![Token { rule: Rule::one, start: 2, end: 3 }]);
+
+        parser.reset();
+
+        assert!(!parser.paren());
+        assert!(parser.limit_error().is_some());
+    }
+
+    #[test]
+    fn tracking() {
+        let mut parser = Rdp::new(StringInput::new("((a"));
+
+        parser.set_tracking(Tracking::Capped(0));
+
+        assert!(!parser.paren());
+        assert_eq!(parser.expected(), (vec![], 2));
+
+        parser.reset();
+
+        assert_eq!(parser.tracking(), Tracking::Capped(0));
+
+        parser.set_tracking(Tracking::Full);
+
+        assert!(!parser.paren());
+        assert_eq!(parser.expected(), (vec![Rule::paren], 2));
+    }
+
+    #[test]
+    fn expectations() {
+        let mut parser = Rdp::new(StringInput::new("( (a"));
+
+        assert!(!parser.paren());
+        assert_eq!(parser.expectations(), (vec![
+            Expected::Rule(Rule::paren),
+            Expected::String("(".to_owned()),
+            Expected::String(")".to_owned())
+        ], 3));
+
+        parser.reset();
+        parser.set_tracking(Tracking::Off);
+
+        assert!(!parser.paren());
+        assert_eq!(parser.expectations(), (vec![], 0));
+    }
+
+    #[test]
+    fn unclosed() {
+        let mut parser = Rdp::new(StringInput::new("(()(a"));
+
+        assert!(!parser.paren());
+        assert_eq!(parser.unclosed(), Some(Unclosed { open: "(", close: ")", start: 3, pos: 4 }));
+
+        parser.reset();
+
+        assert_eq!(parser.unclosed(), None);
+    }
+
+    #[test]
+    fn max_backtrack() {
+        let mut parser = Rdp::new(StringInput::new("((()"));
+
+        assert!(!parser.paren());
+        assert_eq!(parser.max_backtrack().map(|b| (b.distance, b.from, b.to)), Some((4, 4, 0)));
+
+        parser.reset();
+
+        assert_eq!(parser.max_backtrack(), None);
+        assert!(!parser.try(true, |parser| !parser.match_string("(")));
+        assert_eq!(parser.max_backtrack().map(|b| (b.distance, b.rule)), Some((1, None)));
+    }
+
+    #[test]
+    fn parse_complete() {
+        let mut parser = Rdp::new(StringInput::new("() // trailing\n "));
+
+        assert!(parser.parse_complete(Rdp::expression));
+        assert!(parser.end());
+
+        let mut parser = Rdp::new(StringInput::new("()a"));
+
+        assert!(!parser.parse_complete(Rdp::expression));
+        assert_eq!(parser.pos(), 0);
+        assert_eq!(parser.expected(), (vec![Rule::eoi, Rule::paren], 2));
+    }
+
+    #[test]
+    fn lex() {
+        let mut parser = Rdp::new(StringInput::new("bbaa()b"));
+        let lexemes = parser.lex(&[Rdp::zero, Rdp::one, Rdp::paren]);
+
+        assert_eq!(lexemes, vec![
+            Lexeme { kind: LexemeKind::Error, start: 0, end: 2 },
+            Lexeme { kind: LexemeKind::Rule(Rule::zero), start: 2, end: 4 },
+            Lexeme { kind: LexemeKind::Rule(Rule::paren), start: 4, end: 6 },
+            Lexeme { kind: LexemeKind::Error, start: 6, end: 7 }
+        ]);
+        assert!(parser.end());
+        assert!(parser.queue().is_empty());
+    }
+
     #[test]
     fn end() {
         let input = StringInput::new("asdasdf");
@@ -530,6 +2763,14 @@ mod tests {
         assert_eq!(parser.queue(), &queue);
     }
 
+    #[test]
+    fn lints() {
+        let parser = Rdp::new(StringInput::new(""));
+        let rules: Vec<_> = parser.lints().into_iter().map(|lint| lint.rule).collect();
+
+        assert_eq!(rules, vec!["zero", "one"]);
+    }
+
     #[test]
     fn comment() {
         let mut parser = Rdp::new(StringInput::new("// hi\n(())"));
@@ -559,4 +2800,159 @@ mod tests {
 
         assert_eq!(parser.queue(), &queue);
     }
+
+    #[test]
+    fn skip() {
+        let mut parser = Rdp::new(StringInput::new("ăb\ncd"));
+
+        assert!(parser.skip(0));
+        assert!(parser.at_column(1));
+        assert!(parser.skip(2));
+        assert_eq!(parser.pos(), 3);
+        assert!(parser.at_column(3));
+        assert!(!parser.skip(4));
+        assert_eq!(parser.pos(), 3);
+        assert!(parser.skip(3));
+        assert!(parser.at_column(3));
+        assert!(parser.end());
+    }
+
+    #[test]
+    fn trivia_leading() {
+        let mut parser = Rdp::new(StringInput::new("   // hi\n  ( ( ))"));
+
+        parser.set_trivia(::Trivia::Leading);
+
+        assert!(parser.expression());
+        assert!(parser.end());
+
+        let queue = vec![
+            Token { rule: Rule::paren, start: 0, end: 17 },
+            Token { rule: Rule::paren, start: 12, end: 16 }
+        ];
+
+        assert_eq!(parser.queue(), &queue);
+    }
+
+    #[test]
+    fn trivia_trailing() {
+        let mut parser = Rdp::new(StringInput::new("( ( ) // hi\n ) "));
+
+        parser.set_trivia(::Trivia::Trailing);
+
+        assert!(parser.expression());
+        assert!(parser.end());
+
+        let queue = vec![
+            Token { rule: Rule::paren, start: 0, end: 15 },
+            Token { rule: Rule::paren, start: 2, end: 13 }
+        ];
+
+        assert_eq!(parser.queue(), &queue);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid queue: token 1 starts inside of token 0 but ends after it")]
+    fn validation() {
+        let mut parser = Rdp::new(StringInput::new("()()"));
+
+        parser.set_validation(true);
+
+        assert!(parser.paren());
+
+        parser.queue_mut().push(Token { rule: Rule::paren, start: 1, end: 3 });
+
+        parser.paren();
+    }
+
+    #[test]
+    fn memoize_keys() {
+        let mut parser = Rdp::new(StringInput::new("()"));
+
+        assert!(parser.paren());
+
+        let mut cursor = Cursor::default();
+
+        let depth = parser.memoize("depth", &mut cursor, |_| 1usize);
+        let width = parser.memoize("width", &mut cursor, |_| 2usize);
+
+        assert_eq!((depth, width), (1, 2));
+        assert_eq!(parser.memoize("depth", &mut cursor, |_| 3usize), 1);
+        assert_eq!(parser.memoize("width", &mut cursor, |_| 3usize), 2);
+    }
+
+    #[test]
+    fn inject() {
+        let mut parser = Rdp::new(StringInput::new("(()"));
+
+        assert!(parser.match_string("("));
+
+        parser.inject(Rule::zero, 1);
+
+        assert!(parser.paren());
+        assert_eq!(parser.queue(), &vec![
+            Token { rule: Rule::zero, start: 1, end: 1 },
+            Token { rule: Rule::paren, start: 1, end: 3 }
+        ]);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot inject a Token at 3, outside of 0..2")]
+    fn inject_ahead() {
+        let mut parser = Rdp::new(StringInput::new("(())"));
+
+        assert!(parser.match_string("(("));
+
+        parser.inject(Rule::zero, 3);
+    }
+
+    #[test]
+    fn search() {
+        let mut parser = Rdp::new(StringInput::new("(()())()"));
+
+        assert!(parser.expression());
+
+        let queue = parser.queue().clone();
+
+        assert_eq!(parser.index_of(&queue[2]), Some(2));
+        assert_eq!(parser.index_of(&Token { rule: Rule::zero, start: 1, end: 3 }), None);
+        assert_eq!(parser.descendants_of(&queue[0]), &queue[1..3]);
+        assert_eq!(parser.children_of(&queue[0]), vec![&queue[1], &queue[2]]);
+        assert_eq!(parser.parent_of(&queue[2]), Some(&queue[0]));
+        assert_eq!(parser.parent_of(&queue[3]), None);
+        assert_eq!(parser.next_sibling(&queue[1]), Some(&queue[2]));
+        assert_eq!(parser.next_sibling(&queue[2]), None);
+        assert_eq!(parser.next_sibling(&queue[0]), Some(&queue[3]));
+    }
+
+    #[test]
+    fn stream() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let events = Rc::new(RefCell::new(vec![]));
+        let sink = events.clone();
+
+        let mut parser = Rdp::new(StringInput::new("(())()"));
+
+        parser.set_stream(&[Rule::paren], move |event, string: &str| {
+            sink.borrow_mut().push((event, string.to_owned()));
+        });
+
+        assert!(parser.expression());
+        assert!(parser.queue().is_empty());
+
+        let outer = Token { rule: Rule::paren, start: 0, end: 4 };
+        let inner = Token { rule: Rule::paren, start: 1, end: 3 };
+        let last = Token { rule: Rule::paren, start: 4, end: 6 };
+
+        assert_eq!(*events.borrow(), vec![
+            (::Event::Start(outer), "(())".to_owned()),
+            (::Event::Start(inner), "()".to_owned()),
+            (::Event::End(inner), "()".to_owned()),
+            (::Event::End(outer), "(())".to_owned()),
+            (::Event::Start(last), "()".to_owned()),
+            (::Event::End(last), "()".to_owned())
+        ]);
+    }
 }
@@ -5,7 +5,18 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
+use std::cmp::Ordering;
+use std::fmt::Debug;
+
+use super::super::Input;
+
 /// A `struct` representing tokens generated by a parser.
+///
+/// `Token`s are ordered by their start, then by their end in reverse, and finally by their
+/// `Rule`. Sorting non-empty `Token`s thus puts parents before their children, like in the
+/// [queue](trait.Parser#tymethod.queue). Empty `Token`s break this: an empty `Token` is sorted
+/// after all longer `Token`s with the same start, even if it comes before them in the queue, so
+/// sorted `Token`s are not necessarily in the queue's order.
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct Token<Rule> {
     /// matched [`Rule`](macro.impl_rdp!#rule)
@@ -15,3 +26,280 @@ pub struct Token<Rule> {
     /// ending position in `Input`
     pub end:   usize
 }
+
+impl<Rule: PartialEq> Token<Rule> {
+    /// Returns whether `other` is nested inside of a `Token`, i.e. its span is enclosed by the
+    /// `Token`'s span. Since a rule can match exactly the same span as one of its sub-rules, a
+    /// different `Token` with an identical span is also considered a descendant; in the
+    /// [queue](trait.Parser#tymethod.queue), the ancestor always comes first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::Token;
+    /// let parent = Token { rule: 0, start: 0, end: 4 };
+    /// let child  = Token { rule: 1, start: 1, end: 3 };
+    ///
+    /// assert!(parent.is_ancestor_of(&child));
+    /// assert!(!child.is_ancestor_of(&parent));
+    /// assert!(!parent.is_ancestor_of(&parent));
+    /// ```
+    #[inline]
+    pub fn is_ancestor_of(&self, other: &Token<Rule>) -> bool {
+        self.start <= other.start && other.end <= self.end && self != other
+    }
+
+    /// Returns the length of a `Token`'s span.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::Token;
+    /// assert_eq!(Token { rule: (), start: 2, end: 5 }.len(), 3);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Returns whether a `Token`'s span is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Returns whether `pos` is inside of a `Token`'s span, i.e. `start <= pos < end`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::Token;
+    /// let token = Token { rule: (), start: 2, end: 5 };
+    ///
+    /// assert!(token.contains(2));
+    /// assert!(!token.contains(5));
+    /// ```
+    #[inline]
+    pub fn contains(&self, pos: usize) -> bool {
+        self.start <= pos && pos < self.end
+    }
+
+    /// Returns whether the spans of a `Token` and `other` share at least one position. Empty
+    /// spans never overlap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::Token;
+    /// let token = Token { rule: (), start: 2, end: 5 };
+    ///
+    /// assert!(token.overlaps(&Token { rule: (), start: 4, end: 8 }));
+    /// assert!(!token.overlaps(&Token { rule: (), start: 5, end: 8 }));
+    /// ```
+    #[inline]
+    pub fn overlaps(&self, other: &Token<Rule>) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+}
+
+impl<Rule: Copy> Token<Rule> {
+    /// Returns an [`OwnedToken`](struct.OwnedToken) with a copy of the text matched by a `Token`
+    /// in `input` and its line and column, so that it stays usable after `input` is gone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::{Input, StringInput, Token};
+    /// let token = {
+    ///     let input = StringInput::new("a\nbc");
+    ///
+    ///     Token { rule: (), start: 2, end: 4 }.to_owned_with_text(&input)
+    /// };
+    ///
+    /// assert_eq!(token.text, "bc");
+    /// assert_eq!((token.line, token.col), (2, 1));
+    /// ```
+    pub fn to_owned_with_text<I: Input>(&self, input: &I) -> OwnedToken<Rule> {
+        let (line, col) = input.line_col(self.start);
+
+        OwnedToken {
+            rule:  self.rule,
+            start: self.start,
+            end:   self.end,
+            text:  input.slice(self.start, self.end).to_owned(),
+            line,
+            col
+        }
+    }
+}
+
+impl<Rule: Ord> Ord for Token<Rule> {
+    fn cmp(&self, other: &Token<Rule>) -> Ordering {
+        self.start.cmp(&other.start)
+            .then_with(|| other.end.cmp(&self.end))
+            .then_with(|| self.rule.cmp(&other.rule))
+    }
+}
+
+impl<Rule: Ord> PartialOrd for Token<Rule> {
+    fn partial_cmp(&self, other: &Token<Rule>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// A `trait` for the type of the `Token`s a parser generated by [`impl_rdp!`](macro.impl_rdp!)
+/// puts in its queue. Implementing it for a type of one's own, and selecting that type with
+/// [`token!`](macro.impl_rdp!#token-type), stores extra data, like a file id or flags, inline with
+/// every `Token` instead of in a side table indexed like the queue.
+///
+/// The parser creates all of its `Token`s with `new`, so the extra data starts out as whatever
+/// `new` puts there and can then be filled in through
+/// [`queue_mut`](trait.Parser#tymethod.queue_mut).
+///
+/// # Examples
+///
+/// ```
+/// # use pest::{Token, TokenData};
+/// #[derive(Clone, Copy, Debug, PartialEq)]
+/// struct Flagged {
+///     token:  Token<u8>,
+///     marked: bool
+/// }
+///
+/// impl TokenData<u8> for Flagged {
+///     fn new(rule: u8, start: usize, end: usize) -> Flagged {
+///         Flagged { token: Token { rule, start, end }, marked: false }
+///     }
+///
+///     fn rule(&self) -> u8 { self.token.rule }
+///     fn start(&self) -> usize { self.token.start }
+///     fn end(&self) -> usize { self.token.end }
+/// }
+///
+/// let flagged = Flagged::new(1, 2, 5);
+///
+/// assert!(!flagged.marked);
+/// assert_eq!(flagged.token(), Token { rule: 1, start: 2, end: 5 });
+/// ```
+pub trait TokenData<Rule: Copy>: Copy + Debug + PartialEq {
+    /// Creates a `TokenData` of `rule` spanning from `start` to `end`.
+    fn new(rule: Rule, start: usize, end: usize) -> Self;
+
+    /// Returns the matched [`Rule`](macro.impl_rdp!#rule).
+    fn rule(&self) -> Rule;
+
+    /// Returns the starting position in `Input`.
+    fn start(&self) -> usize;
+
+    /// Returns the ending position in `Input`.
+    fn end(&self) -> usize;
+
+    /// Returns the plain `Token`, without any extra data.
+    #[inline]
+    fn token(&self) -> Token<Rule> {
+        Token {
+            rule:  self.rule(),
+            start: self.start(),
+            end:   self.end()
+        }
+    }
+}
+
+impl<Rule: Copy + Debug + PartialEq> TokenData<Rule> for Token<Rule> {
+    #[inline]
+    fn new(rule: Rule, start: usize, end: usize) -> Token<Rule> {
+        Token { rule, start, end }
+    }
+
+    #[inline]
+    fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    #[inline]
+    fn start(&self) -> usize {
+        self.start
+    }
+
+    #[inline]
+    fn end(&self) -> usize {
+        self.end
+    }
+
+    #[inline]
+    fn token(&self) -> Token<Rule> {
+        *self
+    }
+}
+
+/// A `struct` representing a `Token` together with the text it matched and where it starts,
+/// independent of the `Input` it was matched in. Created with
+/// [`Token::to_owned_with_text`](struct.Token#method.to_owned_with_text).
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct OwnedToken<Rule> {
+    /// matched [`Rule`](macro.impl_rdp!#rule)
+    pub rule:  Rule,
+    /// starting position in `Input`
+    pub start: usize,
+    /// ending position in `Input`
+    pub end:   usize,
+    /// matched text
+    pub text:  String,
+    /// line of `start`, starting at `1`
+    pub line:  usize,
+    /// column of `start`, starting at `1`
+    pub col:   usize
+}
+
+impl<Rule: Copy> OwnedToken<Rule> {
+    /// Returns the `Token` without its text.
+    pub fn token(&self) -> Token<Rule> {
+        Token {
+            rule:  self.rule,
+            start: self.start,
+            end:   self.end
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Token;
+
+    #[test]
+    fn pre_order() {
+        let mut tokens = vec![
+            Token { rule: 1, start: 2, end: 3 },
+            Token { rule: 0, start: 2, end: 3 },
+            Token { rule: 0, start: 0, end: 1 },
+            Token { rule: 0, start: 0, end: 3 }
+        ];
+
+        tokens.sort();
+
+        assert_eq!(tokens, vec![
+            Token { rule: 0, start: 0, end: 3 },
+            Token { rule: 0, start: 0, end: 1 },
+            Token { rule: 0, start: 2, end: 3 },
+            Token { rule: 1, start: 2, end: 3 }
+        ]);
+    }
+
+    #[test]
+    fn empty_after_longer() {
+        // e.g. `list = { items ~ x }` with an empty `items`, queued as list, items, x
+        let mut tokens = vec![
+            Token { rule: 0, start: 0, end: 1 },
+            Token { rule: 1, start: 0, end: 0 },
+            Token { rule: 2, start: 0, end: 1 }
+        ];
+
+        tokens.sort();
+
+        assert_eq!(tokens, vec![
+            Token { rule: 0, start: 0, end: 1 },
+            Token { rule: 2, start: 0, end: 1 },
+            Token { rule: 1, start: 0, end: 0 }
+        ]);
+    }
+}
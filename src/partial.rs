@@ -0,0 +1,28 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::{LimitError, Token};
+
+/// A `struct` holding what a `Parser` had matched when it terminated early, either because one
+/// of its `Limits` was exceeded or because it was cancelled. Returned by
+/// [`Parser::partial`](trait.Parser#tymethod.partial).
+///
+/// Since every rule fails after early termination, the queue itself ends up empty. `tokens` is
+/// a copy of the queue right before that: the `Token`s of every rule that had completely
+/// matched, in pre-order. Rules that were still matching have no `Token`, so `tokens` is a
+/// forest made out of the finished sub-trees, which is still a valid
+/// [queue](fn.validate_queue).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Partial<R> {
+    /// reason and position of the termination
+    pub error:    LimitError,
+    /// `Token`s of all completely matched rules
+    pub tokens:   Vec<Token<R>>,
+    /// [expected](trait.Parser#tymethod.expected) `Rule`s at the farthest failure so far, and
+    /// its position
+    pub expected: (Vec<R>, usize)
+}
@@ -0,0 +1,298 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::iter::Peekable;
+
+use super::Token;
+
+/// An `enum` representing an expression tree built by [`Pratt`](struct.Pratt).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Expr<R> {
+    /// `Token` that is not an operator in its position
+    Primary(Token<R>),
+    /// prefix operator and its operand
+    Prefix(Token<R>, Box<Expr<R>>),
+    /// left operand, infix operator, and right operand
+    Infix(Box<Expr<R>>, Token<R>, Box<Expr<R>>),
+    /// operand and postfix operator
+    Postfix(Box<Expr<R>>, Token<R>)
+}
+
+impl<R> Expr<R> {
+    /// Returns the starting and ending positions of the whole expression.
+    pub fn span(&self) -> (usize, usize) {
+        match *self {
+            Expr::Primary(ref token)         => (token.start, token.end),
+            Expr::Prefix(ref op, ref rhs)    => (op.start, rhs.span().1),
+            Expr::Infix(ref lhs, _, ref rhs) => (lhs.span().0, rhs.span().1),
+            Expr::Postfix(ref lhs, ref op)   => (lhs.span().0, op.end)
+        }
+    }
+}
+
+/// An `enum` representing why [`Pratt::parse`](struct.Pratt#method.parse) failed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PrattError<R> {
+    /// the `Token`s ended where an operand was expected
+    UnexpectedEnd,
+    /// `Token` that cannot appear where it was found
+    Unexpected(Token<R>)
+}
+
+/// A `struct` that nests a flat sequence of operand and operator `Token`s into an `Expr` tree
+/// according to numeric binding powers. It is an alternative to
+/// [precedence climbing](macro.grammar!#precedence-climbing) for expression grammars with prefix
+/// and postfix operators or with precedences that are easier to state as numbers; the
+/// [`pratt!`](macro.pratt!) macro builds one declaratively.
+///
+/// An operator binds tighter the higher its binding power is. Infix operators have a left and a
+/// right binding power: `(1, 2)` is left-associative, `(2, 1)` is right-associative. The same
+/// `Rule` can be both a prefix and an infix operator, e.g. `-`, since only operators expected in
+/// a position are looked up there. Every other `Token` is a primary operand.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::{Expr, Pratt};
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         expression = { operand ~ ((plus | minus | times | power) ~ operand)* }
+///         operand    = _{ minus* ~ number ~ factorial* }
+///         number     = @{ ['0'..'9']+ }
+///         plus       =  { ["+"] }
+///         minus      =  { ["-"] }
+///         times      =  { ["*"] }
+///         power      =  { ["^"] }
+///         factorial  =  { ["!"] }
+///     }
+/// }
+///
+/// fn eval(expr: &Expr<Rule>, source: &str) -> i64 {
+///     match *expr {
+///         Expr::Primary(token)              => source[token.start..token.end].parse().unwrap(),
+///         Expr::Prefix(_, ref rhs)          => -eval(rhs, source),
+///         Expr::Postfix(ref lhs, _)         => (1..eval(lhs, source) + 1).product(),
+///         Expr::Infix(ref lhs, op, ref rhs) => {
+///             let (lhs, rhs) = (eval(lhs, source), eval(rhs, source));
+///
+///             match op.rule {
+///                 Rule::plus  => lhs + rhs,
+///                 Rule::minus => lhs - rhs,
+///                 Rule::times => lhs * rhs,
+///                 _           => lhs.pow(rhs as u32)
+///             }
+///         }
+///     }
+/// }
+///
+/// let pratt = pratt! {
+///     infix   Rule::plus      => 1, 2;
+///     infix   Rule::minus     => 1, 2;
+///     infix   Rule::times     => 3, 4;
+///     prefix  Rule::minus     => 5;
+///     infix   Rule::power     => 7, 6;
+///     postfix Rule::factorial => 8;
+/// };
+///
+/// let source = "-2^2*3!-1";
+/// let mut parser = Rdp::new(StringInput::new(source));
+///
+/// assert!(parser.expression());
+///
+/// let tokens = parser.queue()[1..].iter().cloned();
+/// let expr = pratt.parse(tokens).unwrap();
+///
+/// assert_eq!(eval(&expr, source), -25);
+/// assert_eq!(expr.span(), (0, 9));
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Pratt<R> {
+    prefix:  Vec<(R, u8)>,
+    infix:   Vec<(R, u8, u8)>,
+    postfix: Vec<(R, u8)>
+}
+
+impl<R: Copy + PartialEq> Pratt<R> {
+    /// Creates a `Pratt` without operators.
+    pub fn new() -> Pratt<R> {
+        Pratt {
+            prefix:  vec![],
+            infix:   vec![],
+            postfix: vec![]
+        }
+    }
+
+    /// Adds a prefix operator `rule` with binding power `right` towards its operand.
+    pub fn prefix(mut self, rule: R, right: u8) -> Pratt<R> {
+        self.prefix.push((rule, right));
+
+        self
+    }
+
+    /// Adds an infix operator `rule` with binding powers `left` and `right` towards its
+    /// operands.
+    pub fn infix(mut self, rule: R, left: u8, right: u8) -> Pratt<R> {
+        self.infix.push((rule, left, right));
+
+        self
+    }
+
+    /// Adds a postfix operator `rule` with binding power `left` towards its operand.
+    pub fn postfix(mut self, rule: R, left: u8) -> Pratt<R> {
+        self.postfix.push((rule, left));
+
+        self
+    }
+
+    /// Nests `tokens`, the operands and operators of an expression in the order they were
+    /// matched, into an `Expr`. `tokens` should not contain descendants of operands, e.g. use
+    /// [`Parser::children_of`](trait.Parser#tymethod.children_of) to get them.
+    pub fn parse<I>(&self, tokens: I) -> Result<Expr<R>, PrattError<R>>
+        where I: IntoIterator<Item = Token<R>> {
+
+        let mut tokens = tokens.into_iter().peekable();
+        let expr = self.expr(&mut tokens, 0)?;
+
+        match tokens.next() {
+            Some(token) => Err(PrattError::Unexpected(token)),
+            None        => Ok(expr)
+        }
+    }
+
+    fn expr<I>(&self, tokens: &mut Peekable<I>, min: u8) -> Result<Expr<R>, PrattError<R>>
+        where I: Iterator<Item = Token<R>> {
+
+        let token = tokens.next().ok_or(PrattError::UnexpectedEnd)?;
+
+        let mut lhs = match self.prefix.iter().find(|&&(rule, _)| rule == token.rule) {
+            Some(&(_, right)) => Expr::Prefix(token, Box::new(self.expr(tokens, right)?)),
+            None              => {
+                if self.is_infix(token.rule) || self.is_postfix(token.rule) {
+                    return Err(PrattError::Unexpected(token));
+                }
+
+                Expr::Primary(token)
+            }
+        };
+
+        while let Some(&token) = tokens.peek() {
+            if let Some(&(_, left)) = self.postfix.iter().find(|&&(rule, _)| rule == token.rule) {
+                if left < min {
+                    break;
+                }
+
+                tokens.next();
+                lhs = Expr::Postfix(Box::new(lhs), token);
+
+                continue;
+            }
+
+            match self.infix.iter().find(|&&(rule, _, _)| rule == token.rule) {
+                Some(&(_, left, right)) => {
+                    if left < min {
+                        break;
+                    }
+
+                    tokens.next();
+
+                    let rhs = self.expr(tokens, right)?;
+
+                    lhs = Expr::Infix(Box::new(lhs), token, Box::new(rhs));
+                },
+                None                    => break
+            }
+        }
+
+        Ok(lhs)
+    }
+
+    fn is_infix(&self, rule: R) -> bool {
+        self.infix.iter().any(|&(other, _, _)| other == rule)
+    }
+
+    fn is_postfix(&self, rule: R) -> bool {
+        self.postfix.iter().any(|&(other, _)| other == rule)
+    }
+}
+
+/// A `macro` that builds a [`Pratt`](struct.Pratt) out of `;`-separated operator declarations.
+/// Every declaration starts with `prefix`, `infix`, or `postfix`, followed by the operator's
+/// `Rule`, `=>`, and its binding powers, two for infix operators.
+///
+/// ```ignore
+/// pratt! {
+///     infix   Rule::plus => 1, 2;
+///     prefix  Rule::neg  => 5;
+///     postfix Rule::fact => 6;
+/// }
+/// ```
+#[macro_export]
+macro_rules! pratt {
+    ( $( $kind:ident $rule:expr => $( $power:expr ),+ );* $( ; )* ) => {
+        $crate::runtime::Pratt::new() $( .$kind($rule, $( $power ),+) )*
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(rule: char, start: usize) -> Token<char> {
+        Token { rule, start, end: start + 1 }
+    }
+
+    fn parse(expr: &str) -> Result<Expr<char>, PrattError<char>> {
+        let pratt = pratt! {
+            infix   '+' => 1, 2;
+            infix   '-' => 1, 2;
+            infix   '^' => 4, 3;
+            prefix  '-' => 5;
+            postfix '!' => 6;
+        };
+
+        pratt.parse(expr.chars().enumerate().map(|(i, c)| token(c, i)))
+    }
+
+    fn show(expr: &Expr<char>) -> String {
+        match *expr {
+            Expr::Primary(token)              => token.rule.to_string(),
+            Expr::Prefix(op, ref rhs)         => format!("({}{})", op.rule, show(rhs)),
+            Expr::Infix(ref lhs, op, ref rhs) => {
+                format!("({}{}{})", show(lhs), op.rule, show(rhs))
+            },
+            Expr::Postfix(ref lhs, op)        => format!("({}{})", show(lhs), op.rule)
+        }
+    }
+
+    #[test]
+    fn associativity() {
+        assert_eq!(show(&parse("a-b-c").unwrap()), "((a-b)-c)");
+        assert_eq!(show(&parse("a^b^c").unwrap()), "(a^(b^c))");
+    }
+
+    #[test]
+    fn prefix_postfix() {
+        assert_eq!(show(&parse("-a!+-b^c").unwrap()), "((-(a!))+((-b)^c))");
+        assert_eq!(show(&parse("--a").unwrap()), "(-(-a))");
+    }
+
+    #[test]
+    fn span() {
+        assert_eq!(parse("-a+b!").unwrap().span(), (0, 5));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(parse("a+"), Err(PrattError::UnexpectedEnd));
+        assert_eq!(parse("ab"), Err(PrattError::Unexpected(token('b', 1))));
+        assert_eq!(parse("+a"), Err(PrattError::Unexpected(token('+', 0))));
+    }
+}
@@ -6,5 +6,19 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 //! A `mod` that contains `pest::Input`, `pest::Parser`, `pest::StringInput`, and `pest::Token`.
+//!
+//! The `macro`s refer to everything by its full path, so neither this `mod` nor a glob import is
+//! needed to define a parser. Code that only needs part of it can import one of the smaller
+//! preludes instead, e.g. in libraries that avoid glob imports.
 
-pub use super::{Input, Parser, StringInput, Token};
+pub use super::runtime::{Input, Parser, StringInput, Token};
+
+/// A `mod` with what running a parser needs: `Input`, `Parser`, and `StringInput`.
+pub mod parser {
+    pub use super::super::runtime::{Input, Parser, StringInput};
+}
+
+/// A `mod` with what reading the `Token`s of a parser needs: `Parser` and `Token`.
+pub mod process {
+    pub use super::super::runtime::{Parser, Token};
+}
@@ -0,0 +1,342 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::ops::Range;
+
+use super::LineIndex;
+
+/// A `trait` for stages that rewrite a source file before it is parsed, e.g. by splicing in other
+/// files. The resulting `Preprocessed` remembers where every part of its output comes from and is
+/// parsed through a [`ChainedInput`](struct.ChainedInput), whose lines and columns are those of
+/// the original files.
+pub trait Preprocessor {
+    /// error returned when preprocessing fails
+    type Error;
+
+    /// Preprocesses `source`, which was read from `file`.
+    fn preprocess(&mut self, file: &str, source: &str) -> Result<Preprocessed, Self::Error>;
+}
+
+/// A `struct` describing where a position of a `Preprocessed` output comes from. `line` and
+/// `col` start at `1`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Origin<'a> {
+    /// name of the original file
+    pub file: &'a str,
+    /// byte position in the original file
+    pub pos:  usize,
+    /// line in the original file
+    pub line: usize,
+    /// column in the original file
+    pub col:  usize
+}
+
+#[derive(Clone, Debug)]
+struct File {
+    name:   String,
+    source: String,
+    lines:  LineIndex
+}
+
+/// A `struct` holding the output of a `Preprocessor` together with a map from its positions to
+/// the files they were copied from.
+#[derive(Clone, Debug, Default)]
+pub struct Preprocessed {
+    string:   String,
+    files:    Vec<File>,
+    // output range, file index, position in file
+    segments: Vec<(Range<usize>, usize, usize)>
+}
+
+impl Preprocessed {
+    /// Creates an empty `Preprocessed`.
+    pub fn new() -> Preprocessed {
+        Preprocessed::default()
+    }
+
+    /// Registers an original `file` with its `source` and returns its index.
+    pub fn add_file(&mut self, file: &str, source: String) -> usize {
+        self.files.push(File {
+            name:   file.to_owned(),
+            lines:  LineIndex::new(&source),
+            source
+        });
+
+        self.files.len() - 1
+    }
+
+    /// Appends the bytes `start..end` of the source of the file at index `file` to the output,
+    /// remembering where they come from.
+    pub fn push_mapped(&mut self, file: usize, start: usize, end: usize) {
+        if start == end {
+            return;
+        }
+
+        let output = self.string.len();
+
+        self.string.push_str(&self.files[file].source[start..end]);
+        self.segments.push((output..self.string.len(), file, start));
+    }
+
+    /// Appends `string` to the output without mapping it to any file.
+    pub fn push_str(&mut self, string: &str) {
+        self.string.push_str(string);
+    }
+
+    /// Returns the output.
+    pub fn as_str(&self) -> &str {
+        &self.string
+    }
+
+    /// Returns the `Origin` of the output position `pos`, or `None` if `pos` was not copied from
+    /// a file. The end of the output maps to the end of the last copied part.
+    pub fn origin(&self, pos: usize) -> Option<Origin<'_>> {
+        let i = self.segments.partition_point(|segment| segment.0.end <= pos);
+
+        let &(ref output, file, start) = match self.segments.get(i) {
+            Some(segment) if segment.0.start <= pos => segment,
+            Some(_)                                 => return None,
+            None                                    => {
+                self.segments.last().filter(|segment| segment.0.end == pos)?
+            }
+        };
+
+        let file = &self.files[file];
+        let pos = start + pos - output.start;
+        let (line, col) = file.lines.line_col(&file.source, pos);
+
+        Some(Origin {
+            file: &file.name,
+            pos,
+            line,
+            col
+        })
+    }
+}
+
+/// An `enum` describing why an `Includer` failed.
+#[derive(Debug)]
+pub enum IncludeError {
+    /// the included `file` could not be loaded
+    Io {
+        /// name of the file
+        file:  String,
+        /// error returned by the loader
+        error: io::Error
+    },
+    /// `file` includes itself, directly or indirectly
+    Cycle {
+        /// name of the file
+        file: String
+    },
+    /// the include directive at `line` of `file`, starting at `1`, is not followed by a quoted
+    /// file name
+    Malformed {
+        /// name of the file
+        file: String,
+        /// line of the directive
+        line: usize
+    }
+}
+
+impl fmt::Display for IncludeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            IncludeError::Io { ref file, ref error } => {
+                write!(f, "cannot include {}: {}", file, error)
+            },
+            IncludeError::Cycle { ref file }         => write!(f, "{} includes itself", file),
+            IncludeError::Malformed { ref file, line } => {
+                write!(f, "malformed include directive at {}:{}", file, line)
+            }
+        }
+    }
+}
+
+impl Error for IncludeError {}
+
+/// A `Preprocessor` that replaces every line consisting of an include directive followed by a
+/// quoted file name, e.g. `#include "file"`, with the preprocessed contents of that file. Files
+/// are loaded by name with a loader function. The directive only counts as a whole word, so lines
+/// like `#includes "file"` are kept as they are.
+///
+/// # Examples
+///
+/// ```
+/// # use pest::{Includer, Origin, Preprocessor};
+/// let includer = Includer::new(|file: &str| {
+///     match file {
+///         "lib.h" => Ok("int lib;\n".to_owned()),
+///         _       => Err(std::io::Error::from(std::io::ErrorKind::NotFound))
+///     }
+/// });
+///
+/// let preprocessed = includer.directive("#include")
+///                            .preprocess("main.c", "#include \"lib.h\"\nint main;\n")
+///                            .unwrap();
+///
+/// assert_eq!(preprocessed.as_str(), "int lib;\nint main;\n");
+/// assert_eq!(preprocessed.origin(4),
+///            Some(Origin { file: "lib.h", pos: 4, line: 1, col: 5 }));
+/// assert_eq!(preprocessed.origin(13),
+///            Some(Origin { file: "main.c", pos: 21, line: 2, col: 5 }));
+/// ```
+pub struct Includer<F> {
+    load:      F,
+    directive: String
+}
+
+impl<F: FnMut(&str) -> io::Result<String>> Includer<F> {
+    /// Creates an `Includer` with the `#include` directive that loads files with `load`.
+    pub fn new(load: F) -> Includer<F> {
+        Includer {
+            load,
+            directive: "#include".to_owned()
+        }
+    }
+
+    /// Sets the include `directive`.
+    pub fn directive(mut self, directive: &str) -> Includer<F> {
+        self.directive = directive.to_owned();
+
+        self
+    }
+
+    fn splice(&mut self, result: &mut Preprocessed, file: &str, source: &str,
+              stack: &mut Vec<String>) -> Result<(), IncludeError> {
+        if stack.iter().any(|included| included == file) {
+            return Err(IncludeError::Cycle { file: file.to_owned() });
+        }
+
+        let index = result.add_file(file, source.to_owned());
+        let mut start = 0;
+        let mut pos = 0;
+
+        stack.push(file.to_owned());
+
+        for (i, line) in source.split_inclusive('\n').enumerate() {
+            let content = line.trim_end_matches(['\n', '\r']);
+
+            // the directive has to be a whole word, so `#includes` is left alone
+            let rest = content.trim().strip_prefix(self.directive.as_str()).filter(|rest| {
+                !rest.starts_with(|c: char| c.is_alphanumeric() || c == '_')
+            });
+
+            if let Some(rest) = rest {
+                let rest = rest.trim();
+
+                if rest.len() < 2 || !rest.starts_with('"') || !rest.ends_with('"') {
+                    return Err(IncludeError::Malformed { file: file.to_owned(), line: i + 1 });
+                }
+
+                let name = &rest[1..rest.len() - 1];
+                let included = (self.load)(name).map_err(|error| {
+                    IncludeError::Io { file: name.to_owned(), error }
+                })?;
+
+                result.push_mapped(index, start, pos);
+                self.splice(result, name, &included, stack)?;
+
+                // the directive's line break is kept unless the included file ends with one
+                start = pos + content.len();
+
+                if result.as_str().ends_with('\n') {
+                    start = pos + line.len();
+                }
+            }
+
+            pos += line.len();
+        }
+
+        result.push_mapped(index, start, pos);
+        stack.pop();
+
+        Ok(())
+    }
+}
+
+impl Includer<fn(&str) -> io::Result<String>> {
+    /// Creates an `Includer` that loads files from the file system, relative to the current
+    /// directory.
+    pub fn fs() -> Includer<fn(&str) -> io::Result<String>> {
+        Includer::new(|file| fs::read_to_string(file))
+    }
+}
+
+impl<F: FnMut(&str) -> io::Result<String>> Preprocessor for Includer<F> {
+    type Error = IncludeError;
+
+    fn preprocess(&mut self, file: &str, source: &str) -> Result<Preprocessed, IncludeError> {
+        let mut result = Preprocessed::new();
+
+        self.splice(&mut result, file, source, &mut vec![])?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::*;
+
+    fn load(file: &str) -> io::Result<String> {
+        match file {
+            "a" => Ok("a1\n#include \"b\"\na2".to_owned()),
+            "b" => Ok("b1".to_owned()),
+            "c" => Ok("#include \"c\"\n".to_owned()),
+            _   => Err(io::Error::from(io::ErrorKind::NotFound))
+        }
+    }
+
+    #[test]
+    fn nested() {
+        let preprocessed = Includer::new(load).preprocess("main", "#include \"a\"\nm\n").unwrap();
+
+        assert_eq!(preprocessed.as_str(), "a1\nb1\na2\nm\n");
+        assert_eq!(preprocessed.origin(3).map(|origin| origin.file), Some("b"));
+        assert_eq!(preprocessed.origin(5).map(|origin| (origin.file, origin.pos)), Some(("a", 15)));
+        assert_eq!(preprocessed.origin(7).map(|origin| (origin.file, origin.line)), Some(("a", 3)));
+        assert_eq!(preprocessed.origin(8).map(|origin| (origin.file, origin.pos)),
+                   Some(("main", 12)));
+        assert_eq!(preprocessed.origin(11).map(|origin| (origin.file, origin.line)),
+                   Some(("main", 3)));
+    }
+
+    #[test]
+    fn errors() {
+        let mut includer = Includer::new(load);
+
+        match includer.preprocess("main", "#include \"c\"") {
+            Err(IncludeError::Cycle { file }) => assert_eq!(file, "c"),
+            result                            => panic!("{:?}", result)
+        }
+
+        match includer.preprocess("main", "x\n#include c\n") {
+            Err(IncludeError::Malformed { line, .. }) => assert_eq!(line, 2),
+            result                                    => panic!("{:?}", result)
+        }
+
+        match includer.preprocess("main", "#include \"d\"") {
+            Err(IncludeError::Io { file, .. }) => assert_eq!(file, "d"),
+            result                             => panic!("{:?}", result)
+        }
+    }
+
+    #[test]
+    fn whole_word() {
+        let source = "#includes \"b\"\n#include_next \"b\"\n#include\"b\"\n";
+        let preprocessed = Includer::new(load).preprocess("main", source).unwrap();
+
+        assert_eq!(preprocessed.as_str(), "#includes \"b\"\n#include_next \"b\"\nb1\n");
+    }
+}
@@ -22,9 +22,75 @@
 /// | `_: R`           | skips a `Token` of rule `R`                          |
 /// | `item: fn()`     | call matcher `fn` and store result in `item`         |
 /// | `mut item: fn()` | call matcher `fn` and store mutable result in `item` |
+/// | `opt(item: R)`   | matches an optional `Token` of rule `R` as `Option`  |
+/// | `opt(&item: R)`  | captures an optional `Token` of rule `R` as `Option` |
+/// | `opt(_: R)`      | skips an optional `Token` of rule `R`                |
 ///
 /// `process` automatically calls the `main` matcher which is mandatory.
 ///
+/// # Optional items
+///
+/// `opt(...)` items never fail to match. If the next `Token` is of rule `R`, it is consumed and
+/// stored as `Some`, otherwise `item` is `None` and the queue is left untouched. This makes
+/// optional parts of a grammar like `sign?` easy to process in a single pattern:
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         number = { sign? ~ digits }
+///         sign   = { ["-"] }
+///         digits = { ['0'..'9']+ }
+///     }
+///
+///     process! {
+///         main(&self) -> i32 {
+///             (_: number, opt(sign: sign), &digits: digits) => {
+///                 let value: i32 = digits.parse().unwrap();
+///
+///                 if sign.is_some() { -value } else { value }
+///             }
+///         }
+///     }
+/// }
+///
+/// let mut parser = Rdp::new(StringInput::new("-12"));
+///
+/// assert!(parser.number());
+/// assert_eq!(parser.process(), -12);
+/// # }
+/// ```
+///
+/// # Arguments
+///
+/// Matchers can declare lifetimes and take extra arguments after `&self`, which are then passed
+/// along explicitly when calling them from a pattern, e.g. `item: fn(arena)`. `process` takes the
+/// same arguments as `main`. This is useful for threading an [`Arena`](struct.Arena) through the
+/// matchers in order to build ASTs out of references:
+///
+/// ```ignore
+/// main<'a>(&self, arena: &'a Arena<Node<'a>>) -> &'a Node<'a> {
+///     (_: paren, node: main(arena)) => arena.alloc(Node::Paren(node))
+/// }
+/// ```
+///
+/// # Cursor
+///
+/// Every matcher also takes the [`Cursor`](struct.Cursor) pointing at the next `Token`, which
+/// patterns advance and pass on to the matchers they call. To use it in a body, name it right
+/// after `&self`; it can then be passed to other matchers explicitly:
+///
+/// ```ignore
+/// _value(&self, &mut cursor) -> String {
+///     () => match self.peek_rule(*cursor) {
+///         Some(Rule::number) => self._number(cursor),
+///         _                  => self._word(cursor)
+///     }
+/// }
+/// ```
+///
 /// # Panics
 ///
 /// In case all the patterns inside of `process!` won't match, the `process` method will `panic!`.
@@ -273,11 +339,11 @@
 macro_rules! process {
     // handle patterns
     // _ : rule
-    ( @pattern $slf:ident ($block:expr) _ : $typ:ident ) => {
+    ( @pattern $slf:ident $cur:ident ($block:expr) _ : $typ:ident ) => {
         {
-            if let Some(token) = $slf.queue().get($slf.queue_index()) {
-                if token.rule == Rule::$typ {
-                    $slf.inc_queue_index();
+            if let Some(token) = $crate::runtime::Parser::queue($slf).get($cur.index()) {
+                if token.rule() == Rule::$typ {
+                    *$cur = $cur.advance(1);
 
                     Some($block)
                 } else {
@@ -288,13 +354,13 @@ macro_rules! process {
             }
         }
     };
-    ( @pattern $slf:ident ($block:expr) _ : $typ:ident, $( $tail:tt )* ) => {
+    ( @pattern $slf:ident $cur:ident ($block:expr) _ : $typ:ident, $( $tail:tt )* ) => {
         {
-            if let Some(token) = $slf.queue().get($slf.queue_index()) {
-                if token.rule == Rule::$typ {
-                    $slf.inc_queue_index();
+            if let Some(token) = $crate::runtime::Parser::queue($slf).get($cur.index()) {
+                if token.rule() == Rule::$typ {
+                    *$cur = $cur.advance(1);
 
-                    process!(@pattern $slf ($block) $( $tail )*)
+                    $crate::process!(@pattern $slf $cur ($block) $( $tail )*)
                 } else {
                     None
                 }
@@ -304,28 +370,29 @@ macro_rules! process {
         }
     };
     // _
-    ( @pattern $slf:ident ($block:expr) _ ) => {
+    ( @pattern $slf:ident $cur:ident ($block:expr) _ ) => {
         {
-            $slf.inc_queue_index();
+            *$cur = $cur.advance(1);
 
             Some($block)
         }
     };
-    ( @pattern $slf:ident ($block:expr) _, $( $tail:tt )* ) => {
+    ( @pattern $slf:ident $cur:ident ($block:expr) _, $( $tail:tt )* ) => {
         {
-            $slf.inc_queue_index();
+            *$cur = $cur.advance(1);
 
-            process!(@pattern $slf ($block) $( $tail )*)
+            $crate::process!(@pattern $slf $cur ($block) $( $tail )*)
         }
     };
     // &name : rule
-    ( @pattern $slf:ident ($block:expr) &$head:ident : $typ:ident ) => {
+    ( @pattern $slf:ident $cur:ident ($block:expr) &$head:ident : $typ:ident ) => {
         {
-            if let Some(token) = $slf.queue().get($slf.queue_index()) {
-                if token.rule == Rule::$typ {
-                    let $head = $slf.slice_input(token.start, token.end);
+            if let Some(token) = $crate::runtime::Parser::queue($slf).get($cur.index()) {
+                if token.rule() == Rule::$typ {
+                    let $head = $crate::runtime::Parser::slice_input($slf, token.start(),
+                                                                     token.end());
 
-                    $slf.inc_queue_index();
+                    *$cur = $cur.advance(1);
 
                     Some($block)
                 } else {
@@ -336,15 +403,16 @@ macro_rules! process {
             }
         }
     };
-    ( @pattern $slf:ident ($block:expr) &$head:ident : $typ:ident, $( $tail:tt )* ) => {
+    ( @pattern $slf:ident $cur:ident ($block:expr) &$head:ident : $typ:ident, $( $tail:tt )* ) => {
         {
-            if let Some(token) = $slf.queue().get($slf.queue_index()) {
-                if token.rule == Rule::$typ {
-                    let $head = $slf.slice_input(token.start, token.end);
+            if let Some(token) = $crate::runtime::Parser::queue($slf).get($cur.index()) {
+                if token.rule() == Rule::$typ {
+                    let $head = $crate::runtime::Parser::slice_input($slf, token.start(),
+                                                                     token.end());
 
-                    $slf.inc_queue_index();
+                    *$cur = $cur.advance(1);
 
-                    process!(@pattern $slf ($block) $( $tail )*)
+                    $crate::process!(@pattern $slf $cur ($block) $( $tail )*)
                 } else {
                     None
                 }
@@ -354,12 +422,12 @@ macro_rules! process {
         }
     };
     // &name
-    ( @pattern $slf:ident ($block:expr) &$head:ident ) => {
+    ( @pattern $slf:ident $cur:ident ($block:expr) &$head:ident ) => {
         {
-            if let Some(token) = $slf.queue().get($slf.queue_index()) {
-                let $head = $slf.slice_input(token.start, token.end);
+            if let Some(token) = $crate::runtime::Parser::queue($slf).get($cur.index()) {
+                let $head = $crate::runtime::Parser::slice_input($slf, token.start(), token.end());
 
-                $slf.inc_queue_index();
+                *$cur = $cur.advance(1);
 
                 Some($block)
             } else {
@@ -367,57 +435,113 @@ macro_rules! process {
             }
         }
     };
-    ( @pattern $slf:ident ($block:expr) &$head:ident, $( $tail:tt )* ) => {
+    ( @pattern $slf:ident $cur:ident ($block:expr) &$head:ident, $( $tail:tt )* ) => {
         {
-            if let Some(token) = $slf.queue().get($slf.queue_index()) {
-                let $head = $slf.slice_input(token.start, token.end);
+            if let Some(token) = $crate::runtime::Parser::queue($slf).get($cur.index()) {
+                let $head = $crate::runtime::Parser::slice_input($slf, token.start(), token.end());
 
-                $slf.inc_queue_index();
+                *$cur = $cur.advance(1);
 
-                process!(@pattern $slf ($block) $( $tail )*)
+                $crate::process!(@pattern $slf $cur ($block) $( $tail )*)
             } else {
                 None
             }
         }
     };
-    // mut name : fn()
-    ( @pattern $slf:ident ($block:expr) mut $head:ident : $call:ident() ) => {
+    // opt(_ : rule)
+    ( @pattern $slf:ident $cur:ident ($block:expr) opt(_ : $typ:ident) ) => {
+        {
+            $crate::process!(@opt $slf $cur $typ);
+
+            Some($block)
+        }
+    };
+    ( @pattern $slf:ident $cur:ident ($block:expr) opt(_ : $typ:ident), $( $tail:tt )* ) => {
+        {
+            $crate::process!(@opt $slf $cur $typ);
+
+            $crate::process!(@pattern $slf $cur ($block) $( $tail )*)
+        }
+    };
+    // opt(&name : rule)
+    ( @pattern $slf:ident $cur:ident ($block:expr) opt(&$head:ident : $typ:ident) ) => {
         {
-            let mut $head = $slf.$call();
+            let $head = $crate::process!(@opt $slf $cur $typ).map(|token| {
+                $crate::runtime::Parser::slice_input($slf, token.start(), token.end())
+            });
 
             Some($block)
         }
     };
-    ( @pattern $slf:ident ($block:expr) mut $head:ident : $call:ident(), ( $tail:tt )* ) => {
+    ( @pattern $slf:ident $cur:ident ($block:expr)
+      opt(&$head:ident : $typ:ident), $( $tail:tt )* ) => {
         {
-            let mut $head = $slf.$call();
+            let $head = $crate::process!(@opt $slf $cur $typ).map(|token| {
+                $crate::runtime::Parser::slice_input($slf, token.start(), token.end())
+            });
 
-            process!(@pattern $slf ($block) $( $tail )*)
+            $crate::process!(@pattern $slf $cur ($block) $( $tail )*)
         }
     };
-    // name : fn()
-    ( @pattern $slf:ident ($block:expr) $head:ident : $call:ident() ) => {
+    // opt(name : rule)
+    ( @pattern $slf:ident $cur:ident ($block:expr) opt($head:ident : $typ:ident) ) => {
         {
-            let $head = $slf.$call();
+            let $head = $crate::process!(@opt $slf $cur $typ);
 
             Some($block)
         }
     };
-    ( @pattern $slf:ident ($block:expr) $head:ident : $call:ident(), $( $tail:tt )* ) => {
+    ( @pattern $slf:ident $cur:ident ($block:expr)
+      opt($head:ident : $typ:ident), $( $tail:tt )* ) => {
         {
-            let $head = $slf.$call();
+            let $head = $crate::process!(@opt $slf $cur $typ);
 
-            process!(@pattern $slf ($block) $( $tail )*)
+            $crate::process!(@pattern $slf $cur ($block) $( $tail )*)
+        }
+    };
+    // mut name : fn(args)
+    ( @pattern $slf:ident $cur:ident ($block:expr)
+      mut $head:ident : $call:ident( $( $arg:expr ),* ) ) => {
+        {
+            let mut $head = $slf.$call(&mut *$cur $( , $arg )*);
+
+            Some($block)
+        }
+    };
+    ( @pattern $slf:ident $cur:ident ($block:expr)
+      mut $head:ident : $call:ident( $( $arg:expr ),* ),
+      $( $tail:tt )* ) => {
+        {
+            let mut $head = $slf.$call(&mut *$cur $( , $arg )*);
+
+            $crate::process!(@pattern $slf $cur ($block) $( $tail )*)
+        }
+    };
+    // name : fn(args)
+    ( @pattern $slf:ident $cur:ident ($block:expr)
+      $head:ident : $call:ident( $( $arg:expr ),* ) ) => {
+        {
+            let $head = $slf.$call(&mut *$cur $( , $arg )*);
+
+            Some($block)
+        }
+    };
+    ( @pattern $slf:ident $cur:ident ($block:expr) $head:ident : $call:ident( $( $arg:expr ),* ),
+      $( $tail:tt )* ) => {
+        {
+            let $head = $slf.$call(&mut *$cur $( , $arg )*);
+
+            $crate::process!(@pattern $slf $cur ($block) $( $tail )*)
         }
     };
     // name : rule
-    ( @pattern $slf:ident ($block:expr) $head:ident : $typ:ident ) => {
+    ( @pattern $slf:ident $cur:ident ($block:expr) $head:ident : $typ:ident ) => {
         {
-            if let Some(token) = $slf.queue().get($slf.queue_index()) {
-                if token.rule == Rule::$typ {
-                    let $head = $slf.queue()[$slf.queue_index()];
+            if let Some(token) = $crate::runtime::Parser::queue($slf).get($cur.index()) {
+                if token.rule() == Rule::$typ {
+                    let $head = $crate::runtime::Parser::queue($slf)[$cur.index()];
 
-                    $slf.inc_queue_index();
+                    *$cur = $cur.advance(1);
 
                     Some($block)
                 } else {
@@ -428,15 +552,15 @@ macro_rules! process {
             }
         }
     };
-    ( @pattern $slf:ident ($block:expr) $head:ident : $typ:ident, $( $tail:tt )* ) => {
+    ( @pattern $slf:ident $cur:ident ($block:expr) $head:ident : $typ:ident, $( $tail:tt )* ) => {
         {
-            if let Some(token) = $slf.queue().get($slf.queue_index()) {
-                if token.rule == Rule::$typ {
-                    let $head = $slf.queue()[$slf.queue_index()];
+            if let Some(token) = $crate::runtime::Parser::queue($slf).get($cur.index()) {
+                if token.rule() == Rule::$typ {
+                    let $head = $crate::runtime::Parser::queue($slf)[$cur.index()];
 
-                    $slf.inc_queue_index();
+                    *$cur = $cur.advance(1);
 
-                    process!(@pattern $slf ($block) $( $tail )*)
+                    $crate::process!(@pattern $slf $cur ($block) $( $tail )*)
                 } else {
                     None
                 }
@@ -446,12 +570,12 @@ macro_rules! process {
         }
     };
     // name
-    ( @pattern $slf:ident ($block:expr) $head:ident ) => {
+    ( @pattern $slf:ident $cur:ident ($block:expr) $head:ident ) => {
         {
-            if let Some(token) = $slf.queue().get($slf.queue_index()) {
+            if let Some(token) = $crate::runtime::Parser::queue($slf).get($cur.index()) {
                 let $head = token;
 
-                $slf.inc_queue_index();
+                *$cur = $cur.advance(1);
 
                 Some($block)
             } else {
@@ -459,84 +583,111 @@ macro_rules! process {
             }
         }
     };
-    ( @pattern $slf:ident ($block:expr) $head:ident, $( $tail:tt )* ) => {
+    ( @pattern $slf:ident $cur:ident ($block:expr) $head:ident, $( $tail:tt )* ) => {
         {
-            if let Some(token) = $slf.queue().get($slf.queue_index()) {
+            if let Some(token) = $crate::runtime::Parser::queue($slf).get($cur.index()) {
                 let $head = token;
 
-                $slf.inc_queue_index();
+                *$cur = $cur.advance(1);
 
-                process!(@pattern $slf ($block) $( $tail )*)
+                $crate::process!(@pattern $slf $cur ($block) $( $tail )*)
             } else {
                 None
             }
         }
     };
     // empty
-    ( @pattern $slf:ident ($block:expr) ) => {
+    ( @pattern $slf:ident $cur:ident ($block:expr) ) => {
         {
             Some($block)
         }
     };
 
+    // consume the next Token if it is of rule typ
+    ( @opt $slf:ident $cur:ident $typ:ident ) => {
+        {
+            match $crate::runtime::Parser::queue($slf).get($cur.index()) {
+                Some(&token) if token.rule() == Rule::$typ => {
+                    *$cur = $cur.advance(1);
+
+                    Some(token)
+                },
+                _ => None
+            }
+        }
+    };
+
     // handle branches; panic if no branch matches
-    ( @branches $slf:ident $name:ident ( $( $pattern:tt )* ) => $block:expr) => {
-        if let Some(result) = process!(@pattern $slf ($block) $( $pattern )*) {
+    ( @branches $slf:ident $cur:ident $name:ident ( $( $pattern:tt )* ) => $block:expr) => {
+        if let Some(result) = $crate::process!(@pattern $slf $cur ($block) $( $pattern )*) {
             result
         } else {
-            let next = $slf.queue()[$slf.queue_index()..]
+            let next = $crate::runtime::Parser::queue($slf)[$cur.index()..]
                            .iter()
                            .take(3)
-                           .map(|token| token.rule)
+                           .map(|token| token.rule())
                            .fold("".to_owned(), |acc, rule| acc + &format!("{:?}, ", rule));
 
             panic!("no pattern matched in {}; failed at [{}...]", stringify!($name), next)
         }
     };
-    ( @branches $slf:ident $name:ident ( $( $pattern:tt )* ) => $block:expr,) => {
-        if let Some(result) = process!(@pattern $slf ($block) $( $pattern )*) {
+    ( @branches $slf:ident $cur:ident $name:ident ( $( $pattern:tt )* ) => $block:expr,) => {
+        if let Some(result) = $crate::process!(@pattern $slf $cur ($block) $( $pattern )*) {
             result
         } else {
-            let next = $slf.queue()[$slf.queue_index()..]
+            let next = $crate::runtime::Parser::queue($slf)[$cur.index()..]
                            .iter()
                            .take(3)
-                           .map(|token| token.rule)
+                           .map(|token| token.rule())
                            .fold("".to_owned(), |acc, rule| acc + &format!("{:?}, ", rule));
 
             panic!("no pattern matched in {}; failed at [{}...]", stringify!($name), next)
         }
     };
-    ( @branches $slf:ident $name:ident ( $( $pattern:tt )* ) => $block:expr, $( $tail:tt )* ) => {
+    ( @branches $slf:ident $cur:ident $name:ident ( $( $pattern:tt )* ) => $block:expr,
+      $( $tail:tt )* ) => {
         {
-            let index = $slf.queue_index();
+            let start = *$cur;
 
-            if let Some(result) = process!(@pattern $slf ($block) $( $pattern )*) {
+            if let Some(result) = $crate::process!(@pattern $slf $cur ($block) $( $pattern )*) {
                 result
             } else {
-                $slf.set_queue_index(index);
+                *$cur = start;
 
-                process!(@branches $slf $name $( $tail )*)
+                $crate::process!(@branches $slf $cur $name $( $tail )*)
             }
         }
     };
 
     // get main's type
-    ( @type main $typ:ty ) => {
-        pub fn process(&self) -> $typ {
-            self.set_queue_index(0);
+    ( @type main [ $( $lt:lifetime ),* ] [ $( $arg:ident : $argty:ty ),* ] $typ:ty ) => {
+        pub fn process<$( $lt ),*>(&self $( , $arg: $argty )*) -> $typ {
+            self.main(&mut $crate::runtime::Cursor::default() $( , $arg )*)
+        }
+    };
+    ( @type $_name:ident $_lts:tt $_args:tt $_typ:ty ) => ();
 
-            self.main()
+    // matchers that don't name their Cursor still need one to pass on
+    ( @matcher $name:ident $lts:tt $slf:ident [ ] $args:tt $typ:ty { $( $ts:tt )* } ) => {
+        $crate::process!(@matcher $name $lts $slf [ cursor ] $args $typ { $( $ts )* });
+    };
+    ( @matcher $name:ident [ $( $lt:lifetime ),* ] $slf:ident [ $cur:ident ]
+      [ $( $arg:ident : $argty:ty ),* ] $typ:ty { $( $ts:tt )* } ) => {
+        fn $name<$( $lt ),*>(&$slf, $cur: &mut $crate::runtime::Cursor $( , $arg: $argty )*)
+            -> $typ {
+
+            $crate::process!(@branches $slf $cur $name $( $ts )*)
         }
+
+        $crate::process!(@type $name [ $( $lt ),* ] [ $( $arg : $argty ),* ] $typ);
     };
-    ( @type $_name:ident $_typ:ty ) => ();
 
-    ( $( $name:ident (&$slf:ident) -> $typ:ty { $( $ts:tt )* } )* ) => {
+    ( $( $name:ident $( < $( $lt:lifetime ),* > )*
+         (&$slf:ident $( , &mut $cur:ident )? $( , $arg:ident : $argty:ty )* )
+         -> $typ:ty { $( $ts:tt )* } )* ) => {
         $(
-            fn $name(&$slf) -> $typ {
-                process!(@branches $slf $name $( $ts )*)
-            }
-
-            process!(@type $name $typ);
+            $crate::process!(@matcher $name [ $( $( $lt ),* )* ] $slf [ $( $cur )? ]
+                             [ $( $arg : $argty ),* ] $typ { $( $ts )* });
         )*
     };
 }
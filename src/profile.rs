@@ -0,0 +1,342 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use super::Alternative;
+
+/// A `struct` with the counters a [`Profile`](struct.Profile) records for a rule.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct RuleStats {
+    /// number of times the rule was tried
+    pub attempts: usize,
+    /// number of times the rule matched
+    pub matches:  usize,
+    /// number of times the rule was tried again at a position it had already been tried at
+    pub retries:  usize
+}
+
+/// An `enum` representing a suggestion made by a [`Profile`](struct.Profile).
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Hint {
+    /// the rule was tried again at the same position `retries` times because its callers
+    /// backtracked
+    Retried {
+        /// name of the rule
+        rule:    &'static str,
+        /// number of retries
+        retries: usize
+    },
+    /// two top-level alternatives of a rule that was tried `attempts` times start with the same
+    /// term, which is matched again whenever the first alternative fails after it
+    CommonPrefix {
+        /// name of the rule
+        rule:     &'static str,
+        /// index of the first alternative
+        first:    usize,
+        /// index of the second alternative
+        second:   usize,
+        /// source of the shared term
+        prefix:   &'static str,
+        /// number of times the rule was tried
+        attempts: usize
+    }
+}
+
+impl Hint {
+    fn count(&self) -> usize {
+        match *self {
+            Hint::Retried { retries, .. }       => retries,
+            Hint::CommonPrefix { attempts, .. } => attempts
+        }
+    }
+}
+
+impl fmt::Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Hint::Retried { rule, retries } => {
+                write!(f, "rule `{}` is retried at the same position {} times; consider factoring \
+                           out common prefixes of the rules calling it or guarding them with `&`",
+                       rule, retries)
+            },
+            Hint::CommonPrefix { rule, first, second, prefix, attempts } => {
+                write!(f, "rule `{}` is tried {} times and its alternatives #{} and #{} both \
+                           start with `{}`; consider factoring it out so that it is only \
+                           matched once", rule, attempts, first, second, prefix)
+            }
+        }
+    }
+}
+
+/// A `struct` that counts how many times every rule was tried, matched, and retried at the same
+/// position, and combines these counters with the grammar's top-level `Alternative`s to suggest
+/// where backtracking can be avoided.
+///
+/// Its `Display` prints a report of all tried rules, most retried first, followed by the
+/// `Hint`s.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::Hint;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         call  = { ident ~ ["("] ~ ident ~ [")"] | ident ~ ["["] ~ ident ~ ["]"] }
+///         ident = @{ ['a'..'z']+ }
+///     }
+/// }
+///
+/// let mut parser = Rdp::new(StringInput::new("f[x]"));
+///
+/// parser.enable_profiling();
+///
+/// assert!(parser.call());
+///
+/// let profile = parser.profile().unwrap();
+///
+/// assert_eq!(profile.stats("ident").attempts, 3);
+/// assert_eq!(profile.stats("ident").retries, 1);
+/// assert_eq!(profile.hints(1), vec![
+///     Hint::Retried { rule: "ident", retries: 1 },
+///     Hint::CommonPrefix { rule: "call", first: 0, second: 1, prefix: "ident", attempts: 1 }
+/// ]);
+/// # }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Profile {
+    alternatives: Vec<Alternative>,
+    stats:        HashMap<&'static str, RuleStats>,
+    tried:        HashSet<(&'static str, usize)>
+}
+
+impl Profile {
+    /// Creates an empty `Profile` of a grammar with `alternatives`.
+    pub fn new(alternatives: Vec<Alternative>) -> Profile {
+        Profile {
+            alternatives,
+            stats: HashMap::new(),
+            tried: HashSet::new()
+        }
+    }
+
+    /// Records that `rule` was tried at `pos` and whether it `matched`.
+    #[inline]
+    pub fn record(&mut self, rule: &'static str, pos: usize, matched: bool) {
+        let retried = !self.tried.insert((rule, pos));
+        let stats = self.stats.entry(rule).or_default();
+
+        stats.attempts += 1;
+
+        if matched {
+            stats.matches += 1;
+        }
+
+        if retried {
+            stats.retries += 1;
+        }
+    }
+
+    /// Returns the counters of `rule`, all `0` if it was never tried.
+    pub fn stats(&self, rule: &str) -> RuleStats {
+        self.stats.get(rule).cloned().unwrap_or_default()
+    }
+
+    /// Returns the tried rules with their counters, most retried and then most tried first.
+    pub fn rules(&self) -> Vec<(&'static str, RuleStats)> {
+        let mut rules: Vec<_> = self.stats.iter().map(|(&rule, &stats)| (rule, stats)).collect();
+
+        rules.sort_by(|&(a, a_stats), &(b, b_stats)| {
+            b_stats.retries.cmp(&a_stats.retries)
+                           .then_with(|| b_stats.attempts.cmp(&a_stats.attempts))
+                           .then_with(|| a.cmp(b))
+        });
+
+        rules
+    }
+
+    /// Returns the `Hint`s for rules that were retried, or tried, at least `min` times, most
+    /// frequent first. Two alternatives share a prefix when their first `~`-separated terms are
+    /// identical and neither is optional, repeated, or a lookahead.
+    pub fn hints(&self, min: usize) -> Vec<Hint> {
+        let mut hints = vec![];
+
+        for (rule, stats) in self.rules() {
+            if stats.retries >= min.max(1) {
+                hints.push(Hint::Retried { rule, retries: stats.retries });
+            }
+        }
+
+        for (i, first) in self.alternatives.iter().enumerate() {
+            let attempts = self.stats(first.rule).attempts;
+
+            if attempts < min.max(1) {
+                continue;
+            }
+
+            let prefix = match first_term(first.text) {
+                Some(prefix) => prefix,
+                None         => continue
+            };
+
+            let second = self.alternatives[i + 1..].iter().find(|second| {
+                second.rule == first.rule && first_term(second.text) == Some(prefix)
+            });
+
+            let already = self.alternatives[..i].iter().any(|other| {
+                other.rule == first.rule && first_term(other.text) == Some(prefix)
+            });
+
+            if let (Some(second), false) = (second, already) {
+                hints.push(Hint::CommonPrefix {
+                    rule:   first.rule,
+                    first:  first.index,
+                    second: second.index,
+                    prefix,
+                    attempts
+                });
+            }
+        }
+
+        hints.sort_by_key(|hint| Reverse(hint.count()));
+
+        hints
+    }
+
+    /// Adds the counters of `other` to a `Profile`, e.g. to combine the `Profile`s of several
+    /// `Parser`s. Retries are only counted within the same `Parser`.
+    pub fn merge(&mut self, other: &Profile) {
+        for &alternative in &other.alternatives {
+            if !self.alternatives.contains(&alternative) {
+                self.alternatives.push(alternative);
+            }
+        }
+
+        for (&rule, other) in &other.stats {
+            let stats = self.stats.entry(rule).or_default();
+
+            stats.attempts += other.attempts;
+            stats.matches += other.matches;
+            stats.retries += other.retries;
+        }
+    }
+}
+
+// returns the first top-level `~`-separated term of an alternative's source unless it might not
+// consume input
+pub fn first_term(text: &str) -> Option<&str> {
+    let mut depth = 0;
+    let mut quoted = false;
+    let mut escaped = false;
+    let mut end = text.len();
+
+    for (i, c) in text.char_indices() {
+        if quoted {
+            match c {
+                _ if escaped => escaped = false,
+                '\\'         => escaped = true,
+                '"'          => quoted = false,
+                _            => ()
+            }
+
+            continue;
+        }
+
+        match c {
+            '"'                   => quoted = true,
+            '(' | '['             => depth += 1,
+            ')' | ']'             => depth -= 1,
+            '~' if depth == 0     => {
+                end = i;
+
+                break;
+            },
+            _                     => ()
+        }
+    }
+
+    let term = text[..end].trim();
+
+    if term.is_empty() || term.starts_with('!') || term.starts_with('&') ||
+       term.ends_with('*') || term.ends_with('?') {
+        None
+    } else {
+        Some(term)
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (rule, stats) in self.rules() {
+            writeln!(f, "{}: {} attempts, {} matches, {} retries", rule, stats.attempts,
+                     stats.matches, stats.retries)?;
+        }
+
+        for hint in self.hints(1) {
+            writeln!(f, "hint: {}", hint)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_terms() {
+        assert_eq!(first_term("[\"(\"] ~ a ~ [\")\"]"), Some("[\"(\"]"));
+        assert_eq!(first_term("(a ~ b) ~ c"), Some("(a ~ b)"));
+        assert_eq!(first_term("[\"~\\\"\"] ~ a"), Some("[\"~\\\"\"]"));
+        assert_eq!(first_term("a"), Some("a"));
+        assert_eq!(first_term("a? ~ b"), None);
+        assert_eq!(first_term("!a ~ b"), None);
+    }
+
+    #[test]
+    fn display() {
+        let alternatives = vec![
+            Alternative { rule: "a", index: 0, text: "b ~ [\"x\"]" },
+            Alternative { rule: "a", index: 1, text: "b ~ [\"y\"]" },
+            Alternative { rule: "b", index: 0, text: "[\"b\"]" }
+        ];
+        let mut profile = Profile::new(alternatives);
+
+        profile.record("b", 0, true);
+        profile.record("b", 0, true);
+        profile.record("a", 0, true);
+
+        assert_eq!(profile.to_string(), "b: 2 attempts, 2 matches, 1 retries\n\
+                                         a: 1 attempts, 1 matches, 0 retries\n\
+                                         hint: rule `b` is retried at the same position 1 times; \
+                                         consider factoring out common prefixes of the rules \
+                                         calling it or guarding them with `&`\n\
+                                         hint: rule `a` is tried 1 times and its alternatives #0 \
+                                         and #1 both start with `b`; consider factoring it out so \
+                                         that it is only matched once\n");
+    }
+
+    #[test]
+    fn merge() {
+        let mut first = Profile::new(vec![]);
+        let mut second = Profile::new(vec![]);
+
+        first.record("a", 0, false);
+        second.record("a", 0, true);
+
+        first.merge(&second);
+
+        assert_eq!(first.stats("a"), RuleStats { attempts: 2, matches: 1, retries: 0 });
+        assert_eq!(first.stats("b"), RuleStats::default());
+    }
+}
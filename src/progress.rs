@@ -0,0 +1,61 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+/// A `struct` passed to the observer installed with
+/// [`Parser::set_observer`](trait.Parser#tymethod.set_observer) that describes how far a
+/// `Parser` has gotten.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::Progress;
+/// # use std::cell::RefCell;
+/// # use std::rc::Rc;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         words = _{ word* }
+///         word  = @{ ['a'..'z']+ }
+///
+///         whitespace = _{ [" "] }
+///     }
+/// }
+///
+/// let reports = Rc::new(RefCell::new(vec![]));
+/// let observed = reports.clone();
+///
+/// let mut parser = Rdp::new(StringInput::new("a b c d"));
+///
+/// parser.set_observer(4, move |progress| observed.borrow_mut().push(progress));
+///
+/// assert!(parser.words());
+/// assert_eq!(*reports.borrow(), vec![Progress { pos: 0, len: 7, tokens: 0 },
+///                                    Progress { pos: 4, len: 7, tokens: 2 }]);
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Progress {
+    /// current position
+    pub pos:    usize,
+    /// length of the `Input`
+    pub len:    usize,
+    /// number of `Token`s in the queue
+    pub tokens: usize
+}
+
+impl Progress {
+    /// Returns the completed fraction between `0.0` and `1.0`.
+    pub fn fraction(&self) -> f64 {
+        if self.len == 0 {
+            1.0
+        } else {
+            self.pos as f64 / self.len as f64
+        }
+    }
+}
@@ -0,0 +1,337 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fmt::{self, Write};
+
+use super::joiner::escape;
+
+/// An `enum` representing a decision of a `Parser` captured in a [`Recording`](struct.Recording).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Step {
+    /// `rule` was tried at `pos`
+    Enter {
+        /// name of the rule
+        rule: &'static str,
+        /// position it was tried at
+        pos:  usize
+    },
+    /// `rule` matched from `start` to `end`
+    Match {
+        /// name of the rule
+        rule:  &'static str,
+        /// start of the match
+        start: usize,
+        /// end of the match
+        end:   usize
+    },
+    /// `rule`, tried at `pos`, failed
+    Fail {
+        /// name of the rule
+        rule: &'static str,
+        /// position it was tried at
+        pos:  usize
+    },
+    /// the `Parser` went back from `from` to `to`
+    Backtrack {
+        /// position before going back
+        from: usize,
+        /// position after going back
+        to:   usize
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Step::Enter { rule, pos }        => write!(f, "enter {} at {}", rule, pos),
+            Step::Match { rule, start, end } => write!(f, "match {} at {}..{}", rule, start, end),
+            Step::Fail { rule, pos }         => write!(f, "fail {} at {}", rule, pos),
+            Step::Backtrack { from, to }     => write!(f, "backtrack {} -> {}", from, to)
+        }
+    }
+}
+
+/// A `struct` with every [`Step`](enum.Step) a `Parser` took, in order, recorded after
+/// [`Parser::enable_recording`](trait.Parser#tymethod.enable_recording). Where a
+/// [`Profile`](struct.Profile) only counts, a `Recording` keeps the whole timeline, so that
+/// behavior on a specific input can be inspected after the fact by stepping through a
+/// [`Replay`](struct.Replay), or exported with `to_json` for a visual tool.
+///
+/// Its `Display` prints one `Step` per line.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::Step;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         pair = { key ~ ["="] ~ key | key }
+///         key  = @{ ['a'..'z']+ }
+///     }
+/// }
+///
+/// let mut parser = Rdp::new(StringInput::new("a"));
+///
+/// parser.enable_recording();
+///
+/// assert!(parser.pair());
+///
+/// let recording = parser.recording().unwrap();
+/// let mut replay = recording.replay();
+///
+/// assert_eq!(replay.step(), Some(Step::Enter { rule: "pair", pos: 0 }));
+/// assert_eq!(replay.step(), Some(Step::Enter { rule: "key", pos: 0 }));
+/// assert_eq!(replay.stack(), &[("pair", 0), ("key", 0)]);
+/// assert_eq!(replay.step(), Some(Step::Match { rule: "key", start: 0, end: 1 }));
+/// assert_eq!(replay.step(), Some(Step::Backtrack { from: 1, to: 0 }));
+/// assert_eq!(replay.pos(), 0);
+///
+/// assert_eq!(replay.back(), Some(Step::Backtrack { from: 1, to: 0 }));
+/// assert_eq!(replay.pos(), 1);
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Recording {
+    steps: Vec<Step>
+}
+
+impl Recording {
+    /// Creates an empty `Recording`.
+    pub fn new() -> Recording {
+        Recording { steps: vec![] }
+    }
+
+    /// Appends `step`.
+    #[inline]
+    pub fn record(&mut self, step: Step) {
+        self.steps.push(step);
+    }
+
+    /// Returns all recorded `Step`s, in order.
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    /// Removes all recorded `Step`s.
+    pub fn clear(&mut self) {
+        self.steps.clear();
+    }
+
+    /// Returns a `Replay` before the first `Step`.
+    pub fn replay(&self) -> Replay<'_> {
+        Replay {
+            steps: &self.steps,
+            index: 0,
+            pos:   0,
+            stack: vec![]
+        }
+    }
+
+    /// Serializes the `Recording` to a JSON array with an object per `Step`, with its `step`,
+    /// `"enter"`, `"match"`, `"fail"`, or `"backtrack"`, and its fields.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::{Recording, Step};
+    /// let mut recording = Recording::new();
+    ///
+    /// recording.record(Step::Enter { rule: "key", pos: 0 });
+    /// recording.record(Step::Fail { rule: "key", pos: 0 });
+    ///
+    /// assert_eq!(recording.to_json(), "[{\"step\":\"enter\",\"rule\":\"key\",\"pos\":0},\
+    ///                                   {\"step\":\"fail\",\"rule\":\"key\",\"pos\":0}]");
+    /// ```
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+
+        json.push('[');
+
+        for (i, step) in self.steps.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+
+            match *step {
+                Step::Enter { rule, pos } | Step::Fail { rule, pos } => {
+                    let name = if let Step::Enter { .. } = *step { "enter" } else { "fail" };
+
+                    let _ = write!(json, "{{\"step\":\"{}\",\"rule\":", name);
+                    escape(&mut json, rule);
+                    let _ = write!(json, ",\"pos\":{}}}", pos);
+                },
+                Step::Match { rule, start, end } => {
+                    json.push_str("{\"step\":\"match\",\"rule\":");
+                    escape(&mut json, rule);
+                    let _ = write!(json, ",\"start\":{},\"end\":{}}}", start, end);
+                },
+                Step::Backtrack { from, to } => {
+                    let _ = write!(json, "{{\"step\":\"backtrack\",\"from\":{},\"to\":{}}}", from,
+                                   to);
+                }
+            }
+        }
+
+        json.push(']');
+
+        json
+    }
+}
+
+impl fmt::Display for Recording {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for step in &self.steps {
+            writeln!(f, "{}", step)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A `struct` that steps through a [`Recording`](struct.Recording) forwards and backwards, and
+/// keeps the position of the `Parser` and the rules being tried at the current `Step`.
+#[derive(Clone, Debug)]
+pub struct Replay<'a> {
+    steps: &'a [Step],
+    index: usize,
+    pos:   usize,
+    stack: Vec<(&'static str, usize)>
+}
+
+impl<'a> Replay<'a> {
+    /// Applies the next `Step` and returns it, or `None` at the end of the `Recording`.
+    pub fn step(&mut self) -> Option<Step> {
+        let step = *self.steps.get(self.index)?;
+
+        match step {
+            Step::Enter { rule, pos } => {
+                self.stack.push((rule, pos));
+                self.pos = pos;
+            },
+            Step::Match { end, .. } => {
+                self.stack.pop();
+                self.pos = end;
+            },
+            Step::Fail { pos, .. } => {
+                self.stack.pop();
+                self.pos = pos;
+            },
+            Step::Backtrack { to, .. } => self.pos = to
+        }
+
+        self.index += 1;
+
+        Some(step)
+    }
+
+    /// Undoes the last applied `Step` and returns it, or `None` at the start of the `Recording`.
+    pub fn back(&mut self) -> Option<Step> {
+        let index = self.index.checked_sub(1)?;
+
+        self.seek(index);
+
+        Some(self.steps[index])
+    }
+
+    /// Moves to right before the `Step` at `index`, or to the end if `index` is out of bounds.
+    pub fn seek(&mut self, index: usize) {
+        if index < self.index {
+            self.index = 0;
+            self.pos = 0;
+            self.stack.clear();
+        }
+
+        while self.index < index && self.step().is_some() {}
+    }
+
+    /// Returns the number of `Step`s applied so far.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the position of the `Parser` after the applied `Step`s.
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// Returns the rules being tried after the applied `Step`s, outermost first, each with the
+    /// position it was tried at.
+    pub fn stack(&self) -> &[(&'static str, usize)] {
+        &self.stack
+    }
+}
+
+impl<'a> Iterator for Replay<'a> {
+    type Item = Step;
+
+    fn next(&mut self) -> Option<Step> {
+        self.step()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recording() -> Recording {
+        let mut recording = Recording::new();
+
+        recording.record(Step::Enter { rule: "a", pos: 0 });
+        recording.record(Step::Enter { rule: "b", pos: 0 });
+        recording.record(Step::Match { rule: "b", start: 0, end: 2 });
+        recording.record(Step::Backtrack { from: 2, to: 0 });
+        recording.record(Step::Fail { rule: "a", pos: 0 });
+
+        recording
+    }
+
+    #[test]
+    fn seek() {
+        let recording = recording();
+        let mut replay = recording.replay();
+
+        replay.seek(3);
+
+        assert_eq!(replay.index(), 3);
+        assert_eq!(replay.pos(), 2);
+        assert_eq!(replay.stack(), &[("a", 0)]);
+
+        replay.seek(1);
+
+        assert_eq!(replay.stack(), &[("a", 0)]);
+        assert_eq!(replay.pos(), 0);
+
+        replay.seek(usize::MAX);
+
+        assert_eq!(replay.index(), 5);
+        assert_eq!(replay.stack(), &[]);
+        assert_eq!(replay.step(), None);
+        assert_eq!(replay.count(), 0);
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(recording().to_string(), "enter a at 0\n\
+                                             enter b at 0\n\
+                                             match b at 0..2\n\
+                                             backtrack 2 -> 0\n\
+                                             fail a at 0\n");
+    }
+
+    #[test]
+    fn json() {
+        assert_eq!(recording().to_json(),
+                   "[{\"step\":\"enter\",\"rule\":\"a\",\"pos\":0},\
+                    {\"step\":\"enter\",\"rule\":\"b\",\"pos\":0},\
+                    {\"step\":\"match\",\"rule\":\"b\",\"start\":0,\"end\":2},\
+                    {\"step\":\"backtrack\",\"from\":2,\"to\":0},\
+                    {\"step\":\"fail\",\"rule\":\"a\",\"pos\":0}]");
+    }
+}
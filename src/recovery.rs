@@ -0,0 +1,203 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::HashSet;
+use std::hash::Hash;
+
+/// A `struct` representing an error kept by a [`Recovery`](struct.Recovery).
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Diagnostic<R> {
+    /// rules expected at `pos`, as returned by [`Parser::expected`](trait.Parser#tymethod.expected)
+    pub expected: Vec<R>,
+    /// position of the error
+    pub pos:      usize,
+    /// line of the error, starting at `1`
+    pub line:     usize
+}
+
+/// A `struct` that collects the errors of a parse that recovers from them, e.g. by skipping to
+/// the next statement, and keeps pathological inputs from producing thousands of them:
+///
+/// * at most [`max_errors`](#method.max_errors) errors are kept
+/// * errors within [`quiet`](#method.quiet) `Token`s after a [recovery](#method.recovered) are
+///   dropped, since they usually cascade from the previous one
+/// * errors expecting the same rules on the same line as a kept one are dropped
+///
+/// Dropped errors are counted in [`suppressed`](#method.suppressed).
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::{LineIndex, Recovery};
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         stmt  = { ident ~ [";"] ~ ["\n"]? }
+///         ident = @{ ['a'..'z']+ }
+///     }
+/// }
+///
+/// let input = "a;\n1;\n2;\nb;\n3;";
+/// let index = LineIndex::new(input);
+/// let mut parser = Rdp::new(StringInput::new(input));
+/// let mut recovery = Recovery::new().quiet(1);
+///
+/// while !parser.end() {
+///     if !parser.stmt() {
+///         let (expected, pos) = parser.expected();
+///
+///         recovery.report(expected, pos, index.line(pos), parser.queue().len());
+///
+///         let rest = parser.slice_input(parser.pos(), parser.input_len());
+///         let skipped = rest.find('\n').map_or(rest.len(), |i| i + 1);
+///
+///         parser.skip(skipped);
+///         recovery.recovered(parser.queue().len());
+///     }
+/// }
+///
+/// let lines: Vec<_> = recovery.errors().iter().map(|error| error.line).collect();
+///
+/// assert_eq!(lines, vec![2, 5]);
+/// assert_eq!(recovery.suppressed(), 1);
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Recovery<R> {
+    max_errors:   Option<usize>,
+    quiet:        usize,
+    errors:       Vec<Diagnostic<R>>,
+    seen:         HashSet<(Vec<R>, usize)>,
+    recovered_at: Option<usize>,
+    suppressed:   usize
+}
+
+impl<R: Clone + Eq + Hash> Recovery<R> {
+    /// Creates a `Recovery` that keeps every error that is not a duplicate.
+    pub fn new() -> Recovery<R> {
+        Recovery {
+            max_errors:   None,
+            quiet:        0,
+            errors:       vec![],
+            seen:         HashSet::new(),
+            recovered_at: None,
+            suppressed:   0
+        }
+    }
+
+    /// Keeps at most `max_errors` errors.
+    pub fn max_errors(mut self, max_errors: usize) -> Recovery<R> {
+        self.max_errors = Some(max_errors);
+        self
+    }
+
+    /// Drops errors reported before `quiet` more `Token`s have been queued since the last
+    /// recovery.
+    pub fn quiet(mut self, quiet: usize) -> Recovery<R> {
+        self.quiet = quiet;
+        self
+    }
+
+    /// Reports an error expecting `expected` at `pos` on `line` while `tokens` `Token`s are
+    /// queued and returns whether it was kept.
+    pub fn report(&mut self, expected: Vec<R>, pos: usize, line: usize, tokens: usize) -> bool {
+        let cascading = self.recovered_at.is_some_and(|at| tokens.saturating_sub(at) < self.quiet);
+
+        if cascading || self.is_full() || !self.seen.insert((expected.clone(), line)) {
+            self.suppressed += 1;
+
+            return false;
+        }
+
+        self.errors.push(Diagnostic {
+            expected,
+            pos,
+            line
+        });
+
+        true
+    }
+
+    /// Marks that the parse recovered from an error while `tokens` `Token`s are queued.
+    pub fn recovered(&mut self, tokens: usize) {
+        self.recovered_at = Some(tokens);
+    }
+
+    /// Returns whether [`max_errors`](#method.max_errors) errors were kept, after which a parse
+    /// may as well stop.
+    pub fn is_full(&self) -> bool {
+        self.max_errors.is_some_and(|max_errors| self.errors.len() >= max_errors)
+    }
+
+    /// Returns the kept errors in the order they were reported.
+    pub fn errors(&self) -> &[Diagnostic<R>] {
+        &self.errors
+    }
+
+    /// Returns the number of dropped errors.
+    pub fn suppressed(&self) -> usize {
+        self.suppressed
+    }
+
+    /// Returns the kept errors.
+    pub fn into_errors(self) -> Vec<Diagnostic<R>> {
+        self.errors
+    }
+}
+
+impl<R: Clone + Eq + Hash> Default for Recovery<R> {
+    fn default() -> Recovery<R> {
+        Recovery::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Recovery;
+
+    #[test]
+    fn max_errors() {
+        let mut recovery = Recovery::new().max_errors(2);
+
+        assert!(recovery.report(vec!["a"], 0, 1, 0));
+        assert!(!recovery.is_full());
+        assert!(recovery.report(vec!["a"], 5, 2, 0));
+        assert!(recovery.is_full());
+        assert!(!recovery.report(vec!["a"], 10, 3, 0));
+        assert_eq!(recovery.errors().len(), 2);
+        assert_eq!(recovery.suppressed(), 1);
+    }
+
+    #[test]
+    fn cascading() {
+        let mut recovery = Recovery::new().quiet(2);
+
+        assert!(recovery.report(vec!["a"], 0, 1, 3));
+        recovery.recovered(3);
+        assert!(!recovery.report(vec!["b"], 4, 1, 3));
+        assert!(!recovery.report(vec!["c"], 6, 1, 4));
+        assert!(recovery.report(vec!["d"], 8, 1, 5));
+        assert_eq!(recovery.suppressed(), 2);
+    }
+
+    #[test]
+    fn duplicates() {
+        let mut recovery = Recovery::new();
+
+        assert!(recovery.report(vec!["a", "b"], 0, 1, 0));
+        assert!(!recovery.report(vec!["a", "b"], 3, 1, 0));
+        assert!(recovery.report(vec!["a"], 3, 1, 0));
+        assert!(recovery.report(vec!["a", "b"], 6, 2, 0));
+
+        let positions: Vec<_> = recovery.into_errors().into_iter().map(|error| error.pos)
+                                        .collect();
+
+        assert_eq!(positions, vec![0, 3, 6]);
+    }
+}
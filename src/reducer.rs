@@ -0,0 +1,145 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::cmp;
+
+/// A `struct` that shrinks a failing input to a minimal reproducer with delta debugging. It first
+/// removes chunks of lines, then chunks of `char`s, keeping every removal after which `fails`
+/// still returns `true`.
+///
+/// `fails` decides what counts as the same failure, e.g. a failing start rule with the same
+/// [`Parser::expected`](trait.Parser#tymethod.expected) `Rule`s.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::Reducer;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         list   = { ["["] ~ (number ~ ([","] ~ number)*)? ~ ["]"] ~ eoi }
+///         number = @{ ['0'..'9']+ }
+///
+///         whitespace = _{ [" "] | ["\n"] }
+///     }
+/// }
+///
+/// // the expected rules along with the char where parsing failed
+/// fn failure(input: &str) -> Option<(Vec<Rule>, Option<char>)> {
+///     let mut parser = Rdp::new(StringInput::new(input));
+///
+///     if parser.list() {
+///         None
+///     } else {
+///         let (rules, pos) = parser.expected();
+///
+///         Some((rules, input[pos..].chars().next()))
+///     }
+/// }
+///
+/// let input = "[1, 2,\n 3, 4,,\n 5, 6]";
+/// let original = failure(input);
+///
+/// let reduced = Reducer::new(|input| failure(input) == original).reduce(input);
+///
+/// assert_eq!(reduced, "[,");
+/// # }
+/// ```
+pub struct Reducer<F> {
+    fails: F
+}
+
+impl<F> Reducer<F> where F: FnMut(&str) -> bool {
+    /// Creates a `Reducer` that preserves the failure described by `fails`.
+    pub fn new(fails: F) -> Reducer<F> {
+        Reducer {
+            fails
+        }
+    }
+
+    /// Returns a minimized version of `input`. If `input` itself does not fail, it is returned
+    /// unchanged.
+    pub fn reduce(&mut self, input: &str) -> String {
+        if !(self.fails)(input) {
+            return input.to_owned();
+        }
+
+        let lines = self.ddmin(input.split_inclusive('\n').collect());
+
+        let reduced = lines.concat();
+        let chars = reduced.char_indices().map(|(i, c)| &reduced[i..i + c.len_utf8()]).collect();
+
+        self.ddmin(chars).concat()
+    }
+
+    fn ddmin<'a>(&mut self, mut units: Vec<&'a str>) -> Vec<&'a str> {
+        let mut n = 2;
+
+        while units.len() >= 2 {
+            let len = units.len();
+            let chunk = len.div_ceil(n);
+            let mut reduced = false;
+
+            for start in (0..len).step_by(chunk) {
+                let end = cmp::min(start + chunk, len);
+
+                let candidate: Vec<&str> = units[..start].iter()
+                                                         .chain(units[end..].iter())
+                                                         .cloned()
+                                                         .collect();
+
+                if (self.fails)(&candidate.concat()) {
+                    units = candidate;
+                    n = cmp::max(n - 1, 2);
+                    reduced = true;
+
+                    break;
+                }
+            }
+
+            if !reduced {
+                if n >= len {
+                    break;
+                }
+
+                n = cmp::min(n * 2, len);
+            }
+        }
+
+        units
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Reducer;
+
+    #[test]
+    fn substring() {
+        let reduced = Reducer::new(|input: &str| input.contains("bug")).reduce("a\nxbugx\nb\n");
+
+        assert_eq!(reduced, "bug");
+    }
+
+    #[test]
+    fn passing() {
+        let reduced = Reducer::new(|input: &str| input.contains("bug")).reduce("fine");
+
+        assert_eq!(reduced, "fine");
+    }
+
+    #[test]
+    fn scattered() {
+        let reduced = Reducer::new(|input: &str| {
+            input.contains('ă') && input.contains('z')
+        }).reduce("ăbc\ndef\nxyz");
+
+        assert_eq!(reduced, "ăz");
+    }
+}
@@ -0,0 +1,114 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::sync::Arc;
+
+use super::{LimitError, LineIndex, Token, Unclosed};
+
+/// A `struct` holding everything a run of a `Parser` produced, returned by
+/// [`Parser::run`](trait.Parser#tymethod.run). It owns its data and is `Send + Sync` for any
+/// `Send + Sync` `Rule`, so it can be inspected on other threads while the `Parser` that produced
+/// it is already parsing something else. Cloning it does not copy the input.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use std::thread;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         words = { word* ~ eoi }
+///         word  = @{ ['a'..'z']+ }
+///
+///         whitespace = _{ [" "] | ["\n"] }
+///     }
+/// }
+///
+/// let mut parser = Rdp::new(StringInput::new("one two\nthree"));
+/// let result = parser.run(Rdp::words);
+///
+/// assert!(result.is_complete());
+/// assert!(parser.queue().is_empty());
+///
+/// let last = thread::spawn(move || {
+///     let token = result.tokens.last().unwrap();
+///
+///     (result.slice(token).to_owned(), result.line_col(token.start))
+/// });
+///
+/// assert_eq!(last.join().unwrap(), ("three".to_owned(), (2, 1)));
+/// # }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseResult<R> {
+    /// whether the rule matched
+    pub matched:     bool,
+    /// position where the rule stopped matching
+    pub end:         usize,
+    /// queued `Token`s, in pre-order
+    pub tokens:      Vec<Token<R>>,
+    /// the whole input
+    pub input:       Arc<str>,
+    /// [`LineIndex`](struct.LineIndex) of `input`
+    pub line_index:  LineIndex,
+    /// [expected](trait.Parser#tymethod.expected) `Rule`s at the farthest failure, and its
+    /// position
+    pub expected:    (Vec<R>, usize),
+    /// [unclosed](trait.Parser#tymethod.unclosed) delimiter at the farthest failure, if any
+    pub unclosed:    Option<Unclosed>,
+    /// exceeded [limit](struct.Limits), if any
+    pub limit_error: Option<LimitError>
+}
+
+impl<R> ParseResult<R> {
+    /// Returns whether the rule matched the whole input.
+    pub fn is_complete(&self) -> bool {
+        self.matched && self.end == self.input.len()
+    }
+
+    /// Returns the part of the input matched by `token`.
+    pub fn slice(&self, token: &Token<R>) -> &str {
+        &self.input[token.start..token.end]
+    }
+
+    /// Returns the line and column of `pos`, both starting at `1`.
+    pub fn line_col(&self, pos: usize) -> (usize, usize) {
+        self.line_index.line_col(&self.input, pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn send_sync() {
+        is_send_sync::<ParseResult<u8>>();
+    }
+
+    #[test]
+    fn complete() {
+        let result = ParseResult {
+            matched:     true,
+            end:         2,
+            tokens:      vec![Token { rule: 0u8, start: 0, end: 2 }],
+            input:       Arc::from("ab\nc"),
+            line_index:  LineIndex::new("ab\nc"),
+            expected:    (vec![], 0),
+            unclosed:    None,
+            limit_error: None
+        };
+
+        assert!(!result.is_complete());
+        assert_eq!(result.slice(&result.tokens[0]), "ab");
+        assert_eq!(result.line_col(3), (2, 1));
+    }
+}
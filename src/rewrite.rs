@@ -0,0 +1,328 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::Token;
+
+// index of every Token's parent in a pre-order queue
+pub fn parents<R: PartialEq>(queue: &[Token<R>]) -> Vec<Option<usize>> {
+    let mut parents = Vec::with_capacity(queue.len());
+    let mut stack: Vec<usize> = vec![];
+
+    for (i, token) in queue.iter().enumerate() {
+        while let Some(&top) = stack.last() {
+            if queue[top].is_ancestor_of(token) {
+                break;
+            }
+
+            stack.pop();
+        }
+
+        parents.push(stack.last().cloned());
+        stack.push(i);
+    }
+
+    parents
+}
+
+/// A `struct` that changes the `Rule` of `Token`s in a [queue](trait.Parser#tymethod.queue)
+/// depending on their parent, so that `process!` can tell apart `Token`s that only differ by
+/// context.
+///
+/// Patterns are matched against the `Rule`s the queue had before relabeling.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::Relabel;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         call = { ident ~ ["("] ~ (ident ~ ([","] ~ ident)*)? ~ [")"] }
+///         ident = @{ ['a'..'z']+ }
+///         function_name = token
+///     }
+/// }
+///
+/// let mut parser = Rdp::new(StringInput::new("f(a,b)"));
+///
+/// assert!(parser.call());
+///
+/// let relabeled = Relabel::new().nth_child(Rule::call, Rule::ident, 0, Rule::function_name)
+///                               .apply(parser.queue_mut());
+///
+/// assert_eq!(relabeled, 1);
+/// assert_eq!(parser.queue().iter().map(|token| token.rule).collect::<Vec<_>>(),
+///            vec![Rule::call, Rule::function_name, Rule::ident, Rule::ident]);
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Relabel<R> {
+    patterns: Vec<(R, R, Option<usize>, R)>
+}
+
+impl<R: Copy + PartialEq> Relabel<R> {
+    /// Creates a `Relabel` without patterns.
+    pub fn new() -> Relabel<R> {
+        Relabel {
+            patterns: vec![]
+        }
+    }
+
+    /// Relabels every direct child of rule `child` of a `Token` of rule `parent` to `to`.
+    pub fn child(mut self, parent: R, child: R, to: R) -> Relabel<R> {
+        self.patterns.push((parent, child, None, to));
+
+        self
+    }
+
+    /// Relabels the `n`th direct child of rule `child`, counting from `0`, of a `Token` of rule
+    /// `parent` to `to`.
+    pub fn nth_child(mut self, parent: R, child: R, n: usize, to: R) -> Relabel<R> {
+        self.patterns.push((parent, child, Some(n), to));
+
+        self
+    }
+
+    /// Relabels the `Token`s of a pre-order `queue` and returns how many were relabeled. The
+    /// first matching pattern wins.
+    pub fn apply(&self, queue: &mut [Token<R>]) -> usize {
+        let parents = parents(queue);
+        let mut relabels = vec![];
+
+        for (i, token) in queue.iter().enumerate() {
+            let parent = match parents[i] {
+                Some(parent) => parent,
+                None         => continue
+            };
+
+            let pattern = self.patterns.iter().find(|&&(parent_rule, child, n, _)| {
+                queue[parent].rule == parent_rule && token.rule == child && match n {
+                    Some(n) => {
+                        (parent + 1..i).filter(|&j| {
+                            parents[j] == Some(parent) && queue[j].rule == child
+                        }).count() == n
+                    },
+                    None    => true
+                }
+            });
+
+            if let Some(&(_, _, _, to)) = pattern {
+                relabels.push((i, to));
+            }
+        }
+
+        for &(i, to) in &relabels {
+            queue[i].rule = to;
+        }
+
+        relabels.len()
+    }
+}
+
+/// A `struct` that normalizes a pre-order [queue](trait.Parser#tymethod.queue) into a cleaner
+/// tree before it is processed. Rewrites are configured per `Rule` and run in the order
+/// [`remove`](#method.remove), [`flatten`](#method.flatten), [`merge`](#method.merge).
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::Rewriter;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         sum     = { term ~ (plus ~ term)* }
+///         term    = { digits }
+///         digits  = { digit+ }
+///         digit   = { ['0'..'9'] }
+///         plus    = { ["+"] }
+///     }
+/// }
+///
+/// let mut parser = Rdp::new(StringInput::new("12+3"));
+///
+/// assert!(parser.sum());
+///
+/// Rewriter::new().remove(Rule::plus)
+///                .flatten(Rule::term)
+///                .merge(Rule::digit)
+///                .apply(parser.queue_mut());
+///
+/// assert_eq!(parser.queue(), &vec![
+///     Token { rule: Rule::sum, start: 0, end: 4 },
+///     Token { rule: Rule::digits, start: 0, end: 2 },
+///     Token { rule: Rule::digit, start: 0, end: 2 },
+///     Token { rule: Rule::digits, start: 3, end: 4 },
+///     Token { rule: Rule::digit, start: 3, end: 4 }
+/// ]);
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Rewriter<R> {
+    remove:  Vec<R>,
+    flatten: Vec<R>,
+    merge:   Vec<R>
+}
+
+impl<R: Copy + PartialEq> Rewriter<R> {
+    /// Creates a `Rewriter` that leaves queues unchanged.
+    pub fn new() -> Rewriter<R> {
+        Rewriter {
+            remove:  vec![],
+            flatten: vec![],
+            merge:   vec![]
+        }
+    }
+
+    /// Removes `Token`s of `rule`. Their children are kept and take their place in the tree.
+    pub fn remove(mut self, rule: R) -> Rewriter<R> {
+        self.remove.push(rule);
+
+        self
+    }
+
+    /// Removes `Token`s of `rule` that have exactly one child, collapsing single-child chains.
+    pub fn flatten(mut self, rule: R) -> Rewriter<R> {
+        self.flatten.push(rule);
+
+        self
+    }
+
+    /// Merges adjacent sibling `Token`s of `rule` where one ends where the next one starts into
+    /// a single `Token` containing the children of both.
+    pub fn merge(mut self, rule: R) -> Rewriter<R> {
+        self.merge.push(rule);
+
+        self
+    }
+
+    /// Rewrites a pre-order `queue` in place.
+    pub fn apply(&self, queue: &mut Vec<Token<R>>) {
+        if !self.remove.is_empty() {
+            queue.retain(|token| !self.remove.contains(&token.rule));
+        }
+
+        if !self.flatten.is_empty() {
+            let parents = parents(queue);
+            let mut children = vec![0; queue.len()];
+
+            for &parent in parents.iter().flatten() {
+                children[parent] += 1;
+            }
+
+            let mut i = 0;
+
+            queue.retain(|token| {
+                let keep = children[i] != 1 || !self.flatten.contains(&token.rule);
+                i += 1;
+
+                keep
+            });
+        }
+
+        if !self.merge.is_empty() {
+            let parents = parents(queue);
+            let mut last_sibling: Vec<Option<usize>> = vec![None; queue.len() + 1];
+            let mut merged_into: Vec<Option<usize>> = vec![None; queue.len()];
+
+            for i in 0..queue.len() {
+                // siblings of root Tokens are stored at the end
+                let parent = parents[i].unwrap_or(queue.len());
+
+                if let Some(prev) = last_sibling[parent] {
+                    let target = merged_into[prev].unwrap_or(prev);
+
+                    if self.merge.contains(&queue[i].rule) && queue[target].rule == queue[i].rule &&
+                       queue[target].end == queue[i].start {
+                        queue[target].end = queue[i].end;
+                        merged_into[i] = Some(target);
+                    }
+                }
+
+                last_sibling[parent] = Some(i);
+            }
+
+            let mut i = 0;
+
+            queue.retain(|_| {
+                let keep = merged_into[i].is_none();
+                i += 1;
+
+                keep
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(rule: u8, start: usize, end: usize) -> Token<u8> {
+        Token { rule, start, end }
+    }
+
+    #[test]
+    fn tree() {
+        let queue = vec![token(0, 0, 10), token(1, 0, 5), token(2, 1, 2), token(1, 5, 10)];
+
+        assert_eq!(parents(&queue), vec![None, Some(0), Some(1), Some(0)]);
+    }
+
+    #[test]
+    fn relabel() {
+        let mut queue = vec![
+            token(0, 0, 10),
+            token(1, 0, 5),
+            token(1, 1, 2),
+            token(1, 5, 8),
+            token(1, 8, 10)
+        ];
+
+        let count = Relabel::new().nth_child(0, 1, 1, 7)
+                                  .child(0, 1, 8)
+                                  .apply(&mut queue);
+
+        assert_eq!(count, 3);
+        assert_eq!(queue.iter().map(|token| token.rule).collect::<Vec<_>>(), vec![0, 8, 1, 7, 8]);
+    }
+
+    #[test]
+    fn remove_and_flatten() {
+        let mut queue = vec![token(0, 0, 4), token(1, 0, 4), token(2, 0, 4), token(3, 0, 1)];
+
+        Rewriter::new().remove(3).flatten(0).flatten(1).apply(&mut queue);
+
+        assert_eq!(queue, vec![token(2, 0, 4)]);
+    }
+
+    #[test]
+    fn merge() {
+        let mut queue = vec![
+            token(0, 0, 6),
+            token(1, 0, 2),
+            token(2, 0, 1),
+            token(1, 2, 3),
+            token(1, 4, 5),
+            token(1, 5, 6),
+            token(2, 5, 6)
+        ];
+
+        Rewriter::new().merge(1).apply(&mut queue);
+
+        assert_eq!(queue, vec![
+            token(0, 0, 6),
+            token(1, 0, 3),
+            token(2, 0, 1),
+            token(1, 4, 6),
+            token(2, 5, 6)
+        ]);
+    }
+}
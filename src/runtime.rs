@@ -0,0 +1,60 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A `mod` with the runtime of pest: the `Parser` and `Input` `trait`s, the provided `Input`s,
+//! `Token`, errors, and every other item that code generated by
+//! [`impl_rdp!`](../macro.impl_rdp!), [`grammar!`](../macro.grammar!), and the other `macro`s
+//! refers to.
+//!
+//! Apart from each other, the `macro`s only ever refer to paths in this `mod`, so it is the
+//! surface generated parsers depend on. Tools that only drive `Parser`s, implement `Input`s, or
+//! consume `Token`s can import everything they need from here without touching the `macro`s. The
+//! [`prelude`](../prelude/index.html) is a subset of this `mod`.
+//!
+//! Changes to this `mod` follow the version of the crate strictly: items are only ever added in
+//! minor versions, and removed or changed in major ones.
+
+pub use super::{Alternative, Backtrack, BacktrackBounds, Coverage, Cursor, Event, Events,
+                Expected, FirstSet, GeneratedParser, Heredoc, Input, Interner, Keywords, Lexeme,
+                LexemeKind, LimitError, LimitKind, Limits, LineIndex, Lint, LintLevel, Modes,
+                ParseResult, Parser, Partial, Pratt, Profile, Progress, QueueError, Recording,
+                RuleKind, Step, Suggestion, Symbol, Token, TokenData, TokenIndex, Tracking, Trivia,
+                Unclosed};
+pub use super::{CrlfInput, LossyInput, StringInput};
+#[cfg(feature = "encoding")]
+pub use super::{DecodeError, DecodedInput};
+pub use super::{children, validate_queue, validate_spans};
+#[doc(hidden)]
+pub use super::{trace_enter, trace_exit, EmbeddedTable, MemoTable, SavedState};
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn macros_only_use_runtime() {
+        let sources = [
+            ("grammar.rs", include_str!("grammar.rs")),
+            ("parsers/rdp.rs", include_str!("parsers/rdp.rs")),
+            ("pratt.rs", include_str!("pratt.rs")),
+            ("process.rs", include_str!("process.rs")),
+            ("tree.rs", include_str!("tree.rs"))
+        ];
+
+        for &(name, source) in &sources {
+            for (line, text) in source.lines().enumerate() {
+                let outside = text.match_indices("$crate::").any(|(i, _)| {
+                    let path = &text[i + "$crate::".len()..];
+                    let name_len = path.find(|c: char| !c.is_alphanumeric() && c != '_')
+                                       .unwrap_or(path.len());
+
+                    !path.starts_with("runtime::") && !path[name_len..].starts_with('!')
+                });
+
+                assert!(!outside, "{}:{} refers to a path outside of runtime", name, line + 1);
+            }
+        }
+    }
+}
@@ -0,0 +1,274 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::fmt;
+
+use super::{Parser, Token};
+use super::first_set::literal_prefixes;
+
+/// A `struct` that finds the records of several top-level rules in a large input, e.g. log
+/// lines of a few kinds among many others. All the literal strings the rules start with are
+/// compiled into one Aho-Corasick automaton, which finds every candidate start in a single pass
+/// over the input; only candidates are then confirmed by matching the full rules.
+///
+/// Every rule needs to start with a literal string, which is derived from its source, like a
+/// [`FirstSet`](struct.FirstSet). Where candidates of several rules start at the same position,
+/// they are tried in the order the rules were passed in. Records do not overlap: scanning goes on
+/// after the end of every confirmed record.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::Scanner;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         error   = @{ ["ERROR "] ~ message }
+///         warning = @{ (["WARN "] | ["WARNING "]) ~ message }
+///         message = @{ (!["\n"] ~ any)+ }
+///     }
+/// }
+///
+/// let input = "INFO a\nERROR b\nWARNING c\nERROR \n";
+/// let mut parser = Rdp::new(StringInput::new(input));
+///
+/// let scanner = Scanner::new(&parser, &[(Rule::error, Rdp::error),
+///                                       (Rule::warning, Rdp::warning)]).unwrap();
+///
+/// assert_eq!(scanner.scan(&mut parser), vec![
+///     Token { rule: Rule::error, start: 7, end: 14 },
+///     Token { rule: Rule::warning, start: 15, end: 24 }
+/// ]);
+/// # }
+/// ```
+#[allow(clippy::type_complexity)]
+pub struct Scanner<P: Parser> {
+    rules:     Vec<(P::Rule, fn(&mut P) -> bool)>,
+    automaton: Automaton
+}
+
+impl<P: Parser> Scanner<P> where P::Rule: Copy + fmt::Debug {
+    /// Creates a `Scanner` of `rules` of `parser`'s grammar, each with the method matching it.
+    /// Returns a `ScannerError` with the first rule that does not start with a literal string.
+    #[allow(clippy::type_complexity)]
+    pub fn new(parser: &P, rules: &[(P::Rule, fn(&mut P) -> bool)])
+               -> Result<Scanner<P>, ScannerError<P::Rule>> {
+        let alternatives = parser.alternatives();
+        let mut patterns = vec![];
+
+        for (index, &(rule, _)) in rules.iter().enumerate() {
+            let prefixes = literal_prefixes(&alternatives, &format!("{:?}", rule))
+                               .ok_or(ScannerError { rule })?;
+
+            patterns.extend(prefixes.into_iter().map(|prefix| (prefix, index)));
+        }
+
+        Ok(Scanner {
+            rules:     rules.to_vec(),
+            automaton: Automaton::new(&patterns)
+        })
+    }
+
+    /// Returns the positions at or after `parser`'s current one where a rule could start, in
+    /// order, each with the indices of the rules in the order they were passed to `new`.
+    pub fn candidates(&self, parser: &P) -> Vec<(usize, Vec<usize>)> {
+        let pos = parser.pos();
+        let mut starts: Vec<(usize, usize)> = vec![];
+
+        self.automaton.find(parser.slice_input(pos, parser.input_len()).as_bytes(),
+                            |start, index| starts.push((pos + start, index)));
+
+        starts.sort();
+        starts.dedup();
+
+        let mut candidates: Vec<(usize, Vec<usize>)> = vec![];
+
+        for (start, index) in starts {
+            match candidates.last_mut().filter(|candidate| candidate.0 == start) {
+                Some(candidate) => candidate.1.push(index),
+                None            => candidates.push((start, vec![index]))
+            }
+        }
+
+        candidates
+    }
+
+    /// Scans `parser`'s input from its current position and returns a `Token` for every record
+    /// confirmed by its rule. The `Token`s of the records are added to the queue like with any
+    /// other rule, and `parser` ends up after the last one.
+    pub fn scan(&self, parser: &mut P) -> Vec<Token<P::Rule>> {
+        let mut records = vec![];
+        let mut end = parser.pos();
+
+        for (start, indices) in self.candidates(parser) {
+            if start < end {
+                continue;
+            }
+
+            for index in indices {
+                let (rule, matcher) = self.rules[index];
+
+                parser.set_pos(start);
+
+                if parser.try(false, matcher) {
+                    end = parser.pos();
+
+                    records.push(Token { rule, start, end });
+
+                    break;
+                }
+            }
+        }
+
+        parser.set_pos(end);
+
+        records
+    }
+}
+
+/// A `struct` describing a rule passed to [`Scanner::new`](struct.Scanner#method.new) that does
+/// not start with a literal string.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ScannerError<R> {
+    /// the rule without a literal prefix
+    pub rule: R
+}
+
+impl<R: fmt::Debug> fmt::Display for ScannerError<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "rule {:?} does not start with a literal string", self.rule)
+    }
+}
+
+impl<R: fmt::Debug> Error for ScannerError<R> {}
+
+// an Aho-Corasick automaton over bytes, whose states are the prefixes of all patterns
+struct Automaton {
+    next:    Vec<HashMap<u8, usize>>,
+    fail:    Vec<usize>,
+    // (pattern length, pattern index) of all patterns ending in a state, including the ones
+    // reachable through failure links
+    outputs: Vec<Vec<(usize, usize)>>
+}
+
+impl Automaton {
+    fn new(patterns: &[(String, usize)]) -> Automaton {
+        let mut automaton = Automaton {
+            next:    vec![HashMap::new()],
+            fail:    vec![0],
+            outputs: vec![vec![]]
+        };
+
+        for &(ref pattern, index) in patterns {
+            let mut state = 0;
+
+            for &byte in pattern.as_bytes() {
+                state = match automaton.next[state].get(&byte) {
+                    Some(&next) => next,
+                    None        => {
+                        let next = automaton.next.len();
+
+                        automaton.next.push(HashMap::new());
+                        automaton.fail.push(0);
+                        automaton.outputs.push(vec![]);
+                        automaton.next[state].insert(byte, next);
+
+                        next
+                    }
+                };
+            }
+
+            automaton.outputs[state].push((pattern.len(), index));
+        }
+
+        // breadth-first, so that the failure links of shorter prefixes are known first
+        let mut queue: VecDeque<usize> = automaton.next[0].values().cloned().collect();
+
+        while let Some(state) = queue.pop_front() {
+            let transitions: Vec<_> = automaton.next[state].iter().map(|(&b, &s)| (b, s))
+                                                           .collect();
+
+            for (byte, next) in transitions {
+                let mut fail = automaton.fail[state];
+
+                while fail != 0 && !automaton.next[fail].contains_key(&byte) {
+                    fail = automaton.fail[fail];
+                }
+
+                let fail = automaton.next[fail].get(&byte).cloned().unwrap_or(0);
+                let inherited = automaton.outputs[fail].clone();
+
+                automaton.fail[next] = fail;
+                automaton.outputs[next].extend(inherited);
+
+                queue.push_back(next);
+            }
+        }
+
+        automaton
+    }
+
+    // calls found with the start and pattern index of every occurrence of a pattern in bytes
+    fn find<F>(&self, bytes: &[u8], mut found: F) where F: FnMut(usize, usize) {
+        let mut state = 0;
+
+        for (i, byte) in bytes.iter().enumerate() {
+            while state != 0 && !self.next[state].contains_key(byte) {
+                state = self.fail[state];
+            }
+
+            state = self.next[state].get(byte).cloned().unwrap_or(0);
+
+            for &(len, index) in &self.outputs[state] {
+                found(i + 1 - len, index);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Automaton;
+
+    fn find(patterns: &[&str], haystack: &str) -> Vec<(usize, usize)> {
+        let patterns: Vec<_> = patterns.iter().enumerate().map(|(i, &pattern)| {
+            (pattern.to_owned(), i)
+        }).collect();
+        let mut found = vec![];
+
+        Automaton::new(&patterns).find(haystack.as_bytes(), |start, index| {
+            found.push((start, index))
+        });
+
+        found.sort();
+
+        found
+    }
+
+    #[test]
+    fn overlapping() {
+        assert_eq!(find(&["he", "she", "his", "hers"], "ushers"),
+                   vec![(1, 1), (2, 0), (2, 3)]);
+    }
+
+    #[test]
+    fn failure_links() {
+        assert_eq!(find(&["abcd", "bc", "c"], "abcabcd"),
+                   vec![(1, 1), (2, 2), (3, 0), (4, 1), (5, 2)]);
+        assert_eq!(find(&["aa"], "aaaa"), vec![(0, 0), (1, 0), (2, 0)]);
+    }
+
+    #[test]
+    fn none() {
+        assert_eq!(find(&["x"], "abc"), vec![]);
+        assert_eq!(find(&[], "abc"), vec![]);
+    }
+}
@@ -0,0 +1,147 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Rules for the extended ISO 8601 date & time formats, as profiled by
+//! [RFC 3339](https://tools.ietf.org/html/rfc3339). Fields are range-checked, but days are not
+//! checked against their month.
+//!
+//! # Examples
+//!
+//! ```
+//! # #[macro_use] extern crate pest;
+//! # use pest::prelude::*;
+//! use pest::stdlib::datetime::DateTime;
+//!
+//! # fn main() {
+//! impl_rdp! {
+//!     grammar! {
+//!         timestamp = @{ iso8601_datetime ~ eoi }
+//!     }
+//! }
+//!
+//! assert!(Rdp::new(StringInput::new("2016-06-13T21:05:00.5+02:00")).timestamp());
+//! assert!(!Rdp::new(StringInput::new("2016-13-13T21:05:00Z")).timestamp());
+//! # }
+//! ```
+
+use super::super::Parser;
+use super::digit;
+
+/// A `trait` that adds ISO 8601 date & time rules to every `Parser`.
+pub trait DateTime: Parser + Sized {
+    /// Matches a calendar date, e.g. `2016-06-13`.
+    fn iso8601_date(&mut self) -> bool {
+        self.try(false, |parser| {
+            fixed_digits(parser, 4) && parser.match_string("-") &&
+            two_digits(parser, 1, 12) && parser.match_string("-") &&
+            two_digits(parser, 1, 31)
+        })
+    }
+
+    /// Matches a time of day with optional seconds, fractions of a second, and time zone, e.g.
+    /// `21:05`, `21:05:00.25`, or `21:05:00Z`.
+    fn iso8601_time(&mut self) -> bool {
+        self.try(false, |parser| {
+            if !(two_digits(parser, 0, 23) && parser.match_string(":") &&
+                 two_digits(parser, 0, 59)) {
+                return false;
+            }
+
+            parser.try(false, |parser| {
+                parser.match_string(":") && two_digits(parser, 0, 60) && {
+                    parser.try(false, |parser| {
+                        (parser.match_string(".") || parser.match_string(",")) &&
+                        digit(parser, 10) && {
+                            while digit(parser, 10) {}
+
+                            true
+                        }
+                    });
+
+                    true
+                }
+            });
+
+            let _ = parser.match_string("Z") || parser.try(false, |parser| {
+                (parser.match_string("+") || parser.match_string("-")) &&
+                two_digits(parser, 0, 23) && parser.match_string(":") &&
+                two_digits(parser, 0, 59)
+            });
+
+            true
+        })
+    }
+
+    /// Matches a date followed by `T` and a time, e.g. `2016-06-13T21:05:00Z`.
+    fn iso8601_datetime(&mut self) -> bool {
+        self.try(false, |parser| {
+            parser.iso8601_date() && parser.match_string("T") && parser.iso8601_time()
+        })
+    }
+}
+
+impl<P: Parser> DateTime for P {}
+
+fn fixed_digits<P: Parser>(parser: &mut P, count: usize) -> bool {
+    (0..count).all(|_| digit(parser, 10))
+}
+
+fn two_digits<P: Parser>(parser: &mut P, min: u32, max: u32) -> bool {
+    let start = parser.pos();
+
+    parser.try(false, |parser| {
+        fixed_digits(parser, 2) && {
+            let value = parser.slice_input(start, start + 2).parse::<u32>().unwrap();
+
+            min <= value && value <= max
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::prelude::*;
+    use super::DateTime;
+
+    impl_rdp! {
+        grammar! {
+            date     = @{ iso8601_date ~ eoi }
+            time     = @{ iso8601_time ~ eoi }
+            datetime = @{ iso8601_datetime ~ eoi }
+        }
+    }
+
+    #[test]
+    fn date() {
+        assert!(Rdp::new(StringInput::new("2016-06-13")).date());
+        assert!(Rdp::new(StringInput::new("0000-12-31")).date());
+
+        assert!(!Rdp::new(StringInput::new("2016-00-13")).date());
+        assert!(!Rdp::new(StringInput::new("2016-06-32")).date());
+        assert!(!Rdp::new(StringInput::new("16-06-13")).date());
+    }
+
+    #[test]
+    fn time() {
+        assert!(Rdp::new(StringInput::new("21:05")).time());
+        assert!(Rdp::new(StringInput::new("23:59:60")).time());
+        assert!(Rdp::new(StringInput::new("21:05:00.123")).time());
+        assert!(Rdp::new(StringInput::new("21:05:00Z")).time());
+        assert!(Rdp::new(StringInput::new("21:05-05:30")).time());
+
+        assert!(!Rdp::new(StringInput::new("24:00")).time());
+        assert!(!Rdp::new(StringInput::new("21:60")).time());
+        assert!(!Rdp::new(StringInput::new("21:05:00.")).time());
+    }
+
+    #[test]
+    fn datetime() {
+        assert!(Rdp::new(StringInput::new("2016-06-13T21:05:00Z")).datetime());
+
+        assert!(!Rdp::new(StringInput::new("2016-06-13 21:05:00Z")).datetime());
+    }
+}
@@ -0,0 +1,239 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A fast path for [RFC 4180](https://tools.ietf.org/html/rfc4180)-style delimited records like
+//! CSV or TSV.
+//!
+//! Since rules in `grammar!` cannot take arguments, `delimited_fields` is called from a small
+//! method of your own which can then be used in `grammar!` like any other rule.
+//!
+//! # Examples
+//!
+//! ```
+//! # #[macro_use] extern crate pest;
+//! # use pest::prelude::*;
+//! use pest::stdlib::delimited::Delimited;
+//!
+//! # fn main() {
+//! impl_rdp! {
+//!     grammar! {
+//!         csv    = { record ~ (["\n"] ~ record)* ~ eoi }
+//!         record = { fields }
+//!         field  = token
+//!     }
+//! }
+//!
+//! impl<T: Input> Rdp<T> {
+//!     fn fields(&mut self) -> bool {
+//!         self.delimited_fields(',', '"', Rule::field)
+//!     }
+//! }
+//!
+//! let mut parser = Rdp::new(StringInput::new("a,\"b,\"\"c\"\"\"\n,d"));
+//!
+//! assert!(parser.csv());
+//!
+//! let fields: Vec<_> = parser.queue().iter()
+//!                            .filter(|token| token.rule == Rule::field)
+//!                            .map(|token| parser.slice_input(token.start, token.end))
+//!                            .collect();
+//!
+//! assert_eq!(fields, vec!["a", "\"b,\"\"c\"\"\"", "", "d"]);
+//! # }
+//! ```
+
+use std::borrow::Cow;
+
+use super::super::{Parser, Token};
+
+/// A `trait` that adds delimited record matching to every `Parser` using `Token`s.
+pub trait Delimited<R>: Parser<Rule = R, Token = Token<R>> + Sized {
+    /// Matches one record of `separator`-delimited fields, stopping before a line break or at the
+    /// end of the `Input`, and adds a `Token` of rule `field` for every field. Fields starting
+    /// with `quote` end at the next lone `quote`, may contain separators and line breaks, and
+    /// escape `quote` by doubling it; their `Token`s include the quotes.
+    ///
+    /// Fails without making progress when a quoted field is not closed or is followed by
+    /// anything other than a separator, a line break, or the end of the `Input`.
+    fn delimited_fields(&mut self, separator: char, quote: char, field: R) -> bool
+        where R: Copy {
+
+        let start = self.pos();
+        let len = self.queue().len();
+        let input_len = self.input_len();
+
+        let mut pos = start;
+
+        loop {
+            let end = {
+                let rest = self.slice_input(pos, input_len);
+
+                if rest.starts_with(quote) {
+                    match quoted_len(&rest[quote.len_utf8()..], quote) {
+                        Some(quoted) => {
+                            let end = pos + quote.len_utf8() + quoted;
+                            let after = &rest[end - pos..];
+
+                            if !(after.is_empty() || after.starts_with(separator) ||
+                                 after.starts_with('\n') || after.starts_with('\r')) {
+                                None
+                            } else {
+                                Some(end)
+                            }
+                        },
+                        None => None
+                    }
+                } else {
+                    let unquoted = rest.find(&[separator, '\n', '\r'][..])
+                                       .unwrap_or(rest.len());
+
+                    Some(pos + unquoted)
+                }
+            };
+
+            let end = match end {
+                Some(end) => end,
+                None      => {
+                    self.queue_mut().truncate(len);
+                    self.set_pos(start);
+
+                    return false;
+                }
+            };
+
+            self.queue_mut().push(Token {
+                rule:  field,
+                start: pos,
+                end
+            });
+
+            if self.slice_input(end, input_len).starts_with(separator) {
+                pos = end + separator.len_utf8();
+            } else {
+                self.set_pos(end);
+
+                return true;
+            }
+        }
+    }
+}
+
+impl<R, P: Parser<Rule = R, Token = Token<R>>> Delimited<R> for P {}
+
+fn quoted_len(rest: &str, quote: char) -> Option<usize> {
+    let mut pos = 0;
+
+    loop {
+        match rest[pos..].find(quote) {
+            Some(found) => {
+                pos += found + quote.len_utf8();
+
+                if rest[pos..].starts_with(quote) {
+                    pos += quote.len_utf8();
+                } else {
+                    return Some(pos);
+                }
+            },
+            None => return None
+        }
+    }
+}
+
+/// Returns the contents of a `field` matched by `Delimited::delimited_fields`, removing its
+/// quotes and undoubling escaped `quote`s. Unquoted `field`s are returned as they are.
+///
+/// # Examples
+///
+/// ```
+/// # use pest::stdlib::delimited::unquote_field;
+/// assert_eq!(unquote_field("\"a\"\"b\"", '"'), "a\"b");
+/// assert_eq!(unquote_field("ab", '"'), "ab");
+/// ```
+pub fn unquote_field(field: &str, quote: char) -> Cow<'_, str> {
+    let len = quote.len_utf8();
+
+    if field.len() >= 2 * len && field.starts_with(quote) && field.ends_with(quote) {
+        let inner = &field[len..field.len() - len];
+        let mut doubled = String::with_capacity(2 * len);
+
+        doubled.push(quote);
+        doubled.push(quote);
+
+        if inner.contains(&doubled[..]) {
+            Cow::Owned(inner.replace(&doubled[..], &doubled[len..]))
+        } else {
+            Cow::Borrowed(inner)
+        }
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::super::super::prelude::*;
+    use super::Delimited;
+
+    impl_rdp! {
+        grammar! {
+            tsv    = { record ~ ((["\r\n"] | ["\n"]) ~ record)* ~ eoi }
+            record = { fields }
+            field  = token
+        }
+    }
+
+    impl<T: Input> Rdp<T> {
+        fn fields(&mut self) -> bool {
+            self.delimited_fields('\t', '\'', Rule::field)
+        }
+    }
+
+    #[test]
+    fn records() {
+        let mut parser = Rdp::new(StringInput::new("a\tbc\r\n'd\te'\t"));
+
+        assert!(parser.tsv());
+
+        let queue = vec![
+            Token { rule: Rule::tsv, start: 0, end: 12 },
+            Token { rule: Rule::record, start: 0, end: 4 },
+            Token { rule: Rule::field, start: 0, end: 1 },
+            Token { rule: Rule::field, start: 2, end: 4 },
+            Token { rule: Rule::record, start: 6, end: 12 },
+            Token { rule: Rule::field, start: 6, end: 11 },
+            Token { rule: Rule::field, start: 12, end: 12 }
+        ];
+
+        assert_eq!(parser.queue(), &queue);
+    }
+
+    #[test]
+    fn quoted_line_break() {
+        let mut parser = Rdp::new(StringInput::new("'a\nb'''"));
+
+        assert!(parser.tsv());
+        assert_eq!(parser.queue()[2], Token { rule: Rule::field, start: 0, end: 7 });
+    }
+
+    #[test]
+    fn unclosed() {
+        let mut parser = Rdp::new(StringInput::new("a\t'b"));
+
+        assert!(!parser.fields());
+        assert_eq!(parser.pos(), 0);
+        assert!(parser.queue().is_empty());
+    }
+
+    #[test]
+    fn trailing_garbage() {
+        let mut parser = Rdp::new(StringInput::new("'a'b"));
+
+        assert!(!parser.fields());
+        assert_eq!(parser.pos(), 0);
+    }
+}
@@ -0,0 +1,277 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A configurable decoder for backslash escape sequences.
+//!
+//! # Examples
+//!
+//! ```
+//! # #[macro_use] extern crate pest;
+//! # use pest::prelude::*;
+//! use pest::stdlib::escape::{UnescapeError, Unescaper};
+//! use pest::stdlib::literals::Literals;
+//!
+//! # fn main() {
+//! impl_rdp! {
+//!     grammar! {
+//!         string = @{ string_literal }
+//!     }
+//!
+//!     process! {
+//!         main(&self) -> Result<String, UnescapeError> {
+//!             (&string: string) => Unescaper::default().unquote(string)
+//!         }
+//!     }
+//! }
+//!
+//! let mut parser = Rdp::new(StringInput::new(r#""a\tb\u{1F600}""#));
+//!
+//! assert!(parser.string());
+//! assert_eq!(parser.process(), Ok("a\tb\u{1F600}".to_owned()));
+//! # }
+//! ```
+
+use std::char;
+use std::error::Error;
+use std::fmt;
+
+/// An `enum` describing an invalid escape sequence.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum UnescapeErrorKind {
+    /// the `char` following `\` is not a known escape
+    Unknown(char),
+    /// a `\x` or `\u` escape is malformed or is not a Unicode scalar value
+    InvalidCodePoint,
+    /// the `&str` ends with a lone `\`
+    UnexpectedEnd,
+    /// the `&str` given to `Unescaper::unquote` is not delimited by quotes
+    Unquoted
+}
+
+/// A `struct` describing an invalid escape sequence starting at byte `pos` of the unescaped
+/// `&str`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct UnescapeError {
+    /// kind of error
+    pub kind: UnescapeErrorKind,
+    /// byte position of the `\` starting the escape sequence
+    pub pos:  usize
+}
+
+impl fmt::Display for UnescapeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            UnescapeErrorKind::Unknown(c)       => {
+                write!(f, "unknown escape sequence \\{} at {}", c, self.pos)
+            },
+            UnescapeErrorKind::InvalidCodePoint => {
+                write!(f, "invalid code point escape at {}", self.pos)
+            },
+            UnescapeErrorKind::UnexpectedEnd    => {
+                write!(f, "unexpected end of string in escape at {}", self.pos)
+            },
+            UnescapeErrorKind::Unquoted         => write!(f, "string is not quoted")
+        }
+    }
+}
+
+impl Error for UnescapeError {}
+
+/// A `struct` that decodes backslash escape sequences. `\\` is always recognized, while other
+/// escapes need to be registered.
+///
+/// [`Unescaper::default`](#method.default) recognizes `\"`, `\'`, `\\`, `\/`, `\0`, `\b`,
+/// `\f`, `\n`, `\r`, `\t`, `\xNN`, `\uNNNN`, and `\u{N...}`, which covers Rust, JSON, and most
+/// C-like languages.
+///
+/// # Examples
+///
+/// ```
+/// # use pest::stdlib::escape::Unescaper;
+/// let unescaper = Unescaper::new().escape('n', '\n').hex(true);
+///
+/// assert_eq!(unescaper.unescape(r"a\nb\x41"), Ok("a\nbA".to_owned()));
+/// assert!(unescaper.unescape(r"\t").is_err());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Unescaper {
+    escapes:       Vec<(char, char)>,
+    hex:           bool,
+    unicode:       bool,
+    unicode_brace: bool
+}
+
+impl Unescaper {
+    /// Creates an `Unescaper` that only recognizes `\\`.
+    pub fn new() -> Unescaper {
+        Unescaper {
+            escapes:       vec![('\\', '\\')],
+            hex:           false,
+            unicode:       false,
+            unicode_brace: false
+        }
+    }
+
+    /// Registers `\` followed by `escape` to decode to `value`.
+    pub fn escape(mut self, escape: char, value: char) -> Unescaper {
+        self.escapes.retain(|&(c, _)| c != escape);
+        self.escapes.push((escape, value));
+
+        self
+    }
+
+    /// Sets whether `\xNN` escapes with exactly two hex digits are recognized.
+    pub fn hex(mut self, value: bool) -> Unescaper {
+        self.hex = value;
+
+        self
+    }
+
+    /// Sets whether `\uNNNN` escapes with exactly four hex digits are recognized.
+    pub fn unicode(mut self, value: bool) -> Unescaper {
+        self.unicode = value;
+
+        self
+    }
+
+    /// Sets whether `\u{N...}` escapes with one to six hex digits are recognized.
+    pub fn unicode_brace(mut self, value: bool) -> Unescaper {
+        self.unicode_brace = value;
+
+        self
+    }
+
+    /// Decodes all escape sequences in `string`.
+    pub fn unescape(&self, string: &str) -> Result<String, UnescapeError> {
+        let mut result = String::with_capacity(string.len());
+        let mut pos = 0;
+
+        while let Some(next) = string[pos..].find('\\') {
+            result.push_str(&string[pos..pos + next]);
+            pos += next;
+
+            let error = |kind| UnescapeError { kind, pos };
+
+            let escape = match string[pos + 1..].chars().next() {
+                Some(escape) => escape,
+                None         => return Err(error(UnescapeErrorKind::UnexpectedEnd))
+            };
+            let rest = &string[pos + 1 + escape.len_utf8()..];
+
+            if let Some(&(_, value)) = self.escapes.iter().find(|&&(c, _)| c == escape) {
+                result.push(value);
+                pos += 1 + escape.len_utf8();
+
+                continue;
+            }
+
+            let (digits, len) = match escape {
+                'x' if self.hex => (rest.get(0..2), 2),
+                'u' if self.unicode_brace && rest.starts_with('{') => {
+                    match rest.find('}') {
+                        Some(end) if end > 1 && end <= 7 => (rest.get(1..end), end + 1),
+                        _ => return Err(error(UnescapeErrorKind::InvalidCodePoint))
+                    }
+                },
+                'u' if self.unicode => (rest.get(0..4), 4),
+                _ => return Err(error(UnescapeErrorKind::Unknown(escape)))
+            };
+
+            let value = digits.filter(|digits| digits.chars().all(|c| c.is_ascii_hexdigit()))
+                              .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+                              .and_then(char::from_u32);
+
+            match value {
+                Some(value) => result.push(value),
+                None        => return Err(error(UnescapeErrorKind::InvalidCodePoint))
+            }
+
+            pos += 2 + len;
+        }
+
+        result.push_str(&string[pos..]);
+
+        Ok(result)
+    }
+
+    /// Strips the quotes of a `string` matched by
+    /// [`Literals::string_literal`](../literals/trait.Literals.html#method.string_literal), or any
+    /// other `"`- or `'`-quoted `string`, and decodes its escapes. Error positions are relative to
+    /// the quoted `string`.
+    pub fn unquote(&self, string: &str) -> Result<String, UnescapeError> {
+        let mut chars = string.chars();
+
+        match (chars.next(), chars.next_back()) {
+            (Some(start), Some(end)) if start == end && (start == '"' || start == '\'') => {
+                let len = start.len_utf8();
+
+                self.unescape(&string[len..string.len() - len]).map_err(|error| {
+                    UnescapeError { kind: error.kind, pos: error.pos + len }
+                })
+            },
+            _ => Err(UnescapeError { kind: UnescapeErrorKind::Unquoted, pos: 0 })
+        }
+    }
+}
+
+impl Default for Unescaper {
+    fn default() -> Unescaper {
+        Unescaper::new().escape('"', '"')
+                        .escape('\'', '\'')
+                        .escape('/', '/')
+                        .escape('0', '\0')
+                        .escape('b', '\u{8}')
+                        .escape('f', '\u{c}')
+                        .escape('n', '\n')
+                        .escape('r', '\r')
+                        .escape('t', '\t')
+                        .hex(true)
+                        .unicode(true)
+                        .unicode_brace(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults() {
+        let unescaper = Unescaper::default();
+
+        assert_eq!(unescaper.unescape(r#"\"\'\\\/\0\b\f\n\r\t"#),
+                   Ok("\"'\\/\0\u{8}\u{c}\n\r\t".to_owned()));
+        assert_eq!(unescaper.unescape(r"ă\x41\u00e9\u{1F600}"), Ok("ăAé\u{1F600}".to_owned()));
+    }
+
+    #[test]
+    fn errors() {
+        let unescaper = Unescaper::default();
+
+        assert_eq!(unescaper.unescape(r"ab\q"),
+                   Err(UnescapeError { kind: UnescapeErrorKind::Unknown('q'), pos: 2 }));
+        assert_eq!(unescaper.unescape(r"ab\"),
+                   Err(UnescapeError { kind: UnescapeErrorKind::UnexpectedEnd, pos: 2 }));
+        assert_eq!(unescaper.unescape(r"\x4"),
+                   Err(UnescapeError { kind: UnescapeErrorKind::InvalidCodePoint, pos: 0 }));
+        assert_eq!(unescaper.unescape(r"\uD800"),
+                   Err(UnescapeError { kind: UnescapeErrorKind::InvalidCodePoint, pos: 0 }));
+        assert_eq!(unescaper.unescape(r"\u{1234567}"),
+                   Err(UnescapeError { kind: UnescapeErrorKind::InvalidCodePoint, pos: 0 }));
+    }
+
+    #[test]
+    fn unquote() {
+        let unescaper = Unescaper::default();
+
+        assert_eq!(unescaper.unquote(r#""a\nb""#), Ok("a\nb".to_owned()));
+        assert_eq!(unescaper.unquote(r"'\q'"),
+                   Err(UnescapeError { kind: UnescapeErrorKind::Unknown('q'), pos: 1 }));
+        assert_eq!(unescaper.unquote("a"),
+                   Err(UnescapeError { kind: UnescapeErrorKind::Unquoted, pos: 0 }));
+    }
+}
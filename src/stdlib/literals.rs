@@ -0,0 +1,316 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Rules for common literals along with overflow-checked conversions of their matched `&str`s.
+//!
+//! # Examples
+//!
+//! ```
+//! # #[macro_use] extern crate pest;
+//! # use pest::prelude::*;
+//! use pest::stdlib::literals::{self, Literals};
+//!
+//! # fn main() {
+//! impl_rdp! {
+//!     grammar! {
+//!         number = @{ hex_literal | decimal_literal }
+//!     }
+//!
+//!     process! {
+//!         main(&self) -> Result<u8, literals::LiteralError> {
+//!             (&number: number) => literals::parse_int(number)
+//!         }
+//!     }
+//! }
+//!
+//! let mut parser = Rdp::new(StringInput::new("0xff"));
+//!
+//! assert!(parser.number());
+//! assert_eq!(parser.process(), Ok(255));
+//!
+//! let mut parser = Rdp::new(StringInput::new("256"));
+//!
+//! assert!(parser.number());
+//! assert_eq!(parser.process(), Err(literals::LiteralError::Overflow));
+//! # }
+//! ```
+
+use std::error::Error;
+use std::fmt;
+use std::num::{IntErrorKind, ParseIntError};
+
+use super::super::Parser;
+use super::{any_char, digits};
+
+/// A `trait` that adds literal rules to every `Parser`.
+pub trait Literals: Parser + Sized {
+    /// Matches an optionally signed decimal integer, e.g. `-42`.
+    fn decimal_literal(&mut self) -> bool {
+        self.try(false, |parser| {
+            sign(parser);
+
+            digits(parser, 10)
+        })
+    }
+
+    /// Matches an optionally signed hexadecimal integer prefixed by `0x`, e.g. `0xff`.
+    fn hex_literal(&mut self) -> bool {
+        self.try(false, |parser| {
+            sign(parser);
+
+            (parser.match_string("0x") || parser.match_string("0X")) && digits(parser, 16)
+        })
+    }
+
+    /// Matches an optionally signed octal integer prefixed by `0o`, e.g. `0o17`.
+    fn octal_literal(&mut self) -> bool {
+        self.try(false, |parser| {
+            sign(parser);
+
+            (parser.match_string("0o") || parser.match_string("0O")) && digits(parser, 8)
+        })
+    }
+
+    /// Matches an optionally signed binary integer prefixed by `0b`, e.g. `0b101`.
+    fn binary_literal(&mut self) -> bool {
+        self.try(false, |parser| {
+            sign(parser);
+
+            (parser.match_string("0b") || parser.match_string("0B")) && digits(parser, 2)
+        })
+    }
+
+    /// Matches an optionally signed float with a fractional part, an exponent, or both, e.g.
+    /// `1.5`, `2e10`, or `-3.0E-4`.
+    fn float_literal(&mut self) -> bool {
+        self.try(false, |parser| {
+            sign(parser);
+
+            if !digits(parser, 10) {
+                return false;
+            }
+
+            let fraction = parser.try(false, |parser| {
+                parser.match_string(".") && digits(parser, 10)
+            });
+            let exponent = parser.try(false, |parser| {
+                (parser.match_string("e") || parser.match_string("E")) && {
+                    sign(parser);
+
+                    digits(parser, 10)
+                }
+            });
+
+            fraction || exponent
+        })
+    }
+
+    /// Matches a `"`-delimited string where `\` escapes any following `char`, e.g. `"a\"b"`.
+    /// The escapes themselves are not validated.
+    fn string_literal(&mut self) -> bool {
+        self.try(false, |parser| {
+            if !parser.match_string("\"") {
+                return false;
+            }
+
+            loop {
+                if parser.match_string("\"") {
+                    return true;
+                }
+
+                if parser.match_string("\\") {
+                    if !any_char(parser) {
+                        return false;
+                    }
+                } else if !any_char(parser) {
+                    return false;
+                }
+            }
+        })
+    }
+}
+
+impl<P: Parser> Literals for P {}
+
+fn sign<P: Parser>(parser: &mut P) {
+    let _ = parser.match_string("-") || parser.match_string("+");
+}
+
+/// An `enum` describing why a literal could not be converted.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LiteralError {
+    /// the `&str` is not a valid literal
+    Invalid,
+    /// the literal does not fit in the requested type
+    Overflow
+}
+
+impl fmt::Display for LiteralError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LiteralError::Invalid  => write!(f, "invalid literal"),
+            LiteralError::Overflow => write!(f, "literal out of range")
+        }
+    }
+}
+
+impl Error for LiteralError {}
+
+/// A `trait` implemented by all primitive integers that `parse_int` can convert to.
+pub trait Integer: Sized {
+    /// Converts `string` in base `radix`, like the inherent `from_str_radix` of integers.
+    fn from_str_radix(string: &str, radix: u32) -> Result<Self, ParseIntError>;
+}
+
+macro_rules! integer {
+    ( $( $typ:ty )* ) => {
+        $(
+            impl Integer for $typ {
+                #[inline]
+                fn from_str_radix(string: &str, radix: u32) -> Result<$typ, ParseIntError> {
+                    <$typ>::from_str_radix(string, radix)
+                }
+            }
+        )*
+    };
+}
+
+integer!(i8 i16 i32 i64 i128 isize u8 u16 u32 u64 u128 usize);
+
+/// Converts an integer matched by any of the integer rules in `Literals` to `T`, returning
+/// `LiteralError::Overflow` if it does not fit. `-0` is `0` for unsigned types as well.
+///
+/// # Examples
+///
+/// ```
+/// # use pest::stdlib::literals::{parse_int, LiteralError};
+/// assert_eq!(parse_int::<i8>("-0x80"), Ok(-128));
+/// assert_eq!(parse_int::<u8>("0b100000000"), Err(LiteralError::Overflow));
+/// assert_eq!(parse_int::<u8>("-1"), Err(LiteralError::Overflow));
+/// ```
+pub fn parse_int<T: Integer>(literal: &str) -> Result<T, LiteralError> {
+    let (negative, unsigned) = if let Some(rest) = literal.strip_prefix('-') {
+        (true, rest)
+    } else {
+        (false, literal.strip_prefix('+').unwrap_or(literal))
+    };
+
+    let (radix, digits) = match unsigned.get(0..2) {
+        Some("0x") | Some("0X") => (16, &unsigned[2..]),
+        Some("0o") | Some("0O") => (8, &unsigned[2..]),
+        Some("0b") | Some("0B") => (2, &unsigned[2..]),
+        _                       => (10, unsigned)
+    };
+
+    if digits.is_empty() || digits.starts_with('+') || digits.starts_with('-') {
+        return Err(LiteralError::Invalid);
+    }
+
+    // -0 is 0, which fits in unsigned types as well
+    let negative = negative && digits.chars().any(|c| c != '0');
+
+    let result = if negative {
+        T::from_str_radix(&format!("-{}", digits), radix)
+    } else {
+        T::from_str_radix(digits, radix)
+    };
+
+    result.map_err(|error| {
+        match *error.kind() {
+            IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => LiteralError::Overflow,
+            IntErrorKind::InvalidDigit if negative && digits.chars().all(|c| c.is_digit(radix)) => {
+                LiteralError::Overflow
+            },
+            _ => LiteralError::Invalid
+        }
+    })
+}
+
+/// Converts a float matched by `Literals::float_literal` (or an integer) to an `f64`, returning
+/// `LiteralError::Overflow` if it is too large to be represented.
+///
+/// # Examples
+///
+/// ```
+/// # use pest::stdlib::literals::{parse_float, LiteralError};
+/// assert_eq!(parse_float("-2.5e2"), Ok(-250.0));
+/// assert_eq!(parse_float("1e400"), Err(LiteralError::Overflow));
+/// ```
+pub fn parse_float(literal: &str) -> Result<f64, LiteralError> {
+    match literal.parse::<f64>() {
+        Ok(float) if float.is_infinite() => Err(LiteralError::Overflow),
+        Ok(float)                        => Ok(float),
+        Err(_)                           => Err(LiteralError::Invalid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::prelude::*;
+    use super::*;
+
+    impl_rdp! {
+        grammar! {
+            decimal = @{ decimal_literal ~ eoi }
+            hex     = @{ hex_literal ~ eoi }
+            octal   = @{ octal_literal ~ eoi }
+            binary  = @{ binary_literal ~ eoi }
+            float   = @{ float_literal ~ eoi }
+            string  = @{ string_literal ~ eoi }
+        }
+    }
+
+    #[test]
+    fn integers() {
+        assert!(Rdp::new(StringInput::new("-123")).decimal());
+        assert!(Rdp::new(StringInput::new("0xfF")).hex());
+        assert!(Rdp::new(StringInput::new("+0o17")).octal());
+        assert!(Rdp::new(StringInput::new("0b101")).binary());
+
+        assert!(!Rdp::new(StringInput::new("-")).decimal());
+        assert!(!Rdp::new(StringInput::new("0x")).hex());
+        assert!(!Rdp::new(StringInput::new("0o8")).octal());
+        assert!(!Rdp::new(StringInput::new("0b2")).binary());
+    }
+
+    #[test]
+    fn floats() {
+        assert!(Rdp::new(StringInput::new("1.5")).float());
+        assert!(Rdp::new(StringInput::new("2e10")).float());
+        assert!(Rdp::new(StringInput::new("-3.0E-4")).float());
+
+        assert!(!Rdp::new(StringInput::new("3")).float());
+        assert!(!Rdp::new(StringInput::new("3.")).float());
+        assert!(!Rdp::new(StringInput::new("3e")).float());
+    }
+
+    #[test]
+    fn strings() {
+        assert!(Rdp::new(StringInput::new("\"\"")).string());
+        assert!(Rdp::new(StringInput::new("\"a\\\"ă\"")).string());
+
+        assert!(!Rdp::new(StringInput::new("\"a")).string());
+        assert!(!Rdp::new(StringInput::new("\"a\\\"")).string());
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(parse_int::<u8>("255"), Ok(255));
+        assert_eq!(parse_int::<u8>("256"), Err(LiteralError::Overflow));
+        assert_eq!(parse_int::<i8>("-128"), Ok(-128));
+        assert_eq!(parse_int::<i8>("-129"), Err(LiteralError::Overflow));
+        assert_eq!(parse_int::<u8>("-0"), Ok(0));
+        assert_eq!(parse_int::<u8>("-0x00"), Ok(0));
+        assert_eq!(parse_int::<i8>("-0"), Ok(0));
+        assert_eq!(parse_int::<u32>("0o17"), Ok(15));
+        assert_eq!(parse_int::<u32>("0x"), Err(LiteralError::Invalid));
+        assert_eq!(parse_int::<u32>("0x-1"), Err(LiteralError::Invalid));
+        assert_eq!(parse_int::<u32>("a"), Err(LiteralError::Invalid));
+        assert_eq!(parse_float("1.5"), Ok(1.5));
+        assert_eq!(parse_float("a"), Err(LiteralError::Invalid));
+    }
+}
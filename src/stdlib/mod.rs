@@ -0,0 +1,57 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A `mod` of reusable rules that can be mixed into any `grammar!`.
+//!
+//! Every rule is a method of a `trait` implemented for all `Parser`s, so bringing the `trait` in
+//! scope is enough to call its rules from `grammar!` like any other rule. These rules behave like
+//! [silent](../macro.grammar!#silent-rules-_) rules and never produce `Token`s on their own;
+//! wrap them in a rule of your own to get one.
+
+pub mod datetime;
+pub mod delimited;
+pub mod escape;
+pub mod literals;
+pub mod net;
+
+use super::Parser;
+
+fn digit<P: Parser>(parser: &mut P, radix: u32) -> bool {
+    match radix {
+        2  => parser.match_range('0', '1'),
+        8  => parser.match_range('0', '7'),
+        10 => parser.match_range('0', '9'),
+        16 => {
+            parser.match_range('0', '9') || parser.match_range('a', 'f') ||
+            parser.match_range('A', 'F')
+        },
+        _  => unreachable!()
+    }
+}
+
+fn digits<P: Parser>(parser: &mut P, radix: u32) -> bool {
+    if !digit(parser, radix) {
+        return false;
+    }
+
+    while digit(parser, radix) {}
+
+    true
+}
+
+fn alpha<P: Parser>(parser: &mut P) -> bool {
+    parser.match_range('a', 'z') || parser.match_range('A', 'Z')
+}
+
+fn one_of<P: Parser>(parser: &mut P, chars: &str) -> bool {
+    chars.chars().any(|c| parser.match_range(c, c))
+}
+
+fn any_char<P: Parser>(parser: &mut P) -> bool {
+    parser.match_range('\u{0}', '\u{7f}') || parser.match_range('\u{80}', '\u{7ff}') ||
+    parser.match_range('\u{800}', '\u{ffff}') || parser.match_range('\u{10000}', '\u{10ffff}')
+}
@@ -0,0 +1,301 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Rules for IP addresses and URIs as defined by [RFC 3986](https://tools.ietf.org/html/rfc3986),
+//! and for e-mail addresses in the `dot-atom` form of
+//! [RFC 5322](https://tools.ietf.org/html/rfc5322#section-3.4.1).
+//!
+//! # Examples
+//!
+//! ```
+//! # #[macro_use] extern crate pest;
+//! # use pest::prelude::*;
+//! use pest::stdlib::net::Net;
+//!
+//! # fn main() {
+//! impl_rdp! {
+//!     grammar! {
+//!         link = @{ uri ~ eoi }
+//!     }
+//! }
+//!
+//! assert!(Rdp::new(StringInput::new("https://user@[::1]:8080/a/b?c=d#e")).link());
+//! assert!(!Rdp::new(StringInput::new("://nope")).link());
+//! # }
+//! ```
+
+use super::super::Parser;
+use super::{alpha, digit, one_of};
+
+const SUB_DELIMS: &str = "!$&'()*+,;=";
+const ATEXT: &str = "!#$%&'*+-/=?^_`{|}~";
+
+/// A `trait` that adds IP address, URI, and e-mail address rules to every `Parser`.
+pub trait Net: Parser + Sized {
+    /// Matches a dotted-decimal IPv4 address, e.g. `127.0.0.1`.
+    fn ipv4_address(&mut self) -> bool {
+        self.try(false, |parser| {
+            dec_octet(parser) && (0..3).all(|_| parser.match_string(".") && dec_octet(parser))
+        })
+    }
+
+    /// Matches an IPv6 address in any of its textual forms, e.g. `::1`, `fe80::1:2`, or
+    /// `::ffff:127.0.0.1`.
+    fn ipv6_address(&mut self) -> bool {
+        self.try(false, |parser| {
+            let mut groups = 0;
+            let mut compressed = parser.match_string("::");
+
+            loop {
+                if groups <= 6 && parser.ipv4_address() {
+                    groups += 2;
+
+                    break;
+                }
+
+                if !h16(parser) {
+                    break;
+                }
+
+                groups += 1;
+
+                if groups == 8 {
+                    break;
+                }
+
+                if !compressed && parser.match_string("::") {
+                    compressed = true;
+
+                    continue;
+                }
+
+                let separated = parser.try(false, |parser| {
+                    parser.match_string(":") && parser.try(true, |parser| h16(parser))
+                });
+
+                if !separated {
+                    break;
+                }
+            }
+
+            if compressed {
+                groups <= 7
+            } else {
+                groups == 8
+            }
+        })
+    }
+
+    /// Matches an absolute URI with optional query and fragment, e.g.
+    /// `http://example.com/path?query#fragment` or `mailto:a@b.c`.
+    fn uri(&mut self) -> bool {
+        self.try(false, |parser| {
+            if !scheme(parser) || !parser.match_string(":") {
+                return false;
+            }
+
+            let authority = parser.try(false, |parser| {
+                parser.match_string("//") && authority(parser)
+            });
+
+            if authority {
+                while parser.match_string("/") {
+                    while pchar(parser) {}
+                }
+            } else {
+                while pchar(parser) || parser.match_string("/") {}
+            }
+
+            if parser.match_string("?") {
+                while pchar(parser) || one_of(parser, "/?") {}
+            }
+
+            if parser.match_string("#") {
+                while pchar(parser) || one_of(parser, "/?") {}
+            }
+
+            true
+        })
+    }
+
+    /// Matches an e-mail address of the form `local@domain`, e.g. `first.last@example.com`.
+    fn email_address(&mut self) -> bool {
+        self.try(false, |parser| {
+            dot_separated(parser, atext) && parser.match_string("@") &&
+            dot_separated(parser, label)
+        })
+    }
+}
+
+impl<P: Parser> Net for P {}
+
+fn dec_octet<P: Parser>(parser: &mut P) -> bool {
+    parser.try(false, |parser| {
+        parser.match_string("25") && parser.match_range('0', '5')
+    }) || parser.try(false, |parser| {
+        parser.match_string("2") && parser.match_range('0', '4') && digit(parser, 10)
+    }) || parser.try(false, |parser| {
+        parser.match_string("1") && digit(parser, 10) && digit(parser, 10)
+    }) || parser.try(false, |parser| {
+        parser.match_range('1', '9') && digit(parser, 10)
+    }) || digit(parser, 10)
+}
+
+fn h16<P: Parser>(parser: &mut P) -> bool {
+    if !digit(parser, 16) {
+        return false;
+    }
+
+    for _ in 0..3 {
+        if !digit(parser, 16) {
+            break;
+        }
+    }
+
+    true
+}
+
+fn unreserved<P: Parser>(parser: &mut P) -> bool {
+    alpha(parser) || digit(parser, 10) || one_of(parser, "-._~")
+}
+
+fn pct_encoded<P: Parser>(parser: &mut P) -> bool {
+    parser.try(false, |parser| {
+        parser.match_string("%") && digit(parser, 16) && digit(parser, 16)
+    })
+}
+
+fn pchar<P: Parser>(parser: &mut P) -> bool {
+    unreserved(parser) || pct_encoded(parser) || one_of(parser, SUB_DELIMS) ||
+    one_of(parser, ":@")
+}
+
+fn scheme<P: Parser>(parser: &mut P) -> bool {
+    if !alpha(parser) {
+        return false;
+    }
+
+    while alpha(parser) || digit(parser, 10) || one_of(parser, "+-.") {}
+
+    true
+}
+
+fn authority<P: Parser>(parser: &mut P) -> bool {
+    parser.try(false, |parser| {
+        while unreserved(parser) || pct_encoded(parser) || one_of(parser, SUB_DELIMS) ||
+              parser.match_string(":") {}
+
+        parser.match_string("@")
+    });
+
+    let host = parser.try(false, |parser| {
+        parser.match_string("[") && parser.ipv6_address() && parser.match_string("]")
+    });
+
+    if !host {
+        while unreserved(parser) || pct_encoded(parser) || one_of(parser, SUB_DELIMS) {}
+    }
+
+    if parser.match_string(":") {
+        while digit(parser, 10) {}
+    }
+
+    true
+}
+
+fn atext<P: Parser>(parser: &mut P) -> bool {
+    alpha(parser) || digit(parser, 10) || one_of(parser, ATEXT)
+}
+
+fn label<P: Parser>(parser: &mut P) -> bool {
+    alpha(parser) || digit(parser, 10) || parser.match_string("-")
+}
+
+fn dot_separated<P: Parser, F>(parser: &mut P, mut chars: F) -> bool
+    where F: FnMut(&mut P) -> bool {
+
+    if !chars(parser) {
+        return false;
+    }
+
+    loop {
+        while chars(parser) {}
+
+        let dotted = parser.try(false, |parser| {
+            parser.match_string(".") && chars(parser)
+        });
+
+        if !dotted {
+            return true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::prelude::*;
+    use super::Net;
+
+    impl_rdp! {
+        grammar! {
+            ipv4  = @{ ipv4_address ~ eoi }
+            ipv6  = @{ ipv6_address ~ eoi }
+            link  = @{ uri ~ eoi }
+            email = @{ email_address ~ eoi }
+        }
+    }
+
+    #[test]
+    fn ipv4() {
+        assert!(Rdp::new(StringInput::new("127.0.0.1")).ipv4());
+        assert!(Rdp::new(StringInput::new("255.249.199.9")).ipv4());
+
+        assert!(!Rdp::new(StringInput::new("256.0.0.1")).ipv4());
+        assert!(!Rdp::new(StringInput::new("1.2.3")).ipv4());
+    }
+
+    #[test]
+    fn ipv6() {
+        assert!(Rdp::new(StringInput::new("::")).ipv6());
+        assert!(Rdp::new(StringInput::new("::1")).ipv6());
+        assert!(Rdp::new(StringInput::new("1::")).ipv6());
+        assert!(Rdp::new(StringInput::new("fe80::1:2")).ipv6());
+        assert!(Rdp::new(StringInput::new("1:2:3:4:5:6:7:8")).ipv6());
+        assert!(Rdp::new(StringInput::new("1:2:3:4:5:6:7::")).ipv6());
+        assert!(Rdp::new(StringInput::new("::ffff:127.0.0.1")).ipv6());
+        assert!(Rdp::new(StringInput::new("1:2:3:4:5:6:1.2.3.4")).ipv6());
+
+        assert!(!Rdp::new(StringInput::new("1:2:3:4:5:6:7")).ipv6());
+        assert!(!Rdp::new(StringInput::new("1:2:3:4:5:6:7:8:9")).ipv6());
+        assert!(!Rdp::new(StringInput::new("1::2::3")).ipv6());
+        assert!(!Rdp::new(StringInput::new("12345::")).ipv6());
+        assert!(!Rdp::new(StringInput::new(":1")).ipv6());
+    }
+
+    #[test]
+    fn uri() {
+        assert!(Rdp::new(StringInput::new("http://example.com")).link());
+        assert!(Rdp::new(StringInput::new("https://a:b@127.0.0.1:80/x%20y?q=1#top")).link());
+        assert!(Rdp::new(StringInput::new("ftp://[fe80::1]/")).link());
+        assert!(Rdp::new(StringInput::new("mailto:a@b.c")).link());
+        assert!(Rdp::new(StringInput::new("urn:isbn:0451450523")).link());
+
+        assert!(!Rdp::new(StringInput::new("1http://a")).link());
+        assert!(!Rdp::new(StringInput::new("http://a b")).link());
+        assert!(!Rdp::new(StringInput::new("http://a/%zz")).link());
+    }
+
+    #[test]
+    fn email() {
+        assert!(Rdp::new(StringInput::new("first.last@example.com")).email());
+        assert!(Rdp::new(StringInput::new("a+tag@b-c.d")).email());
+
+        assert!(!Rdp::new(StringInput::new("a..b@c")).email());
+        assert!(!Rdp::new(StringInput::new("a@")).email());
+        assert!(!Rdp::new(StringInput::new("@b")).email());
+    }
+}
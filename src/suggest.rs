@@ -0,0 +1,153 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::cmp;
+use std::fmt;
+
+/// Returns the number of `char` insertions, deletions, substitutions, and transpositions of
+/// adjacent `char`s needed to turn `a` into `b`.
+///
+/// # Examples
+///
+/// ```
+/// # use pest::edit_distance;
+/// assert_eq!(edit_distance("while", "while"), 0);
+/// assert_eq!(edit_distance("whlie", "while"), 1);
+/// assert_eq!(edit_distance("retrun", "return"), 1);
+/// assert_eq!(edit_distance("fi", "if"), 1);
+/// assert_eq!(edit_distance("els", "else"), 1);
+/// assert_eq!(edit_distance("whiel", "while"), 1);
+/// assert_eq!(edit_distance("fnuction", "function"), 1);
+/// assert_eq!(edit_distance("cat", "dog"), 3);
+/// ```
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    // rows i - 2, i - 1, and i of the distance matrix
+    let mut before: Vec<usize> = vec![0; b.len() + 1];
+    let mut last: Vec<usize> = (0..b.len() + 1).collect();
+    let mut row = vec![0; b.len() + 1];
+
+    for i in 1..a.len() + 1 {
+        row[0] = i;
+
+        for j in 1..b.len() + 1 {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+
+            row[j] = cmp::min(cmp::min(last[j] + 1, row[j - 1] + 1), last[j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = cmp::min(row[j], before[j - 2] + 1);
+            }
+        }
+
+        before.clone_from(&last);
+        last.clone_from(&row);
+    }
+
+    last[b.len()]
+}
+
+/// A `struct` suggesting a keyword or literal for a misspelled word at the position where a
+/// `Parser` failed, returned by [`Parser::suggestion`](trait.Parser#tymethod.suggestion).
+///
+/// Its `Display` prints e.g. `found "whlie", did you mean "while"?`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Suggestion {
+    /// misspelled word
+    pub found:    String,
+    /// starting position of the word in `Input`
+    pub start:    usize,
+    /// ending position of the word in `Input`
+    pub end:      usize,
+    /// suggested replacement
+    pub expected: String,
+    /// [`edit_distance`](fn.edit_distance) between `found` and `expected`
+    pub distance: usize
+}
+
+impl Suggestion {
+    /// Suggests the candidate closest to `found`, which starts at `start`, ties going to the
+    /// first one, unless `found` is one of the candidates itself. Only candidates at an edit
+    /// distance of `1` are considered for words of up to three `char`s, and of `1` or `2` for
+    /// longer ones, so that short words are not suggested for anything.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::Suggestion;
+    /// let suggestion = Suggestion::new("retrun", 4, vec!["return", "break"]).unwrap();
+    ///
+    /// assert_eq!(suggestion.expected, "return");
+    /// assert_eq!((suggestion.start, suggestion.end), (4, 10));
+    /// assert_eq!(suggestion.to_string(), "found \"retrun\", did you mean \"return\"?");
+    ///
+    /// assert_eq!(Suggestion::new("x", 0, vec!["if"]), None);
+    /// ```
+    pub fn new<'a, I>(found: &str, start: usize, candidates: I) -> Option<Suggestion>
+        where I: IntoIterator<Item = &'a str> {
+
+        let mut best: Option<(&str, usize)> = None;
+
+        for candidate in candidates {
+            let len = cmp::max(found.chars().count(), candidate.chars().count());
+            let max = if len > 3 { 2 } else { 1 };
+            let distance = edit_distance(found, candidate);
+
+            if distance == 0 {
+                return None;
+            }
+
+            if distance <= max &&
+               best.is_none_or(|(_, best)| distance < best) {
+                best = Some((candidate, distance));
+            }
+        }
+
+        best.map(|(expected, distance)| {
+            Suggestion {
+                found: found.to_owned(),
+                start,
+                end: start + found.len(),
+                expected: expected.to_owned(),
+                distance
+            }
+        })
+    }
+}
+
+impl fmt::Display for Suggestion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "found {:?}, did you mean {:?}?", self.found, self.expected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distances() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("abc", ""), 3);
+        assert_eq!(edit_distance("ab", "ba"), 1);
+        assert_eq!(edit_distance("ca", "abc"), 3);
+        assert_eq!(edit_distance("ăîș", "ășî"), 1);
+    }
+
+    #[test]
+    fn closest() {
+        let candidates = vec!["for", "form", "format"];
+
+        assert_eq!(Suggestion::new("fro", 0, candidates.clone()).unwrap().expected, "for");
+        assert_eq!(Suggestion::new("formt", 0, candidates.clone()).unwrap().expected, "form");
+        assert_eq!(Suggestion::new("for", 0, candidates.clone()), None);
+        assert_eq!(Suggestion::new("while", 0, candidates), None);
+    }
+}
@@ -0,0 +1,122 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use super::{Cursor, TokenData};
+
+/// A `struct` that maps every `Rule` to the indices of its `Token`s in a queue, so that analyses
+/// like collecting all imports take time proportional to the number of matches instead of
+/// scanning the whole queue once per query. Created with
+/// [`Parser::token_index`](trait.Parser#tymethod.token_index), or with `TokenIndex::new` for any
+/// slice of `Token`s.
+///
+/// The indices are those of the queue when the `TokenIndex` was created, so it needs to be
+/// created again after the queue is changed.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         file   = { (import | call)* }
+///         import = { ["use"] ~ name }
+///         call   = { name ~ ["()"] }
+///         name   = @{ ['a'..'z']+ }
+///
+///         whitespace = _{ [" "] | ["\n"] }
+///     }
+///
+///     process! {
+///         main(&self) -> String {
+///             (_: import, &name: name) => name.to_owned()
+///         }
+///     }
+/// }
+///
+/// let mut parser = Rdp::new(StringInput::new("use a\nf()\nuse b\ng()"));
+///
+/// assert!(parser.file());
+///
+/// let index = parser.token_index();
+///
+/// assert_eq!(index.indices(Rule::import), &[1, 5]);
+/// assert_eq!(index.count(Rule::name), 4);
+///
+/// let imports: Vec<_> = index.cursors(Rule::import).map(|mut cursor| {
+///     parser.main(&mut cursor)
+/// }).collect();
+///
+/// assert_eq!(imports, vec!["a", "b"]);
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct TokenIndex<R> {
+    indices: HashMap<R, Vec<usize>>
+}
+
+impl<R: Copy + Eq + Hash> TokenIndex<R> {
+    /// Creates a `TokenIndex` of `queue`.
+    pub fn new<T: TokenData<R>>(queue: &[T]) -> TokenIndex<R> {
+        let mut indices: HashMap<R, Vec<usize>> = HashMap::new();
+
+        for (i, token) in queue.iter().enumerate() {
+            indices.entry(token.rule()).or_default().push(i);
+        }
+
+        TokenIndex { indices }
+    }
+
+    /// Returns the indices of the `Token`s of `rule` in the queue, in ascending order.
+    pub fn indices(&self, rule: R) -> &[usize] {
+        self.indices.get(&rule).map_or(&[], |indices| &indices[..])
+    }
+
+    /// Returns the number of `Token`s of `rule`.
+    pub fn count(&self, rule: R) -> usize {
+        self.indices(rule).len()
+    }
+
+    /// Returns the `Token`s of `rule` in `queue`, which needs to be the queue the `TokenIndex`
+    /// was created from.
+    pub fn tokens<'a, T: TokenData<R>>(&'a self, rule: R, queue: &'a [T])
+                                       -> impl Iterator<Item = &'a T> + 'a {
+        self.indices(rule).iter().map(move |&i| &queue[i])
+    }
+
+    /// Returns a [`Cursor`](struct.Cursor) pointing at every `Token` of `rule`, e.g. to run a
+    /// `process!` matcher on each of them in a separate pass.
+    pub fn cursors(&self, rule: R) -> impl Iterator<Item = Cursor> + '_ {
+        self.indices(rule).iter().map(|&i| Cursor::new(i))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::Token;
+
+    #[test]
+    fn index() {
+        let queue = vec![
+            Token { rule: 'a', start: 0, end: 4 },
+            Token { rule: 'b', start: 0, end: 1 },
+            Token { rule: 'a', start: 2, end: 3 }
+        ];
+        let index = TokenIndex::new(&queue);
+
+        assert_eq!(index.indices('a'), &[0, 2]);
+        assert_eq!(index.indices('c'), &[]);
+        assert_eq!(index.count('b'), 1);
+        assert_eq!(index.tokens('a', &queue).collect::<Vec<_>>(), vec![&queue[0], &queue[2]]);
+        assert_eq!(index.cursors('b').collect::<Vec<_>>(), vec![Cursor::new(1)]);
+    }
+}
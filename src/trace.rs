@@ -0,0 +1,82 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Per-rule `trace` records for the `log` feature. Every rule invocation logs a record with the
+//! `pest` target when it is entered and one when it exits, so that the nesting of rules can be
+//! reconstructed from the `depth` of consecutive records, e.g. to draw a flamegraph. Without the
+//! feature, both functions are empty and inlined away.
+
+/// Logs that `rule` was entered at `pos` with `depth` enclosing rules. Used in `impl_rdp!`.
+#[inline]
+#[allow(unused_variables)]
+pub fn trace_enter(rule: &'static str, pos: usize, depth: usize) {
+    #[cfg(feature = "log")]
+    trace!(target: "pest", "enter {} at {} (depth {})", rule, pos, depth);
+}
+
+/// Logs that `rule`, entered at `start` with `depth` enclosing rules, exited at `end`. Used in
+/// `impl_rdp!`.
+#[inline]
+#[allow(unused_variables)]
+pub fn trace_exit(rule: &'static str, start: usize, end: usize, depth: usize, matched: bool) {
+    #[cfg(feature = "log")]
+    trace!(target: "pest", "{} {} at {}..{} (depth {})",
+           if matched { "match" } else { "fail" }, rule, start, end, depth);
+}
+
+#[cfg(all(test, feature = "log"))]
+mod tests {
+    use std::sync::Mutex;
+
+    use log::{self, Level, LevelFilter, Log, Metadata, Record};
+
+    use super::super::prelude::*;
+
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl Log for Recorder {
+        fn enabled(&self, metadata: &Metadata) -> bool {
+            metadata.target() == "pest" && metadata.level() <= Level::Trace
+        }
+
+        fn log(&self, record: &Record) {
+            if self.enabled(record.metadata()) {
+                self.0.lock().unwrap().push(format!("{}", record.args()));
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    static RECORDER: Recorder = Recorder(Mutex::new(vec![]));
+
+    impl_rdp! {
+        grammar! {
+            pair = { key ~ ["="] ~ key }
+            key  = { ['a'..'z']+ }
+        }
+    }
+
+    #[test]
+    fn records() {
+        log::set_logger(&RECORDER).unwrap();
+        log::set_max_level(LevelFilter::Trace);
+
+        let mut parser = Rdp::new(StringInput::new("a=1"));
+
+        assert!(!parser.pair());
+
+        assert_eq!(*RECORDER.0.lock().unwrap(), vec![
+            "enter pair at 0 (depth 0)",
+            "enter key at 0 (depth 1)",
+            "match key at 0..1 (depth 1)",
+            "enter key at 2 (depth 1)",
+            "fail key at 2..2 (depth 1)",
+            "fail pair at 0..0 (depth 0)"
+        ]);
+    }
+}
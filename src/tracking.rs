@@ -0,0 +1,106 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fmt;
+
+/// An `enum` that decides how much a `Parser` records about failed rules for
+/// [`Parser::expected`](trait.Parser#tymethod.expected). Set with
+/// [`Parser::set_tracking`](trait.Parser#tymethod.set_tracking).
+///
+/// Tracking every failure at the farthest position costs allocations on every parse, even if
+/// errors are never looked at. Throughput-critical callers can cap or disable it; the setting is
+/// kept across [`Parser::reset`](trait.Parser#tymethod.reset), so it can be changed per parse.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::Tracking;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         pair  = { key ~ [":"] ~ value }
+///         key   = @{ ['a'..'z']+ }
+///         value = { number | string | boolean }
+///         number  = @{ ['0'..'9']+ }
+///         string  = @{ ["\""] ~ ['a'..'z']* ~ ["\""] }
+///         boolean = @{ ["true"] | ["false"] }
+///     }
+/// }
+///
+/// let mut parser = Rdp::new(StringInput::new("a:?"));
+///
+/// assert!(!parser.pair());
+/// assert_eq!(parser.expected(), (vec![Rule::number, Rule::string, Rule::boolean], 2));
+///
+/// parser.reset();
+/// parser.set_tracking(Tracking::Capped(1));
+///
+/// assert!(!parser.pair());
+/// assert_eq!(parser.expected(), (vec![Rule::number], 2));
+///
+/// parser.reset();
+/// parser.set_tracking(Tracking::Off);
+///
+/// assert!(!parser.pair());
+/// assert_eq!(parser.expected(), (vec![], 0));
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Tracking {
+    /// every rule that failed at the farthest position is recorded (default)
+    #[default]
+    Full,
+    /// at most this many distinct rules are recorded at the farthest position, which is always
+    /// tracked; `Capped(0)` only tracks the position
+    Capped(usize),
+    /// nothing is recorded and `Parser::expected` returns `(vec![], 0)`
+    Off
+}
+
+/// An `enum` representing something a `Parser` expected at the farthest position it failed at,
+/// returned by [`Parser::expectations`](trait.Parser#tymethod.expectations). Unlike
+/// [`Parser::expected`](trait.Parser#tymethod.expected), it also names the strings and ranges
+/// that failed, even when they are not rules of their own.
+///
+/// Its `Display` prints rules by name, strings quoted, and ranges as `'a'..'z'`.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Expected<R> {
+    /// a rule
+    Rule(R),
+    /// a string, e.g. `[")"]`
+    String(String),
+    /// a range, e.g. `['a'..'z']`
+    Range(char, char)
+}
+
+impl<R: fmt::Debug> fmt::Display for Expected<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Expected::Rule(ref rule)        => write!(f, "{:?}", rule),
+            Expected::String(ref string)    => write!(f, "{:?}", string),
+            Expected::Range(left, right)    => write!(f, "{:?}..{:?}", left, right)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Expected;
+
+    #[test]
+    fn display() {
+        let expected: Vec<_> = [
+            Expected::Rule("number"),
+            Expected::String(")\n".to_owned()),
+            Expected::Range('a', 'z')
+        ].iter().map(|expected| expected.to_string()).collect();
+
+        assert_eq!(expected, vec!["\"number\"", "\")\\n\"", "'a'..'z'"]);
+    }
+}
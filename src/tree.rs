@@ -0,0 +1,275 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::Token;
+
+/// Returns the subtrees of the direct children of the first `Token` in a pre-order `tree`, e.g. a
+/// [queue](trait.Parser#tymethod.queue). Every subtree starts with the child itself, followed by
+/// all its descendants.
+///
+/// # Examples
+///
+/// ```
+/// # use pest::{children, Token};
+/// let tree = [
+///     Token { rule: 0, start: 0, end: 3 },
+///     Token { rule: 1, start: 0, end: 1 },
+///     Token { rule: 2, start: 0, end: 1 },
+///     Token { rule: 1, start: 2, end: 3 }
+/// ];
+///
+/// assert_eq!(children(&tree), vec![&tree[1..3], &tree[3..4]]);
+/// ```
+pub fn children<R: PartialEq>(tree: &[Token<R>]) -> Vec<&[Token<R>]> {
+    let mut children = vec![];
+
+    if let Some(root) = tree.first() {
+        let mut i = 1;
+
+        while i < tree.len() && root.is_ancestor_of(&tree[i]) {
+            let start = i;
+
+            i += 1;
+
+            while i < tree.len() && tree[start].is_ancestor_of(&tree[i]) {
+                i += 1;
+            }
+
+            children.push(&tree[start..i]);
+        }
+    }
+
+    children
+}
+
+/// A `macro` for destructuring a tree of `Token`s outside of [`process!`](macro.process!), e.g. in
+/// ad-hoc analysis code. It matches the first `Token` of a pre-order `&[Token<Rule>]`, like a
+/// [queue](trait.Parser#tymethod.queue), against comma-separated arms and evaluates to the body
+/// of the first arm that matches.
+///
+/// An arm's pattern is either `_`, which matches anything, `R`, which matches a `Token` of rule
+/// `R` regardless of its children, or `R { ... }`, which also matches the `Token`'s direct
+/// [`children`](fn.children) against the following comma-separated fields, in order:
+///
+/// | Field             | What it does                                                        |
+/// |-------------------|---------------------------------------------------------------------|
+/// | `item: R`         | captures a child of rule `R`                                        |
+/// | `item: R { ... }` | captures a child of rule `R` whose children match the nested fields |
+/// | `item: [R, ..]`   | captures one or more consecutive children of rule `R` as a `Vec`    |
+/// | `item: [..]`      | captures all remaining children as a `Vec`                          |
+/// | `_: R`            | skips a child of rule `R`                                           |
+/// | `..`              | skips all remaining children                                        |
+///
+/// Every captured child is the `&[Token<Rule>]` subtree that starts with it, so it can be matched
+/// with `match_tree!` in turn. Unless the fields end in `..`, all children must be matched. In
+/// case no arm matches, `match_tree!` will `panic!`.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         call  = { ident ~ ["("] ~ (expr ~ ([","] ~ expr)*)? ~ [")"] }
+///         expr  = { call | ident }
+///         ident = @{ ['a'..'z']+ }
+///     }
+/// }
+///
+/// let mut parser = Rdp::new(StringInput::new("f(x,g(y))"));
+///
+/// assert!(parser.call());
+///
+/// let arity = match_tree!(parser.queue(), {
+///     call { name: ident, args: [expr, ..] } => {
+///         (parser.slice_input(name[0].start, name[0].end), args.len())
+///     },
+///     call { name: ident } => (parser.slice_input(name[0].start, name[0].end), 0)
+/// });
+///
+/// assert_eq!(arity, ("f", 2));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! match_tree {
+    // fields; the list always ends in a comma
+    ( @fields $children:ident ($cont:expr) $( , )* ) => {
+        if $children.next().is_none() {
+            $cont
+        } else {
+            None
+        }
+    };
+    ( @fields $children:ident ($cont:expr) .. , $( , )* ) => {
+        $cont
+    };
+    ( @fields $children:ident ($cont:expr) _ : $typ:ident , $( $tail:tt )* ) => {
+        match $children.next() {
+            Some(child) if child[0].rule == Rule::$typ => {
+                $crate::match_tree!(@fields $children ($cont) $( $tail )*)
+            },
+            _ => None
+        }
+    };
+    ( @fields $children:ident ($cont:expr) $name:ident : [ .. ] , $( $tail:tt )* ) => {
+        {
+            let $name: ::std::vec::Vec<_> = $children.by_ref().collect();
+
+            $cont
+        }
+    };
+    ( @fields $children:ident ($cont:expr) $name:ident : [ $typ:ident , .. ] , $( $tail:tt )* ) => {
+        {
+            let mut $name = vec![];
+
+            while $children.peek().map_or(false, |child| child[0].rule == Rule::$typ) {
+                $name.extend($children.next());
+            }
+
+            if $name.is_empty() {
+                None
+            } else {
+                $crate::match_tree!(@fields $children ($cont) $( $tail )*)
+            }
+        }
+    };
+    ( @fields $children:ident ($cont:expr) $name:ident : $typ:ident { $( $fields:tt )* } ,
+      $( $tail:tt )* ) => {
+        match $children.next() {
+            Some($name) => {
+                $crate::match_tree!(@pattern $name
+                                    ($crate::match_tree!(@fields $children ($cont) $( $tail )*))
+                                    $typ { $( $fields )* })
+            },
+            None        => None
+        }
+    };
+    ( @fields $children:ident ($cont:expr) $name:ident : $typ:ident , $( $tail:tt )* ) => {
+        match $children.next() {
+            Some($name) if $name[0].rule == Rule::$typ => {
+                $crate::match_tree!(@fields $children ($cont) $( $tail )*)
+            },
+            _ => None
+        }
+    };
+
+    // patterns
+    ( @pattern $tree:ident ($cont:expr) $typ:ident { $( $fields:tt )* } ) => {
+        if $tree.first().map_or(false, |token| token.rule == Rule::$typ) {
+            #[allow(unused_mut, unused_variables)]
+            let mut children = $crate::runtime::children($tree).into_iter().peekable();
+
+            $crate::match_tree!(@fields children ($cont) $( $fields )* ,)
+        } else {
+            None
+        }
+    };
+
+    // arms; panic if no arm matches
+    ( @arms $tree:ident $( , )* ) => {
+        panic!("no pattern matched in match_tree!; failed at {:?}", $tree.first())
+    };
+    ( @arms $tree:ident _ => $body:expr $( , )* ) => {
+        $body
+    };
+    ( @arms $tree:ident $typ:ident { $( $fields:tt )* } => $body:expr , $( $tail:tt )* ) => {
+        match $crate::match_tree!(@pattern $tree (Some($body)) $typ { $( $fields )* }) {
+            Some(result) => result,
+            None         => $crate::match_tree!(@arms $tree $( $tail )*)
+        }
+    };
+    ( @arms $tree:ident $typ:ident => $body:expr , $( $tail:tt )* ) => {
+        if $tree.first().map_or(false, |token| token.rule == Rule::$typ) {
+            $body
+        } else {
+            $crate::match_tree!(@arms $tree $( $tail )*)
+        }
+    };
+
+    ( $tree:expr, { $( $arms:tt )* } ) => {
+        {
+            let tree: &[$crate::runtime::Token<_>] = $tree;
+
+            $crate::match_tree!(@arms tree $( $arms )* ,)
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Token;
+    use super::children;
+
+    #[allow(non_camel_case_types)]
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    enum Rule {
+        call,
+        ident,
+        expr
+    }
+
+    fn token(rule: Rule, start: usize, end: usize) -> Token<Rule> {
+        Token { rule, start, end }
+    }
+
+    // f(x,g(y))
+    fn tree() -> Vec<Token<Rule>> {
+        vec![
+            token(Rule::call, 0, 9),
+            token(Rule::ident, 0, 1),
+            token(Rule::expr, 2, 3),
+            token(Rule::ident, 2, 3),
+            token(Rule::expr, 4, 8),
+            token(Rule::call, 4, 8),
+            token(Rule::ident, 4, 5),
+            token(Rule::expr, 6, 7),
+            token(Rule::ident, 6, 7)
+        ]
+    }
+
+    fn describe(tree: &[Token<Rule>]) -> String {
+        match_tree!(tree, {
+            call { name: ident, args: [expr, ..] } => {
+                let args: Vec<_> = args.iter().map(|arg| describe(arg)).collect();
+
+                format!("{}({})", name[0].start, args.join(","))
+            },
+            expr { inner: call { .. } } => format!("call {}", describe(inner)),
+            expr { _: ident } => "ident".to_owned(),
+            _ => "?".to_owned()
+        })
+    }
+
+    #[test]
+    fn nested() {
+        assert_eq!(describe(&tree()), "0(ident,call 4(ident))");
+        assert_eq!(describe(&tree()[1..]), "?");
+        assert_eq!(describe(&[]), "?");
+    }
+
+    #[test]
+    fn fields() {
+        let tree = tree();
+
+        assert!(match_tree!(&tree, { call { _: ident } => false, call => true }));
+        assert!(match_tree!(&tree, { call { _: ident, rest: [..] } => rest.len() == 2 }));
+        assert!(match_tree!(&tree[4..], {
+            expr { call: call { _: ident, .. } } => call.len() == 4
+        }));
+        assert_eq!(children(&tree[4..]), vec![&tree[5..]]);
+    }
+
+    #[test]
+    #[should_panic(expected = "no pattern matched in match_tree!")]
+    fn no_match() {
+        let tree = tree();
+
+        match_tree!(&tree, { ident => () });
+    }
+}
@@ -0,0 +1,56 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+/// An `enum` that decides whether the `whitespace` and `comment`s around a rule, i.e. its
+/// trivia, are part of its `Token`'s span. Set with
+/// [`Parser::set_trivia`](trait.Parser#tymethod.set_trivia).
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::Trivia;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         words = { word* }
+///         word  = @{ ['a'..'z']+ }
+///
+///         whitespace = _{ [" "] }
+///     }
+/// }
+///
+/// let mut parser = Rdp::new(StringInput::new(" a  b "));
+///
+/// assert!(parser.words());
+/// assert_eq!(parser.queue()[2], Token { rule: Rule::word, start: 4, end: 5 });
+///
+/// parser.reset();
+/// parser.set_trivia(Trivia::Leading);
+///
+/// assert!(parser.words());
+/// assert_eq!(parser.queue()[2], Token { rule: Rule::word, start: 2, end: 5 });
+///
+/// parser.reset();
+/// parser.set_trivia(Trivia::Trailing);
+///
+/// assert!(parser.words());
+/// assert_eq!(parser.queue()[1], Token { rule: Rule::word, start: 1, end: 4 });
+/// assert_eq!(parser.queue()[2], Token { rule: Rule::word, start: 4, end: 6 });
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Trivia {
+    /// spans start after preceding trivia and end before following trivia (default)
+    #[default]
+    Detached,
+    /// spans include the trivia directly preceding them
+    Leading,
+    /// rules consume and include the trivia directly following them
+    Trailing
+}
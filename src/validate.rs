@@ -0,0 +1,219 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::error::Error;
+use std::fmt;
+
+use super::{GeneratedParser, ParseResult, Token};
+
+/// An `enum` describing a broken invariant of a [queue](trait.Parser#tymethod.queue).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum QueueErrorKind {
+    /// the `Token` ends before it starts
+    Inverted,
+    /// the `Token` starts before the `Token` at the given index that precedes it
+    Unsorted(usize),
+    /// the `Token` starts inside of the `Token` at the given index, but ends after it
+    Overlapping(usize)
+}
+
+/// A `struct` describing a broken invariant of the `Token` at `index` in a
+/// [queue](trait.Parser#tymethod.queue).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct QueueError {
+    /// kind of error
+    pub kind:  QueueErrorKind,
+    /// index of the offending `Token`
+    pub index: usize
+}
+
+impl fmt::Display for QueueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            QueueErrorKind::Inverted           => {
+                write!(f, "token {} ends before it starts", self.index)
+            },
+            QueueErrorKind::Unsorted(previous) => {
+                write!(f, "token {} starts before token {}", self.index, previous)
+            },
+            QueueErrorKind::Overlapping(parent) => {
+                write!(f, "token {} starts inside of token {} but ends after it", self.index,
+                       parent)
+            }
+        }
+    }
+}
+
+impl Error for QueueError {}
+
+/// Checks that a `queue` is a well-nested tree in pre-order: every `Token` ends after it starts,
+/// `Token`s are sorted by their start, and every `Token` is either enclosed by or comes after
+/// each preceding `Token`.
+///
+/// # Examples
+///
+/// ```
+/// # use pest::{validate_queue, QueueError, QueueErrorKind, Token};
+/// let queue = vec![
+///     Token { rule: 0, start: 0, end: 3 },
+///     Token { rule: 1, start: 2, end: 5 }
+/// ];
+///
+/// assert_eq!(validate_queue(&queue),
+///            Err(QueueError { kind: QueueErrorKind::Overlapping(0), index: 1 }));
+/// ```
+pub fn validate_queue<R>(queue: &[Token<R>]) -> Result<(), QueueError> {
+    let mut stack: Vec<usize> = vec![];
+
+    for (index, token) in queue.iter().enumerate() {
+        let error = |kind| Err(QueueError { kind, index });
+
+        if token.start > token.end {
+            return error(QueueErrorKind::Inverted);
+        }
+
+        if index > 0 && token.start < queue[index - 1].start {
+            return error(QueueErrorKind::Unsorted(index - 1));
+        }
+
+        while let Some(&top) = stack.last() {
+            if token.end <= queue[top].end {
+                break;
+            }
+
+            if token.start < queue[top].end {
+                return error(QueueErrorKind::Overlapping(top));
+            }
+
+            stack.pop();
+        }
+
+        stack.push(index);
+    }
+
+    Ok(())
+}
+
+/// A `struct` describing a `Token` of a [`ParseResult`](struct.ParseResult) whose span is not
+/// reproduced when its slice of the input is parsed again by its rule, found by
+/// [`validate_spans`](fn.validate_spans).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SpanError<R> {
+    /// index of the offending `Token`
+    pub index:    usize,
+    /// the offending `Token`
+    pub token:    Token<R>,
+    /// `Token` produced by parsing the slice again, with positions in the whole input, or `None`
+    /// if the rule did not match the whole slice
+    pub reparsed: Option<Token<R>>
+}
+
+impl<R: fmt::Debug> fmt::Display for SpanError<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "token {} ({:?} at {}..{}) ", self.index, self.token.rule, self.token.start,
+               self.token.end)?;
+
+        match self.reparsed {
+            Some(ref token) => {
+                write!(f, "is reparsed as {:?} at {}..{}", token.rule, token.start, token.end)
+            },
+            None            => write!(f, "does not match its slice in isolation")
+        }
+    }
+}
+
+impl<R: fmt::Debug> Error for SpanError<R> {}
+
+/// Checks that every `Token` of `result` spans exactly what its rule matches when parsed again
+/// on its own, i.e. that [`P::parse`](trait.GeneratedParser#tymethod.parse) of the `Token`'s
+/// slice matches the whole slice and produces the same `Token` first. This catches grammar bugs
+/// where whitespace or atomicity make spans misleading, e.g. a `Token` that includes skipped
+/// whitespace or ends before its rule does.
+///
+/// Every `Token` is parsed again, so this is meant for debug builds and tests, e.g. in a
+/// `debug_assert_eq!`. Operators of precedence climbing rules cannot be parsed on their own and
+/// are skipped; rules that depend on what comes before their slice, like stack or lookbehind
+/// rules, may not match in isolation.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::{validate_spans, GeneratedParser, SpanError};
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         pair  = { key ~ ["="] ~ value }
+///         key   = @{ ['a'..'z']+ ~ &["="] }
+///         value = @{ ['0'..'9']+ }
+///
+///         whitespace = _{ [" "] }
+///     }
+/// }
+///
+/// let result = Rdp::<StringInput>::parse(Rule::pair, "a=1");
+///
+/// // key peeks past its span, so "a" on its own is not a key
+/// assert_eq!(validate_spans::<Rdp<StringInput>>(&result), Err(SpanError {
+///     index:    1,
+///     token:    Token { rule: Rule::key, start: 0, end: 1 },
+///     reparsed: None
+/// }));
+///
+/// let result = Rdp::<StringInput>::parse(Rule::value, "12");
+///
+/// assert_eq!(validate_spans::<Rdp<StringInput>>(&result), Ok(()));
+/// # }
+/// ```
+pub fn validate_spans<P: GeneratedParser>(result: &ParseResult<P::Rule>)
+                                          -> Result<(), SpanError<P::Rule>> {
+    for (index, &token) in result.tokens.iter().enumerate() {
+        if !P::is_callable(token.rule) {
+            continue;
+        }
+
+        let reparsed = P::parse(token.rule, result.slice(&token));
+        let first = reparsed.tokens.first().filter(|_| reparsed.matched).map(|first| Token {
+            rule:  first.rule,
+            start: token.start + first.start,
+            end:   token.start + first.end
+        });
+
+        if first != Some(token) {
+            return Err(SpanError { index, token, reparsed: first });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(start: usize, end: usize) -> Token<()> {
+        Token { rule: (), start, end }
+    }
+
+    #[test]
+    fn valid() {
+        let queue = vec![token(0, 6), token(0, 2), token(1, 1), token(2, 6), token(6, 6)];
+
+        assert_eq!(validate_queue(&queue), Ok(()));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(validate_queue(&[token(0, 2), token(2, 1)]),
+                   Err(QueueError { kind: QueueErrorKind::Inverted, index: 1 }));
+        assert_eq!(validate_queue(&[token(0, 6), token(3, 4), token(2, 3)]),
+                   Err(QueueError { kind: QueueErrorKind::Unsorted(1), index: 2 }));
+        assert_eq!(validate_queue(&[token(0, 6), token(0, 2), token(1, 3)]),
+                   Err(QueueError { kind: QueueErrorKind::Overlapping(1), index: 2 }));
+    }
+}
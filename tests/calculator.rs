@@ -9,6 +9,7 @@
 extern crate pest;
 
 use pest::prelude::*;
+use pest::GeneratedParser;
 
 impl_rdp! {
     grammar! {
@@ -128,3 +129,22 @@ fn complex() {
     assert!(parser.expression());
     assert_eq!(parser.process(), 44);
 }
+
+#[test]
+fn generated() {
+    type Calculator<'a> = Rdp<StringInput<'a>>;
+
+    let names: Vec<_> = Calculator::rules().iter().map(|&rule| Calculator::rule_name(rule))
+                                                  .collect();
+
+    assert_eq!(names, vec!["any", "eoi", "expression", "addition", "multiplication", "number",
+                           "plus", "minus", "times", "slash", "any_byte", "any_ascii",
+                           "line_ending"]);
+
+    assert!(Calculator::is_callable(Rule::expression));
+    assert!(!Calculator::is_callable(Rule::addition));
+
+    assert!(Calculator::parse(Rule::expression, "1 + 2 * 3").is_complete());
+    assert!(!Calculator::parse(Rule::expression, "1 + 2 3").matched);
+    assert!(!Calculator::parse(Rule::addition, "+").matched);
+}
@@ -19,7 +19,7 @@ impl_rdp! {
 
     process! {
         main(&self) -> Result<String, String> {
-            (_: ab, res: secondary()) => Ok(try!(res) + "b")
+            (_: ab, res: secondary()) => Ok(res? + "b")
         }
 
         secondary(&self) -> Result<String, String> {
@@ -0,0 +1,77 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+#![cfg(feature = "formats")]
+
+extern crate pest;
+
+use pest::formats::{csv, ini, json};
+use pest::formats::json::Json;
+
+#[test]
+fn ini_sections() {
+    let source = "; header\r\nroot=1\r\n\r\n[ a b ]\r\nx = 2 # two\r\ny =\r\n";
+    let sections = ini::parse(source).unwrap();
+
+    assert_eq!(sections, vec![
+        ini::Section { name: "", pairs: vec![("root", "1")] },
+        ini::Section { name: "a b", pairs: vec![("x", "2"), ("y", "")] }
+    ]);
+}
+
+#[test]
+fn ini_empty() {
+    assert_eq!(ini::parse("").unwrap(), vec![ini::Section { name: "", pairs: vec![] }]);
+}
+
+#[test]
+fn ini_invalid() {
+    assert_eq!(ini::parse("[a"), None);
+    assert_eq!(ini::parse("= 1"), None);
+}
+
+#[test]
+fn csv_records() {
+    let records = csv::parse("a,b\n\"c\nd\",\n").unwrap();
+
+    assert_eq!(records, vec![vec!["a", "b"], vec!["c\nd", ""]]);
+}
+
+#[test]
+fn csv_no_final_line_break() {
+    assert_eq!(csv::parse("a").unwrap(), vec![vec!["a"]]);
+}
+
+#[test]
+fn csv_invalid() {
+    assert_eq!(csv::parse("\"a\"b"), None);
+}
+
+#[test]
+fn json_nested() {
+    let value = json::parse(" [ {\"a\\u0041\" : -1.5e2}, [], {} ] ").unwrap();
+
+    assert_eq!(value, Json::Array(vec![
+        Json::Object(vec![("aA".to_owned(), Json::Number(-150.0))]),
+        Json::Array(vec![]),
+        Json::Object(vec![])
+    ]));
+}
+
+#[test]
+fn json_scalars() {
+    assert_eq!(json::parse("0"), Some(Json::Number(0.0)));
+    assert_eq!(json::parse("false"), Some(Json::Bool(false)));
+    assert_eq!(json::parse("\"\\\"\""), Some(Json::String("\"".to_owned())));
+}
+
+#[test]
+fn json_invalid() {
+    assert_eq!(json::parse("01"), None);
+    assert_eq!(json::parse("[1,]"), None);
+    assert_eq!(json::parse("\"\\q\""), None);
+}
@@ -357,3 +357,1730 @@ fn expression_space_after() {
 
     assert_eq!(parser.queue(), &queue);
 }
+
+#[test]
+fn rule_order() {
+    assert_eq!(Rule::any as usize, 0);
+    assert_eq!(Rule::eoi as usize, 1);
+    assert_eq!(Rule::paren as usize, 2);
+    assert_eq!(Rule::power as usize, 12);
+    assert_eq!(Rule::expression as usize, 13);
+    assert_eq!(Rule::mul as usize, 14);
+    assert_eq!(Rule::pow as usize, 15);
+    assert_eq!(Rule::any_byte as usize, 0xfffd);
+    assert_eq!(Rule::line_ending as usize, 0xffff);
+}
+
+#[test]
+fn expression_pre_order() {
+    let mut parser = Rdp::new(StringInput::new("1 + 2 + 3 * 9^2^2 + 2"));
+
+    assert!(parser.expression());
+
+    let queue = parser.queue();
+
+    for (i, token) in queue.iter().enumerate() {
+        for next in &queue[i + 1..] {
+            assert!(token.start <= next.start);
+            assert!(!next.is_ancestor_of(token));
+        }
+    }
+
+    assert!(queue[5].is_ancestor_of(&queue[8]));
+    assert!(queue[8].is_ancestor_of(&queue[11]));
+    assert!(!queue[11].is_ancestor_of(&queue[15]));
+}
+
+mod alias {
+    use pest::prelude::*;
+
+    impl_rdp! {
+        grammar! {
+            declaration = { ident ~ [":"] ~ type_name }
+            ident       = @{ ['a'..'z']+ }
+            type_name   = alias ident
+
+            whitespace = _{ [" "] }
+        }
+    }
+
+    #[test]
+    fn alias() {
+        let mut parser = Rdp::new(StringInput::new("a : int"));
+
+        assert!(parser.declaration());
+        assert!(parser.end());
+
+        let queue = vec![
+            Token { rule: Rule::declaration, start: 0, end: 7 },
+            Token { rule: Rule::ident, start: 0, end: 1 },
+            Token { rule: Rule::type_name, start: 4, end: 7 }
+        ];
+
+        assert_eq!(parser.queue(), &queue);
+    }
+
+    #[test]
+    fn alias_order() {
+        assert_eq!(Rule::type_name as usize, 4);
+    }
+}
+
+mod flat {
+    use pest::prelude::*;
+
+    impl_rdp! {
+        grammar! {
+            call = { ident ~ ["("] ~ args? ~ [")"] }
+            args = flat { arg ~ ([","] ~ rest)? }
+            rest = _{ args }
+            arg  = _{ call | ident }
+            ident = @{ ['a'..'z']+ }
+
+            whitespace = _{ [" "] }
+        }
+    }
+
+    #[test]
+    fn flat() {
+        let mut parser = Rdp::new(StringInput::new("f(a, g(b, c), d)"));
+
+        assert!(parser.call());
+        assert!(parser.end());
+
+        let queue = vec![
+            Token { rule: Rule::call, start: 0, end: 16 },
+            Token { rule: Rule::ident, start: 0, end: 1 },
+            Token { rule: Rule::args, start: 2, end: 15 },
+            Token { rule: Rule::ident, start: 2, end: 3 },
+            Token { rule: Rule::call, start: 5, end: 12 },
+            Token { rule: Rule::ident, start: 5, end: 6 },
+            Token { rule: Rule::args, start: 7, end: 11 },
+            Token { rule: Rule::ident, start: 7, end: 8 },
+            Token { rule: Rule::ident, start: 10, end: 11 },
+            Token { rule: Rule::ident, start: 14, end: 15 }
+        ];
+
+        assert_eq!(parser.queue(), &queue);
+    }
+
+    #[test]
+    fn long() {
+        let input = vec!["a"; 1000].join(",");
+        let mut parser = Rdp::new(StringInput::new(&input));
+
+        assert!(parser.args());
+        assert!(parser.end());
+        assert_eq!(parser.queue().len(), 1001);
+        assert_eq!(parser.queue()[0], Token { rule: Rule::args, start: 0, end: input.len() });
+    }
+
+    #[test]
+    fn stream() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        let events = Rc::new(Cell::new(0));
+        let sink = events.clone();
+
+        let mut parser = Rdp::new(StringInput::new("a, b"));
+
+        parser.set_stream(&[Rule::args], move |_, _| sink.set(sink.get() + 1));
+
+        assert!(parser.args());
+        assert!(parser.queue().is_empty());
+        assert_eq!(events.get(), 6);
+    }
+}
+
+mod lints {
+    use pest::prelude::*;
+    use pest::{Lint, LintKind, LintLevel};
+
+    impl_rdp! {
+        grammar! {
+            #![deny(nullable_loop, shadowed_alternative)]
+            #![allow(unused_rule, split_word)]
+            #![warn(split_word)]
+
+            list   = { item ~ ([","] ~ item)* }
+            item   = { ['a'..'z']+ }
+            op     = @{ ["<"] | ["<="] }
+            blanks = _{ ([" "]?)* }
+            unused = _{ ["unused"] }
+
+            whitespace = _{ [" "] }
+        }
+    }
+
+    #[test]
+    fn levels() {
+        let parser = Rdp::new(StringInput::new(""));
+
+        assert_eq!(parser.lints(), vec![
+            Lint { rule: "item", kind: LintKind::SplitWord, level: LintLevel::Warn },
+            Lint {
+                rule:  "op",
+                kind:  LintKind::ShadowedAlternative { index: 1, by: 0 },
+                level: LintLevel::Deny
+            },
+            Lint { rule: "blanks", kind: LintKind::NullableLoop, level: LintLevel::Deny }
+        ]);
+    }
+}
+
+mod non_atomic {
+    use pest::prelude::*;
+
+    impl_rdp! {
+        grammar! {
+            string = @{ ["\""] ~ (["${"] ~ interp ~ ["}"] | !["\""] ~ any)* ~ ["\""] }
+            interp = !{ sum }
+            sum    = { number ~ (["+"] ~ number)* }
+            number = @{ ['0'..'9']+ }
+
+            whitespace = _{ [" "] }
+        }
+    }
+
+    #[test]
+    fn interpolation() {
+        let mut parser = Rdp::new(StringInput::new("\"a ${ 1 + 2 } b\""));
+
+        assert!(parser.string());
+        assert!(parser.end());
+        assert!(!parser.is_atomic());
+
+        let queue = vec![
+            Token { rule: Rule::string, start: 0, end: 16 },
+            Token { rule: Rule::interp, start: 6, end: 11 },
+            Token { rule: Rule::sum, start: 6, end: 11 },
+            Token { rule: Rule::number, start: 6, end: 7 },
+            Token { rule: Rule::number, start: 10, end: 11 }
+        ];
+
+        assert_eq!(parser.queue(), &queue);
+    }
+
+    #[test]
+    fn atomic_around() {
+        let mut parser = Rdp::new(StringInput::new("\" a\""));
+
+        assert!(parser.string());
+        assert_eq!(parser.queue(), &vec![Token { rule: Rule::string, start: 0, end: 4 }]);
+    }
+
+    #[test]
+    fn fails() {
+        let mut parser = Rdp::new(StringInput::new("  1 +"));
+
+        parser.set_atomic(true);
+
+        assert!(parser.interp());
+        assert_eq!(parser.pos(), 4);
+        assert!(parser.is_atomic());
+
+        let mut parser = Rdp::new(StringInput::new("  +"));
+
+        parser.set_atomic(true);
+
+        assert!(!parser.interp());
+        assert_eq!(parser.pos(), 0);
+        assert!(parser.is_atomic());
+        assert_eq!(parser.expected(), (vec![Rule::number], 2));
+    }
+}
+
+mod nested_comments {
+    use pest::prelude::*;
+
+    impl_rdp! {
+        grammar! {
+            list  = { ident ~ ([","] ~ ident)* }
+            ident = @{ ['a'..'z']+ }
+
+            whitespace = _{ [" "] | ["\n"] }
+            comment    = _{
+                ["/*"] ~ (comment | !["*/"] ~ any)* ~ ["*/"] |
+                ["//"] ~ (!["\n"] ~ any)* ~ ["\n"]
+            }
+        }
+    }
+
+    #[test]
+    fn nested() {
+        let mut parser = Rdp::new(StringInput::new("a, /* x /* y */ z */ /**/b /* /* */ */"));
+
+        assert!(parser.list());
+
+        parser.skip_com();
+
+        assert!(parser.end());
+        assert_eq!(parser.queue(), &vec![
+            Token { rule: Rule::list, start: 0, end: 26 },
+            Token { rule: Rule::ident, start: 0, end: 1 },
+            Token { rule: Rule::ident, start: 25, end: 26 }
+        ]);
+        assert_eq!(parser.comments(), &[3..20, 21..25, 27..38]);
+    }
+
+    #[test]
+    fn line() {
+        let mut parser = Rdp::new(StringInput::new("a, // x /*\n b"));
+
+        assert!(parser.list());
+        assert!(parser.end());
+        assert_eq!(parser.comments()[0], 3..11);
+    }
+
+    #[test]
+    fn unterminated() {
+        let mut parser = Rdp::new(StringInput::new("a /* /* */, b"));
+
+        assert!(parser.list());
+        assert_eq!(parser.pos(), 1);
+        assert_eq!(parser.expected(), (vec![], 0));
+    }
+
+    mod builtin {
+        use pest::prelude::*;
+        use pest::Expected;
+
+        impl_rdp! {
+            grammar! {
+                list  = { ident ~ ([","] ~ ident)* }
+                ident = @{ ['a'..'z']+ }
+                text  = @{ (!nested_comment("(*", "*)") ~ any)* }
+
+                whitespace = _{ [" "] | nested_comment("{-", "-}") }
+                comment    = _{ nested_comment("/*", "*/") }
+            }
+        }
+
+        #[test]
+        fn nested() {
+            let mut parser = Rdp::new(StringInput::new("a, /* x /* y */ z */ {- {- é -} -}b"));
+
+            assert!(parser.list());
+            assert!(parser.end());
+            assert_eq!(parser.queue(), &vec![
+                Token { rule: Rule::list, start: 0, end: 36 },
+                Token { rule: Rule::ident, start: 0, end: 1 },
+                Token { rule: Rule::ident, start: 35, end: 36 }
+            ]);
+            assert_eq!(parser.comments()[0], 3..20);
+        }
+
+        #[test]
+        fn unterminated() {
+            let mut parser = Rdp::new(StringInput::new("/* /* */"));
+
+            assert!(!parser.match_nested("/*", "*/"));
+            assert_eq!(parser.pos(), 0);
+            assert_eq!(parser.expectations(), (vec![Expected::String("*/".to_owned())], 8));
+        }
+
+        #[test]
+        fn negated() {
+            let mut parser = Rdp::new(StringInput::new("ab(* (* *) *)"));
+
+            assert!(parser.text());
+            assert_eq!(parser.pos(), 2);
+        }
+    }
+
+    mod in_whitespace {
+        use pest::prelude::*;
+
+        impl_rdp! {
+            grammar! {
+                list  = { ident ~ ([","] ~ ident)* }
+                ident = @{ ['a'..'z']+ }
+                block = { ["/*"] ~ (block | !["*/"] ~ any)* ~ ["*/"] }
+
+                whitespace = _{ [" "] | block }
+            }
+        }
+
+        #[test]
+        fn nested() {
+            let mut parser = Rdp::new(StringInput::new("a/* x /* y */ */ , b"));
+
+            assert!(parser.list());
+            assert!(parser.end());
+            assert_eq!(parser.queue(), &vec![
+                Token { rule: Rule::list, start: 0, end: 20 },
+                Token { rule: Rule::ident, start: 0, end: 1 },
+                Token { rule: Rule::block, start: 1, end: 16 },
+                Token { rule: Rule::block, start: 6, end: 13 },
+                Token { rule: Rule::ident, start: 19, end: 20 }
+            ]);
+        }
+    }
+}
+
+mod line_prefixes {
+    use pest::prelude::*;
+
+    impl_rdp! {
+        grammar! {
+            quote  = { ["> "] ~ with_prefix("> ", block) }
+            block  = { quote | lines }
+            lines  = @{ line ~ (line_ending ~ prefixed ~ line)* }
+            lazy   = { ["> "] ~ with_prefix("> ", text) }
+            text   = @{ line ~ (line_ending ~ line)* }
+            line   = @{ (!line_ending ~ any)+ }
+        }
+    }
+
+    #[test]
+    fn nested() {
+        let mut parser = Rdp::new(StringInput::new("> > a\n> > b\n> c"));
+
+        assert!(parser.quote());
+        assert_eq!(parser.pos(), 11);
+        assert_eq!(parser.queue(), &vec![
+            Token { rule: Rule::quote, start: 0, end: 11 },
+            Token { rule: Rule::block, start: 2, end: 11 },
+            Token { rule: Rule::quote, start: 2, end: 11 },
+            Token { rule: Rule::block, start: 4, end: 11 },
+            Token { rule: Rule::lines, start: 4, end: 11 },
+            Token { rule: Rule::line, start: 4, end: 5 },
+            Token { rule: Rule::line, start: 10, end: 11 }
+        ]);
+    }
+
+    #[test]
+    fn lazy() {
+        let mut parser = Rdp::new(StringInput::new("> a\n> b\nc"));
+
+        assert!(parser.lazy());
+        assert!(parser.end());
+        assert_eq!(parser.queue()[3..], [
+            Token { rule: Rule::line, start: 6, end: 7 },
+            Token { rule: Rule::line, start: 8, end: 9 }
+        ]);
+    }
+
+    #[test]
+    fn popped() {
+        let mut parser = Rdp::new(StringInput::new("> a\n> "));
+
+        assert!(parser.quote());
+        assert_eq!(parser.pos(), 3);
+        assert!(parser.line_ending());
+        assert_eq!(parser.pos(), 4);
+    }
+
+    mod overridden {
+        use pest::prelude::*;
+
+        impl_rdp! {
+            grammar! {
+                quote    = { prefixed ~ line }
+                prefixed = { ["> "] }
+                line     = @{ (!["\n"] ~ any)+ }
+            }
+        }
+
+        #[test]
+        fn prefixed() {
+            let mut parser = Rdp::new(StringInput::new("> a"));
+
+            assert!(parser.quote());
+            assert!(parser.end());
+            assert_eq!(parser.queue(), &vec![
+                Token { rule: Rule::quote, start: 0, end: 3 },
+                Token { rule: Rule::prefixed, start: 0, end: 2 },
+                Token { rule: Rule::line, start: 2, end: 3 }
+            ]);
+        }
+    }
+}
+
+mod heredoc {
+    use pest::prelude::*;
+    use pest::{Expected, Heredoc};
+
+    impl_rdp! {
+        grammar! {
+            assign = { ident ~ ["="] ~ string ~ [";"] }
+            ident  = @{ ['a'..'z']+ }
+            string = @{ heredoc("<<") | ["'"] ~ (!["'"] ~ any)* ~ ["'"] }
+
+            whitespace = _{ [" "] | ["\n"] }
+        }
+    }
+
+    #[test]
+    fn heredoc() {
+        let mut parser = Rdp::new(StringInput::new("a = <<~SQL\n  select\n    1\n  SQL\n;"));
+
+        assert!(parser.assign());
+        assert!(parser.end());
+        assert_eq!(parser.queue()[2], Token { rule: Rule::string, start: 4, end: 31 });
+
+        let (heredoc, _) = Heredoc::parse("<<", parser.slice_input(4, 31)).unwrap();
+
+        assert_eq!(heredoc.tag, "SQL");
+        assert_eq!(heredoc.text(), "select\n  1\n");
+    }
+
+    #[test]
+    fn unterminated() {
+        let mut parser = Rdp::new(StringInput::new("<<EOF\nEOF;"));
+
+        assert!(!parser.match_heredoc("<<"));
+        assert_eq!(parser.pos(), 0);
+        assert_eq!(parser.expectations(), (vec![Expected::String("<<".to_owned())], 0));
+    }
+}
+
+mod keywords {
+    use pest::prelude::*;
+
+    impl_rdp! {
+        grammar! {
+            statement = { keyword ~ expression | expression }
+            expression = { ident+ }
+            keyword = keywords(ident) { "if", "in", "int" }
+            ident = @{ ['a'..'z']+ }
+            choice = { ident | other }
+            other = @{ ['a'..'z']+ ~ ["!"] }
+
+            whitespace = _{ [" "] }
+        }
+    }
+
+    #[test]
+    fn keyword() {
+        let mut parser = Rdp::new(StringInput::new("if iffy int"));
+
+        assert!(parser.statement());
+        assert!(!parser.end());
+
+        let queue = vec![
+            Token { rule: Rule::statement, start: 0, end: 7 },
+            Token { rule: Rule::keyword, start: 0, end: 2 },
+            Token { rule: Rule::expression, start: 3, end: 7 },
+            Token { rule: Rule::ident, start: 3, end: 7 }
+        ];
+
+        assert_eq!(parser.queue(), &queue);
+    }
+
+    #[test]
+    fn longest() {
+        let mut parser = Rdp::new(StringInput::new("int"));
+
+        assert!(parser.keyword());
+        assert!(parser.end());
+    }
+
+    #[test]
+    fn identifier() {
+        let mut parser = Rdp::new(StringInput::new("inter"));
+
+        assert!(!parser.keyword());
+        assert!(parser.ident());
+        assert!(parser.end());
+    }
+
+    #[test]
+    fn rejected() {
+        let mut parser = Rdp::new(StringInput::new("if!"));
+
+        assert!(!parser.ident());
+        assert_eq!(parser.pos(), 0);
+
+        assert!(parser.choice());
+        assert!(parser.end());
+
+        let queue = vec![
+            Token { rule: Rule::choice, start: 0, end: 3 },
+            Token { rule: Rule::other, start: 0, end: 3 }
+        ];
+
+        assert_eq!(parser.queue(), &queue);
+    }
+}
+
+mod operators {
+    use pest::prelude::*;
+
+    impl_rdp! {
+        grammar! {
+            program    = { (fixity ~ [";"])* ~ expression ~ eoi }
+            fixity     = { ["infixr "] ~ symbol ~ declare }
+            symbol     = @{ (["<"] | [">"] | ["$"])+ }
+            expression = _{
+                { number }
+                addition = { ["+"] }
+            }
+            number     = @{ ['0'..'9']+ }
+            custom     = token
+
+            whitespace = _{ [" "] }
+        }
+    }
+
+    impl<T: Input> Rdp<T> {
+        fn declare(&mut self) -> bool {
+            let symbol = {
+                let token = self.queue().last().unwrap();
+
+                self.slice_input(token.start, token.end).to_owned()
+            };
+
+            self.add_operator(&symbol, Some(Rule::custom), 1, true);
+
+            true
+        }
+    }
+
+    #[test]
+    fn declared() {
+        let mut parser = Rdp::new(StringInput::new("infixr $; 1 $ 2 $ 3 + 4"));
+
+        assert!(parser.program());
+
+        let queue: Vec<_> = parser.queue()[3..].iter().map(|token| {
+            (token.rule, token.start, token.end)
+        }).collect();
+
+        assert_eq!(queue, vec![
+            (Rule::addition, 10, 23),
+            (Rule::custom, 10, 19),
+            (Rule::number, 10, 11),
+            (Rule::custom, 14, 19),
+            (Rule::number, 14, 15),
+            (Rule::number, 18, 19),
+            (Rule::number, 22, 23)
+        ]);
+
+        assert_eq!(parser.operator_of(&parser.queue()[4]), Some(12..13));
+    }
+
+    #[test]
+    fn longest() {
+        let mut parser = Rdp::new(StringInput::new("1<>2"));
+
+        parser.add_operator("<", None, 1, false);
+        parser.add_operator("<>", Some(Rule::custom), 1, false);
+
+        assert!(parser.expression());
+        assert!(parser.end());
+        assert_eq!(parser.queue()[0], Token { rule: Rule::custom, start: 0, end: 4 });
+    }
+
+    #[test]
+    fn removed() {
+        let mut parser = Rdp::new(StringInput::new("1<>2"));
+
+        parser.add_operator("<>", Some(Rule::custom), 1, false);
+        parser.remove_operator("<>");
+
+        assert!(parser.expression());
+        assert!(!parser.end());
+    }
+}
+
+mod functions {
+    use pest::prelude::*;
+    use pest::Cursor;
+
+    impl_rdp! {
+        grammar! {
+            list  = { ["["] ~ items? ~ ["]"] }
+            items = _{ ident ~ ([","] ~ ident)* }
+            ident = @{ ['a'..'z']+ }
+
+            whitespace = _{ [" "] }
+        }
+
+        functions! { list, items, ident, }
+
+        process! {
+            count(&self) -> usize {
+                (_: list, idents: _count()) => idents
+            }
+            _count(&self) -> usize {
+                (_: ident, rest: _count()) => rest + 1,
+                () => 0
+            }
+        }
+    }
+
+    #[test]
+    fn rules() {
+        let mut parser = Rdp::new(StringInput::new("a, bc"));
+
+        assert!(items(&mut parser));
+        assert!(parser.end());
+
+        let mut parser = Rdp::new(StringInput::new("1"));
+
+        assert!(!ident(&mut parser));
+        assert_eq!(parser.expected(), (vec![Rule::ident], 0));
+    }
+
+    #[test]
+    fn process() {
+        let mut parser = Rdp::new(StringInput::new("[a, b, c]"));
+
+        assert!(list(&mut parser));
+        assert_eq!(parser.count(&mut Cursor::default()), 3);
+    }
+}
+
+mod attributes {
+    use pest::prelude::*;
+
+    impl_rdp! {
+        grammar! {
+            #[cold]
+            #[inline(never)]
+            statement = { keyword ~ name ~ number }
+            #[inline(always)]
+            number    = @{ digit+ }
+            #[inline(always)]
+            digit     = _{ ['0'..'9'] }
+            #[inline(never)]
+            keyword   = keywords(ident) { "let" }
+            /// an identifier used as a name
+            name      = alias ident
+            ident     = @{ ['a'..'z']+ }
+
+            whitespace = _{ [" "] }
+        }
+    }
+
+    #[test]
+    fn forwarded() {
+        let mut parser = Rdp::new(StringInput::new("let x 42"));
+
+        assert!(parser.statement());
+        assert!(parser.end());
+
+        let queue = vec![
+            Token { rule: Rule::statement, start: 0, end: 8 },
+            Token { rule: Rule::keyword, start: 0, end: 3 },
+            Token { rule: Rule::name, start: 4, end: 5 },
+            Token { rule: Rule::number, start: 6, end: 8 }
+        ];
+
+        assert_eq!(parser.queue(), &queue);
+    }
+}
+
+mod builtins {
+    use pest::prelude::*;
+
+    impl_rdp! {
+        grammar! {
+            lines = { line* ~ eoi }
+            line  = { (!line_ending ~ any)* ~ line_ending }
+            ascii = { any_ascii* }
+            bytes = { any_byte* }
+        }
+    }
+
+    #[test]
+    fn line_endings() {
+        let mut parser = Rdp::new(StringInput::new("ă\r\nb\nc\r"));
+
+        assert!(parser.lines());
+
+        let queue = vec![
+            Token { rule: Rule::lines, start: 0, end: 8 },
+            Token { rule: Rule::line, start: 0, end: 4 },
+            Token { rule: Rule::line, start: 4, end: 6 },
+            Token { rule: Rule::line, start: 6, end: 8 }
+        ];
+
+        assert_eq!(parser.queue(), &queue);
+    }
+
+    #[test]
+    fn any_ascii() {
+        let mut parser = Rdp::new(StringInput::new("a~ă"));
+
+        assert!(parser.ascii());
+        assert_eq!(parser.pos(), 2);
+        assert_eq!(parser.expected(), (vec![Rule::any_ascii], 2));
+    }
+
+    #[test]
+    fn any_byte() {
+        let mut parser = Rdp::new(StringInput::new("ab"));
+
+        assert!(parser.bytes());
+        assert!(parser.end());
+        assert_eq!(Rule::line_ending as usize, 0xffff);
+    }
+
+    mod overridden {
+        use pest::prelude::*;
+
+        impl_rdp! {
+            grammar! {
+                lines       = { line* ~ eoi }
+                line        = { (!line_ending ~ any)* ~ line_ending }
+                line_ending = { ["\n"] }
+            }
+        }
+
+        #[test]
+        fn line_ending() {
+            let mut parser = Rdp::new(StringInput::new("a\r\nb\n"));
+
+            assert!(parser.lines());
+
+            let queue = vec![
+                Token { rule: Rule::lines, start: 0, end: 5 },
+                Token { rule: Rule::line, start: 0, end: 3 },
+                Token { rule: Rule::line_ending, start: 2, end: 3 },
+                Token { rule: Rule::line, start: 3, end: 5 },
+                Token { rule: Rule::line_ending, start: 4, end: 5 }
+            ];
+
+            assert_eq!(parser.queue(), &queue);
+            assert_eq!(Rule::line_ending as usize, 4);
+            assert_eq!(Rule::any_byte as usize, 0xfffd);
+        }
+    }
+}
+
+mod prolog {
+    use pest::prelude::*;
+
+    impl_rdp! {
+        grammar! {
+            script = { prolog ~ ident* ~ eoi }
+            ident  = @{ ['a'..'z']+ }
+
+            whitespace = _{ [" "] | ["\n"] }
+        }
+    }
+
+    #[test]
+    fn prolog() {
+        let mut parser = Rdp::new(StringInput::new("\u{feff}#!/usr/bin/env x\r\na b"));
+
+        assert!(parser.script());
+        assert_eq!(parser.queue()[1], Token { rule: Rule::ident, start: 21, end: 22 });
+        assert_eq!(parser.comments()[0], 3..19);
+
+        let mut parser = Rdp::new(StringInput::new("\u{feff}a"));
+
+        assert!(parser.script());
+        assert!(parser.comments().is_empty());
+
+        let mut parser = Rdp::new(StringInput::new("#![a]"));
+
+        assert!(parser.prolog());
+        assert_eq!(parser.pos(), 0);
+        assert!(!parser.script());
+
+        let mut parser = Rdp::new(StringInput::new("a #!"));
+
+        assert!(parser.ident());
+        assert!(parser.prolog());
+        assert_eq!(parser.pos(), 1);
+    }
+
+    mod overridden {
+        use pest::prelude::*;
+
+        impl_rdp! {
+            grammar! {
+                script = { prolog? ~ ident* ~ eoi }
+                prolog = @{ ["%!"] ~ (!["\n"] ~ any)* }
+                ident  = @{ ['a'..'z']+ }
+
+                whitespace = _{ [" "] | ["\n"] }
+            }
+        }
+
+        #[test]
+        fn prolog() {
+            let mut parser = Rdp::new(StringInput::new("%!x\na"));
+
+            assert!(parser.script());
+            assert_eq!(parser.queue(), &vec![
+                Token { rule: Rule::script, start: 0, end: 5 },
+                Token { rule: Rule::prolog, start: 0, end: 3 },
+                Token { rule: Rule::ident, start: 4, end: 5 }
+            ]);
+            assert!(parser.comments().is_empty());
+        }
+    }
+}
+
+mod names {
+    use pest::prelude::*;
+
+    fn double(n: u32) -> u32 {
+        n * 2
+    }
+
+    impl_rdp! {
+        names! { numbers: NumberParser, NumberRule }
+
+        grammar! {
+            number = @{ ['0'..'9']+ }
+        }
+
+        functions! { number }
+
+        process! {
+            main(&self) -> u32 {
+                (&number: number) => double(number.parse().unwrap())
+            }
+        }
+    }
+
+    impl_rdp! {
+        names! { words: WordParser, WordRule, }
+
+        grammar! {
+            word = @{ ['a'..'z']+ }
+        }
+    }
+
+    #[test]
+    fn coexist() {
+        let mut parser = NumberParser::new(StringInput::new("21"));
+
+        assert!(numbers::number(&mut parser));
+        assert_eq!(parser.process(), 42);
+
+        let mut parser = WordParser::new(StringInput::new("pest"));
+
+        assert!(parser.word());
+        assert_eq!(parser.queue(), &vec![Token { rule: WordRule::word, start: 0, end: 4 }]);
+        assert_eq!(NumberRule::number as usize, WordRule::word as usize);
+    }
+}
+
+mod categories {
+    use pest::prelude::*;
+    use pest::{GeneratedParser, RuleKind};
+
+    impl_rdp! {
+        grammar! {
+            expression = {
+                { ["("] ~ expression ~ [")"] | number }
+                addition = { plus }
+                power    = {< pow }
+            }
+            number  = @{ ['0'..'9']+ }
+            plus    =  { ["+"] }
+            pow     =  { ["^"] }
+            args    = flat { number ~ ([","] ~ args)? }
+            keyword = keywords(ident) { "if" }
+            ident   = @{ ['a'..'z']+ }
+            name    = alias ident
+            call    = alias args
+            label   = token
+            space   = _{ [" "] }
+        }
+    }
+
+    #[test]
+    fn category() {
+        let categories = vec![
+            (Rule::any, RuleKind::Builtin),
+            (Rule::expression, RuleKind::Normal),
+            (Rule::addition, RuleKind::Operator),
+            (Rule::power, RuleKind::Operator),
+            (Rule::number, RuleKind::Atomic),
+            (Rule::args, RuleKind::Normal),
+            (Rule::keyword, RuleKind::Atomic),
+            (Rule::name, RuleKind::Atomic),
+            (Rule::call, RuleKind::Normal),
+            (Rule::label, RuleKind::Token),
+            (Rule::line_ending, RuleKind::Builtin)
+        ];
+
+        for (rule, category) in categories {
+            assert_eq!(rule.category(), category);
+        }
+    }
+
+    #[test]
+    fn is_terminal() {
+        let terminals: Vec<_> = vec![
+            Rule::eoi, Rule::expression, Rule::addition, Rule::number, Rule::plus, Rule::keyword,
+            Rule::name, Rule::label
+        ].into_iter().filter(Rule::is_terminal).collect();
+
+        assert_eq!(terminals, vec![Rule::eoi, Rule::number, Rule::keyword, Rule::name,
+                                   Rule::label]);
+    }
+
+    #[test]
+    fn token_not_callable() {
+        type Generated<'a> = Rdp<StringInput<'a>>;
+
+        assert!(!<Generated as GeneratedParser>::is_callable(Rule::label));
+        assert!(<Generated as GeneratedParser>::is_callable(Rule::name));
+    }
+}
+
+mod backtrack_bounds {
+    use pest::prelude::*;
+    use pest::Bound;
+
+    impl_rdp! {
+        grammar! {
+            item    = @{ float | int | ident }
+            float   = @{ int ~ ["."] ~ ['0'..'9']+ }
+            int     = @{ ["-"]? ~ ['0'..'9']+ }
+            ident   = @{ ['a'..'z'] ~ ['a'..'z']? }
+            list    =  { item ~ ([","] ~ item)* }
+            keyword = keywords(ident) { "if" }
+            space   = _{ [" "] }
+        }
+    }
+
+    #[test]
+    fn bounds() {
+        let parser = Rdp::new(StringInput::new(""));
+        let bounds = parser.backtrack_bounds();
+
+        assert_eq!(bounds.bound("int"), Some(Bound::Bytes(1)));
+        assert_eq!(bounds.bound("float"), Some(Bound::Unbounded));
+        assert_eq!(bounds.bound("ident"), Some(Bound::Bytes(0)));
+        assert_eq!(bounds.bound("keyword"), None);
+        assert_eq!(bounds.unbounded(), vec!["item", "float", "list"]);
+    }
+
+    #[test]
+    fn measured() {
+        let parser = Rdp::new(StringInput::new(""));
+        let bounds = parser.backtrack_bounds();
+
+        for input in &["-", "-1", "a", "ab", "abc", "1.", "-12"] {
+            let mut parser = Rdp::new(StringInput::new(input));
+
+            parser.int();
+
+            let distance = parser.max_backtrack().map_or(0, |backtrack| backtrack.distance);
+
+            assert!(Bound::Bytes(distance) <= bounds.bound("int").unwrap());
+
+            let mut parser = Rdp::new(StringInput::new(input));
+
+            parser.ident();
+
+            let distance = parser.max_backtrack().map_or(0, |backtrack| backtrack.distance);
+
+            assert!(Bound::Bytes(distance) <= bounds.bound("ident").unwrap());
+        }
+    }
+}
+
+mod without_prelude {
+    impl_rdp! {
+        grammar! {
+            list = { item ~ ([","] ~ item)* }
+            item = @{ ['a'..'z']+ }
+
+            whitespace = _{ [" "] }
+        }
+
+        process! {
+            items(&self) -> Vec<String> {
+                (_: list, items: _items()) => items
+            }
+            _items(&self) -> Vec<String> {
+                (&item: item, mut tail: _items()) => {
+                    tail.insert(0, item.to_owned());
+
+                    tail
+                },
+                () => vec![]
+            }
+        }
+    }
+
+    #[test]
+    fn qualified() {
+        let mut parser = Rdp::new(::pest::StringInput::new("a, bc"));
+
+        assert!(parser.list());
+        assert_eq!(parser.items(&mut ::pest::Cursor::default()), vec!["a", "bc"]);
+    }
+
+    #[test]
+    fn granular() {
+        use pest::prelude::process::*;
+
+        let mut parser = Rdp::new(::pest::StringInput::new("a"));
+
+        assert!(parser.list());
+        assert_eq!(parser.queue()[1], Token { rule: Rule::item, start: 0, end: 1 });
+    }
+}
+
+mod hygiene {
+    use pest::prelude::*;
+
+    // would collide with imports of the expansion
+    #[allow(dead_code)]
+    struct Cell;
+    #[allow(dead_code)]
+    mod cmp {}
+
+    impl_rdp! {
+        grammar! {
+            word = @{ ['a'..'z']+ }
+        }
+    }
+
+    #[test]
+    fn shadowed_names() {
+        let mut parser = Rdp::new(StringInput::new("ab"));
+
+        assert!(parser.word());
+        assert!(parser.end());
+    }
+
+    #[test]
+    fn inside_function() {
+        impl_rdp! {
+            grammar! {
+                number = @{ ['0'..'9']+ }
+            }
+        }
+
+        let mut parser = Rdp::new(StringInput::new("12"));
+
+        assert!(parser.number());
+        assert_eq!(parser.queue(), &vec![Token { rule: Rule::number, start: 0, end: 2 }]);
+    }
+
+    mod parser_methods {
+        use pest::prelude::*;
+        use pest::GeneratedParser;
+
+        // rules named after Parser methods shadow them on Rdp
+        impl_rdp! {
+            grammar! {
+                run    = { skip ~ (stream | trivia)* }
+                skip   = { ["a"] }
+                stream = { ["b"] }
+                trivia = { ["c"] }
+
+                whitespace = _{ [" "] }
+            }
+        }
+
+        #[test]
+        fn shadowed_methods() {
+            let mut parser = Rdp::new(StringInput::new("a b c"));
+
+            assert!(parser.run());
+            assert!(parser.end());
+
+            assert_eq!(parser.queue(), &vec![
+                Token { rule: Rule::run, start: 0, end: 5 },
+                Token { rule: Rule::skip, start: 0, end: 1 },
+                Token { rule: Rule::stream, start: 2, end: 3 },
+                Token { rule: Rule::trivia, start: 4, end: 5 }
+            ]);
+
+            type Generated<'a> = Rdp<StringInput<'a>>;
+
+            assert!(Generated::parse(Rule::run, "a c b").is_complete());
+        }
+    }
+}
+
+mod custom_token {
+    use pest::prelude::*;
+    use pest::TokenData;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Flagged<R> {
+        rule:  R,
+        start: usize,
+        end:   usize,
+        file:  u8
+    }
+
+    impl<R: Copy + ::std::fmt::Debug + PartialEq> TokenData<R> for Flagged<R> {
+        fn new(rule: R, start: usize, end: usize) -> Flagged<R> {
+            Flagged { rule, start, end, file: 0 }
+        }
+
+        fn rule(&self) -> R {
+            self.rule
+        }
+
+        fn start(&self) -> usize {
+            self.start
+        }
+
+        fn end(&self) -> usize {
+            self.end
+        }
+    }
+
+    impl_rdp! {
+        names! { flagged: FlaggedRdp, FlaggedRule }
+
+        token! { Flagged<Rule> }
+
+        grammar! {
+            pair  = { key ~ ["="] ~ value }
+            key   = alias word
+            value = alias word
+            word  = @{ ['a'..'z']+ }
+        }
+
+        process! {
+            main(&self) -> (u8, u8) {
+                (_: pair, key: key, value: value) => (key.file, value.file)
+            }
+        }
+    }
+
+    #[test]
+    fn queue() {
+        let mut parser = FlaggedRdp::new(StringInput::new("a=bc"));
+
+        assert!(parser.pair());
+        assert_eq!(parser.queue()[2], Flagged { rule: FlaggedRule::value, start: 2, end: 4,
+                                                file: 0 });
+
+        let value = parser.queue()[2];
+
+        assert_eq!(parser.parent_of(&value), Some(&parser.queue()[0]));
+        assert!(parser.validate().is_ok());
+    }
+
+    #[test]
+    fn process() {
+        let mut parser = FlaggedRdp::new(StringInput::new("a=bc"));
+
+        assert!(parser.pair());
+
+        parser.queue_mut()[2].file = 3;
+
+        assert_eq!(parser.process(), (0, 3));
+    }
+
+    #[test]
+    fn parse_result() {
+        let mut parser = FlaggedRdp::new(StringInput::new("a=bc"));
+        let result = parser.run(|parser| parser.pair());
+
+        assert!(result.matched);
+        assert_eq!(result.tokens[1], Token { rule: FlaggedRule::key, start: 0, end: 1 });
+    }
+}
+
+mod external {
+    use pest::prelude::*;
+
+    impl_rdp! {
+        names! { sql: SqlRdp, SqlRule }
+
+        grammar! {
+            statement = { select ~ ident ~ from ~ ident }
+            select    = @{ ["select"] }
+            from      = @{ ["from"] }
+            ident     = @{ ['a'..'z']+ }
+
+            whitespace = _{ [" "] }
+        }
+    }
+
+    impl_rdp! {
+        grammar! {
+            queries      = { query+ ~ eoi }
+            query        = { ["query"] ~ ["{"] ~ embedded_sql ~ ["}"] }
+            embedded_sql = external SqlRdp::statement
+
+            whitespace = _{ [" "] | ["\n"] }
+        }
+    }
+
+    fn rules(parser: &Rdp<StringInput>, token: &Token<Rule>) -> Vec<(String, usize, usize)> {
+        parser.embedded(token).iter().map(|embedded| {
+            let rule = parser.interner().resolve(embedded.rule).to_owned();
+
+            (rule, embedded.start, embedded.end)
+        }).collect()
+    }
+
+    #[test]
+    fn embedded() {
+        let mut parser = Rdp::new(StringInput::new("query { select a from b }"));
+
+        assert!(parser.queries());
+        assert_eq!(parser.queue()[2], Token { rule: Rule::embedded_sql, start: 8, end: 23 });
+
+        let token = parser.queue()[2];
+
+        assert_eq!(parser.descendants_of(&token), &[]);
+        assert_eq!(rules(&parser, &token), vec![
+            ("embedded_sql::statement".to_owned(), 8, 23),
+            ("embedded_sql::select".to_owned(), 8, 14),
+            ("embedded_sql::ident".to_owned(), 15, 16),
+            ("embedded_sql::from".to_owned(), 17, 21),
+            ("embedded_sql::ident".to_owned(), 22, 23)
+        ]);
+    }
+
+    #[test]
+    fn several() {
+        let input = "query {select a from b}\nquery {select c from d}";
+        let mut parser = Rdp::new(StringInput::new(input));
+
+        assert!(parser.queries());
+
+        let tokens: Vec<_> = parser.queue().iter().filter(|token| {
+            token.rule == Rule::embedded_sql
+        }).cloned().collect();
+
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(rules(&parser, &tokens[1])[1], ("embedded_sql::select".to_owned(), 31, 37));
+        assert!(parser.embedded(&parser.queue()[0]).is_empty());
+    }
+
+    #[test]
+    fn fails() {
+        let mut parser = Rdp::new(StringInput::new("query { select a }"));
+
+        assert!(!parser.queries());
+        assert_eq!(parser.expected(), (vec![Rule::embedded_sql], 8));
+    }
+}
+
+mod islands {
+    use pest::prelude::*;
+    use pest::FirstSet;
+
+    impl_rdp! {
+        grammar! {
+            signatures = { (skip_to(signature) ~ signature)* ~ any* ~ eoi }
+            signature  = { ["fn"] ~ ident ~ ["("] ~ (ident ~ ([","] ~ ident)*)? ~ [")"] }
+            ident      = @{ (['a'..'z'] | ["_"])+ }
+            anywhere   = { skip_to(anything) ~ anything }
+            anything   = { any? ~ ["!"] }
+
+            whitespace = _{ [" "] | ["\n"] }
+        }
+    }
+
+    #[test]
+    fn signatures() {
+        let input = "let x = 1; fn f(a, b) { x } /* fn? */ fn g() {}";
+        let mut parser = Rdp::new(StringInput::new(input));
+
+        assert!(parser.signatures());
+
+        let names: Vec<_> = parser.queue().iter().filter(|token| token.rule == Rule::signature)
+                                  .map(|token| parser.slice_input(token.start, token.end))
+                                  .collect();
+
+        assert_eq!(names, vec!["fn f(a, b)", "fn g()"]);
+
+        let first = FirstSet::of(&parser.alternatives(), "signature").unwrap();
+
+        assert_eq!(first.ranges(), &[('f', 'f')]);
+        assert_eq!(FirstSet::of(&parser.alternatives(), "anything"), None);
+    }
+
+    #[test]
+    fn unknown_first_set() {
+        let mut parser = Rdp::new(StringInput::new("ab!"));
+
+        assert!(parser.anywhere());
+        assert_eq!(parser.queue(), &vec![
+            Token { rule: Rule::anywhere, start: 0, end: 3 },
+            Token { rule: Rule::anything, start: 1, end: 3 }
+        ]);
+    }
+
+    #[test]
+    fn none() {
+        let mut parser = Rdp::new(StringInput::new("no functions"));
+
+        assert!(!parser.skip_to("signature", |parser| parser.signature()));
+        assert_eq!(parser.pos(), 0);
+        assert_eq!(parser.expected(), (vec![], 0));
+    }
+}
+
+mod scanner {
+    use pest::prelude::*;
+    use pest::{Scanner, ScannerError};
+
+    impl_rdp! {
+        grammar! {
+            request  = @{ method ~ [" "] ~ path }
+            method   = { ["GET"] | ["POST"] }
+            path     = @{ ["/"] ~ (['a'..'z'] | ["/"])* }
+            get_root = @{ ["GET /"] ~ !['a'..'z'] }
+            status   = @{ ['0'..'9']+ }
+        }
+    }
+
+    #[test]
+    fn records() {
+        let input = "x GET /a y POST /b/c GETS /d GET /";
+        let mut parser = Rdp::new(StringInput::new(input));
+        let scanner = Scanner::new(&parser, &[(Rule::get_root, Rdp::get_root),
+                                              (Rule::request, Rdp::request)]).unwrap();
+
+        assert_eq!(scanner.candidates(&parser).len(), 4);
+        assert_eq!(scanner.scan(&mut parser), vec![
+            Token { rule: Rule::request, start: 2, end: 8 },
+            Token { rule: Rule::request, start: 11, end: 20 },
+            Token { rule: Rule::get_root, start: 29, end: 34 }
+        ]);
+        assert_eq!(parser.pos(), 34);
+    }
+
+    #[test]
+    fn no_literal_prefix() {
+        let parser = Rdp::new(StringInput::new(""));
+        let error = Scanner::new(&parser, &[(Rule::status, Rdp::status)]).err().unwrap();
+
+        assert_eq!(error, ScannerError { rule: Rule::status });
+        assert_eq!(error.to_string(), "rule status does not start with a literal string");
+    }
+}
+
+mod recording {
+    use pest::prelude::*;
+    use pest::Step;
+
+    impl_rdp! {
+        grammar! {
+            list = { ["("] ~ item ~ ([","] ~ item)* ~ [")"] }
+            item = @{ ['a'..'z']+ }
+        }
+    }
+
+    #[test]
+    fn replay() {
+        let mut parser = Rdp::new(StringInput::new("(a,)"));
+
+        parser.enable_recording();
+
+        assert!(!parser.list());
+
+        let recording = parser.recording().unwrap();
+        let mut replay = recording.replay();
+
+        assert_eq!(replay.position(|step| step == Step::Fail { rule: "item", pos: 3 }), Some(4));
+        assert_eq!(replay.stack(), &[("list", 0)]);
+        assert_eq!(recording.steps().last(), Some(&Step::Fail { rule: "list", pos: 0 }));
+    }
+
+    #[test]
+    fn reset() {
+        let mut parser = Rdp::new(StringInput::new("(a)"));
+
+        assert!(parser.list());
+        assert_eq!(parser.recording(), None);
+
+        parser.enable_recording();
+        parser.reset();
+
+        assert!(parser.list());
+        assert_eq!(parser.recording().unwrap().steps().len(), 4);
+
+        parser.reset();
+
+        assert!(parser.recording().unwrap().steps().is_empty());
+    }
+}
+
+mod spans {
+    use pest::prelude::*;
+    use pest::{validate_spans, SpanError, Trivia};
+
+    impl_rdp! {
+        grammar! {
+            words = { word* }
+            word  = @{ ['a'..'z']+ }
+
+            whitespace = _{ [" "] }
+        }
+    }
+
+    #[test]
+    fn trivia() {
+        let mut parser = Rdp::new(StringInput::new("a  b"));
+        let result = parser.run(Rdp::words);
+
+        assert_eq!(validate_spans::<Rdp<StringInput>>(&result), Ok(()));
+
+        parser.reset();
+        parser.set_trivia(Trivia::Leading);
+
+        let result = parser.run(Rdp::words);
+        let error = validate_spans::<Rdp<StringInput>>(&result).unwrap_err();
+
+        assert_eq!(error, SpanError {
+            index:    2,
+            token:    Token { rule: Rule::word, start: 1, end: 4 },
+            reparsed: Some(Token { rule: Rule::word, start: 3, end: 4 })
+        });
+        assert_eq!(error.to_string(), "token 2 (word at 1..4) is reparsed as word at 3..4");
+    }
+}
+
+mod classes {
+    use pest::prelude::*;
+
+    impl_rdp! {
+        grammar! {
+            ident      = @{ start ~ (start | ['0'..'9'])* }
+            start      = _{ ['a'..'z'] - ["q"] | ['A'..'Z'] & (['A'..'F'] | ['X'..'Z']) }
+            consonants = @{ (['a'..'z'] - (["a"] | ["e"] | ["i"] | ["o"] | ["u"]) - ["y"])+ }
+            not_digit  = @{ !['0'..'9'] - ["5"] ~ any }
+            keyword    = { word & ["let"] }
+            word       = { ['a'..'z']+ }
+
+            whitespace = _{ [" "] }
+        }
+    }
+
+    #[test]
+    fn difference() {
+        let mut parser = Rdp::new(StringInput::new("abXq"));
+
+        assert!(parser.ident());
+        assert_eq!(parser.pos(), 3);
+
+        let mut parser = Rdp::new(StringInput::new("Gx"));
+
+        assert!(!parser.ident());
+        assert_eq!(parser.pos(), 0);
+
+        let mut parser = Rdp::new(StringInput::new("bcdyfa"));
+
+        assert!(parser.consonants());
+        assert_eq!(parser.pos(), 3);
+    }
+
+    #[test]
+    fn prefix() {
+        let mut parser = Rdp::new(StringInput::new("5"));
+
+        assert!(parser.not_digit());
+
+        let mut parser = Rdp::new(StringInput::new("4"));
+
+        assert!(!parser.not_digit());
+    }
+
+    #[test]
+    fn intersection() {
+        let mut parser = Rdp::new(StringInput::new("let"));
+
+        assert!(parser.keyword());
+        assert_eq!(parser.queue(), &vec![Token { rule: Rule::keyword, start: 0, end: 3 },
+                                         Token { rule: Rule::word, start: 0, end: 3 }]);
+
+        let mut parser = Rdp::new(StringInput::new("lets"));
+
+        assert!(!parser.keyword());
+        assert!(parser.queue().is_empty());
+    }
+}
+
+mod constants {
+    use pest::prelude::*;
+
+    mod syntax {
+        pub const ASSIGN: &str = ":=";
+    }
+
+    const CONTROL: &[&str] = &["if", "else", "while"];
+    const TYPES: [&str; 2] = ["int", "bool"];
+    const RETURN: &str = "return";
+    const END: &str = ";";
+    const LOW: char = 'a';
+    const HIGH: char = 'z';
+
+    impl_rdp! {
+        grammar! {
+            statement = { keyword ~ ident ~ [END] | ident ~ [syntax::ASSIGN] ~ ident ~ [END] }
+            control   = { keyword ~ ident }
+            keyword   = keywords(ident) { CONTROL, TYPES, RETURN, "let" }
+            ident     = @{ [LOW..HIGH]+ }
+
+            whitespace = _{ [" "] }
+        }
+    }
+
+    #[test]
+    fn keywords() {
+        for &word in CONTROL.iter().chain(&TYPES).chain(&[RETURN, "let"]) {
+            let mut parser = Rdp::new(StringInput::new(word));
+
+            assert!(parser.keyword());
+            assert!(parser.end());
+
+            let mut parser = Rdp::new(StringInput::new(word));
+
+            assert!(!parser.ident());
+        }
+    }
+
+    #[test]
+    fn literals() {
+        let mut parser = Rdp::new(StringInput::new("a := b;"));
+
+        assert!(parser.statement());
+        assert!(parser.end());
+
+        let mut parser = Rdp::new(StringInput::new("while x;"));
+
+        assert!(parser.statement());
+        assert!(parser.end());
+    }
+
+    #[test]
+    fn suggestion() {
+        let mut parser = Rdp::new(StringInput::new("whlie x"));
+
+        assert!(!parser.control());
+        assert_eq!(parser.suggestion().unwrap().expected, "while");
+    }
+}
+
+mod error_positions {
+    use pest::prelude::*;
+    use pest::Expected;
+
+    mod inline {
+        impl_rdp! {
+            grammar! {
+                statement = { word ~ [";"] }
+                word      = @{ ['a'..'z']+ }
+
+                whitespace = _{ [" "] }
+                comment    = { ["#"] ~ (!["\n"] ~ any)* ~ ["\n"] }
+            }
+        }
+    }
+
+    mod extracted {
+        impl_rdp! {
+            grammar! {
+                statement = { word ~ semicolon }
+                semicolon = @{ [";"] }
+                word      = @{ ['a'..'z']+ }
+
+                whitespace = _{ [" "] }
+                comment    = { ["#"] ~ (!["\n"] ~ any)* ~ ["\n"] }
+            }
+        }
+    }
+
+    #[test]
+    fn normalized() {
+        let mut inline = inline::Rdp::new(StringInput::new("a #c\n ?"));
+        let mut extracted = extracted::Rdp::new(StringInput::new("a #c\n ?"));
+
+        assert!(!inline.statement());
+        assert!(!extracted.statement());
+        assert_eq!(inline.expectations().1, 2);
+        assert_eq!(extracted.expectations().1, 6);
+
+        inline.reset();
+        inline.set_error_normalization(true);
+
+        assert!(!inline.statement());
+        assert_eq!(inline.expectations(), (vec![Expected::String(";".to_owned())], 6));
+        assert!(inline.queue().is_empty());
+        assert!(inline.comments().is_empty());
+    }
+
+    #[test]
+    fn unchanged() {
+        let mut parser = extracted::Rdp::new(StringInput::new("a #c\n ?"));
+
+        assert!(!parser.statement());
+
+        let expectations = parser.expectations();
+
+        parser.reset();
+        parser.set_error_normalization(true);
+
+        assert!(!parser.statement());
+        assert_eq!(parser.expectations(), expectations);
+        assert_eq!(parser.comments()[0], 2..5);
+    }
+}
+
+mod empty_tokens {
+    use pest::prelude::*;
+
+    impl_rdp! {
+        grammar! {
+            list  = { items ~ x ~ items }
+            pair  = { wrap ~ items }
+            wrap  = { items }
+            items = { ["a"]* }
+            x     = { ["x"] }
+        }
+    }
+
+    #[test]
+    fn at_both_ends() {
+        let mut parser = Rdp::new(StringInput::new("x"));
+
+        assert!(parser.list());
+
+        let queue = parser.queue().clone();
+
+        assert_eq!(queue, vec![
+            Token { rule: Rule::list, start: 0, end: 1 },
+            Token { rule: Rule::items, start: 0, end: 0 },
+            Token { rule: Rule::x, start: 0, end: 1 },
+            Token { rule: Rule::items, start: 1, end: 1 }
+        ]);
+
+        assert_eq!(parser.index_of(&queue[2]), Some(2));
+        assert_eq!(parser.parent_of(&queue[2]), Some(&queue[0]));
+        assert_eq!(parser.parent_of(&queue[3]), Some(&queue[0]));
+        assert_eq!(parser.descendants_of(&queue[0]), &queue[1..]);
+        assert_eq!(parser.descendants_of(&queue[1]), &[]);
+        assert_eq!(parser.descendants_of(&queue[2]), &[]);
+        assert_eq!(parser.children_of(&queue[0]), vec![&queue[1], &queue[2], &queue[3]]);
+        assert_eq!(parser.next_sibling(&queue[1]), Some(&queue[2]));
+        assert_eq!(parser.next_sibling(&queue[2]), Some(&queue[3]));
+    }
+
+    #[test]
+    fn same_span() {
+        let mut parser = Rdp::new(StringInput::new(""));
+
+        assert!(parser.pair());
+
+        let queue = parser.queue();
+
+        assert_eq!(queue.iter().map(|token| token.rule).collect::<Vec<_>>(),
+                   vec![Rule::pair, Rule::wrap, Rule::items, Rule::items]);
+
+        assert_eq!(parser.index_of(&queue[3]), Some(3));
+        assert_eq!(parser.descendants_of(&queue[0]), &queue[1..]);
+        assert_eq!(parser.descendants_of(&queue[1]), &queue[2..3]);
+        assert_eq!(parser.descendants_of(&queue[2]), &[]);
+        assert_eq!(parser.children_of(&queue[0]), vec![&queue[1], &queue[3]]);
+        assert_eq!(parser.parent_of(&queue[2]), Some(&queue[1]));
+        assert_eq!(parser.parent_of(&queue[3]), Some(&queue[0]));
+        assert_eq!(parser.next_sibling(&queue[1]), Some(&queue[3]));
+        assert_eq!(parser.next_sibling(&queue[2]), None);
+    }
+}
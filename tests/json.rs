@@ -310,10 +310,10 @@ fn fail_string() {
     assert!(!parser.json());
 
     assert_eq!(parser.expected(), (vec![
-        Rule::number,
-        Rule::string,
+        Rule::object,
         Rule::array,
-        Rule::object
+        Rule::string,
+        Rule::number
     ], 0));
 }
 
@@ -324,10 +324,10 @@ fn fail_string_unicode() {
     assert!(!parser.json());
 
     assert_eq!(parser.expected(), (vec![
-        Rule::number,
-        Rule::string,
+        Rule::object,
         Rule::array,
-        Rule::object
+        Rule::string,
+        Rule::number
     ], 0));
 }
 
@@ -338,10 +338,10 @@ fn fail_array() {
     assert!(!parser.json());
 
     assert_eq!(parser.expected(), (vec![
-        Rule::number,
-        Rule::string,
+        Rule::object,
         Rule::array,
-        Rule::object
+        Rule::string,
+        Rule::number
     ], 4));
 }
 
@@ -352,10 +352,10 @@ fn fail_object_pair() {
     assert!(!parser.json());
 
     assert_eq!(parser.expected(), (vec![
-        Rule::number,
-        Rule::string,
+        Rule::object,
         Rule::array,
-        Rule::object
+        Rule::string,
+        Rule::number
     ], 7));
 }
 
@@ -366,9 +366,9 @@ fn fail_object_open() {
     assert!(!parser.json());
 
     assert_eq!(parser.expected(), (vec![
-        Rule::number,
-        Rule::string,
+        Rule::object,
         Rule::array,
-        Rule::object
+        Rule::string,
+        Rule::number
     ], 7));
 }
@@ -0,0 +1,71 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+#[macro_use]
+extern crate pest;
+
+use pest::prelude::*;
+use pest::{Lexeme, LexemeKind};
+
+impl_rdp! {
+    grammar! {
+        less       = @{ ["<"] }
+        less_equal = @{ ["<="] }
+        shift      = @{ ["<<"] }
+        keyword    = @{ ["if"] | ["else"] }
+        ident      = @{ ['a'..'z']+ }
+        space      = _{ [" "]+ }
+    }
+}
+
+type Lex<'a> = fn(&mut Rdp<StringInput<'a>>) -> bool;
+
+fn lex<'a>(source: &'a str, rules: &[Lex<'a>]) -> Vec<(LexemeKind<Rule>, &'a str)> {
+    let mut parser = Rdp::new(StringInput::new(source));
+
+    parser.lex(rules).into_iter().map(|Lexeme { kind, start, end }| {
+        (kind, &source[start..end])
+    }).collect()
+}
+
+#[test]
+fn longest() {
+    let expected = vec![
+        (LexemeKind::Rule(Rule::less_equal), "<="),
+        (LexemeKind::Rule(Rule::shift), "<<"),
+        (LexemeKind::Rule(Rule::less), "<")
+    ];
+
+    assert_eq!(lex("<=<<<", &[Rdp::less, Rdp::less_equal, Rdp::shift]), expected);
+    assert_eq!(lex("<=<<<", &[Rdp::shift, Rdp::less_equal, Rdp::less]), expected);
+}
+
+#[test]
+fn priority() {
+    let rules: [Lex; 3] = [Rdp::keyword, Rdp::ident, Rdp::space];
+
+    assert_eq!(lex("if iffy else", &rules), vec![
+        (LexemeKind::Rule(Rule::keyword), "if"),
+        (LexemeKind::Trivia, " "),
+        (LexemeKind::Rule(Rule::ident), "iffy"),
+        (LexemeKind::Trivia, " "),
+        (LexemeKind::Rule(Rule::keyword), "else")
+    ]);
+    assert_eq!(lex("if", &[Rdp::ident, Rdp::keyword]), vec![
+        (LexemeKind::Rule(Rule::ident), "if")
+    ]);
+}
+
+#[test]
+fn errors() {
+    assert_eq!(lex("a?!<", &[Rdp::ident, Rdp::less]), vec![
+        (LexemeKind::Rule(Rule::ident), "a"),
+        (LexemeKind::Error, "?!"),
+        (LexemeKind::Rule(Rule::less), "<")
+    ]);
+    assert_eq!(lex("", &[Rdp::ident]), vec![]);
+}
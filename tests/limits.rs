@@ -0,0 +1,150 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+#[macro_use]
+extern crate pest;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+
+use pest::prelude::*;
+use pest::{LimitError, LimitKind, Limits};
+
+impl_rdp! {
+    grammar! {
+        lines = _{ line* ~ eoi }
+        line  =  { word* ~ ["\n"] }
+        word  = @{ ['a'..'z']+ }
+        list  =  { ["["] ~ (list | word)* ~ ["]"] }
+
+        whitespace = _{ [" "] }
+    }
+}
+
+#[test]
+fn no_limits() {
+    let mut parser = Rdp::new(StringInput::new("[[[a b] c]]"));
+
+    assert!(parser.list());
+    assert_eq!(parser.limit_error(), None);
+}
+
+#[test]
+fn depth() {
+    let mut parser = Rdp::new(StringInput::new("[[[a]]]"));
+
+    parser.set_limits(Limits::new().max_depth(3));
+
+    assert!(!parser.list());
+    assert_eq!(parser.limit_error(), Some(LimitError { kind: LimitKind::Depth, pos: 3 }));
+
+    parser.reset();
+    parser.set_limits(Limits::new().max_depth(4));
+
+    assert!(parser.list());
+    assert_eq!(parser.limit_error(), None);
+}
+
+#[test]
+fn tokens() {
+    let mut parser = Rdp::new(StringInput::new("a b c d\n"));
+
+    parser.set_limits(Limits::new().max_tokens(4));
+
+    assert!(!parser.lines());
+    assert_eq!(parser.limit_error().map(|error| error.kind), Some(LimitKind::TokenCount));
+    assert!(parser.queue().len() <= 4);
+}
+
+#[test]
+fn line_length() {
+    let mut parser = Rdp::new(StringInput::new("ab\nabcdef\nab\n"));
+
+    parser.set_limits(Limits::new().max_line_len(4));
+
+    assert!(!parser.lines());
+    assert_eq!(parser.limit_error(), Some(LimitError { kind: LimitKind::LineLength, pos: 7 }));
+
+    let mut parser = Rdp::new(StringInput::new("abcd\nab\n"));
+
+    parser.set_limits(Limits::new().max_line_len(4));
+
+    assert!(parser.lines());
+    assert_eq!(parser.limit_error(), None);
+}
+
+#[test]
+fn huge_values() {
+    let reports = Arc::new(AtomicUsize::new(0));
+    let counter = reports.clone();
+    let mut parser = Rdp::new(StringInput::new("a b\nc\n"));
+
+    parser.set_limits(Limits::new().max_line_len(usize::MAX).max_tokens(usize::MAX)
+                                   .max_depth(usize::MAX));
+    parser.set_observer(usize::MAX, move |_| {
+        counter.fetch_add(1, Ordering::Relaxed);
+    });
+
+    assert!(parser.lines());
+    assert_eq!(parser.limit_error(), None);
+    assert_eq!(reports.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn reset() {
+    let mut parser = Rdp::new(StringInput::new("[[a]]"));
+
+    parser.set_limits(Limits::new().max_depth(1));
+
+    assert!(!parser.list());
+    assert!(parser.limit_error().is_some());
+
+    parser.reset();
+
+    assert_eq!(parser.limit_error(), None);
+}
+
+#[test]
+fn cancellation() {
+    let flag = Arc::new(AtomicBool::new(false));
+    let cancel = flag.clone();
+    let mut parser = Rdp::new(StringInput::new("a b\nc d\ne\n"));
+
+    parser.set_cancellation(flag);
+    parser.set_observer(1, move |progress| {
+        if progress.pos >= 4 {
+            cancel.store(true, Ordering::Relaxed);
+        }
+    });
+
+    assert!(!parser.lines());
+    assert!(parser.queue().is_empty());
+
+    let partial = parser.partial().unwrap();
+
+    assert_eq!(partial.error, LimitError { kind: LimitKind::Cancelled, pos: 4 });
+    assert_eq!(partial.tokens, vec![
+        Token { rule: Rule::line, start: 0, end: 4 },
+        Token { rule: Rule::word, start: 0, end: 1 },
+        Token { rule: Rule::word, start: 2, end: 3 }
+    ]);
+}
+
+#[test]
+fn timeout() {
+    let mut parser = Rdp::new(StringInput::new("a\n"));
+
+    parser.set_limits(Limits::new().timeout(Duration::from_secs(0)));
+
+    assert!(!parser.lines());
+    assert_eq!(parser.limit_error().map(|error| error.kind), Some(LimitKind::Timeout));
+
+    parser.reset();
+
+    assert_eq!(parser.partial(), None);
+}
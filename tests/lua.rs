@@ -9,8 +9,6 @@
 //
 //     https://github.com/antlr/grammars-v4/blob/master/lua/Lua.g4
 
-#![recursion_limit = "400"]
-
 #[macro_use]
 extern crate pest;
 
@@ -0,0 +1,113 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+#[macro_use]
+extern crate pest;
+
+use pest::prelude::*;
+
+impl_rdp! {
+    grammar! {
+        number   = { sign? ~ digits ~ fraction? ~ exponent? }
+        sign     = { ["-"] | ["+"] }
+        digits   = @{ ['0'..'9']+ }
+        fraction = { ["."] ~ digits }
+        exponent = { ["e"] ~ digits }
+    }
+
+    process! {
+        main(&self) -> (Option<String>, String, Option<String>, bool) {
+            (_: number, opt(&sign: sign), &int: digits, opt(_: fraction),
+             opt(&frac: digits), opt(exponent: exponent), _: digits) => {
+                (sign.map(|s| s.to_owned()), int.to_owned(), frac.map(|s| s.to_owned()),
+                 exponent.is_some())
+            },
+            (_: number, opt(&sign: sign), &int: digits, opt(_: fraction),
+             opt(&frac: digits)) => {
+                (sign.map(|s| s.to_owned()), int.to_owned(), frac.map(|s| s.to_owned()), false)
+            }
+        }
+    }
+}
+
+fn process(input: &str) -> (Option<String>, String, Option<String>, bool) {
+    let mut parser = Rdp::new(StringInput::new(input));
+
+    assert!(parser.number());
+    assert!(parser.end());
+
+    parser.process()
+}
+
+#[test]
+fn opt_all() {
+    assert_eq!(process("-1.5e3"),
+               (Some("-".to_owned()), "1".to_owned(), Some("5".to_owned()), true));
+}
+
+#[test]
+fn opt_none() {
+    assert_eq!(process("12"), (None, "12".to_owned(), None, false));
+}
+
+#[test]
+fn opt_some() {
+    assert_eq!(process("+3e4"), (Some("+".to_owned()), "3".to_owned(), None, true));
+    assert_eq!(process("3.1"), (None, "3".to_owned(), Some("1".to_owned()), false));
+}
+
+mod peek {
+    use pest::prelude::*;
+    use pest::Cursor;
+
+    impl_rdp! {
+        grammar! {
+            values = _{ value* }
+            value  = _{ number | word }
+            number = @{ ['0'..'9']+ }
+            word   = @{ ['a'..'z']+ }
+
+            whitespace = _{ [" "] }
+        }
+
+        process! {
+            main(&self, &mut cursor) -> Vec<String> {
+                () => {
+                    let mut values = vec![];
+
+                    while let Some(token) = self.peek_token(*cursor) {
+                        values.push(match token.rule {
+                            Rule::number => self._number(cursor),
+                            _            => self._word(cursor)
+                        });
+                    }
+
+                    values
+                }
+            }
+
+            _number(&self) -> String {
+                (&number: number) => format!("#{}", number)
+            }
+
+            _word(&self) -> String {
+                (&word: word) => word.to_uppercase()
+            }
+        }
+    }
+
+    #[test]
+    fn peek() {
+        let mut parser = Rdp::new(StringInput::new("a 1 bc 23"));
+
+        assert!(parser.values());
+        assert_eq!(parser.peek_rule(Cursor::default()), Some(Rule::word));
+        assert_eq!(parser.process(), vec!["A", "#1", "BC", "#23"]);
+        assert_eq!(parser.peek_token(Cursor::new(4)), None);
+        assert_eq!(parser.peek_rule(Cursor::new(4)), None);
+    }
+}
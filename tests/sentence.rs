@@ -88,3 +88,48 @@ fn word() {
 
     assert_eq!(parser.process(), sentence);
 }
+
+mod arena {
+    use pest::prelude::*;
+    use pest::Arena;
+
+    #[derive(Debug, PartialEq)]
+    pub enum Node<'a> {
+        Word(&'a str, Option<&'a Node<'a>>)
+    }
+
+    impl_rdp! {
+        grammar! {
+            sentence = _{ word ~ ([" "] ~ word)* }
+            word     = @{ ['a'..'z']+ }
+        }
+
+        process! {
+            main<'a>(&self, arena: &'a Arena<Node<'a>>,
+                     words: &'a [&'a str]) -> Option<&'a Node<'a>> {
+                (&word: word, mut next: main(arena, words)) => {
+                    let word = words.iter().find(|w| **w == word).unwrap();
+
+                    next = Some(arena.alloc(Node::Word(word, next)));
+
+                    next
+                },
+                () => None
+            }
+        }
+    }
+
+    #[test]
+    fn sentence() {
+        let words = ["abc", "def"];
+        let arena = Arena::new();
+        let mut parser = Rdp::new(StringInput::new("abc def"));
+
+        assert!(parser.sentence());
+
+        let node = parser.process(&arena, &words).unwrap();
+
+        assert_eq!(node, &Node::Word("abc", Some(&Node::Word("def", None))));
+        assert_eq!(arena.len(), 2);
+    }
+}